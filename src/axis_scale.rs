@@ -0,0 +1,258 @@
+use std::f64::consts::PI;
+
+use crate::decimal_math::{decimal_exp10, Decimal};
+use crate::interval::Interval;
+
+/// Mapping of data coordinates onto an axis of a `GPUCanvas2D`, see `GPUCanvas2D::set_x_scale`.
+///
+/// Ranges stay in data coordinates, the canvas maps the scaled values linearly onto the view.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AxisScale {
+    #[default]
+    Linear,
+    /// Only positive values are visible.
+    Log10,
+    /// Only positive values are visible, ticks are placed at powers of e.
+    Ln,
+    /// Linear around zero and logarithmic beyond `threshold` in both directions, for data
+    /// with both signs spanning many orders of magnitude.
+    Symlog { threshold: f64 },
+    /// Latitude in degrees projected like Web-Mercator maps, so it matches a linear
+    /// longitude axis. Only latitudes within `MAX_LATITUDE` are visible.
+    WebMercator,
+}
+
+impl AxisScale {
+    /// Default range of logarithmic axes if the requested one has no positive part.
+    const LOG_FALLBACK_RANGE: (f64, f64) = (1.0, 10.0);
+    /// Orders of magnitude kept visible when a logarithmic range starts at or below zero.
+    const LOG_MIN_DECADES: i32 = 3;
+    /// Latitude at which Web-Mercator maps are square.
+    pub const MAX_LATITUDE: f64 = 85.05112878;
+
+    pub fn is_linear(&self) -> bool {
+        *self == Self::Linear
+    }
+
+    pub fn is_logarithmic(&self) -> bool {
+        matches!(self, Self::Log10 | Self::Ln)
+    }
+
+    /// Scaled value of `x`, non finite for values a logarithmic scale can't show.
+    pub fn forward(&self, x: f64) -> f64 {
+        match *self {
+            Self::Linear => x,
+            Self::Log10 => x.log10(),
+            Self::Ln => x.ln(),
+            Self::Symlog { threshold } => {
+                x.signum() * (1.0 + x.abs() / threshold.max(f64::MIN_POSITIVE)).log10()
+            }
+            Self::WebMercator => match x.abs() <= Self::MAX_LATITUDE {
+                true => (PI / 4.0 + x.to_radians() / 2.0).tan().ln().to_degrees(),
+                false => f64::NAN,
+            },
+        }
+    }
+
+    pub fn inverse(&self, scaled: f64) -> f64 {
+        match *self {
+            Self::Linear => scaled,
+            Self::Log10 => 10f64.powf(scaled),
+            Self::Ln => scaled.exp(),
+            Self::Symlog { threshold } => {
+                scaled.signum()
+                    * threshold.max(f64::MIN_POSITIVE)
+                    * (10f64.powf(scaled.abs()) - 1.0)
+            }
+            Self::WebMercator => (2.0 * scaled.to_radians().exp().atan() - PI / 2.0).to_degrees(),
+        }
+    }
+
+    pub fn forward_interval(&self, range: &Interval) -> Interval {
+        match self {
+            Self::Linear => *range,
+            _ => Interval::new(self.forward(range.start()), self.forward(range.end())),
+        }
+    }
+
+    pub fn inverse_interval(&self, scaled: &Interval) -> Interval {
+        match self {
+            Self::Linear => *scaled,
+            _ => Interval::new(self.inverse(scaled.start()), self.inverse(scaled.end())),
+        }
+    }
+
+    /// `range` restricted to the values the scale can show.
+    pub fn valid_range(&self, range: Interval) -> Interval {
+        if *self == Self::WebMercator {
+            let max = Self::MAX_LATITUDE;
+            // ranges are moved back first, so panning doesn't shrink them at the edges
+            let range = match (range.start() < -max, range.end() > max) {
+                (false, true) => range.offset(max - range.end()),
+                (true, false) => range.offset(-max - range.start()),
+                _ => range,
+            };
+
+            let clamp = |latitude: f64| latitude.clamp(-max, max);
+            return match Interval::new(clamp(range.start()), clamp(range.end())) {
+                clamped if clamped.is_degenerate() => Interval::new(-max, max),
+                clamped => clamped,
+            };
+        }
+        if !self.is_logarithmic() || range.start() > 0.0 {
+            return range;
+        }
+
+        match range.end() {
+            end if end > 0.0 && end.is_finite() => {
+                Interval::new(end / 10f64.powi(Self::LOG_MIN_DECADES), end)
+            }
+            _ => Interval::new(Self::LOG_FALLBACK_RANGE.0, Self::LOG_FALLBACK_RANGE.1),
+        }
+    }
+
+    /// Where the other axis crosses this one: zero, or the start of the range for
+    /// logarithmic scales.
+    pub fn origin(&self, range: &Interval) -> f64 {
+        match self.is_logarithmic() {
+            true => range.start(),
+            false => 0.0,
+        }
+    }
+
+    /// Ticks at powers of the base for about `steps` major steps, `None` for linear scales
+    /// and ranges too narrow to show two of them (those get linear ticks instead).
+    pub(crate) fn ticks(&self, range: &Interval, steps: u32) -> Option<AxisTicks> {
+        let steps = steps.max(1) as i64;
+        let stride = |count: i64| ((count + steps - 1) / steps).max(1);
+
+        let mut ticks = AxisTicks::default();
+
+        match *self {
+            Self::Linear | Self::WebMercator => return None,
+            Self::Log10 | Self::Ln => {
+                let scaled = self.forward_interval(range);
+                let (first, last) = (scaled.start().ceil() as i64, scaled.end().floor() as i64);
+                if last - first < 1 {
+                    return None;
+                }
+
+                let stride = stride(last - first + 1);
+                for k in first..=last {
+                    let value = self.inverse(k as f64);
+                    if k % stride != 0 {
+                        ticks.substeps.push(value);
+                        continue;
+                    }
+
+                    let label = match self {
+                        Self::Log10 => TickLabel::Number(decimal_exp10(k as i32)),
+                        _ => TickLabel::Text(match k {
+                            0 => "1".to_string(),
+                            1 => "e".to_string(),
+                            k => format!("e^{}", k),
+                        }),
+                    };
+                    ticks.steps.push(AxisTick {
+                        index: k / stride,
+                        value,
+                        label,
+                    });
+                }
+
+                if *self == Self::Log10 && stride == 1 {
+                    ticks.substeps.extend(
+                        (first - 1..=last)
+                            .flat_map(|k| (2..=9).map(move |m| m as f64 * 10f64.powi(k as i32)))
+                            .filter(|value| range.contains(*value)),
+                    );
+                }
+            }
+            Self::Symlog { threshold } => {
+                // exponents of the visible decades beyond the threshold on one side
+                let decades = |extent: f64| {
+                    let first = threshold.max(f64::MIN_POSITIVE).log10().ceil() as i64;
+                    let last = match extent > 0.0 {
+                        true => extent.log10().floor() as i64,
+                        false => first - 1,
+                    };
+                    first..=last
+                };
+                let positive = decades(range.end());
+                let negative = decades(-range.start());
+
+                let count = positive.clone().count() + negative.clone().count();
+                if count < 2 {
+                    return None;
+                }
+                let stride = stride(count as i64);
+
+                if range.contains(0.0) {
+                    ticks.steps.push(AxisTick {
+                        index: 0,
+                        value: 0.0,
+                        label: TickLabel::Number(Decimal::from(0)),
+                    });
+                }
+                for (sign, exponents) in [(1.0, positive), (-1.0, negative)] {
+                    let first = *exponents.start();
+                    for k in exponents {
+                        let value = sign * 10f64.powi(k as i32);
+                        let position = k - first + 1;
+                        if !range.contains(value) {
+                            continue;
+                        }
+                        if position % stride != 0 {
+                            ticks.substeps.push(value);
+                            continue;
+                        }
+
+                        let number = decimal_exp10(k as i32);
+                        ticks.steps.push(AxisTick {
+                            index: sign as i64 * position / stride,
+                            value,
+                            label: TickLabel::Number(match sign > 0.0 {
+                                true => number,
+                                false => -number,
+                            }),
+                        });
+
+                        if stride == 1 {
+                            ticks.substeps.extend(
+                                (2..=9)
+                                    .map(|m| sign * m as f64 * 10f64.powi(k as i32))
+                                    .filter(|value| range.contains(*value)),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(ticks)
+    }
+}
+
+/// Grid lines, ticks and labels along one axis of a canvas.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AxisTicks {
+    pub steps: Vec<AxisTick>,
+    /// Data coordinates of the subticks
+    pub substeps: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AxisTick {
+    /// Position in the sequence of major steps, stable while panning (for label skipping)
+    pub index: i64,
+    pub value: f64,
+    pub label: TickLabel,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum TickLabel {
+    /// Formatted with the `TickLabelFormat` of the axis
+    Number(Decimal),
+    Text(String),
+}
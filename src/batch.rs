@@ -0,0 +1,349 @@
+//! Headless rendering of many parameterized plots from a spec file.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::context::VisContext;
+use crate::export::{write_png, write_svg};
+use crate::expr::Expr;
+use crate::graph::GraphStyle;
+use crate::plot::Plot;
+
+/// One image of a batch, see `parse_batch_spec`.
+#[derive(Debug, Clone)]
+pub struct BatchJob {
+    pub output: PathBuf,
+    pub expressions: Vec<Expr>,
+    pub params: HashMap<String, f64>,
+    pub x_range: Option<Range<f64>>,
+    pub y_range: Option<Range<f64>>,
+    pub size: (u32, u32),
+    pub title: Option<String>,
+}
+
+impl BatchJob {
+    pub fn to_plot(&self) -> anyhow::Result<Plot> {
+        let mut plot = Plot::new().deterministic(true);
+        let params = Arc::new(self.params.clone());
+
+        for (index, expr) in self.expressions.iter().enumerate() {
+            if let Some(missing) = expr
+                .variables()
+                .into_iter()
+                .find(|name| *name != "x" && !params.contains_key(*name))
+            {
+                return Err(anyhow::Error::msg(format!(
+                    "Parameter {} is not set.",
+                    missing
+                )));
+            }
+
+            let expr = expr.clone();
+            let params = params.clone();
+            plot = plot.line_styled(
+                move |x, _| expr.eval_x(x, &params),
+                GraphStyle {
+                    color: Plot::DEFAULT_COLORS[index % Plot::DEFAULT_COLORS.len()],
+                    ..Default::default()
+                },
+            );
+        }
+
+        if let Some(x_range) = &self.x_range {
+            plot = plot.x_range(x_range.clone());
+        }
+        if let Some(y_range) = &self.y_range {
+            plot = plot.y_range(y_range.clone());
+        }
+        plot = plot.window_size(self.size.0, self.size.1);
+        if let Some(title) = &self.title {
+            plot = plot.title(title);
+        }
+
+        Ok(plot)
+    }
+
+    /// Renders the job with `context` and writes it to `output`, creating missing directories.
+    pub fn render(&self, context: &VisContext) -> anyhow::Result<()> {
+        self.render_inner(context)
+            .with_context(|| format!("Failed to render {}.", self.output.display()))
+    }
+
+    fn render_inner(&self, context: &VisContext) -> anyhow::Result<()> {
+        let extension = self
+            .output
+            .extension()
+            .and_then(|extension| extension.to_str());
+        let svg = match extension {
+            Some("png") => false,
+            Some("svg") => true,
+            _ => return Err(anyhow::Error::msg("Expected a .png or .svg output.")),
+        };
+
+        let plot = self.to_plot()?;
+
+        if let Some(parent) = self.output.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}.", parent.display()))?;
+        }
+
+        if svg {
+            return write_svg(&self.output, &plot.render_svg_in(context)?);
+        }
+
+        let pixels = plot.render_image_in(context)?;
+        let (width, height) = self.size;
+        write_png(&self.output, width, height, &pixels)
+    }
+}
+
+/// Parses a batch spec into its jobs.
+///
+/// The spec is a JSON object with the keys `output` (`.png` or `.svg` path template), `expressions`
+/// (a string or an array of them), `params` (name to value), `grid` (name to an array of
+/// values or `{"start", "end", "steps"}`), `x_range` and `y_range` (`[start, end]`),
+/// `size` (`[width, height]`, default `[1024, 768]`) and `title` (template). A `jobs`
+/// array of objects with the same keys overrides the top level keys per entry.
+///
+/// Every job is rendered once for each combination of its grid values. `{name}` in the
+/// templates is replaced by the value of a parameter, `{index}` by the position of the
+/// image in the batch. Relative outputs are resolved against `base_dir`.
+pub fn parse_batch_spec(source: &str, base_dir: impl AsRef<Path>) -> anyhow::Result<Vec<BatchJob>> {
+    let value: Value = serde_json::from_str(source)?;
+    let Value::Object(spec) = value else {
+        return Err(anyhow::Error::msg("Expected an object."));
+    };
+
+    let entries = match spec.get("jobs") {
+        None => vec![spec.clone()],
+        Some(Value::Array(jobs)) => jobs
+            .iter()
+            .map(|job| match job {
+                Value::Object(job) => {
+                    let mut merged = spec.clone();
+                    merged.remove("jobs");
+                    merged.extend(job.clone());
+                    Ok(merged)
+                }
+                job => Err(anyhow::Error::msg(format!(
+                    "Expected a job object but found {}.",
+                    job
+                ))),
+            })
+            .collect::<anyhow::Result<_>>()?,
+        Some(jobs) => {
+            return Err(anyhow::Error::msg(format!(
+                "Expected an array of jobs but found {}.",
+                jobs
+            )))
+        }
+    };
+
+    let mut jobs = Vec::new();
+    for (entry_index, entry) in entries.iter().enumerate() {
+        batch_entry(entry, base_dir.as_ref(), &mut jobs)
+            .with_context(|| format!("Invalid job {}.", entry_index))?;
+    }
+
+    Ok(jobs)
+}
+
+pub fn read_batch_spec(path: impl AsRef<Path>) -> anyhow::Result<Vec<BatchJob>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}.", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    parse_batch_spec(&source, base_dir)
+        .with_context(|| format!("Failed to parse {}.", path.display()))
+}
+
+/// Renders all `jobs` on up to `threads` threads sharing `context` (its device and pipeline
+/// cache), returning the result of every job in order.
+pub fn render_batch(
+    context: &VisContext,
+    jobs: &[BatchJob],
+    threads: usize,
+) -> Vec<anyhow::Result<()>> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<anyhow::Result<()>>>> =
+        Mutex::new(jobs.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+
+                let result = job.render(context);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err(anyhow::Error::msg("Job was not rendered."))))
+        .collect()
+}
+
+//-- spec parsing
+
+fn batch_entry(
+    entry: &Map<String, Value>,
+    base_dir: &Path,
+    jobs: &mut Vec<BatchJob>,
+) -> anyhow::Result<()> {
+    let output = match entry.get("output") {
+        Some(Value::String(output)) => output,
+        Some(value) => return Err(type_error("a string output", value)),
+        None => return Err(anyhow::Error::msg("Missing output.")),
+    };
+
+    let expressions = match entry.get("expressions") {
+        None => Vec::new(),
+        Some(Value::String(source)) => vec![Expr::parse(source)?],
+        Some(Value::Array(sources)) => sources
+            .iter()
+            .map(|source| match source {
+                Value::String(source) => Expr::parse(source),
+                value => Err(type_error("an expression string", value)),
+            })
+            .collect::<anyhow::Result<_>>()?,
+        Some(value) => return Err(type_error("expressions", value)),
+    };
+
+    let mut params = HashMap::new();
+    match entry.get("params") {
+        None => (),
+        Some(Value::Object(values)) => {
+            for (name, value) in values {
+                params.insert(name.clone(), number(value)?);
+            }
+        }
+        Some(value) => return Err(type_error("an object of params", value)),
+    }
+
+    let grid = match entry.get("grid") {
+        None => Vec::new(),
+        Some(Value::Object(axes)) => axes
+            .iter()
+            .map(|(name, values)| Ok((name.clone(), grid_values(values)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?,
+        Some(value) => return Err(type_error("an object of grid values", value)),
+    };
+
+    let x_range = entry.get("x_range").map(range).transpose()?;
+    let y_range = entry.get("y_range").map(range).transpose()?;
+    let size = entry
+        .get("size")
+        .map(size)
+        .transpose()?
+        .unwrap_or((1024, 768));
+    let title = match entry.get("title") {
+        None => None,
+        Some(Value::String(title)) => Some(title),
+        Some(value) => return Err(type_error("a string title", value)),
+    };
+
+    // cartesian product of the grid values, the last grid axis changing fastest
+    let count: usize = grid.iter().map(|(_, values)| values.len()).product();
+    for combination in 0..count {
+        let mut params = params.clone();
+        let mut rest = combination;
+        for (name, values) in grid.iter().rev() {
+            params.insert(name.clone(), values[rest % values.len()]);
+            rest /= values.len();
+        }
+
+        let index = jobs.len();
+        let output = base_dir.join(fill_template(output, &params, index));
+
+        jobs.push(BatchJob {
+            output,
+            expressions: expressions.clone(),
+            params: params.clone(),
+            x_range: x_range.clone(),
+            y_range: y_range.clone(),
+            size,
+            title: title.map(|title| fill_template(title, &params, index)),
+        });
+    }
+
+    Ok(())
+}
+
+fn grid_values(value: &Value) -> anyhow::Result<Vec<f64>> {
+    match value {
+        Value::Array(values) if !values.is_empty() => values.iter().map(number).collect(),
+        Value::Object(object) => {
+            let field = |name: &str| number(object.get(name).unwrap_or(&Value::Null));
+            let (start, end, steps) = (field("start")?, field("end")?, field("steps")?);
+
+            if steps < 1.0 {
+                return Err(anyhow::Error::msg("Grid steps must be at least 1."));
+            }
+            let steps = steps as usize;
+
+            Ok((0..steps)
+                .map(|step| match steps {
+                    1 => start,
+                    _ => start + (end - start) * step as f64 / (steps - 1) as f64,
+                })
+                .collect())
+        }
+        value => Err(type_error(
+            "a non-empty array or {\"start\", \"end\", \"steps\"}",
+            value,
+        )),
+    }
+}
+
+fn range(value: &Value) -> anyhow::Result<Range<f64>> {
+    match value {
+        Value::Array(bounds) if bounds.len() == 2 => Ok(number(&bounds[0])?..number(&bounds[1])?),
+        value => Err(type_error("a [start, end] range", value)),
+    }
+}
+
+fn size(value: &Value) -> anyhow::Result<(u32, u32)> {
+    let dimension = |value: &Value| {
+        value
+            .as_u64()
+            .filter(|dimension| (1..=u32::MAX as u64).contains(dimension))
+            .map(|dimension| dimension as u32)
+            .ok_or_else(|| type_error("a positive integer", value))
+    };
+
+    match value {
+        Value::Array(size) if size.len() == 2 => Ok((dimension(&size[0])?, dimension(&size[1])?)),
+        value => Err(type_error("a [width, height] size", value)),
+    }
+}
+
+fn number(value: &Value) -> anyhow::Result<f64> {
+    value.as_f64().ok_or_else(|| type_error("a number", value))
+}
+
+fn type_error(expected: &str, found: &Value) -> anyhow::Error {
+    anyhow::Error::msg(format!("Expected {} but found {}.", expected, found))
+}
+
+/// Replaces `{name}` by the value of the parameter and `{index}` by `index`.
+fn fill_template(template: &str, params: &HashMap<String, f64>, index: usize) -> String {
+    let mut filled = template.replace("{index}", &index.to_string());
+    for (name, value) in params {
+        filled = filled.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    filled
+}
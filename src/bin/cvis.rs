@@ -0,0 +1,452 @@
+//! Quick plotting from the command line.
+//!
+//! ```text
+//! cvis "sin(x)*a" data.csv -p a=2 -x -5:5 -o plot.png
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use complex_vis::*;
+
+const USAGE: &str = "\
+Usage: cvis [OPTIONS] <EXPRESSION | FILE.csv | FILE.json>...
+
+Plots every expression (as a function of x) and every data file (CSV: first column x,
+remaining columns y; JSON: arrays of [x, y] pairs) in one canvas.
+
+Options:
+  -x, --x-range START:END   Visible x range (default: -1:1, or fitted to the data)
+  -y, --y-range START:END   Visible y range (default: -1:1, or fitted to the data)
+  -p, --param NAME=VALUE    Value of a parameter used in the expressions
+  -c, --color RRGGBB[AA]    Color of the following series
+  -w, --thickness VALUE     Thickness of the following series
+                            (extrathin, thin, medium, bold, extrabold or a number)
+  -t, --title TITLE         Window title / title shown above the plot
+      --x-title TEXT        Title below the x axis
+      --y-title TEXT        Title along the y axis
+  -s, --size WIDTHxHEIGHT   Window or image size in pixels (default: 1024x768)
+  -o, --output FILE.png     Write a PNG (or .html) instead of opening a window
+      --batch SPEC.json     Render the images of a batch spec instead
+  -j, --jobs N              Number of images rendered in parallel with --batch
+                            (default: number of CPUs)
+      --style FILE          Theme file (.json, .toml or .ron) applied on top of the default
+      --watch               Reload data files (and the style file) when they change on disk
+      --listen ADDRESS      Accept \"<series> <x> <y>\" lines on a TCP address (host:port)
+                            or Unix socket path (requires the `server` feature)
+      --no-grid             Hide grid lines
+      --no-text             Hide axis labels
+  -h, --help                Print this help
+";
+
+enum Source {
+    Expression(Expr),
+    Data(String),
+}
+
+struct SeriesArg {
+    source: Source,
+    color: Option<RGBA>,
+    thickness: f32,
+}
+
+struct Args {
+    series: Vec<SeriesArg>,
+    params: HashMap<String, f64>,
+    x_range: Option<Range<f64>>,
+    y_range: Option<Range<f64>>,
+    title: Option<String>,
+    x_title: Option<String>,
+    y_title: Option<String>,
+    size: Option<(u32, u32)>,
+    output: Option<String>,
+    batch: Option<String>,
+    jobs: Option<usize>,
+    style: Option<String>,
+    watch: bool,
+    listen: Option<String>,
+    grid: bool,
+    text: bool,
+}
+
+fn parse_range(value: &str) -> anyhow::Result<Range<f64>> {
+    let (start, end) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow::Error::msg(format!("Invalid range \"{}\".", value)))?;
+
+    Ok(start.trim().parse()?..end.trim().parse()?)
+}
+
+fn parse_color(value: &str) -> anyhow::Result<RGBA> {
+    let hex = value.trim_start_matches('#');
+    if !hex.is_ascii() {
+        return Err(anyhow::Error::msg(format!("Invalid color \"{}\".", value)));
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+
+    match hex.len() {
+        6 => Ok(RGBA::new(component(0)?, component(2)?, component(4)?, 255)),
+        8 => Ok(RGBA::new(
+            component(0)?,
+            component(2)?,
+            component(4)?,
+            component(6)?,
+        )),
+        _ => Err(anyhow::Error::msg(format!("Invalid color \"{}\".", value))),
+    }
+}
+
+fn parse_thickness(value: &str) -> anyhow::Result<f32> {
+    Ok(match value {
+        "extrathin" => Thickness::EXTRATHIN,
+        "thin" => Thickness::THIN,
+        "medium" => Thickness::MEDIUM,
+        "bold" => Thickness::BOLD,
+        "extrabold" => Thickness::EXTRABOLD,
+        value => value.parse()?,
+    })
+}
+
+fn parse_size(value: &str) -> anyhow::Result<(u32, u32)> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| anyhow::Error::msg(format!("Invalid size \"{}\".", value)))?;
+
+    Ok((width.trim().parse()?, height.trim().parse()?))
+}
+
+fn parse_jobs(value: &str) -> anyhow::Result<usize> {
+    match value.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(anyhow::Error::msg(format!(
+            "Invalid job count \"{}\", expected a positive integer.",
+            value
+        ))),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Option<Args>> {
+    let mut parsed = Args {
+        series: Vec::new(),
+        params: HashMap::new(),
+        x_range: None,
+        y_range: None,
+        title: None,
+        x_title: None,
+        y_title: None,
+        size: None,
+        output: None,
+        batch: None,
+        jobs: None,
+        style: None,
+        watch: false,
+        listen: None,
+        grid: true,
+        text: true,
+    };
+
+    let mut color = None;
+    let mut thickness = Thickness::MEDIUM;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {}.", name)))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-x" | "--x-range" => parsed.x_range = Some(parse_range(&value(&arg)?)?),
+            "-y" | "--y-range" => parsed.y_range = Some(parse_range(&value(&arg)?)?),
+            "-p" | "--param" => {
+                let param = value(&arg)?;
+                let (name, param_value) = param.split_once('=').ok_or_else(|| {
+                    anyhow::Error::msg(format!("Invalid parameter \"{}\".", param))
+                })?;
+
+                parsed
+                    .params
+                    .insert(name.trim().to_string(), param_value.trim().parse()?);
+            }
+            "-c" | "--color" => color = Some(parse_color(&value(&arg)?)?),
+            "-w" | "--thickness" => thickness = parse_thickness(&value(&arg)?)?,
+            "-t" | "--title" => parsed.title = Some(value(&arg)?),
+            "--x-title" => parsed.x_title = Some(value(&arg)?),
+            "--y-title" => parsed.y_title = Some(value(&arg)?),
+            "-s" | "--size" => parsed.size = Some(parse_size(&value(&arg)?)?),
+            "-o" | "--output" => parsed.output = Some(value(&arg)?),
+            "--batch" => parsed.batch = Some(value(&arg)?),
+            "-j" | "--jobs" => parsed.jobs = Some(parse_jobs(&value(&arg)?)?),
+            "--style" => parsed.style = Some(value(&arg)?),
+            "--watch" => parsed.watch = true,
+            "--listen" => parsed.listen = Some(value(&arg)?),
+            "--no-grid" => parsed.grid = false,
+            "--no-text" => parsed.text = false,
+            arg if arg.starts_with("--") => {
+                return Err(anyhow::Error::msg(format!("Unknown option {}.", arg)))
+            }
+            source => {
+                let is_file = [".csv", ".txt", ".json"]
+                    .iter()
+                    .any(|extension| source.ends_with(extension));
+
+                let source = match is_file {
+                    true => Source::Data(source.to_string()),
+                    false => Source::Expression(Expr::parse(source)?),
+                };
+
+                parsed.series.push(SeriesArg {
+                    source,
+                    color: color.take(),
+                    thickness,
+                });
+            }
+        }
+    }
+
+    Ok(Some(parsed))
+}
+
+/// Builds the plot and a watcher for every data file (with the positions of its series).
+fn build_plot(args: Args) -> anyhow::Result<(Plot, Vec<WatchedData>)> {
+    let mut plot = Plot::new();
+    let mut data = Vec::new();
+    let mut watched = Vec::new();
+    let mut series_count = 0;
+
+    let params = Arc::new(args.params);
+
+    for (index, series) in args.series.into_iter().enumerate() {
+        let color = series
+            .color
+            .unwrap_or(Plot::DEFAULT_COLORS[index % Plot::DEFAULT_COLORS.len()]);
+
+        match series.source {
+            Source::Expression(expr) => {
+                if let Some(missing) = expr
+                    .variables()
+                    .into_iter()
+                    .find(|name| *name != "x" && !params.contains_key(*name))
+                {
+                    return Err(anyhow::Error::msg(format!(
+                        "Parameter {} is not set (use -p {}=VALUE).",
+                        missing, missing
+                    )));
+                }
+
+                let params = params.clone();
+                plot = plot.line_styled(
+                    move |x, _| expr.eval_x(x, &params),
+                    GraphStyle {
+                        color,
+                        thickness: series.thickness,
+                        ..Default::default()
+                    },
+                );
+                series_count += 1;
+            }
+            Source::Data(path) => {
+                let style = PointStyle {
+                    color,
+                    radius: series.thickness.max(Thickness::EXTRABOLD),
+                    ..Default::default()
+                };
+                let mut series_indices = Vec::new();
+
+                for points in read_data(&path)? {
+                    data.push(points.clone());
+                    plot = plot.scatter_styled(points, style);
+
+                    series_indices.push(series_count);
+                    series_count += 1;
+                }
+
+                watched.push(WatchedData::new(&path, series_indices, style));
+            }
+        }
+    }
+
+    // fit the default ranges to the data
+    let bounds = data_bounds(data.iter().map(Vec::as_slice));
+    let padded = |(min, max): (f64, f64)| {
+        let padding = ((max - min) * 0.05).max(f64::EPSILON.sqrt());
+        (min - padding)..(max + padding)
+    };
+
+    match (args.x_range, bounds) {
+        (Some(x_range), _) => plot = plot.x_range(x_range),
+        (None, Some((x_bounds, _))) => plot = plot.x_range(padded(x_bounds)),
+        (None, None) => (),
+    }
+    match (args.y_range, bounds) {
+        (Some(y_range), _) => plot = plot.y_range(y_range),
+        (None, Some((_, y_bounds))) => plot = plot.y_range(padded(y_bounds)),
+        (None, None) => (),
+    }
+
+    if let Some(title) = &args.title {
+        plot = plot.title(title);
+    }
+    if let Some((width, height)) = args.size {
+        plot = plot.window_size(width, height);
+    }
+
+    if let Some(path) = &args.style {
+        plot = plot.theme(read_theme(path, &Theme::default())?);
+    }
+
+    let style = plot.style_get_mut();
+    if !args.grid {
+        for dimension in [&mut style.x, &mut style.y] {
+            dimension.grid = None;
+            dimension.subgrid = None;
+        }
+    }
+    if !args.text {
+        style.text = None;
+    }
+    if let Some(title) = &args.x_title {
+        style.x.title = Some(AxisTitleStyle::new(title));
+    }
+    if let Some(title) = &args.y_title {
+        style.y.title = Some(AxisTitleStyle::new(title));
+    }
+
+    Ok((plot, watched))
+}
+
+#[cfg(feature = "server")]
+fn serve(address: &str, handle: &PlotHandle<()>) -> anyhow::Result<()> {
+    let server = match address.contains(':') {
+        true => IngestServer::bind_tcp(address)?,
+        #[cfg(unix)]
+        false => IngestServer::bind_unix(address)?,
+        #[cfg(not(unix))]
+        false => return Err(anyhow::Error::msg("Unix sockets are not supported.")),
+    };
+
+    server
+        .with_error_callback(|error| eprintln!("{:#}", error))
+        .serve(handle)
+}
+
+#[cfg(not(feature = "server"))]
+fn serve(_address: &str, _handle: &PlotHandle<()>) -> anyhow::Result<()> {
+    Err(anyhow::Error::msg(
+        "cvis was built without the `server` feature.",
+    ))
+}
+
+/// Shows the plot and keeps it up to date with the watched data files and the data
+/// received on `listen` until the window gets closed.
+fn show_live(
+    plot: Plot,
+    mut watched: Vec<WatchedData>,
+    mut style: Option<WatchedTheme>,
+    listen: Option<String>,
+) -> anyhow::Result<()> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    let handle = plot.show_detached()?;
+
+    std::thread::scope(|scope| {
+        if let Some(address) = &listen {
+            let handle = &handle;
+            scope.spawn(move || {
+                if let Err(error) = serve(address, handle) {
+                    eprintln!("Error: {:#}", error);
+                }
+            });
+        }
+
+        while handle.is_open() {
+            std::thread::sleep(POLL_INTERVAL);
+
+            for data in &mut watched {
+                if let Err(error) = data.update(&handle) {
+                    eprintln!("Error: {:#}", error);
+                }
+            }
+            if let Some(Err(error)) = style.as_mut().map(|style| style.update(&handle)) {
+                eprintln!("Error: {:#}", error);
+            }
+        }
+    });
+
+    handle.join()
+}
+
+fn run_batch(spec: &str, threads: usize) -> anyhow::Result<()> {
+    let jobs = read_batch_spec(spec)?;
+
+    let mut context = VisContext::headless()?;
+    context.set_deterministic(true);
+
+    let results = render_batch(&context, &jobs, threads);
+    let failed = results
+        .iter()
+        .filter_map(|result| result.as_ref().err())
+        .inspect(|error| eprintln!("Error: {:#}", error))
+        .count();
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(anyhow::Error::msg(format!(
+            "{} of {} images failed.",
+            failed,
+            jobs.len()
+        ))),
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let Some(args) = parse_args(std::env::args().skip(1))? else {
+        print!("{}", USAGE);
+        return Ok(());
+    };
+
+    if let Some(spec) = &args.batch {
+        let jobs = args.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        });
+        return run_batch(spec, jobs);
+    }
+
+    if args.series.is_empty() && args.listen.is_none() {
+        print!("{}", USAGE);
+        return Err(anyhow::Error::msg("Nothing to plot."));
+    }
+
+    let output = args.output.clone();
+    let watch = args.watch;
+    let listen = args.listen.clone();
+    let style = args
+        .style
+        .as_ref()
+        .map(|path| WatchedTheme::new(path, Theme::default()));
+    let (plot, watched) = build_plot(args)?;
+
+    match output {
+        Some(path) if path.ends_with(".html") => plot.export_html(path),
+        Some(path) => plot.export_png(path),
+        None if watch || listen.is_some() => {
+            let (watched, style) = match watch {
+                true => (watched, style),
+                false => (Vec::new(), None),
+            };
+            show_live(plot, watched, style, listen)
+        }
+        None => plot.show(),
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    if let Err(error) = run() {
+        eprintln!("Error: {:#}", error);
+        std::process::exit(1);
+    }
+}
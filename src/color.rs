@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RGBA {
     pub r: u8,
     pub g: u8,
@@ -7,7 +7,7 @@ pub struct RGBA {
 }
 
 impl RGBA {
-    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
 
@@ -19,6 +19,39 @@ impl RGBA {
             a: 255,
         }
     }
+
+    /// `hue` in degrees, `saturation` and `value` in `0..1`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f32| ((c + m).clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Self::new(channel(r), channel(g), channel(b), 255)
+    }
+
+    /// Linear interpolation between `self` (`t = 0`) and `other` (`t = 1`).
+    pub fn mix(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: channel(self.a, other.a),
+        }
+    }
 }
 
 impl Into<wgpu::Color> for RGBA {
@@ -71,4 +104,108 @@ impl RGBA {
         b: 128,
         a: 255,
     };
+
+    pub const RED: Self = Self {
+        r: 220,
+        g: 30,
+        b: 30,
+        a: 255,
+    };
+}
+
+/// Categorical palettes which stay distinguishable with common color vision deficiencies.
+pub struct Palette;
+impl Palette {
+    /// Okabe & Ito, "Color Universal Design" (black moved to the end).
+    pub const OKABE_ITO: [RGBA; 8] = [
+        RGBA::new(230, 159, 0, 255),
+        RGBA::new(86, 180, 233, 255),
+        RGBA::new(0, 158, 115, 255),
+        RGBA::new(240, 228, 66, 255),
+        RGBA::new(0, 114, 178, 255),
+        RGBA::new(213, 94, 0, 255),
+        RGBA::new(204, 121, 167, 255),
+        RGBA::new(0, 0, 0, 255),
+    ];
+
+    /// Paul Tol's bright qualitative scheme.
+    pub const TOL_BRIGHT: [RGBA; 7] = [
+        RGBA::new(68, 119, 170, 255),
+        RGBA::new(238, 102, 119, 255),
+        RGBA::new(34, 136, 51, 255),
+        RGBA::new(204, 187, 68, 255),
+        RGBA::new(102, 204, 238, 255),
+        RGBA::new(170, 51, 119, 255),
+        RGBA::new(187, 187, 187, 255),
+    ];
+
+    /// Samples of the sequential viridis color map, dark to bright.
+    pub const VIRIDIS: [RGBA; 5] = [
+        RGBA::new(68, 1, 84, 255),
+        RGBA::new(59, 82, 139, 255),
+        RGBA::new(33, 145, 140, 255),
+        RGBA::new(94, 201, 98, 255),
+        RGBA::new(253, 231, 37, 255),
+    ];
+
+    /// Color at `t` (`0..1`) of the evenly spaced `colors`.
+    pub fn interpolate(colors: &[RGBA], t: f32) -> RGBA {
+        let Some(last) = colors.len().checked_sub(1) else {
+            return RGBA::BLACK;
+        };
+
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position.floor() as usize).min(last.saturating_sub(1));
+
+        match colors.get(index + 1) {
+            Some(next) => colors[index].mix(*next, position - index as f32),
+            None => colors[index],
+        }
+    }
+
+    /// Paul Tol's vibrant qualitative scheme.
+    pub const TOL_VIBRANT: [RGBA; 7] = [
+        RGBA::new(238, 119, 51, 255),
+        RGBA::new(0, 119, 187, 255),
+        RGBA::new(51, 187, 238, 255),
+        RGBA::new(238, 51, 119, 255),
+        RGBA::new(204, 51, 17, 255),
+        RGBA::new(0, 153, 136, 255),
+        RGBA::new(187, 187, 187, 255),
+    ];
+}
+
+/// Simulated color vision deficiency applied to the whole multiview,
+/// see `GPUMultiView::set_color_vision_filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorVisionFilter {
+    #[default]
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorVisionFilter {
+    /// Linear RGB simulation matrix (rows) of Machado et al. 2009 at full severity.
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            Self::None => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
 }
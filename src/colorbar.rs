@@ -0,0 +1,397 @@
+use std::{cell::RefCell, sync::Arc};
+
+use crate::color::{Palette, RGBA};
+use crate::gpucanvas_2d::{format_readout_value, GPUCanvas2D};
+use crate::gpuview::{GPUView, GPUViewFrame};
+use crate::graph::{DimensionStyle, EnviromentStyle, GraphId, GridSpacing, TextStyle, Thickness};
+use crate::interval::Interval;
+use crate::layer::{CanvasLayer, LayerPainter};
+
+/// How values are distributed over the color map of a `ColorScale`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorTransform {
+    #[default]
+    Linear,
+    /// Logarithmic, values below or at `0` get the first color. A range starting at or
+    /// below `0` starts at the smallest positive value of the last `fit` instead.
+    Log,
+    Sqrt,
+    /// Linear between the `low` and `high` percentile (`0..100`) of the last `fit`, so
+    /// outliers don't squeeze the rest of the data into a few colors.
+    PercentileClip {
+        low: f64,
+        high: f64,
+    },
+    /// Every color is used by about the same number of values of the last `fit`.
+    HistogramEqualization,
+}
+
+/// Maps values of `range` onto a color map, shared between the colored data and its
+/// `ColorbarView` (see `ColorScale::shared`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorScale {
+    /// Color map from `range.start()` to `range.end()`, interpolated linearly.
+    pub colors: Vec<RGBA>,
+    pub range: Interval,
+    /// Color of NaN values, e.g. missing data. Transparent by default, so the background
+    /// shows through.
+    pub invalid_color: RGBA,
+    transform: ColorTransform,
+    /// Evenly spaced quantiles of the values passed to `fit`, empty before.
+    quantiles: Vec<f64>,
+    /// Start of logarithmic ranges which start at or below `0`.
+    smallest_positive: Option<f64>,
+}
+
+impl Default for ColorScale {
+    fn default() -> Self {
+        Self::new(&Palette::VIRIDIS, Interval::new(0.0, 1.0))
+    }
+}
+
+impl ColorScale {
+    /// Number of intervals the distribution of the fitted values is summarized with.
+    const QUANTILES: usize = 256;
+
+    pub fn new(colors: &[RGBA], range: impl Into<Interval>) -> Self {
+        Self {
+            colors: colors.to_vec(),
+            range: range.into(),
+            invalid_color: RGBA::TRANSPARENT,
+            transform: ColorTransform::Linear,
+            quantiles: Vec::new(),
+            smallest_positive: None,
+        }
+    }
+
+    pub fn with_invalid_color(mut self, invalid_color: RGBA) -> Self {
+        self.invalid_color = invalid_color;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: ColorTransform) -> Self {
+        self.set_transform(transform);
+        self
+    }
+
+    pub fn shared(self) -> Arc<RefCell<ColorScale>> {
+        Arc::new(RefCell::new(self))
+    }
+
+    pub fn set_range(&mut self, range: impl Into<Interval>) {
+        self.range = range.into();
+    }
+
+    pub fn transform(&self) -> ColorTransform {
+        self.transform
+    }
+
+    /// Switches the transform, a percentile clip recomputes the range from the last `fit`.
+    pub fn set_transform(&mut self, transform: ColorTransform) {
+        self.transform = transform;
+        self.fit_range();
+    }
+
+    /// Summarizes the distribution of the finite `values` and sets the range to their
+    /// minimum and maximum (or the percentiles of `ColorTransform::PercentileClip`).
+    /// Keeps the range if there are no finite values.
+    pub fn fit(&mut self, values: impl IntoIterator<Item = f64>) {
+        let mut values: Vec<f64> = values
+            .into_iter()
+            .filter(|value| value.is_finite())
+            .collect();
+        if values.is_empty() {
+            return;
+        }
+        values.sort_by(f64::total_cmp);
+
+        let last = values.len() - 1;
+        self.quantiles = (0..=Self::QUANTILES)
+            .map(|i| {
+                let position = i as f64 / Self::QUANTILES as f64 * last as f64;
+                let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+                values[lower] + (values[upper] - values[lower]) * position.fract()
+            })
+            .collect();
+
+        self.smallest_positive = values.iter().copied().find(|value| *value > 0.0);
+
+        self.fit_range();
+    }
+
+    fn fit_range(&mut self) {
+        let (Some(min), Some(max)) = (self.quantiles.first(), self.quantiles.last()) else {
+            return;
+        };
+
+        self.range = match self.transform {
+            ColorTransform::PercentileClip { low, high } => {
+                Interval::new(self.quantile(low / 100.0), self.quantile(high / 100.0))
+            }
+            _ => Interval::new(*min, *max),
+        };
+    }
+
+    /// Value below which the fraction `q` (`0..1`) of the fitted values lies.
+    fn quantile(&self, q: f64) -> f64 {
+        let position = q.clamp(0.0, 1.0) * Self::QUANTILES as f64;
+        let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+        self.quantiles[lower] + (self.quantiles[upper] - self.quantiles[lower]) * position.fract()
+    }
+
+    /// Evenly spaced quantiles of the values of the last `fit`, empty before.
+    pub(crate) fn quantiles(&self) -> &[f64] {
+        &self.quantiles
+    }
+
+    /// Position of `value` on the color map, `0` at the first and `1` at the last color.
+    pub fn normalize(&self, value: f64) -> f64 {
+        if self.range.is_degenerate() || value.is_nan() {
+            return 0.5;
+        }
+
+        let linear = self.range.inverse_lerp(value).clamp(0.0, 1.0);
+
+        match self.transform {
+            ColorTransform::Linear | ColorTransform::PercentileClip { .. } => linear,
+            ColorTransform::Sqrt => linear.sqrt(),
+            ColorTransform::Log => {
+                let start = self.log_start();
+                let end = self.range.end();
+
+                if value <= start || end <= start {
+                    return 0.0;
+                }
+                ((value / start).ln() / (end / start).ln()).clamp(0.0, 1.0)
+            }
+            ColorTransform::HistogramEqualization => {
+                if self.quantiles.is_empty() {
+                    return linear;
+                }
+
+                // rank among the quantiles, equal quantiles share the middle of their ranks
+                let below = self.quantiles.partition_point(|q| *q < value);
+                let at_or_below = self.quantiles.partition_point(|q| *q <= value);
+
+                let rank = match (below, at_or_below) {
+                    (0, 0) => 0.0,
+                    (below, at_or_below) if below < at_or_below => {
+                        (below + at_or_below - 1) as f64 / 2.0
+                    }
+                    (below, _) if below == self.quantiles.len() => Self::QUANTILES as f64,
+                    (below, _) => {
+                        let (lower, upper) = (self.quantiles[below - 1], self.quantiles[below]);
+                        (below - 1) as f64 + (value - lower) / (upper - lower)
+                    }
+                };
+                rank / Self::QUANTILES as f64
+            }
+        }
+    }
+
+    /// Inverse of `normalize`, the value at position `t` (`0..1`) of the color map.
+    pub fn value_at(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self.transform {
+            ColorTransform::Linear | ColorTransform::PercentileClip { .. } => self.range.lerp(t),
+            ColorTransform::Sqrt => self.range.lerp(t * t),
+            ColorTransform::Log => {
+                let start = self.log_start();
+                start * (self.range.end() / start).powf(t)
+            }
+            ColorTransform::HistogramEqualization if !self.quantiles.is_empty() => self.quantile(t),
+            ColorTransform::HistogramEqualization => self.range.lerp(t),
+        }
+    }
+
+    /// Start of the logarithmic range, see `ColorTransform::Log`.
+    pub(crate) fn log_start(&self) -> f64 {
+        match self.range.start() > 0.0 {
+            true => self.range.start(),
+            false => self.smallest_positive.unwrap_or(self.range.end() * 1e-6),
+        }
+    }
+
+    /// Color of `value`, values outside of the range get the color of the closer end and
+    /// NaN gets `invalid_color`.
+    pub fn color(&self, value: f64) -> RGBA {
+        if value.is_nan() {
+            return self.invalid_color;
+        }
+
+        Palette::interpolate(&self.colors, self.normalize(value) as f32)
+    }
+}
+
+/// Legend of a `ColorScale` in its own view: a vertical gradient with the range bounds
+/// (and the middle for non linear transforms) as tick labels and an optional title above.
+///
+/// The bar follows changes of the linked scale on the next `display`. The view is placed
+/// through its frame like any other, e.g. with `FrameAnchor::Right` next to the canvas or
+/// as child view of it.
+pub struct ColorbarView {
+    canvas: GPUCanvas2D<()>,
+    scale: Arc<RefCell<ColorScale>>,
+    title: Option<String>,
+    text_style: TextStyle,
+    layer_id: Option<GraphId>,
+}
+
+impl ColorbarView {
+    /// Number of quads the gradient is made of.
+    const GRADIENT_STEPS: usize = 128;
+
+    pub fn new(view_frame: GPUViewFrame, scale: Arc<RefCell<ColorScale>>) -> Self {
+        let hidden = DimensionStyle {
+            spacing: GridSpacing::default(),
+            axis: None,
+            tick: None,
+            subtick: None,
+            grid: None,
+            subgrid: None,
+            label: None,
+            title: None,
+        };
+
+        let mut canvas = GPUCanvas2D::new(view_frame);
+        canvas.set_style(EnviromentStyle {
+            x: hidden.clone(),
+            y: hidden,
+            text: None,
+        });
+        canvas.set_range(0.0..1.0, 0.0..1.0);
+
+        let mut colorbar = Self {
+            canvas,
+            scale,
+            title: None,
+            text_style: TextStyle::default(),
+            layer_id: None,
+        };
+        colorbar.replace_layer();
+        colorbar
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self.replace_layer();
+        self
+    }
+
+    pub fn with_text_style(mut self, text_style: TextStyle) -> Self {
+        self.text_style = text_style;
+        self.replace_layer();
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: RGBA) -> Self {
+        self.canvas.set_clear_color(clear_color);
+        self
+    }
+
+    /// Replaces the linked scale.
+    pub fn link(&mut self, scale: Arc<RefCell<ColorScale>>) {
+        self.scale = scale;
+        self.replace_layer();
+    }
+
+    pub fn scale(&self) -> Arc<RefCell<ColorScale>> {
+        self.scale.clone()
+    }
+
+    pub fn get_view(&self) -> Arc<RefCell<GPUView>> {
+        self.canvas.get_view()
+    }
+
+    pub fn display(&mut self) {
+        self.canvas.display();
+    }
+
+    fn replace_layer(&mut self) {
+        if let Some(id) = self.layer_id.take() {
+            self.canvas.remove_layer(id);
+        }
+
+        self.layer_id = Some(self.canvas.add_layer(Box::new(ColorbarLayer {
+            scale: self.scale.clone(),
+            title: self.title.clone(),
+            text_style: self.text_style.clone(),
+            drawn: None,
+        })));
+    }
+}
+
+struct ColorbarLayer {
+    scale: Arc<RefCell<ColorScale>>,
+    title: Option<String>,
+    text_style: TextStyle,
+    /// Scale of the last tessellation, to notice changes of the linked scale.
+    drawn: Option<ColorScale>,
+}
+
+impl CanvasLayer<()> for ColorbarLayer {
+    fn tessellate(&mut self, painter: &mut LayerPainter<()>) {
+        // in the data coordinates of the colorbar canvas, which are `0..1` in both directions
+        const BAR_X: (f64, f64) = (0.15, 0.45);
+        const BAR_Y: (f64, f64) = (0.08, 0.84);
+        const LABEL_X: f64 = 0.72;
+        const TITLE_Y: f64 = 0.93;
+
+        let scale = self.scale.borrow().clone();
+        let steps = ColorbarView::GRADIENT_STEPS;
+        let bar_height = BAR_Y.1 - BAR_Y.0;
+
+        for step in 0..steps {
+            let t0 = step as f64 / steps as f64;
+            let t1 = (step + 1) as f64 / steps as f64;
+            // evenly spaced on the color map, so non linear transforms only move the ticks
+            let color = Palette::interpolate(&scale.colors, ((t0 + t1) / 2.0) as f32);
+
+            painter.rect(
+                (BAR_X.0, BAR_Y.0 + t0 * bar_height),
+                (BAR_X.1, BAR_Y.0 + t1 * bar_height),
+                color,
+            );
+        }
+
+        let outline = [
+            (BAR_X.0, BAR_Y.0),
+            (BAR_X.1, BAR_Y.0),
+            (BAR_X.1, BAR_Y.1),
+            (BAR_X.0, BAR_Y.1),
+            (BAR_X.0, BAR_Y.0),
+        ];
+        painter.polyline(&outline, Thickness::THIN, self.text_style.color);
+
+        // the middle shows where a non linear transform moved the center of the range
+        let ticks: &[f64] = match scale.transform() {
+            ColorTransform::Linear | ColorTransform::PercentileClip { .. } => &[0.0, 1.0],
+            _ => &[0.0, 0.5, 1.0],
+        };
+
+        for &t in ticks {
+            let (value, y) = (scale.value_at(t), BAR_Y.0 + t * bar_height);
+            painter.polyline(
+                &[(BAR_X.1, y), (BAR_X.1 + 0.06, y)],
+                Thickness::THIN,
+                self.text_style.color,
+            );
+            painter.label(&format_readout_value(value), (LABEL_X, y), &self.text_style);
+        }
+
+        if let Some(title) = &self.title {
+            painter.label(title, (0.5, TITLE_Y), &self.text_style);
+        }
+
+        self.drawn = Some(scale);
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.drawn.as_ref() != Some(&*self.scale.borrow())
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+}
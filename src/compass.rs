@@ -0,0 +1,126 @@
+use crate::color::RGBA;
+use crate::layer::{CanvasLayer, LayerPainter};
+use crate::math::{Degrees, Radians};
+use crate::scale_bar::Corner;
+
+/// Needle pointing north with an "N" beyond its tip, drawn in a corner of the view.
+///
+/// North is up in geographic mode (see `GPUCanvas2D::set_geographic`), `rotation` turns the
+/// needle for rotated imagery. Add it with `GPUCanvas2D::add_layer`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NorthArrow {
+    pub corner: Corner,
+    /// Length of the needle in physical pixels
+    pub size: f32,
+    /// Distance to the edges of the view in physical pixels
+    pub margin: f32,
+    /// Counterclockwise angle between north and up
+    pub rotation: Degrees,
+    /// Text color of the enviroment if `None`
+    pub color: Option<RGBA>,
+}
+
+impl Default for NorthArrow {
+    fn default() -> Self {
+        Self {
+            corner: Corner::TopRight,
+            size: 48.0,
+            margin: 24.0,
+            rotation: Degrees(0.0),
+            color: None,
+        }
+    }
+}
+
+impl NorthArrow {
+    pub fn new(corner: Corner) -> Self {
+        Self {
+            corner,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Degrees) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_color(mut self, color: RGBA) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl<P> CanvasLayer<P> for NorthArrow
+where
+    P: Default,
+{
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>) {
+        // alpha of the right half of the needle
+        const SHADE: u8 = 96;
+
+        let (ux, uy) = painter.pixel_size();
+        let (x_range, y_range) = (painter.x_range(), painter.y_range());
+        if !(ux.is_finite() && uy.is_finite()) {
+            return;
+        }
+
+        let text_style = painter.text_style().cloned();
+        let color = self
+            .color
+            .or(text_style.as_ref().map(|text_style| text_style.color))
+            .unwrap_or(RGBA::BLACK);
+
+        // the needle turns around its center, the label needs room in every direction
+        let half = self.size as f64 / 2.0;
+        let label_room = text_style
+            .as_ref()
+            .map_or(0.0, |text_style| text_style.size as f64);
+        let inset = self.margin as f64 + half + label_room;
+        let center = (
+            match self.corner {
+                Corner::TopLeft | Corner::BottomLeft => x_range.start() + inset * ux,
+                Corner::TopRight | Corner::BottomRight => x_range.end() - inset * ux,
+            },
+            match self.corner {
+                Corner::BottomLeft | Corner::BottomRight => y_range.start() + inset * uy,
+                Corner::TopLeft | Corner::TopRight => y_range.end() - inset * uy,
+            },
+        );
+
+        // pixel offsets along (right, north) of the needle to data coordinates
+        let (sin, cos) = Radians::from(self.rotation).sin_cos();
+        let at = |right: f64, north: f64| {
+            (
+                center.0 + (right * cos - north * sin) * ux,
+                center.1 + (right * sin + north * cos) * uy,
+            )
+        };
+
+        let (tip, notch) = (at(0.0, half), at(0.0, -half / 2.0));
+        let width = half / 2.5;
+        painter.triangle([tip, at(-width, -half), notch], color);
+        painter.triangle(
+            [tip, notch, at(width, -half)],
+            RGBA::new(color.r, color.g, color.b, SHADE),
+        );
+
+        if let Some(text_style) = text_style {
+            painter.label("N", at(0.0, half + label_room * 0.6), &text_style);
+        }
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+}
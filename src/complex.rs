@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_2_PI, TAU};
+use std::sync::Arc;
+
+use crate::color::RGBA;
+use crate::expr::{BinaryOp, Expr, MathFunction};
+use crate::math::{Degrees, Radians};
+
+pub use num_complex::Complex64 as Complex;
+
+/// Maps a complex value to a color: the hue is its argument (red at `0`, counterclockwise
+/// through yellow, green and blue) and the brightness grows with its magnitude from black
+/// at `0` to white at infinity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DomainColoring {
+    /// Darkens the color around every power of two of the magnitude, so `|f(z)|` gets
+    /// contour lines.
+    pub magnitude_bands: bool,
+    /// Darkens the color around every multiple of `2π / phase_bands` of the argument,
+    /// `0` disables it.
+    pub phase_bands: u32,
+    /// Color of non finite values, e.g. at poles.
+    pub invalid_color: RGBA,
+}
+
+impl Default for DomainColoring {
+    fn default() -> Self {
+        Self {
+            magnitude_bands: false,
+            phase_bands: 0,
+            invalid_color: RGBA::grey(128),
+        }
+    }
+}
+
+impl DomainColoring {
+    pub fn with_magnitude_bands(mut self, magnitude_bands: bool) -> Self {
+        self.magnitude_bands = magnitude_bands;
+        self
+    }
+
+    pub fn with_phase_bands(mut self, phase_bands: u32) -> Self {
+        self.phase_bands = phase_bands;
+        self
+    }
+
+    pub fn color(&self, value: Complex) -> RGBA {
+        const BAND_DEPTH: f64 = 0.35;
+
+        let magnitude = value.norm();
+        if value.is_nan() || magnitude.is_nan() {
+            return self.invalid_color;
+        }
+
+        let argument = Radians(value.arg()).normalized();
+
+        // lightness of the HSL model converted to HSV, so `0` is black and `∞` white
+        let lightness = FRAC_2_PI * magnitude.atan();
+        let (saturation, mut brightness) = match lightness < 0.5 {
+            true => (1.0, 2.0 * lightness),
+            false => (2.0 * (1.0 - lightness), 1.0),
+        };
+
+        // continuous in `level`, so the interpolation between samples doesn't alias
+        let band = |level: f64| {
+            let distance = level.rem_euclid(1.0).min(1.0 - level.rem_euclid(1.0)) * 2.0;
+            1.0 - BAND_DEPTH * (1.0 - distance).powi(4)
+        };
+
+        if self.magnitude_bands && magnitude > 0.0 && magnitude.is_finite() {
+            brightness *= band(magnitude.log2());
+        }
+        if self.phase_bands > 0 {
+            brightness *= band(argument.0 / TAU * self.phase_bands as f64);
+        }
+
+        let Degrees(hue) = argument.into();
+        RGBA::from_hsv(hue as f32, saturation as f32, brightness as f32)
+    }
+}
+
+/// Complex function of `z` written as expression, e.g. `(z^2 - 1) / (z - a * i)`.
+///
+/// `i` is the imaginary unit and every other variable a real parameter. Unlike a closure it
+/// can be compiled to a shader, see `ComplexFunctionGraph::from_expr`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexExpr {
+    expr: Expr,
+    params: HashMap<String, f64>,
+}
+
+impl ComplexExpr {
+    /// Fails if a variable of `expr` other than `z` and `i` is not part of `params`.
+    pub fn new(expr: Expr, params: HashMap<String, f64>) -> anyhow::Result<Self> {
+        if let Some(missing) = expr
+            .variables()
+            .into_iter()
+            .find(|name| !matches!(*name, "z" | "i") && !params.contains_key(*name))
+        {
+            return Err(anyhow::Error::msg(format!(
+                "Parameter {} is not set.",
+                missing
+            )));
+        }
+
+        Ok(Self { expr, params })
+    }
+
+    pub fn parse(source: &str, params: HashMap<String, f64>) -> anyhow::Result<Self> {
+        Self::new(Expr::parse(source)?, params)
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn params(&self) -> &HashMap<String, f64> {
+        &self.params
+    }
+
+    pub fn set_param(&mut self, name: &str, value: f64) {
+        self.params.insert(name.to_string(), value);
+    }
+
+    pub fn eval(&self, z: Complex) -> Complex {
+        eval_complex(&self.expr, z, &self.params)
+    }
+}
+
+fn eval_complex(expr: &Expr, z: Complex, params: &HashMap<String, f64>) -> Complex {
+    match expr {
+        Expr::Number(value) => Complex::new(*value, 0.0),
+        Expr::Variable(name) => match name.as_str() {
+            "z" => z,
+            "i" => Complex::i(),
+            name => Complex::new(params.get(name).copied().unwrap_or(f64::NAN), 0.0),
+        },
+        Expr::Negate(expr) => -eval_complex(expr, z, params),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = eval_complex(lhs, z, params);
+            let rhs = eval_complex(rhs, z, params);
+
+            match op {
+                BinaryOp::Add => lhs + rhs,
+                BinaryOp::Sub => lhs - rhs,
+                BinaryOp::Mul => lhs * rhs,
+                BinaryOp::Div => lhs / rhs,
+                BinaryOp::Pow => complex_pow(lhs, rhs),
+            }
+        }
+        Expr::Call(function, argument) => {
+            let value = eval_complex(argument, z, params);
+
+            match function {
+                MathFunction::Sin => value.sin(),
+                MathFunction::Cos => value.cos(),
+                MathFunction::Tan => value.tan(),
+                MathFunction::Asin => value.asin(),
+                MathFunction::Acos => value.acos(),
+                MathFunction::Atan => value.atan(),
+                MathFunction::Sinh => value.sinh(),
+                MathFunction::Cosh => value.cosh(),
+                MathFunction::Tanh => value.tanh(),
+                MathFunction::Exp => value.exp(),
+                MathFunction::Ln => value.ln(),
+                MathFunction::Log => value.log10(),
+                MathFunction::Sqrt => value.sqrt(),
+                MathFunction::Abs => Complex::new(value.norm(), 0.0),
+                MathFunction::Floor => Complex::new(value.re.floor(), value.im.floor()),
+                MathFunction::Ceil => Complex::new(value.re.ceil(), value.im.ceil()),
+            }
+        }
+    }
+}
+
+/// Small integer exponents are multiplied out, so they are exact and `0^n` is `0`.
+/// Same as `c_pow` of the domain coloring shader.
+fn complex_pow(base: Complex, exponent: Complex) -> Complex {
+    const MAX_INTEGER_EXPONENT: f64 = 64.0;
+
+    if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= MAX_INTEGER_EXPONENT
+    {
+        return base.powi(exponent.re as i32);
+    }
+    if base == Complex::new(0.0, 0.0) {
+        return match exponent.re > 0.0 {
+            true => base,
+            false => Complex::new(f64::NAN, f64::NAN),
+        };
+    }
+
+    (exponent * base.ln()).exp()
+}
+
+/// Complex function `f(z)` drawn as a domain coloring over the visible range, where
+/// `z = x + iy`.
+///
+/// Closures are sampled on a grid of `cell_size` pixel cells whenever the range, the view
+/// size or the parameter change, the colors in between are interpolated. Graphs created
+/// with `from_expr` are evaluated for every pixel in a fragment shader instead, as long as
+/// both axes of the canvas are linear and it has no projection.
+pub struct ComplexFunctionGraph<P> {
+    pub function: Arc<dyn Fn(Complex, &P) -> Complex + Send + Sync>,
+    /// Evaluated instead of `function` if set.
+    pub expression: Option<ComplexExpr>,
+    pub coloring: DomainColoring,
+    /// Edge length of the grid cells in physical pixels.
+    pub cell_size: f32,
+}
+
+impl<P> ComplexFunctionGraph<P> {
+    pub const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+    pub fn new(function: impl Fn(Complex, &P) -> Complex + Send + Sync + 'static) -> Self {
+        Self {
+            function: Arc::new(function),
+            expression: None,
+            coloring: DomainColoring::default(),
+            cell_size: Self::DEFAULT_CELL_SIZE,
+        }
+    }
+
+    pub fn from_expr(expression: ComplexExpr) -> Self {
+        let evaluated = expression.clone();
+
+        Self {
+            expression: Some(expression),
+            ..Self::new(move |z, _| evaluated.eval(z))
+        }
+    }
+
+    /// Value of the function at `z`.
+    pub fn eval(&self, z: Complex, parameter: &P) -> Complex {
+        match &self.expression {
+            Some(expression) => expression.eval(z),
+            None => (self.function)(z, parameter),
+        }
+    }
+
+    pub fn with_coloring(mut self, coloring: DomainColoring) -> Self {
+        self.coloring = coloring;
+        self
+    }
+
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size.max(1.0);
+        self
+    }
+}
+
+impl<P> Clone for ComplexFunctionGraph<P> {
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            expression: self.expression.clone(),
+            coloring: self.coloring,
+            cell_size: self.cell_size,
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for ComplexFunctionGraph<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComplexFunctionGraph")
+            .field("expression", &self.expression)
+            .field("coloring", &self.coloring)
+            .field("cell_size", &self.cell_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complex_expr_evaluates_with_complex_semantics() {
+        let params = HashMap::from([("a".to_string(), 2.0)]);
+        let expr = ComplexExpr::parse("(z^2 + 1) / (z - a * i) + exp(i * pi)", params).unwrap();
+
+        let z = Complex::new(0.5, -1.5);
+        let expected = (z * z + 1.0) / (z - Complex::new(0.0, 2.0)) - 1.0;
+        assert!((expr.eval(z) - expected).norm() < 1e-12);
+
+        // multiplied out, so `0^2` doesn't go through the logarithm
+        let square = ComplexExpr::parse("z^2", HashMap::new()).unwrap();
+        assert_eq!(square.eval(Complex::new(0.0, 0.0)), Complex::new(0.0, 0.0));
+        assert!(ComplexExpr::parse("z + b", HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use wgpu::util::DeviceExt;
+
+use crate::complex::{ComplexExpr, DomainColoring};
+use crate::density::split;
+use crate::expr::{BinaryOp, Expr, MathFunction};
+use crate::gpuview::GPUPass;
+use crate::interval::Interval;
+use crate::resources::PipelineCache;
+
+// `fn f(z: vec2<f32>) -> vec2<f32>` is appended, see `function_source`
+const SHADER: &str = r#"
+    struct Domain {
+        // range centers split into a high and a low part
+        center_x: vec2<f32>,
+        center_y: vec2<f32>,
+        // half the visible size in data units
+        half_extent: vec2<f32>,
+        magnitude_bands: u32,
+        phase_bands: u32,
+        invalid_color: vec4<f32>,
+    };
+
+    struct VertexOutput {
+        @builtin(position) clip_position: vec4<f32>,
+        @location(0) position: vec2<f32>,
+    };
+
+    @group(0) @binding(0)
+    var<uniform> domain: Domain;
+
+    @group(0) @binding(1)
+    var<storage, read> params: array<f32>;
+
+    const PI: f32 = 3.14159265358979;
+    const TAU: f32 = 6.28318530717959;
+    const MAX_INTEGER_EXPONENT: f32 = 64.0;
+
+    // fullscreen triangle
+    @vertex
+    fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+        var out: VertexOutput;
+        let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+        out.position = uv * 2.0 - 1.0;
+        out.clip_position = vec4<f32>(out.position, 0.0, 1.0);
+        return out;
+    }
+
+    fn is_nan(value: f32) -> bool {
+        return (bitcast<u32>(value) & 0x7fffffffu) > 0x7f800000u;
+    }
+
+    fn nan() -> f32 {
+        return bitcast<f32>(0x7fc00000u);
+    }
+
+    fn c_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+    }
+
+    fn c_div(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / dot(b, b);
+    }
+
+    fn c_exp(z: vec2<f32>) -> vec2<f32> {
+        return exp(z.x) * vec2<f32>(cos(z.y), sin(z.y));
+    }
+
+    fn c_ln(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(log(length(z)), atan2(z.y, z.x));
+    }
+
+    fn c_log(z: vec2<f32>) -> vec2<f32> {
+        return c_ln(z) / log(10.0);
+    }
+
+    // same as `complex_pow`
+    fn c_pow(base: vec2<f32>, exponent: vec2<f32>) -> vec2<f32> {
+        if exponent.y == 0.0 && fract(exponent.x) == 0.0
+            && abs(exponent.x) <= MAX_INTEGER_EXPONENT {
+            var power = vec2<f32>(1.0, 0.0);
+            var factor = base;
+            var remaining = u32(abs(exponent.x));
+            while remaining > 0u {
+                if (remaining & 1u) == 1u {
+                    power = c_mul(power, factor);
+                }
+                factor = c_mul(factor, factor);
+                remaining = remaining >> 1u;
+            }
+            if exponent.x < 0.0 {
+                return c_div(vec2<f32>(1.0, 0.0), power);
+            }
+            return power;
+        }
+        if all(base == vec2<f32>(0.0)) {
+            if exponent.x > 0.0 {
+                return base;
+            }
+            return vec2<f32>(nan());
+        }
+        return c_exp(c_mul(exponent, c_ln(base)));
+    }
+
+    fn c_sqrt(z: vec2<f32>) -> vec2<f32> {
+        let r = length(z);
+        var im = sqrt(max((r - z.x) / 2.0, 0.0));
+        if z.y < 0.0 {
+            im = -im;
+        }
+        return vec2<f32>(sqrt(max((r + z.x) / 2.0, 0.0)), im);
+    }
+
+    fn c_sin(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(sin(z.x) * cosh(z.y), cos(z.x) * sinh(z.y));
+    }
+
+    fn c_cos(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(cos(z.x) * cosh(z.y), -sin(z.x) * sinh(z.y));
+    }
+
+    fn c_tan(z: vec2<f32>) -> vec2<f32> {
+        return c_div(c_sin(z), c_cos(z));
+    }
+
+    fn c_sinh(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(sinh(z.x) * cos(z.y), cosh(z.x) * sin(z.y));
+    }
+
+    fn c_cosh(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(cosh(z.x) * cos(z.y), sinh(z.x) * sin(z.y));
+    }
+
+    fn c_tanh(z: vec2<f32>) -> vec2<f32> {
+        return c_div(c_sinh(z), c_cosh(z));
+    }
+
+    // -i ln(iz + sqrt(1 - z^2))
+    fn c_asin(z: vec2<f32>) -> vec2<f32> {
+        let w = c_ln(vec2<f32>(-z.y, z.x) + c_sqrt(vec2<f32>(1.0, 0.0) - c_mul(z, z)));
+        return vec2<f32>(w.y, -w.x);
+    }
+
+    fn c_acos(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(PI / 2.0, 0.0) - c_asin(z);
+    }
+
+    // i/2 (ln(1 - iz) - ln(1 + iz))
+    fn c_atan(z: vec2<f32>) -> vec2<f32> {
+        let iz = vec2<f32>(-z.y, z.x);
+        let w = c_ln(vec2<f32>(1.0, 0.0) - iz) - c_ln(vec2<f32>(1.0, 0.0) + iz);
+        return vec2<f32>(-w.y, w.x) / 2.0;
+    }
+
+    fn c_abs(z: vec2<f32>) -> vec2<f32> {
+        return vec2<f32>(length(z), 0.0);
+    }
+
+    // same as `DomainColoring::color`
+    fn band(level: f32) -> f32 {
+        let position = level - floor(level);
+        let distance = min(position, 1.0 - position) * 2.0;
+        return 1.0 - 0.35 * pow(1.0 - distance, 4.0);
+    }
+
+    fn hsv(hue: f32, saturation: f32, value: f32) -> vec4<f32> {
+        let sector = (hue - floor(hue / 360.0) * 360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - abs(sector - floor(sector / 2.0) * 2.0 - 1.0));
+
+        var rgb: vec3<f32>;
+        switch u32(sector) {
+            case 0u: { rgb = vec3<f32>(chroma, x, 0.0); }
+            case 1u: { rgb = vec3<f32>(x, chroma, 0.0); }
+            case 2u: { rgb = vec3<f32>(0.0, chroma, x); }
+            case 3u: { rgb = vec3<f32>(0.0, x, chroma); }
+            case 4u: { rgb = vec3<f32>(x, 0.0, chroma); }
+            default: { rgb = vec3<f32>(chroma, 0.0, x); }
+        }
+        return vec4<f32>(clamp(rgb + (value - chroma), vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+    }
+
+    fn domain_color(w: vec2<f32>) -> vec4<f32> {
+        let magnitude = length(w);
+        if is_nan(w.x) || is_nan(w.y) || is_nan(magnitude) {
+            return domain.invalid_color;
+        }
+
+        var argument = atan2(w.y, w.x);
+        if argument < 0.0 {
+            argument += TAU;
+        }
+
+        let lightness = 2.0 / PI * atan(magnitude);
+        var saturation = 1.0;
+        var brightness = 1.0;
+        if lightness < 0.5 {
+            brightness = 2.0 * lightness;
+        } else {
+            saturation = 2.0 * (1.0 - lightness);
+        }
+
+        if domain.magnitude_bands != 0u && magnitude > 0.0 && magnitude <= 3.4e38 {
+            brightness *= band(log2(magnitude));
+        }
+        if domain.phase_bands > 0u {
+            brightness *= band(argument / TAU * f32(domain.phase_bands));
+        }
+
+        return hsv(degrees(argument), saturation, brightness);
+    }
+
+    @fragment
+    fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+        // the high parts are exact, so only the low part is added to the small offset
+        let offset = in.position * domain.half_extent;
+        let z = vec2<f32>(
+            domain.center_x.x + (domain.center_x.y + offset.x),
+            domain.center_y.x + (domain.center_y.y + offset.y),
+        );
+
+        return domain_color(f(z));
+    }
+"#;
+
+const BIND_GROUP_LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
+    wgpu::BindGroupLayoutDescriptor {
+        label: Some("DomainColoringPass Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    };
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct DomainUniform {
+    center_x: [f32; 2],
+    center_y: [f32; 2],
+    half_extent: [f32; 2],
+    magnitude_bands: u32,
+    phase_bands: u32,
+    invalid_color: [f32; 4],
+}
+
+/// WGSL function `f(z)` of `expression`, parameters are read from the `params` buffer in
+/// the order of `param_names`.
+fn function_source(expression: &ComplexExpr, param_names: &[&str]) -> String {
+    format!(
+        "fn f(z: vec2<f32>) -> vec2<f32> {{\n    return {};\n}}\n",
+        wgsl_expression(expression.expr(), param_names)
+    )
+}
+
+fn wgsl_expression(expr: &Expr, param_names: &[&str]) -> String {
+    match expr {
+        Expr::Number(value) => format!("vec2<f32>({}, 0.0)", wgsl_float(*value as f32)),
+        Expr::Variable(name) => match name.as_str() {
+            "z" => "z".to_string(),
+            "i" => "vec2<f32>(0.0, 1.0)".to_string(),
+            name => match param_names.iter().position(|param| *param == name) {
+                Some(index) => format!("vec2<f32>(params[{}], 0.0)", index),
+                None => "vec2<f32>(nan())".to_string(),
+            },
+        },
+        Expr::Negate(expr) => format!("(-{})", wgsl_expression(expr, param_names)),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = wgsl_expression(lhs, param_names);
+            let rhs = wgsl_expression(rhs, param_names);
+
+            match op {
+                BinaryOp::Add => format!("({} + {})", lhs, rhs),
+                BinaryOp::Sub => format!("({} - {})", lhs, rhs),
+                BinaryOp::Mul => format!("c_mul({}, {})", lhs, rhs),
+                BinaryOp::Div => format!("c_div({}, {})", lhs, rhs),
+                BinaryOp::Pow => format!("c_pow({}, {})", lhs, rhs),
+            }
+        }
+        Expr::Call(function, argument) => {
+            let argument = wgsl_expression(argument, param_names);
+
+            let name = match function {
+                MathFunction::Sin => "c_sin",
+                MathFunction::Cos => "c_cos",
+                MathFunction::Tan => "c_tan",
+                MathFunction::Asin => "c_asin",
+                MathFunction::Acos => "c_acos",
+                MathFunction::Atan => "c_atan",
+                MathFunction::Sinh => "c_sinh",
+                MathFunction::Cosh => "c_cosh",
+                MathFunction::Tanh => "c_tanh",
+                MathFunction::Exp => "c_exp",
+                MathFunction::Ln => "c_ln",
+                MathFunction::Log => "c_log",
+                MathFunction::Sqrt => "c_sqrt",
+                MathFunction::Abs => "c_abs",
+                MathFunction::Floor => "floor",
+                MathFunction::Ceil => "ceil",
+            };
+            format!("{}({})", name, argument)
+        }
+    }
+}
+
+fn wgsl_float(value: f32) -> String {
+    match value.is_finite() {
+        true => format!("{:?}", value),
+        false => format!("bitcast<f32>({}u)", value.to_bits()),
+    }
+}
+
+/// Per pixel domain coloring of a `ComplexFunctionGraph` created from an expression, shared
+/// with the view of the canvas as `GPUPass`.
+pub(crate) struct DomainColoringPass {
+    /// Set while the canvas draws the graph with this pass instead of sampling it.
+    pub visible: bool,
+
+    source: String,
+    params: Vec<f32>,
+    uniform: DomainUniform,
+
+    resources: Option<DomainColoringResources>,
+}
+
+impl DomainColoringPass {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            source: String::new(),
+            params: Vec::new(),
+            uniform: DomainUniform::default(),
+            resources: None,
+        }
+    }
+
+    /// Shows `expression` over the visible `x_range` and `y_range`.
+    pub fn set(
+        &mut self,
+        expression: &ComplexExpr,
+        coloring: &DomainColoring,
+        x_range: Interval,
+        y_range: Interval,
+    ) {
+        let mut param_names: Vec<&str> = expression.params().keys().map(String::as_str).collect();
+        param_names.sort();
+
+        self.source = function_source(expression, &param_names);
+        self.params = param_names
+            .iter()
+            .map(|name| expression.params()[*name] as f32)
+            .collect();
+
+        self.uniform = DomainUniform {
+            center_x: split(x_range.center()),
+            center_y: split(y_range.center()),
+            half_extent: [x_range.half_width() as f32, y_range.half_width() as f32],
+            magnitude_bands: coloring.magnitude_bands as u32,
+            phase_bands: coloring.phase_bands,
+            invalid_color: coloring.invalid_color.into(),
+        };
+    }
+}
+
+impl GPUPass for DomainColoringPass {
+    fn prepare(
+        &mut self,
+        _size: (u32, u32),
+        multisample_state: wgpu::MultisampleState,
+        pipeline_cache: &PipelineCache,
+        _encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        if self.resources.as_ref().is_none_or(|resources| {
+            resources.multisample_state != multisample_state
+                || resources.source != self.source
+                || resources.param_count != self.params.len()
+        }) {
+            self.resources = Some(DomainColoringResources::new(
+                &self.source,
+                self.params.len(),
+                multisample_state,
+                pipeline_cache,
+                device,
+            ));
+        }
+        let resources = self.resources.as_ref().unwrap();
+
+        queue.write_buffer(
+            &resources.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&self.uniform),
+        );
+        if !self.params.is_empty() {
+            queue.write_buffer(
+                &resources.params_buffer,
+                0,
+                bytemuck::cast_slice(&self.params),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        let Some(resources) = self.resources.as_ref().filter(|_| self.visible) else {
+            return;
+        };
+
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, &resources.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn memory_bytes(&self) -> u64 {
+        self.resources.as_ref().map_or(0, |resources| {
+            resources.uniform_buffer.size() + resources.params_buffer.size()
+        })
+    }
+}
+
+struct DomainColoringResources {
+    multisample_state: wgpu::MultisampleState,
+    source: String,
+    param_count: usize,
+
+    pipeline: std::sync::Arc<wgpu::RenderPipeline>,
+    uniform_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl DomainColoringResources {
+    fn new(
+        source: &str,
+        param_count: usize,
+        multisample_state: wgpu::MultisampleState,
+        pipeline_cache: &PipelineCache,
+        device: &wgpu::Device,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&BIND_GROUP_LAYOUT_DESCRIPTOR);
+
+        // parameters are bound, so only the structure of the expression needs a pipeline
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let key = format!(
+            "DomainColoringPass Pipeline {:?} {:016x}",
+            multisample_state,
+            hasher.finish()
+        );
+
+        let pipeline = pipeline_cache.get_or_create(&key, || {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("DomainColoringPass Shader Module"),
+                source: wgpu::ShaderSource::Wgsl(format!("{SHADER}{source}").into()),
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("DomainColoringPass Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("DomainColoringPass Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: Some("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: multisample_state,
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: Some("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+                cache: None,
+            })
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("DomainColoringPass Uniform Buffer"),
+            size: std::mem::size_of::<DomainUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // storage bindings can't be empty
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("DomainColoringPass Params Buffer"),
+            contents: bytemuck::cast_slice(&vec![0f32; param_count.max(1)]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("DomainColoringPass Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            multisample_state,
+            source: source.to_string(),
+            param_count,
+            pipeline,
+            uniform_buffer,
+            params_buffer,
+            bind_group,
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use pollster::FutureExt;
+use winit::window::Window;
+
+use crate::gpudevice::{request_device, AdapterConfig, DeviceCapabilities, FeatureRequest};
+use crate::gpuview::GPUMultiView;
+use crate::resources::{FontRegistry, PipelineCache, TextBrushRegistry, TextureRegistry};
+use crate::timing::FrameClock;
+
+/// Owner of the wgpu instance, device and queue together with the session wide resources
+/// and the frame clock.
+pub struct VisContext {
+    window: Option<Arc<Window>>,
+
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    capabilities: DeviceCapabilities,
+
+    surface: Option<wgpu::Surface<'static>>,
+    surface_config: Option<wgpu::SurfaceConfiguration>,
+
+    fonts: FontRegistry,
+    textures: TextureRegistry,
+    pipelines: PipelineCache,
+    text_brushes: TextBrushRegistry,
+
+    clock: FrameClock,
+    deterministic: bool,
+}
+
+impl VisContext {
+    pub const SURFACE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8Unorm;
+
+    /// Clock step of the deterministic mode, see `set_deterministic`.
+    pub const DETERMINISTIC_FRAME_STEP: Duration = Duration::from_micros(16_667);
+
+    pub fn from_window(window: Window) -> anyhow::Result<Self> {
+        Self::from_window_with_config(window, &AdapterConfig::default(), FeatureRequest::CANVAS_2D)
+    }
+
+    pub fn from_window_with_config(
+        window: Window,
+        adapter_config: &AdapterConfig,
+        features: FeatureRequest,
+    ) -> anyhow::Result<Self> {
+        Self::from_window_async(window, adapter_config, features).block_on()
+    }
+
+    pub async fn from_window_async(
+        window: Window,
+        adapter_config: &AdapterConfig,
+        features: FeatureRequest,
+    ) -> anyhow::Result<Self> {
+        let window = Arc::new(window);
+
+        let instance = adapter_config.create_instance();
+
+        let surface = instance
+            .create_surface(window.clone())
+            .context("Surface creation failed.")?;
+
+        let adapter = adapter_config
+            .request_adapter(&instance, Some(&surface))
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        let (device, queue, capabilities) = request_device(
+            &adapter,
+            features.union(FeatureRequest {
+                required: Self::SURFACE_FORMAT.required_features(),
+                optional: wgpu::Features::empty(),
+            }),
+            None,
+        )
+        .await?;
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: Self::SURFACE_FORMAT,
+            width: window.inner_size().width,
+            height: window.inner_size().height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        // a minimized window reports a zero size, the surface gets configured on the first resize
+        if surface_config.width > 0 && surface_config.height > 0 {
+            surface.configure(&device, &surface_config);
+        }
+
+        Ok(Self {
+            window: Some(window),
+            instance,
+            adapter,
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            capabilities,
+            surface: Some(surface),
+            surface_config: Some(surface_config),
+            fonts: FontRegistry::shared(),
+            textures: TextureRegistry::new(),
+            pipelines: PipelineCache::new(),
+            text_brushes: TextBrushRegistry::new(),
+            clock: FrameClock::new(),
+            deterministic: false,
+        })
+    }
+
+    /// Context without a window, used for offscreen rendering.
+    pub fn headless() -> anyhow::Result<Self> {
+        Self::headless_with_config(&AdapterConfig::default(), FeatureRequest::CANVAS_2D)
+    }
+
+    pub fn headless_with_config(
+        adapter_config: &AdapterConfig,
+        features: FeatureRequest,
+    ) -> anyhow::Result<Self> {
+        Self::headless_async(adapter_config, features).block_on()
+    }
+
+    pub async fn headless_async(
+        adapter_config: &AdapterConfig,
+        features: FeatureRequest,
+    ) -> anyhow::Result<Self> {
+        let instance = adapter_config.create_instance();
+
+        let adapter = adapter_config.request_adapter(&instance, None).await?;
+
+        let (device, queue, capabilities) = request_device(&adapter, features, None).await?;
+
+        Ok(Self {
+            window: None,
+            instance,
+            adapter,
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            capabilities,
+            surface: None,
+            surface_config: None,
+            fonts: FontRegistry::shared(),
+            textures: TextureRegistry::new(),
+            pipelines: PipelineCache::new(),
+            text_brushes: TextBrushRegistry::new(),
+            clock: FrameClock::new(),
+            deterministic: false,
+        })
+    }
+
+    /// Creates a multiview presenting to the window surface of this context.
+    ///
+    /// The surface can only be handed out once.
+    pub fn create_multiview(&mut self) -> anyhow::Result<GPUMultiView<'static>> {
+        let surface = self
+            .surface
+            .take()
+            .context("Context has no (remaining) window surface.")?;
+        let surface_config = self.surface_config.clone().unwrap();
+
+        let mut multiview = GPUMultiView::new();
+        multiview.set_font_registry(self.fonts.clone());
+        multiview.set_texture_registry(self.textures.clone());
+        multiview.set_pipeline_cache(self.pipelines.clone());
+        multiview.set_text_brushes(self.text_brushes.clone());
+        multiview.set_deterministic(self.deterministic);
+        multiview.initialize(surface, surface_config, &self.device);
+
+        Ok(multiview)
+    }
+
+    /// Creates a new surface for the window, e.g. to pass to `GPUMultiView::restore_surface`
+    /// when the application gets resumed after a suspend.
+    pub fn recreate_surface(
+        &self,
+    ) -> anyhow::Result<(wgpu::Surface<'static>, wgpu::SurfaceConfiguration)> {
+        let window = self
+            .window
+            .as_ref()
+            .context("Context has no window to create a surface for.")?;
+
+        let surface = self
+            .instance
+            .create_surface(window.clone())
+            .context("Surface creation failed.")?;
+
+        let surface_caps = surface.get_capabilities(&self.adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: Self::SURFACE_FORMAT,
+            width: window.inner_size().width,
+            height: window.inner_size().height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        Ok((surface, surface_config))
+    }
+
+    /// Creates a multiview rendering into an offscreen texture of the given size.
+    pub fn create_offscreen_multiview(&self, width: u32, height: u32) -> GPUMultiView<'static> {
+        let mut multiview = GPUMultiView::new();
+        multiview.set_font_registry(self.fonts.clone());
+        multiview.set_texture_registry(self.textures.clone());
+        multiview.set_pipeline_cache(self.pipelines.clone());
+        multiview.set_text_brushes(self.text_brushes.clone());
+        multiview.set_deterministic(self.deterministic);
+        multiview.initialize_offscreen(width, height, &self.device);
+
+        multiview
+    }
+
+    pub fn window(&self) -> Option<&Arc<Window>> {
+        self.window.as_ref()
+    }
+
+    pub fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    pub fn instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    pub fn device(&self) -> &Arc<wgpu::Device> {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &Arc<wgpu::Queue> {
+        &self.queue
+    }
+
+    pub fn capabilities(&self) -> &DeviceCapabilities {
+        &self.capabilities
+    }
+
+    pub fn fonts(&self) -> &FontRegistry {
+        &self.fonts
+    }
+
+    pub fn textures(&self) -> &TextureRegistry {
+        &self.textures
+    }
+
+    pub fn pipelines(&self) -> &PipelineCache {
+        &self.pipelines
+    }
+
+    pub fn text_brushes(&self) -> &TextBrushRegistry {
+        &self.text_brushes
+    }
+
+    pub fn clock(&self) -> &FrameClock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut FrameClock {
+        &mut self.clock
+    }
+
+    /// Deterministic mode for golden image tests and reproducible exports: the clock advances
+    /// by `DETERMINISTIC_FRAME_STEP` per frame and multiviews created afterwards render
+    /// single sampled with every frame finished before the next one is encoded.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+        self.clock = match deterministic {
+            true => FrameClock::fixed(Self::DETERMINISTIC_FRAME_STEP),
+            false => FrameClock::new(),
+        };
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+/// Grid edge a contour crosses, `(column, row, vertical)` of its lower left grid point.
+type EdgeKey = (usize, usize, bool);
+
+/// Zero contours of the values of a `(columns + 1) x (rows + 1)` grid (row major, rows from
+/// bottom to top) with marching squares.
+///
+/// Returns polylines in grid coordinates (`0..=columns`, `0..=rows`), closed ones end with
+/// their first point. Cells with a non finite corner are skipped, which splits the contours.
+pub(crate) fn zero_contours(values: &[f64], columns: usize, rows: usize) -> Vec<Vec<(f64, f64)>> {
+    let value = |column: usize, row: usize| values[row * (columns + 1) + column];
+
+    let mut crossings: HashMap<EdgeKey, (f64, f64)> = HashMap::new();
+    let mut crossing = |edge: EdgeKey| {
+        *crossings.entry(edge).or_insert_with(|| {
+            let (column, row, vertical) = edge;
+            let (a, b) = match vertical {
+                true => (value(column, row), value(column, row + 1)),
+                false => (value(column, row), value(column + 1, row)),
+            };
+            let t = a / (a - b);
+
+            match vertical {
+                true => (column as f64, row as f64 + t),
+                false => (column as f64 + t, row as f64),
+            }
+        })
+    };
+
+    //-- cells
+
+    let mut segments: Vec<(EdgeKey, EdgeKey)> = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let corners = [
+                value(column, row),
+                value(column + 1, row),
+                value(column + 1, row + 1),
+                value(column, row + 1),
+            ];
+            if corners.iter().any(|value| !value.is_finite()) {
+                continue;
+            }
+
+            let [v00, v10, v11, v01] = corners.map(|value| value > 0.0);
+            let bottom = (column, row, false);
+            let right = (column + 1, row, true);
+            let top = (column, row + 1, false);
+            let left = (column, row, true);
+
+            let crossed: Vec<EdgeKey> = [
+                (bottom, v00, v10),
+                (right, v10, v11),
+                (top, v01, v11),
+                (left, v00, v01),
+            ]
+            .into_iter()
+            .filter(|(_, a, b)| a != b)
+            .map(|(edge, _, _)| edge)
+            .collect();
+
+            match crossed.len() {
+                2 => segments.push((crossed[0], crossed[1])),
+                // saddle, the center decides which corners are connected
+                4 => match (corners.iter().sum::<f64>() > 0.0) == v00 {
+                    true => segments.extend([(bottom, right), (top, left)]),
+                    false => segments.extend([(left, bottom), (right, top)]),
+                },
+                _ => (),
+            }
+        }
+    }
+
+    //-- chaining
+
+    let mut adjacent: HashMap<EdgeKey, Vec<usize>> = HashMap::new();
+    for (index, (a, b)) in segments.iter().enumerate() {
+        adjacent.entry(*a).or_default().push(index);
+        adjacent.entry(*b).or_default().push(index);
+    }
+
+    let mut visited = vec![false; segments.len()];
+    let next = |edge: EdgeKey, visited: &mut [bool]| {
+        let index = *adjacent[&edge].iter().find(|index| !visited[**index])?;
+        visited[index] = true;
+
+        let (a, b) = segments[index];
+        Some(if a == edge { b } else { a })
+    };
+
+    let mut polylines = Vec::new();
+    for index in 0..segments.len() {
+        if visited[index] {
+            continue;
+        }
+        visited[index] = true;
+
+        let (a, b) = segments[index];
+        let mut forward = vec![a, b];
+        while let Some(edge) = next(*forward.last().unwrap(), &mut visited) {
+            forward.push(edge);
+        }
+        // open contours may continue on the other side as well
+        let mut backward = Vec::new();
+        let mut edge = a;
+        while let Some(previous) = next(edge, &mut visited) {
+            backward.push(previous);
+            edge = previous;
+        }
+
+        polylines.push(
+            backward
+                .into_iter()
+                .rev()
+                .chain(forward)
+                .map(&mut crossing)
+                .collect(),
+        );
+    }
+
+    polylines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values of `f` on a `(size + 1) x (size + 1)` grid over `-2..=2` in both directions.
+    fn grid(size: usize, f: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+        let position = |index: usize| -2.0 + 4.0 * index as f64 / size as f64;
+
+        (0..=size)
+            .flat_map(|row| (0..=size).map(move |column| (column, row)))
+            .map(|(column, row)| f(position(column), position(row)))
+            .collect()
+    }
+
+    fn on_border((x, y): (f64, f64), size: usize) -> bool {
+        let size = size as f64;
+        x == 0.0 || y == 0.0 || x == size || y == size
+    }
+
+    #[test]
+    fn circle_is_one_closed_loop() {
+        let size = 16;
+        let contours = zero_contours(&grid(size, |x, y| x * x + y * y - 1.0), size, size);
+
+        assert_eq!(contours.len(), 1);
+        let contour = &contours[0];
+        assert_eq!(contour.first(), contour.last());
+
+        for (x, y) in contour {
+            let radius = (x * 4.0 / size as f64 - 2.0).hypot(y * 4.0 / size as f64 - 2.0);
+            assert!((radius - 1.0).abs() < 0.05, "radius {radius}");
+        }
+    }
+
+    #[test]
+    fn line_is_one_open_polyline() {
+        let size = 8;
+        let contours = zero_contours(&grid(size, |x, y| x - y + 0.3), size, size);
+
+        assert_eq!(contours.len(), 1);
+        let contour = &contours[0];
+        assert_ne!(contour.first(), contour.last());
+        assert!(on_border(contour[0], size));
+        assert!(on_border(*contour.last().unwrap(), size));
+    }
+
+    #[test]
+    fn saddle_cell_has_two_segments() {
+        // positive lower left and upper right corner, negative center
+        let contours = zero_contours(&[1.0, -1.0, -1.0, 0.5], 1, 1);
+
+        assert_eq!(contours.len(), 2);
+        assert!(contours.iter().all(|contour| contour.len() == 2));
+    }
+}
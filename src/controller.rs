@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::gpuview::GPUMultiView;
+use crate::measure::{AngleAnnotation, MeasureTool, Measurement};
+use crate::snap::{Snap, Snapping};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NavigationAction {
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    /// See `GPUCanvas2D::undo_range`.
+    Undo,
+    Redo,
+    /// See `GPUCanvas2D::reset_range`.
+    Home,
+}
+
+/// Key together with the modifiers which have to be held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyChord {
+    pub key: KeyCode,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modifiers: ModifiersState,
+}
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: ModifiersState) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+}
+
+impl From<KeyCode> for KeyChord {
+    fn from(key: KeyCode) -> Self {
+        Self::new(key)
+    }
+}
+
+/// Remappable table of the key chords triggering each `NavigationAction`.
+///
+/// The defaults are WASD for panning and `+`/`-` for zooming.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyBindings {
+    bindings: Vec<(KeyChord, NavigationAction)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::empty()
+            .with(KeyCode::KeyA, NavigationAction::PanLeft)
+            .with(KeyCode::KeyD, NavigationAction::PanRight)
+            .with(KeyCode::KeyW, NavigationAction::PanUp)
+            .with(KeyCode::KeyS, NavigationAction::PanDown)
+            .with(KeyCode::Equal, NavigationAction::ZoomIn)
+            .with(KeyCode::NumpadAdd, NavigationAction::ZoomIn)
+            .with(KeyCode::Minus, NavigationAction::ZoomOut)
+            .with(KeyCode::NumpadSubtract, NavigationAction::ZoomOut)
+            .with(
+                KeyChord::new(KeyCode::KeyZ).with_modifiers(ModifiersState::CONTROL),
+                NavigationAction::Undo,
+            )
+            .with(
+                KeyChord::new(KeyCode::KeyY).with_modifiers(ModifiersState::CONTROL),
+                NavigationAction::Redo,
+            )
+            .with(
+                KeyChord::new(KeyCode::KeyZ)
+                    .with_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT),
+                NavigationAction::Redo,
+            )
+            .with(KeyCode::Home, NavigationAction::Home)
+    }
+}
+
+impl KeyBindings {
+    /// Bindings without any chords, navigation is then only possible with the mouse.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, chord: impl Into<KeyChord>, action: NavigationAction) -> Self {
+        self.bind(chord, action);
+        self
+    }
+
+    /// Binds `chord` to `action`, replacing a previous binding of `chord`.
+    pub fn bind(&mut self, chord: impl Into<KeyChord>, action: NavigationAction) {
+        let chord = chord.into();
+        self.unbind(chord);
+        self.bindings.push((chord, action));
+    }
+
+    pub fn unbind(&mut self, chord: impl Into<KeyChord>) {
+        let chord = chord.into();
+        self.bindings.retain(|(bound, _)| *bound != chord);
+    }
+
+    /// Removes all chords bound to `action`.
+    pub fn unbind_action(&mut self, action: NavigationAction) {
+        self.bindings.retain(|(_, bound)| *bound != action);
+    }
+
+    pub fn action(&self, chord: KeyChord) -> Option<NavigationAction> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == chord)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn chords(&self, action: NavigationAction) -> impl Iterator<Item = KeyChord> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(chord, _)| *chord)
+    }
+}
+
+/// Translates window input into navigation (panning and zooming) of a `GPUCanvas2D`.
+#[derive(Debug, Clone)]
+pub struct CanvasController {
+    /// Fraction of the range the canvas is moved per key press
+    pub pan_step: f64,
+    /// Relative zoom per scrolled line
+    pub zoom_step: f64,
+    /// Show the values of all function graphs at the cursor position
+    pub cursor_readout: bool,
+    /// Draw a crosshair at the cursor position, see `GPUCanvas2D::set_crosshair_style`
+    pub crosshair: bool,
+    /// Snapping of the cursor position, disabled by default
+    pub snapping: Snapping,
+    /// Left clicks pick the points of a measurement instead of panning, escape clears it
+    pub measure_tool: Option<MeasureTool>,
+    pub key_bindings: KeyBindings,
+
+    modifiers: ModifiersState,
+    mouse_pos: PhysicalPosition<f64>,
+    mouse_left: bool,
+    /// Widget dragged with the mouse
+    mouse_widget: Option<usize>,
+    /// Widgets dragged by touch id
+    touch_widgets: HashMap<u64, usize>,
+    /// Points of the measurement which is currently picked
+    measure_points: Vec<(f64, f64)>,
+}
+
+impl Default for CanvasController {
+    fn default() -> Self {
+        Self {
+            pan_step: 0.1,
+            zoom_step: 0.05,
+            cursor_readout: false,
+            crosshair: false,
+            snapping: Snapping::default(),
+            measure_tool: None,
+            key_bindings: KeyBindings::default(),
+            modifiers: ModifiersState::empty(),
+            mouse_pos: PhysicalPosition { x: 0.0, y: 0.0 },
+            mouse_left: false,
+            mouse_widget: None,
+            touch_widgets: HashMap::new(),
+            measure_points: Vec::new(),
+        }
+    }
+}
+
+impl CanvasController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mouse_position(&self) -> PhysicalPosition<f64> {
+        self.mouse_pos
+    }
+
+    /// Mouse position in multiview screen space (`-1..1`).
+    pub fn mouse_position_screen(&self, multiview: &GPUMultiView) -> Option<(f32, f32)> {
+        Self::physical_to_screen(self.mouse_pos, multiview)
+    }
+
+    /// Snap target of `snapping` at `position` (physical pixels).
+    pub fn snap_at<P: Default>(
+        &self,
+        canvas: &GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+        position: (f64, f64),
+    ) -> Option<Snap> {
+        let data = canvas.pixel_to_data(position, multiview)?;
+        canvas.snap(data, &self.snapping)
+    }
+
+    /// Data coordinates at `position` (physical pixels), snapped if a target is in reach.
+    pub fn snapped_data<P: Default>(
+        &self,
+        canvas: &GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+        position: (f64, f64),
+    ) -> Option<(f64, f64)> {
+        let data = canvas.pixel_to_data(position, multiview)?;
+
+        Some(
+            canvas
+                .snap(data, &self.snapping)
+                .map_or(data, |snap| snap.position),
+        )
+    }
+
+    /// Mouse position in data coordinates of `canvas`, see `snapped_data`.
+    pub fn cursor_data<P: Default>(
+        &self,
+        canvas: &GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+    ) -> Option<(f64, f64)> {
+        self.snapped_data(canvas, multiview, (self.mouse_pos.x, self.mouse_pos.y))
+    }
+
+    fn physical_to_screen(
+        position: PhysicalPosition<f64>,
+        multiview: &GPUMultiView,
+    ) -> Option<(f32, f32)> {
+        multiview.pixel_to_screen((position.x, position.y))
+    }
+
+    /// Presses the widget at `position` (physical pixels) and returns its index.
+    fn widget_press<P: Default>(
+        canvas: &mut GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+        position: (f64, f64),
+    ) -> Option<usize> {
+        let point = canvas.pixel_to_view(position, multiview)?;
+        let index = canvas.widget_at(point)?;
+
+        canvas.widget_input(index, point, true);
+
+        Some(index)
+    }
+
+    fn widget_drag<P: Default>(
+        canvas: &mut GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+        index: usize,
+        position: (f64, f64),
+    ) {
+        if let Some(point) = canvas.pixel_to_view(position, multiview) {
+            canvas.widget_input(index, point, false);
+        }
+    }
+
+    /// Places the next point of the measurement at `position` (physical pixels).
+    fn measure_press<P: Default>(
+        &mut self,
+        canvas: &mut GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+        tool: MeasureTool,
+        position: (f64, f64),
+    ) {
+        let Some(point) = self.snapped_data(canvas, multiview, position) else {
+            return;
+        };
+
+        self.measure_points.push(point);
+        Self::measure_preview(canvas, tool, &self.measure_points);
+
+        if self.measure_points.len() >= tool.points() {
+            self.measure_points.clear();
+        }
+    }
+
+    /// Shows the measurement of `tool` through `points`, missing points repeat the last one.
+    fn measure_preview<P: Default>(
+        canvas: &mut GPUCanvas2D<P>,
+        tool: MeasureTool,
+        points: &[(f64, f64)],
+    ) {
+        let Some(last) = points.last() else {
+            return;
+        };
+        let point = |index: usize| *points.get(index).unwrap_or(last);
+
+        match tool {
+            MeasureTool::Distance => {
+                canvas.set_measurement(Some(Measurement::new(point(0), point(1))));
+            }
+            MeasureTool::Angle => canvas.set_angle_measurement(Some(AngleAnnotation::new(
+                point(0),
+                point(1),
+                point(2),
+            ))),
+        }
+    }
+
+    /// Removes the measurements of `canvas`, also one which is only partially picked.
+    pub fn clear_measurement<P: Default>(&mut self, canvas: &mut GPUCanvas2D<P>) {
+        self.measure_points.clear();
+        canvas.set_measurement(None);
+        canvas.set_angle_measurement(None);
+    }
+
+    pub fn apply_action<P: Default>(&self, action: NavigationAction, canvas: &mut GPUCanvas2D<P>) {
+        let x_step = canvas.x_range_len() * self.pan_step;
+        let y_step = canvas.y_range_len() * self.pan_step;
+
+        match action {
+            NavigationAction::PanLeft => canvas.offset_range((-x_step, 0.0)),
+            NavigationAction::PanRight => canvas.offset_range((x_step, 0.0)),
+            NavigationAction::PanUp => canvas.offset_range((0.0, y_step)),
+            NavigationAction::PanDown => canvas.offset_range((0.0, -y_step)),
+            NavigationAction::ZoomIn => {
+                let scale = 1.0 - self.zoom_step;
+                canvas.scale_range((scale, scale));
+            }
+            NavigationAction::ZoomOut => {
+                let scale = 1.0 + self.zoom_step;
+                canvas.scale_range((scale, scale));
+            }
+            NavigationAction::Undo => {
+                canvas.undo_range();
+            }
+            NavigationAction::Redo => {
+                canvas.redo_range();
+            }
+            NavigationAction::Home => {
+                canvas.reset_range();
+            }
+        }
+    }
+
+    /// Applies navigation input to `canvas`. Returns `true` if the event was consumed.
+    pub fn handle_window_event<P: Default>(
+        &mut self,
+        event: &WindowEvent,
+        canvas: &mut GPUCanvas2D<P>,
+        multiview: &GPUMultiView,
+    ) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                if event.state != ElementState::Pressed {
+                    return false;
+                }
+
+                let PhysicalKey::Code(key_code) = event.physical_key else {
+                    return false;
+                };
+
+                if key_code == KeyCode::Escape
+                    && (canvas.measurement().is_some() || canvas.angle_measurement().is_some())
+                {
+                    self.clear_measurement(canvas);
+                    return true;
+                }
+
+                let chord = KeyChord::new(key_code).with_modifiers(self.modifiers);
+                let Some(action) = self.key_bindings.action(chord) else {
+                    return false;
+                };
+
+                self.apply_action(action, canvas);
+                true
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                false
+            }
+            WindowEvent::MouseInput { button, state, .. } => match (button, state) {
+                (MouseButton::Left, ElementState::Pressed) => {
+                    let position = (self.mouse_pos.x, self.mouse_pos.y);
+                    self.mouse_widget = Self::widget_press(canvas, multiview, position);
+
+                    if let (None, Some(tool)) = (self.mouse_widget, self.measure_tool) {
+                        self.measure_press(canvas, multiview, tool, position);
+                        return true;
+                    }
+
+                    self.mouse_left = self.mouse_widget.is_none();
+                    true
+                }
+                (MouseButton::Left, ElementState::Released) => {
+                    self.mouse_left = false;
+                    self.mouse_widget = None;
+                    true
+                }
+                _ => false,
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                let prev_position = self.mouse_pos;
+                self.mouse_pos = *position;
+
+                if self.cursor_readout {
+                    let x = self.cursor_data(canvas, multiview).map(|(x, _)| x);
+                    canvas.set_cursor_readout(x);
+                }
+
+                if self.crosshair {
+                    let view_id = canvas.get_view().borrow().id();
+                    let view_pos = Self::physical_to_screen(*position, multiview)
+                        .and_then(|screen_pos| multiview.view_at(screen_pos))
+                        .filter(|view_pos| view_pos.view == view_id);
+                    canvas.set_crosshair(view_pos.map(|view_pos| view_pos.coordinates));
+                }
+
+                if let Some(index) = self.mouse_widget {
+                    Self::widget_drag(canvas, multiview, index, (position.x, position.y));
+                    return true;
+                }
+
+                // the next point follows the cursor until it is placed
+                if let (false, Some(tool)) = (self.measure_points.is_empty(), self.measure_tool) {
+                    if let Some(cursor) = self.cursor_data(canvas, multiview) {
+                        let mut points = self.measure_points.clone();
+                        points.push(cursor);
+                        Self::measure_preview(canvas, tool, &points);
+                    }
+                    return true;
+                }
+
+                // a changed readout or crosshair has to be redrawn
+                if !self.mouse_left {
+                    return self.cursor_readout || self.crosshair;
+                }
+
+                let (Some(screen_pos), Some(prev_screen_pos)) = (
+                    Self::physical_to_screen(*position, multiview),
+                    Self::physical_to_screen(prev_position, multiview),
+                ) else {
+                    return false;
+                };
+
+                let view_pos = multiview.view_at(screen_pos);
+                let prev_view_pos = multiview.view_at(prev_screen_pos);
+
+                match (view_pos, prev_view_pos) {
+                    (Some(view_pos), Some(prev_view_pos)) => {
+                        if view_pos.view != prev_view_pos.view {
+                            return false;
+                        }
+
+                        let dx = view_pos.coordinates.0 - prev_view_pos.coordinates.0;
+                        let dy = view_pos.coordinates.1 - prev_view_pos.coordinates.1;
+
+                        let x_rng_len = canvas.x_range_len();
+                        let y_rng_len = canvas.y_range_len();
+
+                        canvas.offset_range((
+                            -dx as f64 * x_rng_len * 0.5,
+                            -dy as f64 * y_rng_len * 0.5,
+                        ));
+
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                let position = (touch.location.x, touch.location.y);
+
+                match touch.phase {
+                    TouchPhase::Started => match Self::widget_press(canvas, multiview, position) {
+                        Some(index) => {
+                            self.touch_widgets.insert(touch.id, index);
+                            true
+                        }
+                        None => false,
+                    },
+                    TouchPhase::Moved => match self.touch_widgets.get(&touch.id) {
+                        Some(index) => {
+                            Self::widget_drag(canvas, multiview, *index, position);
+                            true
+                        }
+                        None => false,
+                    },
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        self.touch_widgets.remove(&touch.id).is_some()
+                    }
+                }
+            }
+            WindowEvent::CursorLeft { .. } if self.cursor_readout || self.crosshair => {
+                canvas.set_cursor_readout(None);
+                canvas.set_crosshair(None);
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(_, y) => {
+                    let scale = 1.0 - (*y as f64 * self.zoom_step);
+                    canvas.scale_range((scale, scale));
+                    true
+                }
+                MouseScrollDelta::PixelDelta(_) => false,
+            },
+            _ => false,
+        }
+    }
+}
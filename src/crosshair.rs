@@ -0,0 +1,66 @@
+use crate::color::RGBA;
+use crate::graph::{GraphId, Thickness};
+
+/// Lines through the cursor drawn by `GPUCanvas2D::set_crosshair`, labeled with the data
+/// coordinates at their intersection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrosshairStyle {
+    /// Text color of the enviroment if `None`
+    pub color: Option<RGBA>,
+    pub thickness: f32,
+    /// Dash length in screen space, solid lines if `None`
+    pub dash: Option<f32>,
+    /// Moves the intersection onto the function graph closest to the cursor at its x, so
+    /// the label shows f(x)
+    pub snap_to_graph: bool,
+    /// Draws the coordinates next to the intersection with the text style of the enviroment
+    pub label: bool,
+}
+
+impl Default for CrosshairStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            thickness: Thickness::THIN,
+            dash: Some(0.01),
+            snap_to_graph: false,
+            label: true,
+        }
+    }
+}
+
+impl CrosshairStyle {
+    pub fn with_color(mut self, color: RGBA) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_dash(mut self, dash: Option<f32>) -> Self {
+        self.dash = dash;
+        self
+    }
+
+    pub fn with_snap_to_graph(mut self, snap_to_graph: bool) -> Self {
+        self.snap_to_graph = snap_to_graph;
+        self
+    }
+
+    pub fn with_label(mut self, label: bool) -> Self {
+        self.label = label;
+        self
+    }
+}
+
+/// Result of `GPUCanvas2D::crosshair_readout`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrosshairReadout {
+    /// Intersection of the crosshair in data coordinates.
+    pub position: (f64, f64),
+    /// Function graph the intersection snapped to.
+    pub graph: Option<GraphId>,
+}
@@ -0,0 +1,273 @@
+//! Loading of data series from files.
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::graph::{PointSeries, PointStyle};
+
+/// Parses delimited text into one series per y column.
+///
+/// The delimiter is the first of `,`, `;` and tab found in the first line, otherwise the
+/// columns are separated by whitespace. Empty fields between delimiters are missing values
+/// and become NaN, so the following values stay in their columns.
+///
+/// The first column holds the x values. A file with a single column is treated as
+/// y values with their row index as x. A leading header line and `#` comments are skipped.
+pub fn parse_csv(source: &str) -> anyhow::Result<Vec<Vec<(f64, f64)>>> {
+    let mut series: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut row_index = 0;
+    let mut delimiter = None;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let delimiter = *delimiter
+            .get_or_insert_with(|| [',', ';', '\t'].into_iter().find(|d| line.contains(*d)));
+
+        let values = match delimiter {
+            Some(delimiter) => line
+                .split(delimiter)
+                .map(|value| match value.trim() {
+                    "" => Ok(f64::NAN),
+                    value => value.parse::<f64>(),
+                })
+                .collect::<Result<Vec<_>, _>>(),
+            None => line
+                .split_whitespace()
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>(),
+        };
+
+        let values = match values {
+            Ok(values) => values,
+            // header
+            Err(_) if row_index == 0 && series.is_empty() => continue,
+            Err(_) => {
+                return Err(anyhow::Error::msg(format!(
+                    "Invalid number in line {}: \"{}\".",
+                    line_index + 1,
+                    line
+                )))
+            }
+        };
+
+        let (x, ys) = match values.as_slice() {
+            [] => continue,
+            [y] => (row_index as f64, std::slice::from_ref(y)),
+            [x, ys @ ..] => (*x, ys),
+        };
+
+        if series.len() < ys.len() {
+            series.resize(ys.len(), Vec::new());
+        }
+
+        for (column, y) in ys.iter().enumerate() {
+            series[column].push((x, *y));
+        }
+
+        row_index += 1;
+    }
+
+    Ok(series)
+}
+
+pub fn read_csv(path: impl AsRef<Path>) -> anyhow::Result<Vec<Vec<(f64, f64)>>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}.", path.display()))?;
+
+    parse_csv(&source).with_context(|| format!("Failed to parse {}.", path.display()))
+}
+
+/// Parses JSON data into series.
+///
+/// Supported layouts are an array of `[x, y]` pairs, an array of `{"x": .., "y": ..}` objects,
+/// an array of y values (with their index as x), an object with `"x"` and `"y"` arrays, or an
+/// object mapping series names to any of these (one series per entry).
+pub fn parse_json(source: &str) -> anyhow::Result<Vec<Vec<(f64, f64)>>> {
+    let value: Value = serde_json::from_str(source)?;
+
+    match &value {
+        Value::Object(object) if !(object.contains_key("x") && object.contains_key("y")) => {
+            object.values().map(json_series).collect()
+        }
+        value => Ok(vec![json_series(value)?]),
+    }
+}
+
+fn json_series(value: &Value) -> anyhow::Result<Vec<(f64, f64)>> {
+    let number = |value: &Value| {
+        value
+            .as_f64()
+            .ok_or_else(|| anyhow::Error::msg(format!("Expected a number but found {}.", value)))
+    };
+
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match value {
+                Value::Array(pair) if pair.len() == 2 => Ok((number(&pair[0])?, number(&pair[1])?)),
+                Value::Object(point) => Ok((
+                    number(point.get("x").unwrap_or(&Value::Null))?,
+                    number(point.get("y").unwrap_or(&Value::Null))?,
+                )),
+                value => Ok((index as f64, number(value)?)),
+            })
+            .collect(),
+        Value::Object(object) => {
+            let (Some(Value::Array(xs)), Some(Value::Array(ys))) =
+                (object.get("x"), object.get("y"))
+            else {
+                return Err(anyhow::Error::msg("Expected \"x\" and \"y\" arrays."));
+            };
+
+            xs.iter()
+                .zip(ys)
+                .map(|(x, y)| Ok((number(x)?, number(y)?)))
+                .collect()
+        }
+        value => Err(anyhow::Error::msg(format!(
+            "Expected an array or object but found {}.",
+            value
+        ))),
+    }
+}
+
+pub fn read_json(path: impl AsRef<Path>) -> anyhow::Result<Vec<Vec<(f64, f64)>>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}.", path.display()))?;
+
+    parse_json(&source).with_context(|| format!("Failed to parse {}.", path.display()))
+}
+
+/// Reads a data file as JSON (`.json`) or delimited text (anything else).
+pub fn read_data(path: impl AsRef<Path>) -> anyhow::Result<Vec<Vec<(f64, f64)>>> {
+    let path = path.as_ref();
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => read_json(path),
+        _ => read_csv(path),
+    }
+}
+
+/// Smallest ranges containing all points of `series`, or `None` if there are no finite points.
+pub fn data_bounds<'a>(
+    series: impl IntoIterator<Item = &'a [(f64, f64)]>,
+) -> Option<((f64, f64), (f64, f64))> {
+    let mut bounds: Option<((f64, f64), (f64, f64))> = None;
+
+    for &(x, y) in series.into_iter().flatten() {
+        if !x.is_finite() || !y.is_finite() {
+            continue;
+        }
+
+        bounds = Some(match bounds {
+            None => ((x, x), (y, y)),
+            Some(((x_min, x_max), (y_min, y_max))) => {
+                ((x_min.min(x), x_max.max(x)), (y_min.min(y), y_max.max(y)))
+            }
+        });
+    }
+
+    bounds
+}
+
+/// Series which points get appended to over time, optionally keeping only the most
+/// recent `capacity` points.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingSeries {
+    points: VecDeque<(f64, f64)>,
+    capacity: Option<usize>,
+}
+
+impl StreamingSeries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            points: VecDeque::with_capacity(capacity),
+            capacity: Some(capacity),
+        }
+    }
+
+    pub fn push(&mut self, point: (f64, f64)) {
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return;
+            }
+
+            while self.points.len() >= capacity {
+                self.points.pop_front();
+            }
+        }
+
+        self.points.push_back(point);
+    }
+
+    pub fn extend(&mut self, points: impl IntoIterator<Item = (f64, f64)>) {
+        for point in points {
+            self.push(point);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &(f64, f64)> {
+        self.points.iter()
+    }
+
+    pub fn to_point_series(&self, style: PointStyle) -> PointSeries {
+        PointSeries {
+            points: self.points.iter().copied().collect(),
+            style,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_keeps_empty_cells_in_their_column() {
+        let series = parse_csv("x,a,b\n0,1,2\n1,,3\n").unwrap();
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0][0], (0.0, 1.0));
+        assert_eq!(series[0][1].0, 1.0);
+        assert!(series[0][1].1.is_nan());
+        assert_eq!(series[1], vec![(0.0, 2.0), (1.0, 3.0)]);
+    }
+
+    #[test]
+    fn csv_uses_one_delimiter_per_file() {
+        let series = parse_csv("0;1.5;2\n1;2.5;3\n").unwrap();
+        assert_eq!(
+            series,
+            vec![vec![(0.0, 1.5), (1.0, 2.5)], vec![(0.0, 2.0), (1.0, 3.0)]]
+        );
+
+        let series = parse_csv("0  1\n1\t 2\n").unwrap();
+        assert_eq!(series, vec![vec![(0.0, 1.0), (1.0, 2.0)]]);
+    }
+}
@@ -0,0 +1,1455 @@
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+use wgpu::util::DeviceExt;
+
+use crate::color::RGBA;
+use crate::colorbar::{ColorScale, ColorTransform};
+use crate::gpuview::{GPUPass, WorkgroupSize};
+use crate::graph::PointSeries;
+use crate::interval::Interval;
+use crate::layer::{CanvasLayer, LayerPainter};
+use crate::resources::PipelineCache;
+
+const SHADER_COMMON: &str = r#"
+    struct Binning {
+        // grid origin relative to the anchor of the points, split into a high and a low part
+        origin_x: vec2<f32>,
+        origin_y: vec2<f32>,
+        // width and height of a bin (center distance of hexagons) in data units
+        bin_size: vec2<f32>,
+        // lowest column and row of the grid relative to the origin bin
+        first: vec2<i32>,
+        // number of columns and rows
+        grid: vec2<u32>,
+        // view space to bins relative to the origin: view_origin + (position + 1) * view_scale
+        view_origin: vec2<f32>,
+        view_scale: vec2<f32>,
+        // 0 for rects, 1 for hexagons
+        shape: u32,
+        weighted: u32,
+    };
+
+    @group(0) @binding(0)
+    var<uniform> binning: Binning;
+
+    // axial coordinates of the hexagon (center distance 1) containing `p`
+    fn hex_round(p: vec2<f32>) -> vec2<i32> {
+        let r = p.y * 2.0 / sqrt(3.0);
+        let q = p.x - r / 2.0;
+
+        // rounding in cube coordinates, the component with the largest error is recomputed
+        let s = -q - r;
+        var rq = round(q);
+        var rr = round(r);
+        let rs = round(s);
+        let dq = abs(rq - q);
+        let dr = abs(rr - r);
+        let ds = abs(rs - s);
+
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        return vec2<i32>(i32(rq), i32(rr));
+    }
+
+    // index of the bin containing `p` (in bins relative to the origin), -1 outside of the grid
+    fn bin_index(p: vec2<f32>) -> i32 {
+        var cell = vec2<i32>(floor(p));
+        if binning.shape == 1u {
+            cell = hex_round(p);
+        }
+
+        let local = cell - binning.first;
+        if any(local < vec2<i32>(0)) || any(vec2<u32>(local) >= binning.grid) {
+            return -1;
+        }
+        return local.x + local.y * i32(binning.grid.x);
+    }
+"#;
+
+// `WORKGROUP_SIZE_X` and `WORKGROUP_SIZE_Y` are injected, see `WorkgroupSize::wgsl_constants`
+const BIN_SHADER: &str = r#"
+    // the points are a flat list, so the workgroups of the binning pass are as well
+    const BIN_WORKGROUP_SIZE: u32 = WORKGROUP_SIZE_X * WORKGROUP_SIZE_Y;
+
+    @group(0) @binding(1)
+    var<storage, read> points: array<vec4<f32>>;
+
+    @group(0) @binding(2)
+    var<storage, read> weights: array<i32>;
+
+    // point count and weight total of every bin
+    @group(0) @binding(3)
+    var<storage, read_write> bins: array<atomic<i32>>;
+
+    // smallest and largest total and smallest positive total of the occupied bins
+    @group(0) @binding(4)
+    var<storage, read_write> stats: array<atomic<i32>, 3>;
+
+    @compute @workgroup_size(BIN_WORKGROUP_SIZE)
+    fn cs_bin(
+        @builtin(global_invocation_id) id: vec3<u32>,
+        @builtin(num_workgroups) workgroups: vec3<u32>,
+    ) {
+        let index = id.x + id.y * workgroups.x * BIN_WORKGROUP_SIZE;
+        if index >= arrayLength(&points) {
+            return;
+        }
+
+        // the high parts are close to each other, so their difference is exact
+        let point = points[index];
+        let x = (point.x - binning.origin_x.x) + (point.y - binning.origin_x.y);
+        let y = (point.z - binning.origin_y.x) + (point.w - binning.origin_y.y);
+
+        let bin = bin_index(vec2<f32>(x, y) / binning.bin_size);
+        if bin < 0 {
+            return;
+        }
+
+        var weight = 1;
+        if binning.weighted != 0u {
+            weight = weights[index];
+        }
+
+        atomicAdd(&bins[2 * bin], 1);
+        atomicAdd(&bins[2 * bin + 1], weight);
+    }
+
+    @compute @workgroup_size(WORKGROUP_SIZE_X, WORKGROUP_SIZE_Y)
+    fn cs_stats(@builtin(global_invocation_id) id: vec3<u32>) {
+        if any(id.xy >= binning.grid) {
+            return;
+        }
+
+        let bin = i32(id.x + id.y * binning.grid.x);
+        if atomicLoad(&bins[2 * bin]) == 0 {
+            return;
+        }
+
+        let total = atomicLoad(&bins[2 * bin + 1]);
+        atomicMin(&stats[0], total);
+        atomicMax(&stats[1], total);
+        if total > 0 {
+            atomicMin(&stats[2], total);
+        }
+    }
+"#;
+
+const DRAW_SHADER: &str = r#"
+    struct Coloring {
+        range: vec2<f32>,
+        log_start: f32,
+        // 0 linear or percentile clip, 1 square root, 2 logarithmic, 3 histogram equalization
+        transform: u32,
+        // fixed point steps per unit of the totals
+        weight_scale: f32,
+        // takes the range from `stats` instead of `range`
+        auto_range: u32,
+        quantile_count: u32,
+    };
+
+    struct VertexOutput {
+        @builtin(position) clip_position: vec4<f32>,
+        @location(0) position: vec2<f32>,
+    };
+
+    @group(0) @binding(1)
+    var<storage, read> bins: array<i32>;
+
+    @group(0) @binding(2)
+    var<storage, read> stats: array<i32, 3>;
+
+    @group(0) @binding(3)
+    var<uniform> coloring: Coloring;
+
+    @group(0) @binding(4)
+    var<storage, read> colors: array<vec4<f32>>;
+
+    @group(0) @binding(5)
+    var<storage, read> quantiles: array<f32>;
+
+    // fullscreen triangle
+    @vertex
+    fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+        var out: VertexOutput;
+        let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+        out.position = uv * 2.0 - 1.0;
+        out.clip_position = vec4<f32>(out.position, 0.0, 1.0);
+        return out;
+    }
+
+    // number of quantiles below `value`, or at or below it if `inclusive`
+    fn quantiles_below(value: f32, inclusive: bool) -> u32 {
+        var low = 0u;
+        var high = coloring.quantile_count;
+        while low < high {
+            let middle = (low + high) / 2u;
+            let quantile = quantiles[middle];
+            if quantile < value || (inclusive && quantile == value) {
+                low = middle + 1u;
+            } else {
+                high = middle;
+            }
+        }
+        return low;
+    }
+
+    // same as `ColorScale::normalize`
+    fn normalize(value: f32) -> f32 {
+        var range = coloring.range;
+        var log_start = coloring.log_start;
+        if coloring.auto_range != 0u {
+            range = vec2<f32>(f32(stats[0]), f32(stats[1])) / coloring.weight_scale;
+            log_start = range.x;
+            if range.x <= 0.0 {
+                log_start = range.y * 1e-6;
+                if stats[2] != 2147483647 {
+                    log_start = f32(stats[2]) / coloring.weight_scale;
+                }
+            }
+        }
+
+        if !(range.y > range.x) {
+            return 0.5;
+        }
+        let linear = clamp((value - range.x) / (range.y - range.x), 0.0, 1.0);
+
+        switch coloring.transform {
+            case 1u: {
+                return sqrt(linear);
+            }
+            case 2u: {
+                if value <= log_start || range.y <= log_start {
+                    return 0.0;
+                }
+                return clamp(log(value / log_start) / log(range.y / log_start), 0.0, 1.0);
+            }
+            case 3u: {
+                let count = coloring.quantile_count;
+                if count == 0u {
+                    return linear;
+                }
+
+                // rank among the quantiles, equal quantiles share the middle of their ranks
+                let below = quantiles_below(value, false);
+                let at_or_below = quantiles_below(value, true);
+                let last = f32(count - 1u);
+
+                if at_or_below == 0u {
+                    return 0.0;
+                }
+                if below < at_or_below {
+                    return f32(below + at_or_below - 1u) / 2.0 / last;
+                }
+                if below == count {
+                    return 1.0;
+                }
+                let lower = quantiles[below - 1u];
+                let upper = quantiles[below];
+                return (f32(below - 1u) + (value - lower) / (upper - lower)) / last;
+            }
+            default: {
+                return linear;
+            }
+        }
+    }
+
+    // same as `Palette::interpolate`
+    fn interpolate(t: f32) -> vec4<f32> {
+        let last = arrayLength(&colors) - 1u;
+        let position = clamp(t, 0.0, 1.0) * f32(last);
+        let index = min(u32(floor(position)), max(last, 1u) - 1u);
+
+        if index + 1u > last {
+            return colors[index];
+        }
+        return mix(colors[index], colors[index + 1u], position - f32(index));
+    }
+
+    @fragment
+    fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+        let bin = bin_index(binning.view_origin + (in.position + 1.0) * binning.view_scale);
+        if bin < 0 || bins[2 * bin] == 0 {
+            discard;
+        }
+
+        return interpolate(normalize(f32(bins[2 * bin + 1]) / coloring.weight_scale));
+    }
+"#;
+
+const fn uniform_entry(binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+const fn storage_entry(
+    binding: u32,
+    visibility: wgpu::ShaderStages,
+    read_only: bool,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+const COMPUTE_BIND_GROUP_LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
+    wgpu::BindGroupLayoutDescriptor {
+        label: Some("DensityLayer Compute Bind Group Layout"),
+        entries: &[
+            uniform_entry(0, wgpu::ShaderStages::COMPUTE),
+            storage_entry(1, wgpu::ShaderStages::COMPUTE, true),
+            storage_entry(2, wgpu::ShaderStages::COMPUTE, true),
+            storage_entry(3, wgpu::ShaderStages::COMPUTE, false),
+            storage_entry(4, wgpu::ShaderStages::COMPUTE, false),
+        ],
+    };
+
+const DRAW_BIND_GROUP_LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
+    wgpu::BindGroupLayoutDescriptor {
+        label: Some("DensityLayer Draw Bind Group Layout"),
+        entries: &[
+            uniform_entry(0, wgpu::ShaderStages::FRAGMENT),
+            storage_entry(1, wgpu::ShaderStages::FRAGMENT, true),
+            storage_entry(2, wgpu::ShaderStages::FRAGMENT, true),
+            uniform_entry(3, wgpu::ShaderStages::FRAGMENT),
+            storage_entry(4, wgpu::ShaderStages::FRAGMENT, true),
+            storage_entry(5, wgpu::ShaderStages::FRAGMENT, true),
+        ],
+    };
+
+/// Shape of the bins of a `DensityLayer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinShape {
+    #[default]
+    Rect,
+    /// Pointy topped hexagons, which are closer to circles and don't produce axis aligned
+    /// artifacts.
+    Hex,
+}
+
+/// Weighted 2D histogram of a point cloud, drawn with the colors of `scale`. Meant for
+/// millions of points where a scatter plot would saturate.
+///
+/// Bins have a fixed size in pixels and are anchored at the data origin, so panning keeps
+/// them in place while zooming rebins. Empty bins are not drawn.
+///
+/// The points are uploaded once and binned on the GPU in a compute pass, the bins are
+/// colored in a fragment shader below the other content of the canvas. With `auto_range`
+/// the colors follow the bin totals right away, the shared `scale` (e.g. of a
+/// `ColorbarView`) is fitted once the totals are read back a frame later.
+pub struct DensityLayer {
+    points: Vec<(f64, f64)>,
+    /// Weight of every point, `1` for all if `None`.
+    weights: Option<Vec<f64>>,
+    pub bin_shape: BinShape,
+    /// Width of the rect bins or distance between neighbouring hex centers in physical pixels.
+    pub bin_size: f32,
+    pub scale: Arc<RefCell<ColorScale>>,
+    /// Fits the range of `scale` to the bin totals after every binning.
+    pub auto_range: bool,
+    /// Of the pass over the bins, the binning pass has flat workgroups with the same
+    /// number of invocations.
+    pub workgroup_size: WorkgroupSize,
+
+    dirty: bool,
+    points_changed: bool,
+    /// Scale of the last tessellation, to notice changes of the shared scale.
+    drawn: Option<ColorScale>,
+    pass: Arc<RefCell<DensityPass>>,
+}
+
+impl DensityLayer {
+    pub const DEFAULT_BIN_SIZE: f32 = 8.0;
+
+    pub fn new(points: Vec<(f64, f64)>, scale: Arc<RefCell<ColorScale>>) -> Self {
+        Self {
+            points,
+            weights: None,
+            bin_shape: BinShape::Rect,
+            bin_size: Self::DEFAULT_BIN_SIZE,
+            scale,
+            auto_range: true,
+            workgroup_size: WorkgroupSize::default(),
+            dirty: true,
+            points_changed: true,
+            drawn: None,
+            pass: Arc::new(RefCell::new(DensityPass::new())),
+        }
+    }
+
+    pub fn from_series(series: &PointSeries, scale: Arc<RefCell<ColorScale>>) -> Self {
+        Self::new(series.points.clone(), scale)
+    }
+
+    /// Weights are matched to the points by index, missing ones count as `0`.
+    ///
+    /// They are summed as fixed point numbers on the GPU, with a resolution of `1 / 65536`
+    /// as long as the absolute weights add up to less than `32768` and proportionally
+    /// coarser beyond.
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self.points_changed = true;
+        self
+    }
+
+    pub fn with_bin_shape(mut self, bin_shape: BinShape) -> Self {
+        self.bin_shape = bin_shape;
+        self
+    }
+
+    pub fn with_bin_size(mut self, bin_size: f32) -> Self {
+        self.bin_size = bin_size.max(1.0);
+        self
+    }
+
+    pub fn with_auto_range(mut self, auto_range: bool) -> Self {
+        self.auto_range = auto_range;
+        self
+    }
+
+    pub fn with_workgroup_size(mut self, workgroup_size: WorkgroupSize) -> Self {
+        self.workgroup_size = workgroup_size;
+        self
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    pub fn set_points(&mut self, points: Vec<(f64, f64)>, weights: Option<Vec<f64>>) {
+        self.points = points;
+        self.weights = weights;
+        self.points_changed = true;
+        self.dirty = true;
+    }
+}
+
+impl<P> CanvasLayer<P> for DensityLayer
+where
+    P: Default,
+{
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>) {
+        let mut pass = self.pass.borrow_mut();
+
+        if self.points_changed {
+            pass.set_points(&self.points, self.weights.as_deref());
+            self.points_changed = false;
+        }
+
+        let pixel_size = painter.pixel_size();
+        let size = self.bin_size.max(1.0) as f64;
+        let bin_size = (pixel_size.0 * size, pixel_size.1 * size);
+        pass.grid = BinGrid::new(
+            self.bin_shape,
+            bin_size,
+            painter.x_range(),
+            painter.y_range(),
+        );
+
+        pass.workgroup_size = self.workgroup_size;
+
+        let scale = self.scale.borrow().clone();
+        pass.set_coloring(&scale, self.auto_range.then(|| self.scale.clone()));
+
+        self.drawn = Some(scale);
+        self.dirty = false;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || self.drawn.as_ref() != Some(&*self.scale.borrow())
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+
+    fn gpu_pass(&self) -> Option<Arc<RefCell<dyn GPUPass>>> {
+        Some(self.pass.clone())
+    }
+}
+
+/// Bins covering the visible range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BinGrid {
+    shape: BinShape,
+    /// Lower left corner (rects) or center (hexagons) of the bin the grid is indexed from,
+    /// in data coordinates.
+    origin: (f64, f64),
+    /// Width and height of a bin in data coordinates.
+    bin_size: (f64, f64),
+    /// Lowest column and row relative to the origin bin.
+    first: (i32, i32),
+    columns: u32,
+    rows: u32,
+    /// Lower left corner of the visible range in bins relative to the origin.
+    view_origin: (f64, f64),
+    /// Half the visible size in bins.
+    view_scale: (f64, f64),
+}
+
+impl BinGrid {
+    /// Bins beyond which the grid is not allocated, far more than pixels on a screen.
+    const MAX_BINS: u64 = 1 << 24;
+
+    /// `None` if the bins can't cover the range, e.g. for non finite ranges.
+    fn new(
+        shape: BinShape,
+        bin_size: (f64, f64),
+        x_range: Interval,
+        y_range: Interval,
+    ) -> Option<Self> {
+        // visible corners in bins, with y pointing up
+        let lower = (x_range.start() / bin_size.0, y_range.start() / bin_size.1);
+        let upper = (x_range.end() / bin_size.0, y_range.end() / bin_size.1);
+        if ![lower.0, lower.1, upper.0, upper.1]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            return None;
+        }
+
+        let (origin, first, last) = match shape {
+            BinShape::Rect => {
+                let origin = (lower.0.floor(), lower.1.floor());
+                let last = ((upper.0 - origin.0).floor(), (upper.1 - origin.1).floor());
+                (origin, (0.0, 0.0), last)
+            }
+            BinShape::Hex => {
+                let (q, r) = hex_round(lower.0, lower.1);
+                let origin = hex_center(q, r);
+
+                // widened by a bin to include the hexagons crossing the border
+                let low = (lower.0 - origin.0 - 1.0, lower.1 - origin.1 - 1.0);
+                let high = (upper.0 - origin.0 + 1.0, upper.1 - origin.1 + 1.0);
+                let rows = (
+                    (low.1 * 2.0 / 3f64.sqrt()).floor(),
+                    (high.1 * 2.0 / 3f64.sqrt()).ceil(),
+                );
+                let columns = (
+                    (low.0 - rows.1 / 2.0).floor(),
+                    (high.0 - rows.0 / 2.0).ceil(),
+                );
+
+                (origin, (columns.0, rows.0), (columns.1, rows.1))
+            }
+        };
+
+        let (columns, rows) = (last.0 - first.0 + 1.0, last.1 - first.1 + 1.0);
+        if columns * rows > Self::MAX_BINS as f64 {
+            return None;
+        }
+
+        Some(Self {
+            shape,
+            origin: (origin.0 * bin_size.0, origin.1 * bin_size.1),
+            bin_size,
+            first: (first.0 as i32, first.1 as i32),
+            columns: columns as u32,
+            rows: rows as u32,
+            view_origin: (lower.0 - origin.0, lower.1 - origin.1),
+            view_scale: (
+                x_range.len() / 2.0 / bin_size.0,
+                y_range.len() / 2.0 / bin_size.1,
+            ),
+        })
+    }
+
+    fn bin_count(&self) -> u64 {
+        self.columns as u64 * self.rows as u64
+    }
+
+    /// Whether both grids sort the points into the same bins, only the visible part differs.
+    fn same_bins(&self, other: &BinGrid) -> bool {
+        (
+            self.shape,
+            self.origin,
+            self.bin_size,
+            self.first,
+            self.columns,
+            self.rows,
+        ) == (
+            other.shape,
+            other.origin,
+            other.bin_size,
+            other.first,
+            other.columns,
+            other.rows,
+        )
+    }
+
+    /// `anchor` is the point all points are uploaded relative to.
+    fn uniform(&self, anchor: (f64, f64), weighted: bool) -> BinningUniform {
+        BinningUniform {
+            origin_x: split(self.origin.0 - anchor.0),
+            origin_y: split(self.origin.1 - anchor.1),
+            bin_size: [self.bin_size.0 as f32, self.bin_size.1 as f32],
+            first: [self.first.0, self.first.1],
+            grid: [self.columns, self.rows],
+            view_origin: [self.view_origin.0 as f32, self.view_origin.1 as f32],
+            view_scale: [self.view_scale.0 as f32, self.view_scale.1 as f32],
+            shape: match self.shape {
+                BinShape::Rect => 0,
+                BinShape::Hex => 1,
+            },
+            weighted: weighted as u32,
+        }
+    }
+}
+
+/// `value` as high and low part, their sum is `value` with about twice the precision of `f32`.
+pub(crate) fn split(value: f64) -> [f32; 2] {
+    let high = value as f32;
+    [high, (value - high as f64) as f32]
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BinningUniform {
+    origin_x: [f32; 2],
+    origin_y: [f32; 2],
+    bin_size: [f32; 2],
+    first: [i32; 2],
+    grid: [u32; 2],
+    view_origin: [f32; 2],
+    view_scale: [f32; 2],
+    shape: u32,
+    weighted: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ColoringUniform {
+    range: [f32; 2],
+    log_start: f32,
+    transform: u32,
+    weight_scale: f32,
+    auto_range: u32,
+    quantile_count: u32,
+    _padding: u32,
+}
+
+/// Bin totals copied for `ColorScale::fit`.
+struct TotalsReadback {
+    buffer: wgpu::Buffer,
+    weight_scale: f64,
+    map_requested: bool,
+    /// Set by the `map_async` callback, `false` if mapping the buffer failed.
+    mapped: Arc<Mutex<Option<bool>>>,
+}
+
+/// GPU side of a `DensityLayer`, shared with the view of the canvas as `GPUPass`.
+struct DensityPass {
+    /// Points relative to `anchor`, each coordinate split into a high and a low part
+    /// (x high, x low, y high, y low) so the bins stay exact far away from the origin.
+    points: Vec<[f32; 4]>,
+    /// Weights as fixed point numbers with `weight_scale` steps per unit, empty if unweighted.
+    weights: Vec<i32>,
+    weight_scale: f64,
+    anchor: (f64, f64),
+    points_changed: bool,
+
+    grid: Option<BinGrid>,
+    workgroup_size: WorkgroupSize,
+    /// Grid of the bin totals on the GPU.
+    binned: Option<BinGrid>,
+
+    coloring: ColoringUniform,
+    colors: Vec<[f32; 4]>,
+    quantiles: Vec<f32>,
+    coloring_changed: bool,
+    /// Scale fitted to the bin totals once they are read back, `None` without auto range.
+    fitted_scale: Option<Arc<RefCell<ColorScale>>>,
+
+    resources: Option<DensityResources>,
+    readback: Option<TotalsReadback>,
+}
+
+impl DensityPass {
+    /// Fixed point steps per unit of weighted totals, as long as the sum of the absolute
+    /// weights leaves room for it.
+    const MAX_WEIGHT_SCALE: f64 = 65536.0;
+
+    fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            weights: Vec::new(),
+            weight_scale: 1.0,
+            anchor: (0.0, 0.0),
+            points_changed: true,
+            grid: None,
+            workgroup_size: WorkgroupSize::default(),
+            binned: None,
+            coloring: ColoringUniform::default(),
+            colors: Vec::new(),
+            quantiles: Vec::new(),
+            coloring_changed: true,
+            fitted_scale: None,
+            resources: None,
+            readback: None,
+        }
+    }
+
+    /// Points or weights which aren't finite are left out.
+    fn set_points(&mut self, points: &[(f64, f64)], weights: Option<&[f64]>) {
+        let valid: Vec<((f64, f64), f64)> = points
+            .iter()
+            .enumerate()
+            .map(|(index, &point)| {
+                let weight = match weights {
+                    Some(weights) => weights.get(index).copied().unwrap_or(0.0),
+                    None => 1.0,
+                };
+                (point, weight)
+            })
+            .filter(|((x, y), weight)| x.is_finite() && y.is_finite() && weight.is_finite())
+            .collect();
+
+        // center of the bounding box, which keeps the relative coordinates small
+        let (min, max) = valid.iter().fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), ((x, y), _)| {
+                (
+                    (min.0.min(*x), min.1.min(*y)),
+                    (max.0.max(*x), max.1.max(*y)),
+                )
+            },
+        );
+        self.anchor = match valid.is_empty() {
+            true => (0.0, 0.0),
+            false => ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0),
+        };
+
+        self.points = valid
+            .iter()
+            .map(|((x, y), _)| {
+                let (x, y) = (split(x - self.anchor.0), split(y - self.anchor.1));
+                [x[0], x[1], y[0], y[1]]
+            })
+            .collect();
+
+        // the totals have to fit into an `i32`
+        let total: f64 = valid.iter().map(|(_, weight)| weight.abs()).sum();
+        self.weight_scale = match (weights, total > 0.0) {
+            (Some(_), true) => (i32::MAX as f64 / total).min(Self::MAX_WEIGHT_SCALE),
+            _ => 1.0,
+        };
+        self.weights = match weights {
+            Some(_) => valid
+                .iter()
+                .map(|(_, weight)| (weight * self.weight_scale).round() as i32)
+                .collect(),
+            None => Vec::new(),
+        };
+        self.coloring.weight_scale = self.weight_scale as f32;
+
+        self.points_changed = true;
+        self.coloring_changed = true;
+    }
+
+    fn set_coloring(&mut self, scale: &ColorScale, fitted_scale: Option<Arc<RefCell<ColorScale>>>) {
+        let transform = scale.transform();
+
+        self.coloring = ColoringUniform {
+            range: [scale.range.start() as f32, scale.range.end() as f32],
+            log_start: scale.log_start() as f32,
+            transform: match transform {
+                ColorTransform::Linear | ColorTransform::PercentileClip { .. } => 0,
+                ColorTransform::Sqrt => 1,
+                ColorTransform::Log => 2,
+                ColorTransform::HistogramEqualization => 3,
+            },
+            weight_scale: self.weight_scale as f32,
+            // clipping and equalization depend on the distribution, which arrives with the fit
+            auto_range: (fitted_scale.is_some()
+                && matches!(
+                    transform,
+                    ColorTransform::Linear | ColorTransform::Sqrt | ColorTransform::Log
+                )) as u32,
+            quantile_count: scale.quantiles().len() as u32,
+            _padding: 0,
+        };
+
+        self.colors = scale.colors.iter().map(|&color| color.into()).collect();
+        if self.colors.is_empty() {
+            self.colors.push(RGBA::BLACK.into());
+        }
+        self.quantiles = scale.quantiles().iter().map(|&q| q as f32).collect();
+
+        self.fitted_scale = fitted_scale;
+        self.coloring_changed = true;
+    }
+
+    /// Fits the shared scale to the totals of the last binning once they are read back.
+    fn resolve_readback(&mut self) {
+        let Some(mapped) = self
+            .readback
+            .as_ref()
+            .and_then(|readback| *readback.mapped.lock().unwrap())
+        else {
+            return;
+        };
+        let readback = self.readback.take().unwrap();
+
+        if mapped {
+            let totals: Vec<f64> = {
+                let data = readback.buffer.slice(..).get_mapped_range();
+                data.chunks_exact(8)
+                    .map(|bin| bytemuck::pod_read_unaligned::<[i32; 2]>(bin))
+                    .filter(|[count, _]| *count > 0)
+                    .map(|[_, total]| total as f64 / readback.weight_scale)
+                    .collect()
+            };
+            readback.buffer.unmap();
+
+            if let Some(scale) = &self.fitted_scale {
+                scale.borrow_mut().fit(totals);
+            }
+        }
+
+        readback.buffer.destroy();
+    }
+}
+
+impl GPUPass for DensityPass {
+    fn prepare(
+        &mut self,
+        _size: (u32, u32),
+        multisample_state: wgpu::MultisampleState,
+        pipeline_cache: &PipelineCache,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<()> {
+        self.resolve_readback();
+
+        if self.resources.as_ref().is_none_or(|resources| {
+            resources.multisample_state != multisample_state
+                || resources.workgroup_size != self.workgroup_size
+        }) {
+            self.workgroup_size.validate(device)?;
+            self.resources = Some(DensityResources::new(
+                multisample_state,
+                self.workgroup_size,
+                pipeline_cache,
+                device,
+            ));
+            self.points_changed = true;
+            self.coloring_changed = true;
+        }
+        let resources = self.resources.as_mut().unwrap();
+
+        if self.points_changed {
+            resources.upload_points(&self.points, &self.weights, device);
+            self.points_changed = false;
+            self.binned = None;
+        }
+
+        if self.coloring_changed {
+            resources.upload_coloring(&self.coloring, &self.colors, &self.quantiles, device, queue);
+            self.coloring_changed = false;
+        }
+
+        let Some(grid) = self.grid.filter(|_| !self.points.is_empty()) else {
+            self.binned = None;
+            return Ok(());
+        };
+
+        let uniform = grid.uniform(self.anchor, !self.weights.is_empty());
+        queue.write_buffer(&resources.binning_buffer, 0, bytemuck::bytes_of(&uniform));
+
+        if self.binned.is_some_and(|binned| binned.same_bins(&grid)) {
+            return Ok(());
+        }
+
+        resources.bin(&grid, encoder, device, queue);
+        self.binned = Some(grid);
+
+        if self.fitted_scale.is_some() {
+            let readback = TotalsReadback {
+                buffer: resources.copy_bins(grid.bin_count(), encoder, device),
+                weight_scale: self.weight_scale,
+                map_requested: false,
+                mapped: Arc::new(Mutex::new(None)),
+            };
+            // totals of an outdated grid are not needed anymore
+            if let Some(outdated) = self.readback.replace(readback) {
+                outdated.buffer.destroy();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        let (Some(resources), Some(_)) = (&self.resources, &self.binned) else {
+            return;
+        };
+
+        render_pass.set_pipeline(&resources.draw_pipeline);
+        render_pass.set_bind_group(0, &resources.draw_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn after_submit(&mut self) {
+        let Some(readback) = &mut self.readback else {
+            return;
+        };
+        if readback.map_requested {
+            return;
+        }
+
+        let mapped = readback.mapped.clone();
+        readback
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock().unwrap() = Some(result.is_ok());
+            });
+        readback.map_requested = true;
+    }
+
+    fn memory_bytes(&self) -> u64 {
+        self.resources
+            .as_ref()
+            .map_or(0, |resources| resources.memory_bytes())
+    }
+}
+
+/// Points of a `DensityLayer` are uploaded in chunks which fit into a storage binding.
+struct PointChunk {
+    points: wgpu::Buffer,
+    /// `None` for unweighted points.
+    weights: Option<wgpu::Buffer>,
+    len: u32,
+}
+
+struct DensityResources {
+    multisample_state: wgpu::MultisampleState,
+    workgroup_size: WorkgroupSize,
+    bin_pipeline: Arc<wgpu::ComputePipeline>,
+    stats_pipeline: Arc<wgpu::ComputePipeline>,
+    draw_pipeline: Arc<wgpu::RenderPipeline>,
+    compute_bind_group_layout: wgpu::BindGroupLayout,
+    draw_bind_group_layout: wgpu::BindGroupLayout,
+
+    binning_buffer: wgpu::Buffer,
+    coloring_buffer: wgpu::Buffer,
+    /// Point count and weight total of every bin, grows with the grid.
+    bins_buffer: wgpu::Buffer,
+    stats_buffer: wgpu::Buffer,
+    colors_buffer: wgpu::Buffer,
+    quantiles_buffer: wgpu::Buffer,
+    /// Bound instead of the weights of unweighted points.
+    no_weights_buffer: wgpu::Buffer,
+    chunks: Vec<PointChunk>,
+
+    /// One per chunk.
+    compute_bind_groups: Vec<wgpu::BindGroup>,
+    draw_bind_group: wgpu::BindGroup,
+}
+
+impl DensityResources {
+    fn new(
+        multisample_state: wgpu::MultisampleState,
+        workgroup_size: WorkgroupSize,
+        pipeline_cache: &PipelineCache,
+        device: &wgpu::Device,
+    ) -> Self {
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&COMPUTE_BIND_GROUP_LAYOUT_DESCRIPTOR);
+        let draw_bind_group_layout =
+            device.create_bind_group_layout(&DRAW_BIND_GROUP_LAYOUT_DESCRIPTOR);
+
+        let compute_pipeline = |label: &str, entry_point: &str| {
+            let key = format!("{} {}x{}", label, workgroup_size.x, workgroup_size.y);
+
+            pipeline_cache.get_or_create_compute(&key, || {
+                let source = format!(
+                    "{}{SHADER_COMMON}{BIN_SHADER}",
+                    workgroup_size.wgsl_constants()
+                );
+                let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("DensityLayer Bin Shader Module"),
+                    source: wgpu::ShaderSource::Wgsl(source.into()),
+                });
+                let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("DensityLayer Compute Pipeline Layout"),
+                    bind_group_layouts: &[&compute_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&layout),
+                    module: &module,
+                    entry_point: Some(entry_point),
+                    compilation_options: Default::default(),
+                    cache: None,
+                })
+            })
+        };
+        let bin_pipeline = compute_pipeline("DensityLayer Bin Pipeline", "cs_bin");
+        let stats_pipeline = compute_pipeline("DensityLayer Stats Pipeline", "cs_stats");
+
+        let draw_key = format!("DensityLayer Draw Pipeline {:?}", multisample_state);
+        let draw_pipeline = pipeline_cache.get_or_create(&draw_key, || {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("DensityLayer Draw Shader Module"),
+                source: wgpu::ShaderSource::Wgsl(format!("{SHADER_COMMON}{DRAW_SHADER}").into()),
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("DensityLayer Draw Pipeline Layout"),
+                bind_group_layouts: &[&draw_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("DensityLayer Draw Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: Some("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: multisample_state,
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: Some("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+                cache: None,
+            })
+        });
+
+        let buffer = |label: &str, size: u64, usage: wgpu::BufferUsages| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let uniform = wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST;
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST;
+
+        let binning_buffer = buffer(
+            "DensityLayer Binning Buffer",
+            std::mem::size_of::<BinningUniform>() as u64,
+            uniform,
+        );
+        let coloring_buffer = buffer(
+            "DensityLayer Coloring Buffer",
+            std::mem::size_of::<ColoringUniform>() as u64,
+            uniform,
+        );
+        let bins_buffer = Self::create_bins_buffer(1, device);
+        let stats_buffer = buffer("DensityLayer Stats Buffer", 12, storage);
+        let colors_buffer = buffer("DensityLayer Colors Buffer", 16, storage);
+        let quantiles_buffer = buffer("DensityLayer Quantiles Buffer", 4, storage);
+        let no_weights_buffer = buffer("DensityLayer Weights Buffer", 4, storage);
+
+        let draw_bind_group = Self::create_draw_bind_group(
+            &draw_bind_group_layout,
+            [
+                &binning_buffer,
+                &bins_buffer,
+                &stats_buffer,
+                &coloring_buffer,
+                &colors_buffer,
+                &quantiles_buffer,
+            ],
+            device,
+        );
+
+        Self {
+            multisample_state,
+            workgroup_size,
+            bin_pipeline,
+            stats_pipeline,
+            draw_pipeline,
+            compute_bind_group_layout,
+            draw_bind_group_layout,
+            binning_buffer,
+            coloring_buffer,
+            bins_buffer,
+            stats_buffer,
+            colors_buffer,
+            quantiles_buffer,
+            no_weights_buffer,
+            chunks: Vec::new(),
+            compute_bind_groups: Vec::new(),
+            draw_bind_group,
+        }
+    }
+
+    fn create_bins_buffer(bins: u64, device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("DensityLayer Bins Buffer"),
+            size: bins.max(1) * 8,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_draw_bind_group(
+        layout: &wgpu::BindGroupLayout,
+        buffers: [&wgpu::Buffer; 6],
+        device: &wgpu::Device,
+    ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("DensityLayer Draw Bind Group"),
+            layout,
+            entries: &entries,
+        })
+    }
+
+    /// Recreates the bind groups after buffers were replaced.
+    fn update_bind_groups(&mut self, device: &wgpu::Device) {
+        self.compute_bind_groups = self
+            .chunks
+            .iter()
+            .map(|chunk| {
+                let buffers = [
+                    &self.binning_buffer,
+                    &chunk.points,
+                    chunk.weights.as_ref().unwrap_or(&self.no_weights_buffer),
+                    &self.bins_buffer,
+                    &self.stats_buffer,
+                ];
+                let entries: Vec<wgpu::BindGroupEntry> = buffers
+                    .iter()
+                    .enumerate()
+                    .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                        binding: binding as u32,
+                        resource: buffer.as_entire_binding(),
+                    })
+                    .collect();
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("DensityLayer Compute Bind Group"),
+                    layout: &self.compute_bind_group_layout,
+                    entries: &entries,
+                })
+            })
+            .collect();
+
+        self.draw_bind_group = Self::create_draw_bind_group(
+            &self.draw_bind_group_layout,
+            [
+                &self.binning_buffer,
+                &self.bins_buffer,
+                &self.stats_buffer,
+                &self.coloring_buffer,
+                &self.colors_buffer,
+                &self.quantiles_buffer,
+            ],
+            device,
+        );
+    }
+
+    /// Replaces the uploaded points, `weights` is empty or matches `points`.
+    fn upload_points(&mut self, points: &[[f32; 4]], weights: &[i32], device: &wgpu::Device) {
+        for chunk in self.chunks.drain(..) {
+            chunk.points.destroy();
+            if let Some(weights) = chunk.weights {
+                weights.destroy();
+            }
+        }
+
+        let max_binding_size = device.limits().max_storage_buffer_binding_size as usize;
+        let chunk_len = (max_binding_size / std::mem::size_of::<[f32; 4]>()).max(1);
+
+        for (index, points) in points.chunks(chunk_len).enumerate() {
+            let points_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("DensityLayer Points Buffer"),
+                contents: bytemuck::cast_slice(points),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+            let weights = (!weights.is_empty()).then(|| {
+                let start = index * chunk_len;
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("DensityLayer Weights Buffer"),
+                    contents: bytemuck::cast_slice(&weights[start..start + points.len()]),
+                    usage: wgpu::BufferUsages::STORAGE,
+                })
+            });
+
+            self.chunks.push(PointChunk {
+                points: points_buffer,
+                weights,
+                len: points.len() as u32,
+            });
+        }
+
+        self.update_bind_groups(device);
+    }
+
+    fn upload_coloring(
+        &mut self,
+        coloring: &ColoringUniform,
+        colors: &[[f32; 4]],
+        quantiles: &[f32],
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        queue.write_buffer(&self.coloring_buffer, 0, bytemuck::bytes_of(coloring));
+
+        let mut replaced = false;
+        // storage bindings can't be empty, the shader only reads `quantile_count` of them
+        let quantiles = match quantiles.is_empty() {
+            true => &[0.0][..],
+            false => quantiles,
+        };
+        for (buffer, data, label) in [
+            (
+                &mut self.colors_buffer,
+                bytemuck::cast_slice::<[f32; 4], u8>(colors),
+                "DensityLayer Colors Buffer",
+            ),
+            (
+                &mut self.quantiles_buffer,
+                bytemuck::cast_slice::<f32, u8>(quantiles),
+                "DensityLayer Quantiles Buffer",
+            ),
+        ] {
+            match buffer.size() as usize == data.len() {
+                true => queue.write_buffer(buffer, 0, data),
+                false => {
+                    buffer.destroy();
+                    *buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some(label),
+                        contents: data,
+                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                    });
+                    replaced = true;
+                }
+            }
+        }
+
+        if replaced {
+            self.update_bind_groups(device);
+        }
+    }
+
+    /// Sums the points into the bins of `grid` and finds the range of the totals.
+    fn bin(
+        &mut self,
+        grid: &BinGrid,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let size = grid.bin_count() * 8;
+        // reallocated like the view textures, when growing or shrinking well below
+        if self.bins_buffer.size() < size || self.bins_buffer.size() / 4 > size {
+            self.bins_buffer.destroy();
+            self.bins_buffer = Self::create_bins_buffer(grid.bin_count(), device);
+            self.update_bind_groups(device);
+        }
+
+        encoder.clear_buffer(&self.bins_buffer, 0, Some(size));
+        queue.write_buffer(
+            &self.stats_buffer,
+            0,
+            bytemuck::cast_slice(&[i32::MAX, i32::MIN, i32::MAX]),
+        );
+
+        let max_workgroups = device.limits().max_compute_workgroups_per_dimension;
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("DensityLayer Bin Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.bin_pipeline);
+
+            for (chunk, bind_group) in self.chunks.iter().zip(&self.compute_bind_groups) {
+                // rows of workgroups once a single row would be too long
+                let workgroups = chunk.len.div_ceil(self.workgroup_size.invocations());
+                let columns = workgroups.min(max_workgroups);
+
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.dispatch_workgroups(columns, workgroups.div_ceil(columns), 1);
+            }
+        }
+
+        // a pass of its own, so all points are binned before the totals are read
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("DensityLayer Stats Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.stats_pipeline);
+        pass.set_bind_group(0, &self.compute_bind_groups[0], &[]);
+        let (x, y) = self.workgroup_size.dispatch_size(grid.columns, grid.rows);
+        pass.dispatch_workgroups(x, y, 1);
+    }
+
+    /// Copies the first `bins` bins into a new readback buffer.
+    fn copy_bins(
+        &self,
+        bins: u64,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+    ) -> wgpu::Buffer {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("DensityLayer Totals Readback Buffer"),
+            size: bins * 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(&self.bins_buffer, 0, &buffer, 0, bins * 8);
+
+        buffer
+    }
+
+    fn memory_bytes(&self) -> u64 {
+        let chunks: u64 = self
+            .chunks
+            .iter()
+            .map(|chunk| {
+                chunk.points.size() + chunk.weights.as_ref().map_or(0, |weights| weights.size())
+            })
+            .sum();
+
+        chunks
+            + [
+                &self.binning_buffer,
+                &self.coloring_buffer,
+                &self.bins_buffer,
+                &self.stats_buffer,
+                &self.colors_buffer,
+                &self.quantiles_buffer,
+                &self.no_weights_buffer,
+            ]
+            .iter()
+            .map(|buffer| buffer.size())
+            .sum::<u64>()
+    }
+}
+
+/// Center of the pointy topped hexagon with axial coordinates `(q, r)` and a center
+/// distance of `1`.
+fn hex_center(q: i64, r: i64) -> (f64, f64) {
+    (q as f64 + r as f64 / 2.0, r as f64 * 3f64.sqrt() / 2.0)
+}
+
+/// Axial coordinates of the hexagon (center distance `1`) containing `(x, y)`.
+fn hex_round(x: f64, y: f64) -> (i64, i64) {
+    let r = y * 2.0 / 3f64.sqrt();
+    let q = x - r / 2.0;
+
+    // rounding in cube coordinates, the component with the largest error is recomputed
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+
+    (rq as i64, rr as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bin_grid_covers_the_visible_bins() {
+        let (x_range, y_range) = (
+            Interval::new(1.7e9, 1.7e9 + 30.0),
+            Interval::new(-7.0, 13.0),
+        );
+        let bin_size = (0.7, 0.4);
+
+        for shape in [BinShape::Rect, BinShape::Hex] {
+            let grid = BinGrid::new(shape, bin_size, x_range, y_range).unwrap();
+
+            for i in 0..=100 {
+                for j in 0..=100 {
+                    let (tx, ty) = (i as f64 / 100.0, j as f64 / 100.0);
+                    let point = (x_range.lerp(tx), y_range.lerp(ty));
+
+                    // the same mapping as `bin_index` in the shader
+                    let relative = (
+                        (point.0 - grid.origin.0) / bin_size.0,
+                        (point.1 - grid.origin.1) / bin_size.1,
+                    );
+                    let cell = match shape {
+                        BinShape::Rect => (relative.0.floor() as i64, relative.1.floor() as i64),
+                        BinShape::Hex => hex_round(relative.0, relative.1),
+                    };
+                    let local = (cell.0 - grid.first.0 as i64, cell.1 - grid.first.1 as i64);
+                    assert!(
+                        (0..grid.columns as i64).contains(&local.0)
+                            && (0..grid.rows as i64).contains(&local.1),
+                        "{shape:?} bin {cell:?} of {point:?} is outside of the grid"
+                    );
+
+                    // fragments find the same bins
+                    let view = (
+                        grid.view_origin.0 + tx * 2.0 * grid.view_scale.0,
+                        grid.view_origin.1 + ty * 2.0 * grid.view_scale.1,
+                    );
+                    assert!((view.0 - relative.0).abs() < 1e-6);
+                    assert!((view.1 - relative.1).abs() < 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn split_keeps_the_precision_lost_by_f32() {
+        let value = 1.7e9 + 0.123;
+        let [high, low] = split(value);
+
+        assert_ne!(high as f64, value);
+        assert!((high as f64 + low as f64 - value).abs() < 1e-6);
+    }
+}
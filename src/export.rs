@@ -0,0 +1,204 @@
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::Context;
+use base64::Engine;
+
+use crate::color::RGBA;
+use crate::context::VisContext;
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::gpuview::GPUMultiView;
+
+/// Renders `canvas` into an offscreen `multiview` (see `VisContext::create_offscreen_multiview`)
+/// and returns the tightly packed RGBA rows of the image.
+pub fn render_canvas_offscreen<P: Default>(
+    context: &VisContext,
+    multiview: &mut GPUMultiView,
+    canvas: &mut GPUCanvas2D<P>,
+) -> anyhow::Result<Vec<u8>> {
+    multiview.set_render_views(vec![canvas.get_view()]);
+    canvas
+        .get_view()
+        .borrow_mut()
+        .initialize(multiview, context.device())?;
+
+    canvas.display();
+    multiview.render(context.device(), context.queue())?;
+
+    multiview.read_pixels(context.device(), context.queue())
+}
+
+/// Displays `canvas` in an offscreen `multiview` like `render_canvas_offscreen` and returns
+/// it as SVG document instead of rendering it, see `GPUMultiView::to_svg`.
+pub fn render_canvas_svg<P: Default>(
+    context: &VisContext,
+    multiview: &mut GPUMultiView,
+    canvas: &mut GPUCanvas2D<P>,
+) -> anyhow::Result<String> {
+    multiview.set_render_views(vec![canvas.get_view()]);
+    canvas
+        .get_view()
+        .borrow_mut()
+        .initialize(multiview, context.device())?;
+
+    canvas.display();
+
+    multiview.to_svg()
+}
+
+pub fn write_svg(path: impl AsRef<Path>, svg: &str) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, svg).with_context(|| format!("Failed to write SVG to {}.", path.display()))
+}
+
+/// Encodes tightly packed RGBA rows as a PNG image.
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+
+    {
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(rgba)?;
+    }
+
+    Ok(png)
+}
+
+pub fn write_png(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, encode_png(width, height, rgba)?)
+        .with_context(|| format!("Failed to write PNG to {}.", path.display()))
+}
+
+/// Description of a single series included in the metadata of an `HtmlExport`.
+#[derive(Debug, Clone)]
+pub struct SeriesMetadata {
+    pub kind: &'static str,
+    pub color: RGBA,
+    /// Number of data points (`None` for sampled functions)
+    pub points: Option<usize>,
+}
+
+/// Self-contained HTML document embedding a rendered plot and its metadata.
+#[derive(Debug, Clone)]
+pub struct HtmlExport {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub png: Vec<u8>,
+
+    pub x_range: Range<f64>,
+    pub y_range: Range<f64>,
+    pub series: Vec<SeriesMetadata>,
+}
+
+impl HtmlExport {
+    fn metadata_json(&self) -> String {
+        let series = self
+            .series
+            .iter()
+            .map(|series| {
+                format!(
+                    r##"{{"kind":"{}","color":"#{:02x}{:02x}{:02x}{:02x}","points":{}}}"##,
+                    series.kind,
+                    series.color.r,
+                    series.color.g,
+                    series.color.b,
+                    series.color.a,
+                    series
+                        .points
+                        .map_or("null".to_string(), |points| points.to_string())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            r#"{{"title":"{}","width":{},"height":{},"x_range":[{},{}],"y_range":[{},{}],"series":[{}]}}"#,
+            escape_json(&self.title),
+            self.width,
+            self.height,
+            self.x_range.start,
+            self.x_range.end,
+            self.y_range.start,
+            self.y_range.end,
+            series
+        )
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let image = base64::engine::general_purpose::STANDARD.encode(&self.png);
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+figure {{ margin: 0; }}
+table {{ border-collapse: collapse; margin-top: 1em; }}
+td, th {{ border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }}
+</style>
+</head>
+<body>
+<figure>
+<img width="{width}" height="{height}" alt="{title}" src="data:image/png;base64,{image}">
+<figcaption>{title}</figcaption>
+</figure>
+<table>
+<tr><th>x</th><td>{x_start} .. {x_end}</td></tr>
+<tr><th>y</th><td>{y_start} .. {y_end}</td></tr>
+</table>
+<script type="application/json" id="plot-metadata">{metadata}</script>
+</body>
+</html>
+"#,
+            width = self.width,
+            height = self.height,
+            x_start = self.x_range.start,
+            x_end = self.x_range.end,
+            y_start = self.y_range.start,
+            y_end = self.y_range.end,
+            metadata = self.metadata_json().replace("</", "<\\/"),
+        )
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_html())
+            .with_context(|| format!("Failed to write HTML to {}.", path.display()))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,403 @@
+//! Parser and evaluator for plain math expressions like `sin(x) * a + 2^x`.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathFunction {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Ln,
+    Log,
+    Sqrt,
+    Abs,
+    Floor,
+    Ceil,
+}
+
+impl MathFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "asin" => Self::Asin,
+            "acos" => Self::Acos,
+            "atan" => Self::Atan,
+            "sinh" => Self::Sinh,
+            "cosh" => Self::Cosh,
+            "tanh" => Self::Tanh,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "log" => Self::Log,
+            "sqrt" => Self::Sqrt,
+            "abs" => Self::Abs,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Self::Sin => value.sin(),
+            Self::Cos => value.cos(),
+            Self::Tan => value.tan(),
+            Self::Asin => value.asin(),
+            Self::Acos => value.acos(),
+            Self::Atan => value.atan(),
+            Self::Sinh => value.sinh(),
+            Self::Cosh => value.cosh(),
+            Self::Tanh => value.tanh(),
+            Self::Exp => value.exp(),
+            Self::Ln => value.ln(),
+            Self::Log => value.log10(),
+            Self::Sqrt => value.sqrt(),
+            Self::Abs => value.abs(),
+            Self::Floor => value.floor(),
+            Self::Ceil => value.ceil(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(MathFunction, Box<Expr>),
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+
+        let expr = parser.expression()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(anyhow::Error::msg(format!(
+                "Unexpected {:?} in expression \"{}\".",
+                token, source
+            ))),
+        }
+    }
+
+    /// Evaluates the expression. Variables which are not part of `variables` evaluate to NaN.
+    pub fn eval(&self, variables: &HashMap<String, f64>) -> f64 {
+        self.eval_with(&|name| variables.get(name).copied())
+    }
+
+    /// Evaluates the expression with `x` bound in addition to `variables`.
+    pub fn eval_x(&self, x: f64, variables: &HashMap<String, f64>) -> f64 {
+        self.eval_with(&|name| match name {
+            "x" => Some(x),
+            name => variables.get(name).copied(),
+        })
+    }
+
+    pub fn eval_with(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> f64 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Variable(name) => lookup(name).unwrap_or(f64::NAN),
+            Self::Negate(expr) => -expr.eval_with(lookup),
+            Self::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval_with(lookup);
+                let rhs = rhs.eval_with(lookup);
+
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div => lhs / rhs,
+                    BinaryOp::Pow => lhs.powf(rhs),
+                }
+            }
+            Self::Call(function, argument) => function.apply(argument.eval_with(lookup)),
+        }
+    }
+
+    /// Names of all variables used in the expression.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables.sort();
+        variables.dedup();
+
+        variables
+    }
+
+    fn collect_variables<'a>(&'a self, variables: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => (),
+            Self::Variable(name) => variables.push(name),
+            Self::Negate(expr) | Self::Call(_, expr) => expr.collect_variables(variables),
+            Self::Binary(_, lhs, rhs) => {
+                lhs.collect_variables(variables);
+                rhs.collect_variables(variables);
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Expr {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+//--------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    LeftParen,
+    RightParen,
+}
+
+fn tokenize(source: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let bytes = source.as_bytes();
+                let mut end = start;
+
+                while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+                    end += 1;
+                }
+
+                // exponent (`1e-3`), but not the start of an identifier (`2exp(x)`)
+                if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+                    let mut exponent_end = end + 1;
+                    if exponent_end < bytes.len()
+                        && (bytes[exponent_end] == b'+' || bytes[exponent_end] == b'-')
+                    {
+                        exponent_end += 1;
+                    }
+
+                    if exponent_end < bytes.len() && bytes[exponent_end].is_ascii_digit() {
+                        end = exponent_end;
+                        while end < bytes.len() && bytes[end].is_ascii_digit() {
+                            end += 1;
+                        }
+                    }
+                }
+
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+
+                let number = &source[start..end];
+                tokens.push(Token::Number(number.parse().map_err(|_| {
+                    anyhow::Error::msg(format!("Invalid number \"{}\".", number))
+                })?));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+
+                tokens.push(Token::Identifier(source[start..end].to_string()));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::LeftParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::RightParen);
+                chars.next();
+            }
+            c => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unexpected character '{}' in expression \"{}\".",
+                    c, source
+                )))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(anyhow::Error::msg(format!(
+                "Expected {:?} but found {:?}.",
+                expected, token
+            ))),
+            None => Err(anyhow::Error::msg(format!(
+                "Expected {:?} but the expression ended.",
+                expected
+            ))),
+        }
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.term()?;
+
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let rhs = self.term()?;
+
+            let op = if op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/') unary | implicit multiplication)*
+    fn term(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.unary()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Operator('*')) => {
+                    self.next();
+                    BinaryOp::Mul
+                }
+                Some(Token::Operator('/')) => {
+                    self.next();
+                    BinaryOp::Div
+                }
+                // `2x`, `3(x + 1)`, `2 sin(x)`
+                Some(Token::Number(_) | Token::Identifier(_) | Token::LeftParen) => BinaryOp::Mul,
+                _ => break,
+            };
+
+            let rhs = self.unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.next();
+                Ok(Expr::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Operator('+')) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    // power := primary ('^' unary)?
+    fn power(&mut self) -> anyhow::Result<Expr> {
+        let base = self.primary()?;
+
+        match self.peek() {
+            Some(Token::Operator('^')) => {
+                self.next();
+                let exponent = self.unary()?;
+                Ok(Expr::Binary(
+                    BinaryOp::Pow,
+                    Box::new(base),
+                    Box::new(exponent),
+                ))
+            }
+            _ => Ok(base),
+        }
+    }
+
+    // primary := number | constant | variable | function '(' expression ')' | '(' expression ')'
+    fn primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Identifier(name)) => {
+                if let Some(function) = MathFunction::from_name(&name) {
+                    if self.peek() == Some(&Token::LeftParen) {
+                        self.next();
+                        let argument = self.expression()?;
+                        self.expect(Token::RightParen)?;
+
+                        return Ok(Expr::Call(function, Box::new(argument)));
+                    }
+                }
+
+                match name.as_str() {
+                    "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+                    "e" => Ok(Expr::Number(std::f64::consts::E)),
+                    _ => Ok(Expr::Variable(name)),
+                }
+            }
+            Some(Token::LeftParen) => {
+                let expr = self.expression()?;
+                self.expect(Token::RightParen)?;
+
+                Ok(expr)
+            }
+            token => Err(anyhow::Error::msg(format!(
+                "Expected a number, variable or '(' but found {:?}.",
+                token
+            ))),
+        }
+    }
+}
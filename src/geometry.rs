@@ -0,0 +1,143 @@
+use crate::interval::Interval;
+use crate::math::{lerp, Scalar};
+
+/// Axis aligned rectangle given by its lower left (`min`) and upper right (`max`) corner.
+///
+/// Screen and view space use `Rect<f32>` (y pointing up), data space `Rect<f64>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect<T = f32> {
+    pub min: [T; 2],
+    pub max: [T; 2],
+}
+
+impl<T: Scalar> Rect<T> {
+    /// Rectangle spanned by two opposite corners in any order.
+    pub fn new(corner1: [T; 2], corner2: [T; 2]) -> Self {
+        Self {
+            min: [corner1[0].min(corner2[0]), corner1[1].min(corner2[1])],
+            max: [corner1[0].max(corner2[0]), corner1[1].max(corner2[1])],
+        }
+    }
+
+    pub fn from_center(center: [T; 2], half_size: [T; 2]) -> Self {
+        Self::new(
+            [center[0] - half_size[0], center[1] - half_size[1]],
+            [center[0] + half_size[0], center[1] + half_size[1]],
+        )
+    }
+
+    /// `-1..1` in both directions.
+    pub fn ndc() -> Self {
+        Self {
+            min: [-T::ONE, -T::ONE],
+            max: [T::ONE, T::ONE],
+        }
+    }
+
+    pub fn width(&self) -> T {
+        self.max[0] - self.min[0]
+    }
+
+    pub fn height(&self) -> T {
+        self.max[1] - self.min[1]
+    }
+
+    pub fn size(&self) -> [T; 2] {
+        [self.width(), self.height()]
+    }
+
+    pub fn center(&self) -> [T; 2] {
+        let two = T::ONE + T::ONE;
+        [
+            (self.min[0] + self.max[0]) / two,
+            (self.min[1] + self.max[1]) / two,
+        ]
+    }
+
+    /// Upper left corner, as used by `GPUViewFrame`.
+    pub fn upper_left(&self) -> [T; 2] {
+        [self.min[0], self.max[1]]
+    }
+
+    /// Lower right corner, as used by `GPUViewFrame`.
+    pub fn lower_right(&self) -> [T; 2] {
+        [self.max[0], self.min[1]]
+    }
+
+    /// True if `point` is inside or on the border.
+    pub fn contains(&self, point: [T; 2]) -> bool {
+        (0..2).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    pub fn contains_rect(&self, other: &Rect<T>) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    /// True if the interiors overlap, touching borders don't count.
+    pub fn intersects(&self, other: &Rect<T>) -> bool {
+        (0..2).all(|i| self.min[i] < other.max[i] && other.min[i] < self.max[i])
+    }
+
+    pub fn intersection(&self, other: &Rect<T>) -> Option<Rect<T>> {
+        let min = [self.min[0].max(other.min[0]), self.min[1].max(other.min[1])];
+        let max = [self.max[0].min(other.max[0]), self.max[1].min(other.max[1])];
+
+        (min[0] <= max[0] && min[1] <= max[1]).then_some(Self { min, max })
+    }
+
+    /// Smallest rectangle containing both.
+    pub fn union(&self, other: &Rect<T>) -> Rect<T> {
+        Self {
+            min: [self.min[0].min(other.min[0]), self.min[1].min(other.min[1])],
+            max: [self.max[0].max(other.max[0]), self.max[1].max(other.max[1])],
+        }
+    }
+
+    /// Grows the rectangle by `amount` on every side, negative amounts shrink it.
+    pub fn expand(&self, amount: [T; 2]) -> Self {
+        Self {
+            min: [self.min[0] - amount[0], self.min[1] - amount[1]],
+            max: [self.max[0] + amount[0], self.max[1] + amount[1]],
+        }
+    }
+
+    pub fn translate(&self, offset: [T; 2]) -> Self {
+        Self {
+            min: [self.min[0] + offset[0], self.min[1] + offset[1]],
+            max: [self.max[0] + offset[0], self.max[1] + offset[1]],
+        }
+    }
+
+    /// Maps `point` inside of the rectangle to `-1..1`.
+    pub fn to_ndc(&self, point: [T; 2]) -> [T; 2] {
+        let ndc = -T::ONE..T::ONE;
+        [
+            lerp(point[0], &(self.min[0]..self.max[0]), &ndc),
+            lerp(point[1], &(self.min[1]..self.max[1]), &ndc),
+        ]
+    }
+
+    /// Maps `-1..1` onto the rectangle.
+    pub fn from_ndc(&self, ndc: [T; 2]) -> [T; 2] {
+        let unit = -T::ONE..T::ONE;
+        [
+            lerp(ndc[0], &unit, &(self.min[0]..self.max[0])),
+            lerp(ndc[1], &unit, &(self.min[1]..self.max[1])),
+        ]
+    }
+}
+
+impl Rect<f64> {
+    pub fn from_intervals(x: Interval, y: Interval) -> Self {
+        Self::new([x.start(), y.start()], [x.end(), y.end()])
+    }
+
+    pub fn x_interval(&self) -> Interval {
+        Interval::new(self.min[0], self.max[0])
+    }
+
+    pub fn y_interval(&self) -> Interval {
+        Interval::new(self.min[1], self.max[1])
+    }
+}
@@ -0,0 +1,392 @@
+use wgpu::util::DeviceExt;
+
+use crate::gpuview::{ShaderDescriptor, Vertex};
+use crate::texture_copy::texture_bytes;
+
+const SHADER: &str = r#"
+    struct VertexOutput {
+        @builtin(position) clip_position: vec4<f32>,
+        @location(0) tex_coords: vec2<f32>,
+    };
+
+    struct Blur {
+        direction: vec2<f32>,
+        radius: f32,
+    };
+
+    @group(0) @binding(0)
+    var texture: texture_2d<f32>;
+
+    @group(0) @binding(1)
+    var texture_sampler: sampler;
+
+    @group(0) @binding(2)
+    var<uniform> blur: Blur;
+
+    // fullscreen triangle
+    @vertex
+    fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+        var out: VertexOutput;
+        let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+        out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+        out.tex_coords = uv;
+        return out;
+    }
+
+    // separable gaussian, `direction` is (1, 0) or (0, 1)
+    @fragment
+    fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+        let pixel = 1.0 / vec2<f32>(textureDimensions(texture));
+        let sigma = max(blur.radius / 2.0, 0.5);
+        let taps = i32(ceil(blur.radius));
+
+        var sum = vec4<f32>(0.0);
+        var total = 0.0;
+        for (var i = -taps; i <= taps; i++) {
+            let weight = exp(-f32(i * i) / (2.0 * sigma * sigma));
+            let offset = blur.direction * f32(i) * pixel;
+
+            sum += textureSampleLevel(texture, texture_sampler, in.tex_coords + offset, 0.0) * weight;
+            total += weight;
+        }
+
+        return sum / total;
+    }
+
+    // the glow texture is premultiplied, so it can be added as is
+    @fragment
+    fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+        return textureSampleLevel(texture, texture_sampler, in.tex_coords, 0.0);
+    }
+"#;
+
+const BIND_GROUP_LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
+    wgpu::BindGroupLayoutDescriptor {
+        label: Some("Glow Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    };
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8Unorm;
+
+/// Offscreen layer of a `GPUView` whose vertices get blurred and added below the
+/// regular render vertices, used for `GraphStyle::glow`.
+pub(crate) struct GlowPass {
+    size: (u32, u32),
+    /// Ping pong textures of the separable blur, the result ends up in the first one
+    textures: [wgpu::Texture; 2],
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// Blur parameters of the horizontal and vertical pass
+    blur_buffers: [wgpu::Buffer; 2],
+
+    shader_bind_group: wgpu::BindGroup,
+    geometry_pipeline: wgpu::RenderPipeline,
+    blur_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    composite_bind_group: Option<wgpu::BindGroup>,
+}
+
+impl GlowPass {
+    /// `multisample_state` has to match the render pass the glow is composited in.
+    pub(crate) fn new(
+        size: (u32, u32),
+        shader_descriptor: &dyn ShaderDescriptor,
+        multisample_state: wgpu::MultisampleState,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<Self> {
+        let textures = [0, 1].map(|_| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Glow Texture"),
+                size: wgpu::Extent3d {
+                    width: size.0.max(1),
+                    height: size.1.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let blur_buffers = [0, 1].map(|_| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Glow Blur Buffer"),
+                size: std::mem::size_of::<[f32; 4]>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BIND_GROUP_LAYOUT_DESCRIPTOR);
+
+        //-- geometry, drawn with the shader of the view
+
+        let (shader_bind_group, shader_bind_group_layout) =
+            shader_descriptor.bind_group_and_layout(device)?;
+
+        let view_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Glow Geometry Shader Module"),
+            source: shader_descriptor.shader_source(),
+        });
+
+        let geometry_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Glow Geometry Pipeline Layout"),
+            bind_group_layouts: &[&shader_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let geometry_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Glow Geometry Pipeline"),
+            layout: Some(&geometry_layout),
+            vertex: wgpu::VertexState {
+                module: &view_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &view_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        //-- blur and composite
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Glow Shader Module"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Glow Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fullscreen_pipeline = |label: &str, entry_point: &str, blend, multisample| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample,
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let blur_pipeline = fullscreen_pipeline(
+            "Glow Blur Pipeline",
+            "fs_blur",
+            wgpu::BlendState::REPLACE,
+            wgpu::MultisampleState::default(),
+        );
+        let composite_pipeline = fullscreen_pipeline(
+            "Glow Composite Pipeline",
+            "fs_composite",
+            wgpu::BlendState {
+                color: additive,
+                alpha: additive,
+            },
+            multisample_state,
+        );
+
+        Ok(Self {
+            size,
+            textures,
+            sampler,
+            bind_group_layout,
+            blur_buffers,
+            shader_bind_group,
+            geometry_pipeline,
+            blur_pipeline,
+            composite_pipeline,
+            composite_bind_group: None,
+        })
+    }
+
+    pub(crate) fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Bytes of the blur textures and parameter buffers.
+    pub(crate) fn memory_bytes(&self) -> u64 {
+        self.textures.iter().map(texture_bytes).sum::<u64>()
+            + self
+                .blur_buffers
+                .iter()
+                .map(|buffer| buffer.size())
+                .sum::<u64>()
+    }
+
+    fn bind_group(&self, texture: usize, blur: usize, device: &wgpu::Device) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Glow Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &self.textures[texture]
+                            .create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.blur_buffers[blur].as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    fn pass<'e>(
+        encoder: &'e mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        label: &str,
+    ) -> wgpu::RenderPass<'e> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.create_view(&wgpu::TextureViewDescriptor::default()),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    /// Draws `vertices` into the glow layer and blurs it by `radius` pixels.
+    pub(crate) fn render(
+        &mut self,
+        vertices: &[Vertex],
+        radius: f32,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for (buffer, direction) in self.blur_buffers.iter().zip([[1.0, 0.0], [0.0, 1.0]]) {
+            let blur: [f32; 4] = [direction[0], direction[1], radius, 0.0];
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&blur));
+        }
+
+        let vertices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Glow Vertices Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        {
+            let mut render_pass = Self::pass(encoder, &self.textures[0], "Glow Geometry Pass");
+            render_pass.set_pipeline(&self.geometry_pipeline);
+            render_pass.set_bind_group(0, &self.shader_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertices_buffer.slice(..));
+            render_pass.draw(0..vertices.len() as u32, 0..1);
+        }
+
+        for (source, target) in [(0, 1), (1, 0)] {
+            let bind_group = self.bind_group(source, source, device);
+
+            let mut render_pass = Self::pass(encoder, &self.textures[target], "Glow Blur Pass");
+            render_pass.set_pipeline(&self.blur_pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.composite_bind_group = Some(self.bind_group(0, 0, device));
+    }
+
+    /// Adds the blurred layer to the targets of `render_pass`, call after `render`.
+    pub(crate) fn composite(&self, render_pass: &mut wgpu::RenderPass) {
+        let Some(bind_group) = &self.composite_bind_group else {
+            return;
+        };
+
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
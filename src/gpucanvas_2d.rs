@@ -1,87 +1,61 @@
+use crate::axis_scale::{AxisScale, AxisTick, AxisTicks, TickLabel};
 use crate::color::*;
+use crate::complex::{Complex, ComplexFunctionGraph};
+use crate::complex_pass::DomainColoringPass;
+use crate::contour::zero_contours;
+use crate::crosshair::{CrosshairReadout, CrosshairStyle};
 use crate::decimal_math::*;
+use crate::geometry::Rect;
 use crate::graph::*;
-use crate::math::lerp;
+use crate::history::NavigationHistory;
+use crate::interval::Interval;
+use crate::job::CancellationToken;
+use crate::layer::{CanvasLayer, LayerPainter};
+use crate::math::{
+    approx_eq, approx_eq_points, approx_zero, inverse_lerp, saturate, smoothstep, Degrees, Radians,
+};
+use crate::measure::{AngleAnnotation, Measurement};
+use crate::projection::{CoordinateTransform, IdentityTransform};
+use crate::snap::{Snap, SnapMode, SnapTarget, Snapping};
+use crate::text_layout::{superscript_exponent, ShapeCache, ShapedLine};
+use crate::theme::Theme;
+use crate::widget::{Widget, WidgetKind};
 use crate::TextSection;
-use crate::{GPUView, GPUViewFrame, ShaderDescriptor, Vertex};
+use crate::{GPUMultiView, GPUPass, GPUView, GPUViewFrame, PickHandle, ShaderDescriptor, Vertex};
 
+use anyhow::Context;
 use fraction::ToPrimitive;
-use wgpu::util::DeviceExt;
+use wgpu_text::glyph_brush::BuiltInLineBreaker;
 use wgpu_text::glyph_brush::HorizontalAlign;
 use wgpu_text::glyph_brush::Layout;
 use wgpu_text::glyph_brush::SectionBuilder;
 use wgpu_text::glyph_brush::Text;
 use wgpu_text::glyph_brush::VerticalAlign;
 
-use std::{cell::RefCell, ops::Range, sync::Arc};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::NoUninit)]
-struct GPUCanvas2DShaderEnv {
-    range_start: [f32; 2],
-    range_end: [f32; 2],
-}
-
-struct GPUCanvas2DShaderDescriptor {
-    enviroment: GPUCanvas2DShaderEnv,
-
-    enviroment_buffer: Option<wgpu::Buffer>,
-
-    is_initialized: bool,
-    enviroment_changed: bool,
-}
+/// Shader of the canvas, vertices are already in screen space so it has no bindings.
+struct GPUCanvas2DShaderDescriptor;
 
 impl GPUCanvas2DShaderDescriptor {
-    fn new(enviroment: GPUCanvas2DShaderEnv) -> Self {
-        Self {
-            enviroment,
-            enviroment_buffer: None,
-            is_initialized: false,
-            enviroment_changed: false,
-        }
-    }
-
-    fn enviroment_get_mut(&mut self) -> &mut GPUCanvas2DShaderEnv {
-        self.enviroment_changed = true;
-        &mut self.enviroment
-    }
-
     fn into_arc_ref_cell(self) -> Arc<RefCell<Self>> {
         Arc::new(RefCell::new(self))
     }
 }
 
 impl ShaderDescriptor for GPUCanvas2DShaderDescriptor {
-    fn initialize(&mut self, device: &wgpu::Device) -> anyhow::Result<()> {
-        self.enviroment_buffer = Some(device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("GPUCanvas2DShaderDescriptor Enviroment Variable Buffer"),
-                contents: bytemuck::bytes_of(&self.enviroment),
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            },
-        ));
-
-        self.is_initialized = true;
-
+    fn initialize(&mut self, _device: &wgpu::Device) -> anyhow::Result<()> {
         Ok(())
     }
 
-    fn update_buffers(&mut self, queue: &wgpu::Queue) -> anyhow::Result<()> {
-        if !self.is_initialized {
-            return Err(anyhow::Error::msg(
-                "Cannot update buffer of uninitialized shader descriptor.",
-            ));
-        }
-
-        if self.enviroment_changed {
-            let new_data = bytemuck::bytes_of(&self.enviroment);
-
-            let buffer = self.enviroment_buffer.as_ref().unwrap();
-            queue.write_buffer(buffer, 0, new_data);
-
-            self.enviroment_changed = false;
-        }
-
+    fn update_buffers(&mut self, _queue: &wgpu::Queue) -> anyhow::Result<()> {
         Ok(())
     }
 
@@ -93,62 +67,260 @@ impl ShaderDescriptor for GPUCanvas2DShaderDescriptor {
         &self,
         device: &wgpu::Device,
     ) -> anyhow::Result<(wgpu::BindGroup, wgpu::BindGroupLayout)> {
-        if !self.is_initialized {
-            return Err(anyhow::Error::msg(
-                "Cannot get BindGroup and BindGroupLayout of uninitialized GPUCanvas2DShaderDescriptor.",
-            ));
-        }
-
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Shader Descripot Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
+            entries: &[],
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Shader Descriptor Bind Group"),
             layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(
-                    self.enviroment_buffer
-                        .as_ref()
-                        .unwrap()
-                        .as_entire_buffer_binding(),
-                ),
-            }],
+            entries: &[],
         });
 
         Ok((bind_group, bind_group_layout))
     }
 }
 
-pub struct GPUCanvas2D<P>
+/// Cached vertices of a part of the canvas which can be tessellated independently.
+#[derive(Debug, Default)]
+struct VertexLayer {
+    vertices: Vec<Vertex>,
+    /// Vertices of the blurred glow layer of the view
+    glow_vertices: Vec<Vertex>,
+    pick_id: u32,
+    dirty: bool,
+}
+
+impl VertexLayer {
+    fn new(pick_id: u32) -> Self {
+        Self {
+            vertices: Vec::new(),
+            glow_vertices: Vec::new(),
+            pick_id,
+            dirty: true,
+        }
+    }
+}
+
+/// What changed since the last `display`.
+///
+/// Graph specific changes are tracked by the `VertexLayer` of each graph.
+#[derive(Debug, Clone, Copy)]
+struct DisplayChanges {
+    /// Invalidates everything as all vertices depend on the range.
+    range: bool,
+    /// Invalidates the enviroment, its text and the label backgrounds.
+    style: bool,
+    /// Invalidates all function graphs.
+    parameter: bool,
+    /// Graphs got added or removed, the layers have to be reassembled.
+    layers: bool,
+    /// Invalidates the cursor readout and the crosshair.
+    readout: bool,
+    /// Invalidates the measurement overlay.
+    measurement: bool,
+    /// Invalidates the widgets.
+    widgets: bool,
+}
+
+impl DisplayChanges {
+    const ALL: Self = Self {
+        range: true,
+        style: true,
+        parameter: true,
+        layers: true,
+        readout: true,
+        measurement: true,
+        widgets: true,
+    };
+
+    const NONE: Self = Self {
+        range: false,
+        style: false,
+        parameter: false,
+        layers: false,
+        readout: false,
+        measurement: false,
+        widgets: false,
+    };
+
+    fn any(&self) -> bool {
+        self.range
+            || self.style
+            || self.parameter
+            || self.layers
+            || self.readout
+            || self.measurement
+            || self.widgets
+    }
+}
+
+struct GraphEntry<P> {
+    id: GraphId,
+    graph: FunctionGraph<f64, P, f64>,
+    layer: VertexLayer,
+    error: Option<GraphError>,
+    /// Sample count of the current tessellation, `0` if it is outdated.
+    sampled: u32,
+    transform: GraphTransform,
+    /// Replaces the function of `graph` if set.
+    derived: Option<DerivedGraph>,
+}
+
+impl<P> GraphEntry<P> {
+    /// Graphs this one is drawn from, it is tessellated again whenever one of them is.
+    fn sources(&self) -> Vec<GraphId> {
+        let mut sources = self
+            .derived
+            .map(|derived| derived.sources())
+            .unwrap_or_default();
+        if let Some(FillStyle {
+            baseline: FillBaseline::Graph(id),
+            ..
+        }) = self.graph.style.fill
+        {
+            sources.push(id);
+        }
+
+        sources
+    }
+}
+
+struct ComplexGraphEntry<P> {
+    id: GraphId,
+    graph: ComplexFunctionGraph<P>,
+    layer: VertexLayer,
+    /// Draws graphs with an expression while the canvas shows data coordinates linearly.
+    pass: Arc<RefCell<DomainColoringPass>>,
+    error: Option<GraphError>,
+}
+
+struct ParametricGraphEntry<P> {
+    id: GraphId,
+    graph: ParametricGraph<P>,
+    layer: VertexLayer,
+    error: Option<GraphError>,
+}
+
+struct ImplicitGraphEntry<P> {
+    id: GraphId,
+    graph: ImplicitGraph<P>,
+    layer: VertexLayer,
+    error: Option<GraphError>,
+}
+
+/// Theme change in progress, see `GPUCanvas2D::apply_theme_animated`.
+struct ThemeTransition {
+    from: Theme,
+    to: Theme,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+struct EllipseEntry {
+    id: GraphId,
+    ellipse: CovarianceEllipse,
+    layer: VertexLayer,
+}
+
+struct LayerEntry<P>
 where
     P: Default,
 {
-    style: EnviromentStyle,
+    id: GraphId,
+    layer: Box<dyn CanvasLayer<P>>,
+    vertices: VertexLayer,
+    sections: Vec<Arc<RefCell<TextSection>>>,
+}
 
-    x_range: Range<f64>, // coordinate space
-    y_range: Range<f64>, // coordinate space
+struct PointSeriesEntry {
+    id: GraphId,
+    series: PointSeries,
+    layer: VertexLayer,
+    transform: GraphTransform,
+}
 
-    functions: Vec<FunctionGraph<f64, P, f64>>,
+pub struct GPUCanvas2D<P>
+where
+    P: Default,
+{
+    style: EnviromentStyle,
+    accessibility: Option<AccessibilityStyle>,
+    /// `style` with the accessibility adjustments applied, used for drawing
+    display_style: EnviromentStyle,
+
+    x_range: Interval, // coordinate space
+    y_range: Interval, // coordinate space
+    x_scale: AxisScale,
+    y_scale: AxisScale,
+    /// Applied to data coordinates before the axis scales, see `set_projection`
+    projection: Box<dyn CoordinateTransform>,
+    /// Ranges are longitude and latitude, see `set_geographic`
+    geographic: bool,
+
+    functions: Vec<GraphEntry<P>>,
+    complex_functions: Vec<ComplexGraphEntry<P>>,
+    parametric_graphs: Vec<ParametricGraphEntry<P>>,
+    implicit_graphs: Vec<ImplicitGraphEntry<P>>,
+    point_series: Vec<PointSeriesEntry>,
+    ellipses: Vec<EllipseEntry>,
+    layers: Vec<LayerEntry<P>>,
+    next_graph_id: u32,
+    graph_names: BTreeMap<GraphId, String>,
+
+    // levels of the graph style cascade below the graphs themselves
+    theme_graph_style: GraphStyle,
+    canvas_graph_style: GraphStyleOverride,
+    group_graph_styles: Vec<(GraphGroup, GraphStyleOverride)>,
+    theme_transition: Option<ThemeTransition>,
     parameter: P,
 
-    shader_descriptor: Arc<RefCell<GPUCanvas2DShaderDescriptor>>,
     view: Arc<RefCell<GPUView>>,
-
-    style_changed: bool,
-    range_changed: bool,
-    function_changed: bool,
+    view_size: Option<(u32, u32)>,
+    clear_color: RGBA,
+
+    label_backgrounds: Vec<(Rect, RGBA)>,
+    shape_cache: ShapeCache,
+
+    /// Vertices of the layer which is currently tessellated.
+    vertices: Vec<Vertex>,
+    enviroment_layer: VertexLayer,
+    label_background_layer: VertexLayer,
+
+    curve_label_layer: VertexLayer,
+    curve_label_sections: Vec<Arc<RefCell<TextSection>>>,
+
+    /// Data space x at which all function graphs get evaluated and labeled.
+    cursor_readout: Option<f64>,
+    readout_layer: VertexLayer,
+    readout_sections: Vec<Arc<RefCell<TextSection>>>,
+
+    /// View space position of the crosshair.
+    crosshair: Option<(f32, f32)>,
+    crosshair_style: CrosshairStyle,
+    crosshair_layer: VertexLayer,
+    crosshair_sections: Vec<Arc<RefCell<TextSection>>>,
+
+    measurement: Option<Measurement>,
+    angle_measurement: Option<AngleAnnotation>,
+    measurement_layer: VertexLayer,
+    measurement_sections: Vec<Arc<RefCell<TextSection>>>,
+
+    widgets: Vec<Widget<P>>,
+    widget_layer: VertexLayer,
+    widget_sections: Vec<Arc<RefCell<TextSection>>>,
+
+    changes: DisplayChanges,
+    progressive_rendering: bool,
+    range_token: CancellationToken,
+    history: NavigationHistory,
+
+    error_callback: Option<Box<dyn FnMut(GraphId, &GraphError)>>,
+    range_callbacks: Vec<Box<dyn FnMut(&Interval, &Interval)>>,
+    parameter_callbacks: Vec<Box<dyn FnMut(&P)>>,
+    pick_callbacks: Vec<Box<dyn FnMut(Option<GraphId>, (f32, f32))>>,
+    pending_picks: Vec<((f32, f32), PickHandle)>,
 }
 
 impl<P> GPUCanvas2D<P>
@@ -156,127 +328,567 @@ where
     P: Default,
 {
     pub fn new(view_frame: GPUViewFrame) -> Self {
-        let shader_enviroment = GPUCanvas2DShaderEnv {
-            range_start: [-1.0, -1.0],
-            range_end: [1.0, 1.0],
-        };
-        let shader_descriptor =
-            GPUCanvas2DShaderDescriptor::new(shader_enviroment).into_arc_ref_cell();
+        let shader_descriptor = GPUCanvas2DShaderDescriptor.into_arc_ref_cell();
 
         Self {
             style: EnviromentStyle::default(),
-            x_range: -1.0..1.0,
-            y_range: -1.0..1.0,
+            accessibility: None,
+            display_style: EnviromentStyle::default(),
+            x_range: Interval::UNIT,
+            y_range: Interval::UNIT,
+            x_scale: AxisScale::Linear,
+            y_scale: AxisScale::Linear,
+            projection: Box::new(IdentityTransform),
+            geographic: false,
             functions: Vec::new(),
+            complex_functions: Vec::new(),
+            parametric_graphs: Vec::new(),
+            implicit_graphs: Vec::new(),
+            point_series: Vec::new(),
+            ellipses: Vec::new(),
+            layers: Vec::new(),
+            next_graph_id: 1,
+            graph_names: BTreeMap::new(),
+
+            theme_graph_style: GraphStyle::default(),
+            canvas_graph_style: GraphStyleOverride::default(),
+            group_graph_styles: Vec::new(),
+            theme_transition: None,
             parameter: P::default(),
-            shader_descriptor: shader_descriptor.clone(),
             view: GPUView::new(view_frame, shader_descriptor).into_arc_ref_cell(),
-            style_changed: true,
-            range_changed: true,
-            function_changed: true,
+            view_size: None,
+            clear_color: RGBA::WHITE,
+            label_backgrounds: Vec::new(),
+            shape_cache: ShapeCache::default(),
+            vertices: Vec::new(),
+            enviroment_layer: VertexLayer::new(0),
+            label_background_layer: VertexLayer::new(0),
+            curve_label_layer: VertexLayer::new(0),
+            curve_label_sections: Vec::new(),
+            cursor_readout: None,
+            readout_layer: VertexLayer::new(0),
+            readout_sections: Vec::new(),
+            crosshair: None,
+            crosshair_style: CrosshairStyle::default(),
+            crosshair_layer: VertexLayer::new(0),
+            crosshair_sections: Vec::new(),
+            measurement: None,
+            angle_measurement: None,
+            measurement_layer: VertexLayer::new(0),
+            measurement_sections: Vec::new(),
+            widgets: Vec::new(),
+            widget_layer: VertexLayer::new(0),
+            widget_sections: Vec::new(),
+            changes: DisplayChanges::ALL,
+            progressive_rendering: false,
+            range_token: CancellationToken::new(),
+            history: NavigationHistory::default(),
+            error_callback: None,
+            range_callbacks: Vec::new(),
+            parameter_callbacks: Vec::new(),
+            pick_callbacks: Vec::new(),
+            pending_picks: Vec::new(),
         }
     }
 
     pub fn set_style(&mut self, style: EnviromentStyle) {
         self.style = style;
-        self.style_changed = true;
+        self.changes.style = true;
     }
 
     pub fn style_get_mut(&mut self) -> &mut EnviromentStyle {
-        self.style_changed = true;
+        self.changes.style = true;
         &mut self.style
     }
 
+    /// Replaces the style, the clear color and the lowest level of the graph style cascade.
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme_transition = None;
+        self.apply_theme(theme);
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_style(theme.enviroment.clone());
+        self.set_clear_color(theme.clear_color);
+        self.theme_graph_style = theme.graph.clone();
+        self.invalidate_graph_styles();
+    }
+
+    /// Like `set_theme`, but fades colors and thicknesses from the current ones over
+    /// `duration`, driven by `advance_animations`.
+    pub fn apply_theme_animated(&mut self, theme: &Theme, duration: Duration) {
+        if duration.is_zero() {
+            self.set_theme(theme);
+            return;
+        }
+
+        let from = Theme {
+            clear_color: self.clear_color,
+            enviroment: self.style.clone(),
+            graph: self.theme_graph_style.clone(),
+        };
+
+        self.theme_transition = Some(ThemeTransition {
+            from,
+            to: theme.clone(),
+            duration,
+            elapsed: Duration::ZERO,
+        });
+    }
+
+    /// Advances running animations by `delta`, e.g. the delta of the frame clock
+    /// (`VisContext::clock`) before every `display`.
+    pub fn advance_animations(&mut self, delta: Duration) {
+        let Some(transition) = &mut self.theme_transition else {
+            return;
+        };
+        transition.elapsed += delta;
+
+        let t = (transition.elapsed.as_secs_f32() / transition.duration.as_secs_f32()).min(1.0);
+        let theme = transition
+            .from
+            .mix(&transition.to, smoothstep(t, &(0.0..1.0)));
+
+        if t >= 1.0 {
+            self.theme_transition = None;
+        }
+        self.apply_theme(&theme);
+    }
+
+    /// True while an animation runs, which needs further frames.
+    pub fn is_animating(&self) -> bool {
+        self.theme_transition.is_some()
+    }
+
+    //-- graph style cascade
+    //
+    // Graphs with a `style_override` are styled by the theme, then the canvas, then every
+    // group containing them (in the order the group styles were set) and finally their
+    // own override. Graphs without one keep using their `style` as is.
+
+    pub fn set_canvas_graph_style(&mut self, style: GraphStyleOverride) {
+        self.canvas_graph_style = style;
+        self.invalidate_graph_styles();
+    }
+
+    /// Sets the style of the graphs in `group`, replacing an earlier style of the same group.
+    pub fn set_group_style(&mut self, group: &GraphGroup, style: GraphStyleOverride) {
+        match self
+            .group_graph_styles
+            .iter_mut()
+            .find(|(other, _)| other == group)
+        {
+            Some((_, group_style)) => *group_style = style,
+            None => self.group_graph_styles.push((group.clone(), style)),
+        }
+        self.invalidate_graph_styles();
+    }
+
+    pub fn remove_group_style(&mut self, group: &GraphGroup) {
+        self.group_graph_styles.retain(|(other, _)| other != group);
+        self.invalidate_graph_styles();
+    }
+
+    /// Style of graph `id` with `graph_style` as the topmost level of the cascade.
+    fn cascade_graph_style(&self, id: GraphId, graph_style: &GraphStyleOverride) -> GraphStyle {
+        let mut style = self.theme_graph_style.clone();
+
+        self.canvas_graph_style.apply(&mut style);
+        for (group, group_style) in self.group_graph_styles.iter() {
+            if group.contains(id) {
+                group_style.apply(&mut style);
+            }
+        }
+        graph_style.apply(&mut style);
+
+        style
+    }
+
+    /// Graphs inheriting their style get restyled on the next `display`.
+    fn invalidate_graph_styles(&mut self) {
+        for entry in self.functions.iter_mut() {
+            entry.layer.dirty |= entry.graph.style_override.is_some();
+        }
+        for entry in self.parametric_graphs.iter_mut() {
+            entry.layer.dirty |= entry.graph.style_override.is_some();
+        }
+        for entry in self.implicit_graphs.iter_mut() {
+            entry.layer.dirty |= entry.graph.style_override.is_some();
+        }
+    }
+
+    /// Applies high contrast colors, a minimum text size and minimum line thickness
+    /// on top of the style, `None` draws the style as is.
+    pub fn set_accessibility(&mut self, accessibility: Option<AccessibilityStyle>) {
+        self.accessibility = accessibility;
+        self.changes.style = true;
+
+        // graph thickness is affected as well
+        for entry in self.functions.iter_mut() {
+            entry.layer.dirty = true;
+            entry.sampled = 0;
+        }
+        for entry in self.point_series.iter_mut() {
+            entry.layer.dirty = true;
+        }
+        for entry in self.ellipses.iter_mut() {
+            entry.layer.dirty = true;
+        }
+    }
+
+    pub fn accessibility(&self) -> Option<&AccessibilityStyle> {
+        self.accessibility.as_ref()
+    }
+
+    pub(crate) fn line_thickness(&self, thickness: f32) -> f32 {
+        match &self.accessibility {
+            Some(accessibility) => accessibility.thickness(thickness),
+            None => thickness,
+        }
+    }
+
+    pub fn parameter(&self) -> &P {
+        &self.parameter
+    }
+
     pub fn parameter_get_mut(&mut self) -> &mut P {
-        self.function_changed = true;
+        self.changes.parameter = true;
         &mut self.parameter
     }
 
-    fn update_shader_env_range(&mut self) {
-        let mut tmp = self.shader_descriptor.borrow_mut();
-        let env = tmp.enviroment_get_mut();
+    fn range_updated(&mut self) {
+        self.changes.range = true;
+        self.cancel_range_token();
+    }
+
+    /// Abandons all jobs computed for the previous range or view size.
+    fn cancel_range_token(&mut self) {
+        self.range_token.cancel();
+        self.range_token = CancellationToken::new();
+    }
 
-        env.range_start = [self.x_range.start as f32, self.y_range.start as f32];
-        env.range_end = [self.x_range.end as f32, self.y_range.end as f32];
+    /// Token which gets cancelled as soon as the range or the view size changes.
+    ///
+    /// Pass it to `Job::spawn` for work that only applies to the current viewport.
+    pub fn range_token(&self) -> CancellationToken {
+        self.range_token.clone()
     }
 
     pub fn get_view(&self) -> Arc<RefCell<GPUView>> {
         self.view.clone()
     }
 
+    /// Reads back the last rendered frame of the canvas, see `GPUView::read_pixels`.
+    pub fn read_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.view.borrow().read_pixels(device, queue)
+    }
+
+    /// Also fills open endpoint markers, see `EndpointMarker`.
     pub fn set_clear_color(&mut self, clear_color: RGBA) {
+        self.clear_color = clear_color;
+        // open markers are punched out with the clear color
+        for entry in self.functions.iter_mut() {
+            entry.layer.dirty |=
+                entry.graph.endpoints != (None, None) || entry.graph.style.gap_marker.is_some();
+        }
+        for entry in self.parametric_graphs.iter_mut() {
+            entry.layer.dirty |= entry.graph.style.gap_marker.is_some();
+        }
+
         self.view
             .as_ref()
             .borrow_mut()
             .set_clear_color(clear_color.into());
     }
 
-    pub fn x_range(&self) -> &Range<f64> {
-        &self.x_range
+    pub fn x_range(&self) -> Interval {
+        self.x_range
     }
 
-    pub fn y_range(&self) -> &Range<f64> {
-        &self.y_range
+    pub fn y_range(&self) -> Interval {
+        self.y_range
     }
 
+    /// Visible area in data coordinates.
+    pub fn data_rect(&self) -> Rect<f64> {
+        Rect::from_intervals(self.x_range, self.y_range)
+    }
+
+    /// Ranges without positive values are replaced on logarithmic axes, see
+    /// `AxisScale::valid_range`.
+    pub fn set_range(&mut self, x_range: impl Into<Interval>, y_range: impl Into<Interval>) {
+        self.x_range = self.x_scale.valid_range(x_range.into());
+        self.y_range = self.y_scale.valid_range(y_range.into());
+
+        self.range_updated();
+    }
+
+    pub fn x_scale(&self) -> AxisScale {
+        self.x_scale
+    }
+
+    pub fn y_scale(&self) -> AxisScale {
+        self.y_scale
+    }
+
+    /// Grid, ticks, sampling of function graphs and navigation follow the scale.
+    pub fn set_x_scale(&mut self, scale: AxisScale) {
+        self.x_scale = scale;
+        self.x_range = scale.valid_range(self.x_range);
+
+        self.range_updated();
+    }
+
+    /// Grid, ticks and navigation follow the scale.
+    pub fn set_y_scale(&mut self, scale: AxisScale) {
+        self.y_scale = scale;
+        self.y_range = scale.valid_range(self.y_range);
+
+        self.range_updated();
+    }
+
+    pub fn projection(&self) -> &dyn CoordinateTransform {
+        self.projection.as_ref()
+    }
+
+    /// Maps data coordinates onto the plane of the ranges before the axis scales, e.g. with
+    /// `PolarTransform`. Graphs and layers keep drawing from data coordinates.
+    pub fn set_projection(&mut self, projection: impl CoordinateTransform + 'static) {
+        self.projection = Box::new(projection);
+
+        self.range_updated();
+    }
+
+    /// Interprets the x range as longitude and the y range as latitude in degrees: y gets the
+    /// `AxisScale::WebMercator` scale and grid labels with the `Auto` format are written as
+    /// degrees east / west and north / south, so the grid becomes a graticule.
+    pub fn set_geographic(&mut self, geographic: bool) {
+        self.geographic = geographic;
+        self.set_x_scale(AxisScale::Linear);
+        self.set_y_scale(match geographic {
+            true => AxisScale::WebMercator,
+            false => AxisScale::Linear,
+        });
+        self.changes.style = true;
+    }
+
+    pub fn is_geographic(&self) -> bool {
+        self.geographic
+    }
+
+    /// Zooms around the center of the scaled ranges.
     pub fn scale_range(&mut self, scale: (f64, f64)) {
         static MAX_RANGE: f32 = 5.0;
         static MIN_RANGE: f32 = 0.2;
 
-        let x_diff = self.x_range_len() * (scale.0 - 1.0);
-        let y_diff = self.y_range_len() * (scale.1 - 1.0);
+        let new_x_range = self
+            .x_scale
+            .inverse_interval(&self.x_scale.forward_interval(&self.x_range).scale(scale.0));
+        let new_y_range = self
+            .y_scale
+            .inverse_interval(&self.y_scale.forward_interval(&self.y_range).scale(scale.1));
+        let (new_x_range, new_y_range) = (
+            self.x_scale.valid_range(new_x_range),
+            self.y_scale.valid_range(new_y_range),
+        );
+
+        if !new_x_range.is_finite() || !new_y_range.is_finite() {
+            return;
+        }
+
+        self.history.record_navigation((self.x_range, self.y_range));
+        self.x_range = new_x_range;
+        self.y_range = new_y_range;
+
+        self.range_updated();
+    }
+
+    /// Pans by `offset` data units, on nonlinear axes by the same fraction of the scaled range.
+    pub fn offset_range(&mut self, offset: (f64, f64)) {
+        let offset_axis = |range: Interval, scale: AxisScale, offset: f64| {
+            if scale.is_linear() {
+                return range.offset(offset);
+            }
+
+            let scaled = scale.forward_interval(&range);
+            scale.inverse_interval(&scaled.offset(offset / range.len() * scaled.len()))
+        };
 
         let new_x_range =
-            (self.x_range.start - (x_diff * 0.5))..(self.x_range.end + (x_diff * 0.5));
+            self.x_scale
+                .valid_range(offset_axis(self.x_range, self.x_scale, offset.0));
         let new_y_range =
-            (self.y_range.start - (y_diff * 0.5))..(self.y_range.end + (y_diff * 0.5));
-
-        if !new_x_range.start.is_finite()
-            || !new_x_range.start.is_finite()
-            || !new_x_range.end.is_finite()
-            || !new_x_range.end.is_finite()
-            || !new_y_range.start.is_finite()
-            || !new_y_range.start.is_finite()
-            || !new_y_range.end.is_finite()
-            || !new_y_range.end.is_finite()
-        {
+            self.y_scale
+                .valid_range(offset_axis(self.y_range, self.y_scale, offset.1));
+
+        if !new_x_range.is_finite() || !new_y_range.is_finite() {
             return;
         }
 
+        self.history.record_navigation((self.x_range, self.y_range));
         self.x_range = new_x_range;
         self.y_range = new_y_range;
 
-        self.range_changed = true;
-        self.update_shader_env_range();
+        self.range_updated();
     }
 
-    pub fn offset_range(&mut self, offset: (f64, f64)) {
-        self.x_range = (self.x_range.start + offset.0)..(self.x_range.end + offset.0);
-        self.y_range = (self.y_range.start + offset.1)..(self.y_range.end + offset.1);
+    //-- navigation history
+    //
+    // `scale_range` and `offset_range` are recorded, `set_range` only after `push_history`.
+
+    pub fn history(&self) -> &NavigationHistory {
+        &self.history
+    }
+
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.set_capacity(capacity);
+    }
+
+    /// Records the current range as an undo step of its own, e.g. before calling `set_range`.
+    pub fn push_history(&mut self) {
+        self.history.push((self.x_range, self.y_range));
+    }
+
+    /// Goes back to the range before the last recorded navigation, `false` if there is none.
+    pub fn undo_range(&mut self) -> bool {
+        match self.history.undo((self.x_range, self.y_range)) {
+            Some((x_range, y_range)) => {
+                self.set_range(x_range, y_range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reverts the last `undo_range`, `false` if there is nothing to redo.
+    pub fn redo_range(&mut self) -> bool {
+        match self.history.redo((self.x_range, self.y_range)) {
+            Some((x_range, y_range)) => {
+                self.set_range(x_range, y_range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Range restored by `reset_range`, by default the range before the first recorded
+    /// navigation.
+    pub fn set_home_range(&mut self, x_range: impl Into<Interval>, y_range: impl Into<Interval>) {
+        self.history.set_home((x_range.into(), y_range.into()));
+    }
 
-        self.range_changed = true;
-        self.update_shader_env_range();
+    /// Goes back to the home range as an undoable step, `false` if there is none yet.
+    pub fn reset_range(&mut self) -> bool {
+        let Some((x_range, y_range)) = self.history.home() else {
+            return false;
+        };
+
+        if (x_range, y_range) != (self.x_range, self.y_range) {
+            self.push_history();
+            self.set_range(x_range, y_range);
+        }
+        true
     }
 
     pub fn x_range_len(&self) -> f64 {
-        self.x_range.end - self.x_range.start
+        self.x_range.len()
     }
 
     pub fn y_range_len(&self) -> f64 {
-        self.y_range.end - self.y_range.start
+        self.y_range.len()
+    }
+
+    /// Center of the visible ranges, the origin of the `f32` vertex positions.
+    pub fn range_center(&self) -> (f64, f64) {
+        (self.x_range.center(), self.y_range.center())
+    }
+
+    /// Maps data coordinates to view space (`-1..1` inside the canvas view).
+    pub fn data_to_view(&self, data: (f64, f64)) -> (f32, f32) {
+        self.global_to_screen(data)
+    }
+
+    /// Maps view space (`-1..1` inside the canvas view) to data coordinates.
+    pub fn view_to_data(&self, view: (f32, f32)) -> (f64, f64) {
+        self.screen_to_global((view.0 as f64, view.1 as f64))
+    }
+
+    /// Maps data coordinates to physical pixels of `multiview` (origin in the upper left corner).
+    pub fn data_to_pixel(&self, data: (f64, f64), multiview: &GPUMultiView) -> Option<(f64, f64)> {
+        let screen = self.view.borrow().view_to_screen(self.data_to_view(data));
+
+        multiview.screen_to_pixel(screen)
+    }
+
+    /// Maps physical pixels of `multiview` (origin in the upper left corner) to view space.
+    pub fn pixel_to_view(&self, pixel: (f64, f64), multiview: &GPUMultiView) -> Option<(f32, f32)> {
+        let screen = multiview.pixel_to_screen(pixel)?;
+
+        Some(self.view.borrow().screen_to_view(screen))
+    }
+
+    /// Maps physical pixels of `multiview` (origin in the upper left corner) to data coordinates.
+    pub fn pixel_to_data(&self, pixel: (f64, f64), multiview: &GPUMultiView) -> Option<(f64, f64)> {
+        let screen = multiview.pixel_to_screen(pixel)?;
+        let view = self.view.borrow().screen_to_view(screen);
+
+        Some(self.view_to_data(view))
     }
 
-    /// Coordinate transform from range to `-1..1`
+    /// Coordinate transform from data coordinates to `-1..1`, see `plane_to_screen`
     fn global_to_screen(&self, global: (f64, f64)) -> (f32, f32) {
-        let (gx, gy) = global;
+        self.plane_to_screen(self.projection.forward(global))
+    }
+
+    /// `global_to_screen`, if the position is inside of the visible area.
+    fn visible_screen_position(&self, global: (f64, f64)) -> Option<(f32, f32)> {
+        let (sx, sy) = self.global_to_screen(global);
+        let visible = |value: f32| value.is_finite() && (-1.0..=1.0).contains(&value);
+
+        (visible(sx) && visible(sy)).then_some((sx, sy))
+    }
+
+    /// Coordinate transform from projected coordinates (the plane of the ranges, grid and
+    /// ticks) to `-1..1`
+    ///
+    /// The range center is subtracted in `f64` first, so only the small relative offset
+    /// gets cast to `f32` (large coordinates like unix timestamps would jitter otherwise).
+    /// This replaces a high/low split center uniform: vertices are emitted in `-1..1` after
+    /// the axis scales were applied, so the shader never sees coordinates needing it. Passes
+    /// binning raw coordinates on the GPU split their anchor instead (see `DensityLayer`).
+    /// Values outside of a logarithmic scale map to non finite coordinates.
+    fn plane_to_screen(&self, plane: (f64, f64)) -> (f32, f32) {
+        let (gx, gy) = plane;
+
+        let to_ndc = |range: &Interval, scale: AxisScale, value: f64| match scale {
+            AxisScale::Linear => range.to_ndc(value),
+            scale => scale.forward_interval(range).to_ndc(scale.forward(value)),
+        };
 
-        let lx = (2.0 * gx - self.x_range.start - self.x_range.end) / self.x_range_len();
-        let ly = (2.0 * gy - self.y_range.start - self.y_range.end) / self.y_range_len();
+        let lx = to_ndc(&self.x_range, self.x_scale, gx);
+        let ly = to_ndc(&self.y_range, self.y_scale, gy);
 
         (lx as f32, ly as f32)
     }
 
-    fn calculate_dynamic_spacing(range_len: f64, num_steps: u32) -> Decimal {
+    /// Inverse of `global_to_screen`.
+    fn screen_to_global(&self, screen: (f64, f64)) -> (f64, f64) {
+        let from_ndc = |range: &Interval, scale: AxisScale, ndc: f64| match scale {
+            AxisScale::Linear => range.from_ndc(ndc),
+            scale => scale.inverse(scale.forward_interval(range).from_ndc(ndc)),
+        };
+
+        self.projection.inverse((
+            from_ndc(&self.x_range, self.x_scale, screen.0),
+            from_ndc(&self.y_range, self.y_scale, screen.1),
+        ))
+    }
+
+    pub(crate) fn calculate_dynamic_spacing(range_len: f64, num_steps: u32) -> Decimal {
         let range_len = decimal_from_to_string(range_len);
         let num_steps = Decimal::from(num_steps);
 
@@ -297,779 +909,4065 @@ where
         closest.0
     }
 
-    pub fn add_function_graph(&mut self, function_graph: FunctionGraph<f64, P, f64>) {
-        self.functions.push(function_graph);
-        self.function_changed = true;
+    /// Spacing of the major grid lines and the number of substeps in between.
+    fn grid_spacing(spacing: &GridSpacing, range_len: f64) -> (Decimal, u32) {
+        match spacing {
+            GridSpacing::Dynamic { steps, substeps } => (
+                Self::calculate_dynamic_spacing(range_len, *steps),
+                *substeps,
+            ),
+            GridSpacing::Fixed { spacing, substeps } => (spacing.clone(), *substeps),
+        }
     }
 
-    fn screen_constant(&self, value: f64) -> f32 {
-        (value * ((self.x_range_len() + self.y_range_len()) / 2.0)) as f32
-    }
+    fn next_graph_id(&mut self) -> GraphId {
+        let id = GraphId::new(self.next_graph_id);
+        self.next_graph_id += 1;
 
-    fn display_refresh_required(&self) -> bool {
-        self.style_changed || self.range_changed || self.function_changed
+        id
     }
 
-    fn display_reset_refresh(&mut self) {
-        self.style_changed = false;
-        self.range_changed = false;
-        self.function_changed = false;
+    pub fn add_function_graph(&mut self, function_graph: FunctionGraph<f64, P, f64>) -> GraphId {
+        let id = self.next_graph_id();
+
+        self.functions.push(GraphEntry {
+            id,
+            graph: function_graph,
+            layer: VertexLayer::new(id.pick_id()),
+            error: None,
+            sampled: 0,
+            transform: GraphTransform::IDENTITY,
+            derived: None,
+        });
+        self.changes.layers = true;
+
+        id
     }
 
-    pub fn display_clear(&mut self) {
-        let mut view = self.view.as_ref().borrow_mut();
+    /// Adds a curve for every parameter value next to the live parameter `P`,
+    /// see `function_family`.
+    pub fn add_function_family(
+        &mut self,
+        function: impl Fn(f64, f64, &P) -> f64 + Send + Sync + 'static,
+        param_values: Vec<f64>,
+        style_fn: impl Fn(f64, RGBA) -> GraphStyle,
+    ) -> GraphGroup {
+        GraphGroup {
+            ids: function_family(function, &param_values, style_fn)
+                .into_iter()
+                .map(|graph| self.add_function_graph(graph))
+                .collect(),
+        }
+    }
 
-        view.clear_render_vertices();
-        view.clear_text_sections();
+    /// Adds every piece as its own function graph and returns their ids.
+    pub fn add_piecewise_graph(&mut self, piecewise_graph: PiecewiseGraph<P>) -> Vec<GraphId> {
+        piecewise_graph
+            .graphs()
+            .into_iter()
+            .map(|graph| self.add_function_graph(graph))
+            .collect()
     }
 
-    pub fn display(&mut self) {
-        if !self.display_refresh_required() {
-            return;
+    /// Adds a function graph computed from other function graphs, which is resampled
+    /// whenever one of its sources or the parameter changes.
+    ///
+    /// Returns `None` if a source doesn't exist.
+    pub fn add_derived_graph(
+        &mut self,
+        derived: DerivedGraph,
+        style: GraphStyle,
+    ) -> Option<GraphId> {
+        let sources_exist = derived
+            .sources()
+            .iter()
+            .all(|source| self.functions.iter().any(|entry| entry.id == *source));
+        if !sources_exist {
+            return None;
         }
 
-        self.display_reset_refresh();
-
-        self.display_clear();
+        let id = self.add_function_graph(FunctionGraph::new(|_, _| f64::NAN, style));
+        self.functions.last_mut().unwrap().derived = Some(derived);
 
-        self.display_enviroment();
-        self.display_function_graphs();
+        Some(id)
     }
 
-    fn display_enviroment(&mut self) {
-        //-- screen mapping of global zero
+    /// Adds a domain coloring of a complex function, which is drawn below the grid.
+    pub fn add_complex_function_graph(&mut self, graph: ComplexFunctionGraph<P>) -> GraphId {
+        let id = self.next_graph_id();
 
-        let (sx0, sy0) = self.global_to_screen((0.0, 0.0));
+        let pass = Arc::new(RefCell::new(DomainColoringPass::new()));
+        self.view.borrow_mut().add_gpu_pass(pass.clone());
 
-        //-- ranges
+        self.complex_functions.push(ComplexGraphEntry {
+            id,
+            graph,
+            layer: VertexLayer::new(id.pick_id()),
+            pass,
+            error: None,
+        });
+        self.changes.layers = true;
 
-        let x_range_start = self.x_range.start;
-        let x_range_end = self.x_range.end;
+        id
+    }
 
-        let y_range_start = self.y_range.start;
-        let y_range_end = self.y_range.end;
+    pub fn complex_function_graph_get_mut(
+        &mut self,
+        id: GraphId,
+    ) -> Option<&mut ComplexFunctionGraph<P>> {
+        let entry = self
+            .complex_functions
+            .iter_mut()
+            .find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
+
+        Some(&mut entry.graph)
+    }
 
-        //-- spacings in decimal representation
+    pub fn add_parametric_graph(&mut self, graph: ParametricGraph<P>) -> GraphId {
+        let id = self.next_graph_id();
 
-        let (x_step_spacing, x_substeps) = match &self.style.x.spacing {
-            GridSpacing::Dynamic { steps, substeps } => (
-                Self::calculate_dynamic_spacing(self.x_range_len(), *steps),
-                *substeps,
-            ),
-            GridSpacing::Fixed { spacing, substeps } => (spacing.clone(), *substeps),
-        };
+        self.parametric_graphs.push(ParametricGraphEntry {
+            id,
+            graph,
+            layer: VertexLayer::new(id.pick_id()),
+            error: None,
+        });
+        self.changes.layers = true;
 
-        let (y_step_spacing, y_substeps) = match &self.style.y.spacing {
-            GridSpacing::Dynamic { steps, substeps } => (
-                Self::calculate_dynamic_spacing(self.y_range_len(), *steps),
-                *substeps,
-            ),
-            GridSpacing::Fixed { spacing, substeps } => (spacing.clone(), *substeps),
-        };
+        id
+    }
 
-        let x_substep_spacing = &x_step_spacing / (x_substeps + 1) as f64;
-        let y_substep_spacing = &y_step_spacing / (y_substeps + 1) as f64;
+    pub fn parametric_graph_get_mut(&mut self, id: GraphId) -> Option<&mut ParametricGraph<P>> {
+        let entry = self
+            .parametric_graphs
+            .iter_mut()
+            .find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
 
-        //-- floating point representations of the spacings
+        Some(&mut entry.graph)
+    }
 
-        let x_step_spacing_f64 = x_step_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
-        let y_step_spacing_f64 = y_step_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
-        let x_substep_spacing_f64 = x_substep_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
-        let y_substep_spacing_f64 = y_substep_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
+    pub fn add_implicit_graph(&mut self, graph: ImplicitGraph<P>) -> GraphId {
+        let id = self.next_graph_id();
 
-        //-- offset to make the ranges symetric to zero
+        self.implicit_graphs.push(ImplicitGraphEntry {
+            id,
+            graph,
+            layer: VertexLayer::new(id.pick_id()),
+            error: None,
+        });
+        self.changes.layers = true;
 
-        let x_sym_offset = x_range_start + (self.x_range_len() / 2.0);
-        let x_sym_offset = (x_sym_offset / x_step_spacing_f64).round() * x_step_spacing_f64;
+        id
+    }
 
-        let y_sym_offset = y_range_start + (self.y_range_len() / 2.0);
-        let y_sym_offset = (y_sym_offset / y_step_spacing_f64).round() * y_step_spacing_f64;
+    pub fn implicit_graph_get_mut(&mut self, id: GraphId) -> Option<&mut ImplicitGraph<P>> {
+        let entry = self
+            .implicit_graphs
+            .iter_mut()
+            .find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
 
-        //-- x spacing indices
+        Some(&mut entry.graph)
+    }
+
+    pub fn add_point_series(&mut self, point_series: PointSeries) -> GraphId {
+        let id = self.next_graph_id();
+
+        self.point_series.push(PointSeriesEntry {
+            id,
+            series: point_series,
+            layer: VertexLayer::new(id.pick_id()),
+            transform: GraphTransform::IDENTITY,
+        });
+        self.changes.layers = true;
+
+        id
+    }
+
+    pub fn function_graph_get_mut(
+        &mut self,
+        id: GraphId,
+    ) -> Option<&mut FunctionGraph<f64, P, f64>> {
+        let entry = self.functions.iter_mut().find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
+        entry.sampled = 0;
+
+        Some(&mut entry.graph)
+    }
+
+    /// Adds a covariance ellipse annotation, see `CovarianceEllipse::from_points`.
+    pub fn add_covariance_ellipse(&mut self, ellipse: CovarianceEllipse) -> GraphId {
+        let id = self.next_graph_id();
+
+        self.ellipses.push(EllipseEntry {
+            id,
+            ellipse,
+            layer: VertexLayer::new(id.pick_id()),
+        });
+        self.changes.layers = true;
+
+        id
+    }
+
+    /// Adds a custom layer drawn above the graphs and returns its id, which is also
+    /// reported by `pick` for the layer. The `CanvasLayer::gpu_pass` of the layer is drawn
+    /// below the vertices of the canvas.
+    pub fn add_layer(&mut self, layer: Box<dyn CanvasLayer<P>>) -> GraphId {
+        let id = self.next_graph_id();
+
+        if let Some(pass) = layer.gpu_pass() {
+            self.view.borrow_mut().add_gpu_pass(pass);
+        }
+
+        self.layers.push(LayerEntry {
+            id,
+            layer,
+            vertices: VertexLayer::new(id.pick_id()),
+            sections: Vec::new(),
+        });
+        self.changes.layers = true;
+
+        id
+    }
+
+    pub fn remove_layer(&mut self, id: GraphId) -> Option<Box<dyn CanvasLayer<P>>> {
+        let index = self.layers.iter().position(|entry| entry.id == id)?;
+        let entry = self.layers.remove(index);
+        self.graph_names.remove(&id);
+
+        if let Some(pass) = entry.layer.gpu_pass() {
+            self.view.borrow_mut().remove_gpu_pass(&pass);
+        }
+        self.view.borrow_mut().remove_text_sections(&entry.sections);
+        self.changes.layers = true;
+
+        Some(entry.layer)
+    }
+
+    /// Mutable access to a custom layer, which gets tessellated again.
+    pub fn layer_get_mut(&mut self, id: GraphId) -> Option<&mut (dyn CanvasLayer<P> + 'static)> {
+        let entry = self.layers.iter_mut().find(|entry| entry.id == id)?;
+        entry.vertices.dirty = true;
+
+        Some(entry.layer.as_mut())
+    }
+
+    pub fn covariance_ellipse_get_mut(&mut self, id: GraphId) -> Option<&mut CovarianceEllipse> {
+        let entry = self.ellipses.iter_mut().find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
+
+        Some(&mut entry.ellipse)
+    }
+
+    pub fn point_series_get_mut(&mut self, id: GraphId) -> Option<&mut PointSeries> {
+        let entry = self.point_series.iter_mut().find(|entry| entry.id == id)?;
+        entry.layer.dirty = true;
+
+        Some(&mut entry.series)
+    }
+
+    /// Offsets and scales a function graph or point series when it is drawn, without
+    /// changing its function or points, see `GraphTransform`.
+    pub fn set_graph_transform(&mut self, id: GraphId, dx: f64, dy: f64, sx: f64, sy: f64) {
+        let transform = GraphTransform::new(dx, dy, sx, sy);
+
+        if let Some(entry) = self.functions.iter_mut().find(|entry| entry.id == id) {
+            entry.transform = transform;
+            entry.layer.dirty = true;
+            entry.sampled = 0;
+            self.changes.layers = true;
+        } else if let Some(entry) = self.point_series.iter_mut().find(|entry| entry.id == id) {
+            entry.transform = transform;
+            entry.layer.dirty = true;
+            self.changes.layers = true;
+        }
+    }
+
+    pub fn graph_transform(&self, id: GraphId) -> Option<GraphTransform> {
+        self.functions
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.transform)
+            .or_else(|| {
+                self.point_series
+                    .iter()
+                    .find(|entry| entry.id == id)
+                    .map(|entry| entry.transform)
+            })
+    }
+
+    /// Samples function graphs coarsely first after a change and refines them over the
+    /// following `display` calls in which nothing changed.
+    ///
+    /// Keep redrawing while `refinement_pending` returns true.
+    pub fn set_progressive_rendering(&mut self, enabled: bool) {
+        self.progressive_rendering = enabled;
+    }
+
+    /// Whether a function graph is still drawn with fewer samples than configured.
+    pub fn refinement_pending(&self) -> bool {
+        self.functions
+            .iter()
+            .any(|entry| entry.sampled < entry.graph.samples.max(1))
+    }
+
+    /// Evaluates every function graph at data space `x` and marks the values with a dot and a
+    /// label, `None` disables the readout.
+    pub fn set_cursor_readout(&mut self, x: Option<f64>) {
+        if self.cursor_readout != x {
+            self.cursor_readout = x;
+            self.changes.readout = true;
+        }
+    }
+
+    /// Draws a crosshair through view space `position` (see `GPUMultiView::view_at`) labeled
+    /// with the data coordinates there, `None` removes it.
+    pub fn set_crosshair(&mut self, position: Option<(f32, f32)>) {
+        if self.crosshair != position {
+            self.crosshair = position;
+            self.changes.readout = true;
+        }
+    }
+
+    pub fn crosshair(&self) -> Option<(f32, f32)> {
+        self.crosshair
+    }
+
+    pub fn set_crosshair_style(&mut self, style: CrosshairStyle) {
+        if self.crosshair_style != style {
+            self.crosshair_style = style;
+            self.changes.readout = true;
+        }
+    }
+
+    pub fn crosshair_style(&self) -> &CrosshairStyle {
+        &self.crosshair_style
+    }
+
+    /// Data coordinates at the intersection of the crosshair, `None` if there is none or it
+    /// is outside of the view.
+    ///
+    /// With `CrosshairStyle::snap_to_graph` the intersection moves onto the function graph
+    /// whose value at the crosshair x is visible and closest to it.
+    pub fn crosshair_readout(&self) -> Option<CrosshairReadout> {
+        let position = self
+            .crosshair
+            .filter(|(x, y)| Rect::ndc().contains([*x, *y]))?;
+        let (x, y) = self.view_to_data(position);
+
+        if !self.crosshair_style.snap_to_graph {
+            return Some(CrosshairReadout {
+                position: (x, y),
+                graph: None,
+            });
+        }
+
+        let nearest = (0..self.functions.len())
+            .filter_map(|index| {
+                let entry = &self.functions[index];
+
+                let source_x = entry.transform.inverse_x(x);
+                let h = self.x_range_len() / entry.graph.samples.max(1) as f64;
+                let value = self.evaluate_graph(index, source_x, h, 0).ok()?;
+                let (_, drawn_y) = entry.transform.apply((source_x, value));
+
+                let (_, sy) = self.global_to_screen((x, drawn_y));
+                (sy.is_finite() && (-1.0..=1.0).contains(&sy)).then_some((
+                    (sy - position.1).abs(),
+                    drawn_y,
+                    entry.id,
+                ))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        Some(match nearest {
+            Some((_, drawn_y, id)) => CrosshairReadout {
+                position: (x, drawn_y),
+                graph: Some(id),
+            },
+            None => CrosshairReadout {
+                position: (x, y),
+                graph: None,
+            },
+        })
+    }
+
+    /// Nearest target of `snapping` around the data space position `data`, `None` if there is
+    /// none within the snap radius.
+    pub fn snap(&self, data: (f64, f64), snapping: &Snapping) -> Option<Snap> {
+        // samples per side when searching the closest point on a curve
+        const CURVE_SAMPLES: u32 = 16;
+
+        if !snapping.is_enabled() {
+            return None;
+        }
+
+        let (px, py) = self.pixel_scale();
+        let (cx, cy) = self.global_to_screen(data);
+        let distance = |position: (f64, f64)| {
+            let (sx, sy) = self.global_to_screen(position);
+            ((sx - cx) * px).hypot((sy - cy) * py)
+        };
+
+        let mut nearest: Option<Snap> = None;
+        let mut consider = |position: (f64, f64), target: SnapTarget| {
+            let distance = distance(position);
+            if distance.is_finite()
+                && distance <= snapping.radius
+                && nearest.map_or(true, |snap| distance < snap.distance)
+            {
+                nearest = Some(Snap {
+                    position,
+                    target,
+                    distance,
+                });
+            }
+        };
+
+        for mode in &snapping.modes {
+            match mode {
+                SnapMode::SamplePoint => {
+                    for entry in &self.point_series {
+                        for (i, point) in entry.series.points.iter().enumerate() {
+                            consider(
+                                entry.transform.apply(*point),
+                                SnapTarget::SamplePoint(entry.id, i),
+                            );
+                        }
+                    }
+                }
+                SnapMode::Curve => {
+                    let radius_x = (snapping.radius / px) as f64 * self.x_range_len() / 2.0;
+
+                    for (index, entry) in self.functions.iter().enumerate() {
+                        let h = self.x_range_len() / entry.graph.samples.max(1) as f64;
+
+                        for i in 0..=2 * CURVE_SAMPLES {
+                            let offset = (i as f64 / CURVE_SAMPLES as f64 - 1.0) * radius_x;
+                            let source_x = entry.transform.inverse_x(data.0 + offset);
+                            let Ok(y) = self.evaluate_graph(index, source_x, h, 0) else {
+                                continue;
+                            };
+
+                            consider(
+                                entry.transform.apply((source_x, y)),
+                                SnapTarget::Curve(entry.id),
+                            );
+                        }
+                    }
+                }
+                SnapMode::GridIntersection => {
+                    let step = |spacing: &GridSpacing, range_len: f64| {
+                        Self::grid_spacing(spacing, range_len)
+                            .0
+                            .to_f64()
+                            .expect(Self::ERROR_DEC_TO_F64)
+                    };
+                    let x_step = step(&self.display_style.x.spacing, self.x_range_len());
+                    let y_step = step(&self.display_style.y.spacing, self.y_range_len());
+
+                    consider(
+                        (
+                            (data.0 / x_step).round() * x_step,
+                            (data.1 / y_step).round() * y_step,
+                        ),
+                        SnapTarget::GridIntersection,
+                    );
+                }
+            }
+        }
+
+        nearest
+    }
+
+    /// Draws a line between the points of `measurement` labeled with their differences,
+    /// `None` removes it.
+    pub fn set_measurement(&mut self, measurement: Option<Measurement>) {
+        if self.measurement != measurement {
+            self.measurement = measurement;
+            self.changes.measurement = true;
+        }
+    }
+
+    pub fn measurement(&self) -> Option<&Measurement> {
+        self.measurement.as_ref()
+    }
+
+    /// Angle picked with the measure tool, drawn like a layer on top of the measurement.
+    pub fn set_angle_measurement(&mut self, angle: Option<AngleAnnotation>) {
+        if self.angle_measurement != angle {
+            self.angle_measurement = angle;
+            self.changes.measurement = true;
+        }
+    }
+
+    pub fn angle_measurement(&self) -> Option<&AngleAnnotation> {
+        self.angle_measurement.as_ref()
+    }
+
+    /// Text style of labels with the accessibility adjustments applied.
+    pub(crate) fn text_style(&self) -> Option<&TextStyle> {
+        self.display_style.text.as_ref()
+    }
+
+    /// Adds a widget which is drawn on top of the canvas and returns its index.
+    pub fn add_widget(&mut self, widget: Widget<P>) -> usize {
+        self.widgets.push(widget);
+        self.changes.widgets = true;
+
+        self.widgets.len() - 1
+    }
+
+    pub fn clear_widgets(&mut self) {
+        self.widgets.clear();
+        self.changes.widgets = true;
+    }
+
+    /// Index of the topmost widget at `point` (view space `-1..1`).
+    pub fn widget_at(&self, point: (f32, f32)) -> Option<usize> {
+        self.widgets
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(index, widget)| {
+                let bounds = widget.bounds(widget.position_or_stacked(*index));
+                bounds.contains(point.into())
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Applies a press (`pressed`) or drag at `point` (view space `-1..1`) to widget `index`.
+    pub fn widget_input(&mut self, index: usize, point: (f32, f32), pressed: bool) {
+        let Some(widget) = self.widgets.get(index) else {
+            return;
+        };
+
+        let position = widget.position_or_stacked(index);
+        widget.input(&mut self.parameter, position, point, pressed);
+
+        self.changes.parameter = true;
+    }
+
+    //-- names
+
+    /// Ids of all graphs, series and layers of the canvas in the order they were added.
+    pub fn graph_ids(&self) -> Vec<GraphId> {
+        let mut ids: Vec<GraphId> = self
+            .functions
+            .iter()
+            .map(|entry| entry.id)
+            .chain(self.complex_functions.iter().map(|entry| entry.id))
+            .chain(self.parametric_graphs.iter().map(|entry| entry.id))
+            .chain(self.implicit_graphs.iter().map(|entry| entry.id))
+            .chain(self.point_series.iter().map(|entry| entry.id))
+            .chain(self.ellipses.iter().map(|entry| entry.id))
+            .chain(self.layers.iter().map(|entry| entry.id))
+            .collect();
+        ids.sort();
+
+        ids
+    }
+
+    /// Names a graph so it can be found with `graph_by_name`, e.g. from a configuration.
+    ///
+    /// Returns false and leaves the names unchanged if the canvas has no graph `id` or
+    /// another graph already has the name.
+    pub fn set_graph_name(&mut self, id: GraphId, name: &str) -> bool {
+        if !self.graph_ids().contains(&id) {
+            return false;
+        }
+        if self.graph_by_name(name).is_some_and(|other| other != id) {
+            return false;
+        }
+
+        self.graph_names.insert(id, name.to_string());
+        true
+    }
+
+    pub fn graph_name(&self, id: GraphId) -> Option<&str> {
+        self.graph_names.get(&id).map(String::as_str)
+    }
+
+    pub fn graph_by_name(&self, name: &str) -> Option<GraphId> {
+        // ordered by id, which is the insertion order
+        self.graph_names
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(id, _)| *id)
+    }
+
+    /// Error of the last sampling of a function graph, if it panicked.
+    pub fn function_graph_error(&self, id: GraphId) -> Option<&GraphError> {
+        if let Some(entry) = self.functions.iter().find(|entry| entry.id == id) {
+            return entry.error.as_ref();
+        }
+        if let Some(entry) = self.parametric_graphs.iter().find(|entry| entry.id == id) {
+            return entry.error.as_ref();
+        }
+        if let Some(entry) = self.implicit_graphs.iter().find(|entry| entry.id == id) {
+            return entry.error.as_ref();
+        }
+
+        self.complex_functions
+            .iter()
+            .find(|entry| entry.id == id)?
+            .error
+            .as_ref()
+    }
+
+    /// Sets a callback which gets called whenever sampling a function graph panicked.
+    ///
+    /// The failing graph is drawn dashed in red in any case.
+    pub fn set_error_callback(&mut self, callback: impl FnMut(GraphId, &GraphError) + 'static) {
+        self.error_callback = Some(Box::new(callback));
+    }
+
+    //-- export
+
+    /// Samples of the visible function and parametric graphs (at their current sample count)
+    /// and the visible points of the point series as CSV with the columns `kind,id,x,y`.
+    ///
+    /// `kind` is `function`, `parametric` or `points` and `id` the `GraphId::pick_id` of the
+    /// graph. Only finite samples inside of the visible range are included.
+    pub fn visible_samples_csv(&self) -> String {
+        let mut csv = String::from("kind,id,x,y\n");
+        let domain = self.projection.x_domain(self.x_range, self.y_range);
+
+        let mut push_row = |kind: &str, id: GraphId, (x, y): (f64, f64)| {
+            if x.is_finite() && y.is_finite() && self.visible_screen_position((x, y)).is_some() {
+                csv.push_str(&format!("{kind},{},{x},{y}\n", id.pick_id()));
+            }
+        };
+
+        for (index, entry) in self.functions.iter().enumerate() {
+            let visible = entry.transform.inverse_x_range(&domain.to_range());
+            let (x_start, x_end) = match &entry.graph.domain {
+                Some(domain) => (visible.start.max(domain.start), visible.end.min(domain.end)),
+                None => (visible.start, visible.end),
+            };
+            if x_start > x_end {
+                continue;
+            }
+
+            let samples = match entry.sampled {
+                0 => entry.graph.samples.max(1),
+                sampled => sampled,
+            };
+            let xs = self.sample_positions(entry.transform, x_start, x_end, samples);
+
+            let values = self.sample_graph(index, &xs);
+
+            for (x, value) in xs.into_iter().zip(values) {
+                if let Ok(y) = value {
+                    push_row("function", entry.id, entry.transform.apply((x, y)));
+                }
+            }
+        }
+
+        for entry in self.parametric_graphs.iter() {
+            let graph = &entry.graph;
+            let samples = graph.samples.max(1);
+            let step = (graph.t_range.end - graph.t_range.start) / samples as f64;
+
+            for i in 0..=samples {
+                let t = graph.t_range.start + step * i as f64;
+                if let Ok(point) = catch_evaluation(|| (graph.function)(t, &self.parameter)) {
+                    push_row("parametric", entry.id, point);
+                }
+            }
+        }
+
+        for entry in self.point_series.iter() {
+            for point in entry.series.points.iter() {
+                push_row("points", entry.id, entry.transform.apply(*point));
+            }
+        }
+
+        csv
+    }
+
+    /// Writes `visible_samples_csv` to `path`.
+    pub fn export_visible_samples(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.visible_samples_csv())
+            .with_context(|| format!("Failed to write samples to {}.", path.display()))
+    }
+
+    //-- observers
+    //
+    // Callbacks are called from `display`, so multiple changes within a frame are reported once.
+
+    /// Registers a callback called with the new x and y range after the range changed.
+    pub fn on_range_changed(&mut self, callback: impl FnMut(&Interval, &Interval) + 'static) {
+        self.range_callbacks.push(Box::new(callback));
+    }
+
+    /// Registers a callback called with the parameter after it was accessed mutably.
+    pub fn on_param_changed(&mut self, callback: impl FnMut(&P) + 'static) {
+        self.parameter_callbacks.push(Box::new(callback));
+    }
+
+    /// Registers a callback called with the result and coordinates of every resolved `pick`.
+    pub fn on_pick(&mut self, callback: impl FnMut(Option<GraphId>, (f32, f32)) + 'static) {
+        self.pick_callbacks.push(Box::new(callback));
+    }
+
+    fn emit_events(&mut self) {
+        if self.changes.range {
+            for callback in self.range_callbacks.iter_mut() {
+                callback(&self.x_range, &self.y_range);
+            }
+        }
+
+        if self.changes.parameter {
+            for callback in self.parameter_callbacks.iter_mut() {
+                callback(&self.parameter);
+            }
+        }
+
+        if self.pending_picks.is_empty() {
+            return;
+        }
+
+        let (resolved, pending) = std::mem::take(&mut self.pending_picks)
+            .into_iter()
+            .partition(|(_, handle)| handle.is_resolved());
+        self.pending_picks = pending;
+
+        for (coordinates, handle) in resolved {
+            let id = handle.try_get().and_then(GraphId::from_pick_id);
+
+            for callback in self.pick_callbacks.iter_mut() {
+                callback(id, coordinates);
+            }
+        }
+    }
+
+    /// Removes all function graphs, parametric and implicit graphs, point series and ellipses.
+    pub fn clear_graphs(&mut self) {
+        for entry in self.complex_functions.drain(..) {
+            let pass: Arc<RefCell<dyn GPUPass>> = entry.pass;
+            self.view.borrow_mut().remove_gpu_pass(&pass);
+        }
+        self.functions.clear();
+        self.parametric_graphs.clear();
+        self.implicit_graphs.clear();
+        self.point_series.clear();
+        self.ellipses.clear();
+        self.changes.layers = true;
+
+        let layers: Vec<GraphId> = self.layers.iter().map(|entry| entry.id).collect();
+        self.graph_names.retain(|id, _| layers.contains(id));
+    }
+
+    /// Enables the id render pass so graphs can be hit tested with `pick`.
+    pub fn set_picking_enabled(&mut self, enabled: bool) {
+        self.view.borrow_mut().set_picking_enabled(enabled);
+        self.changes.layers = true;
+    }
+
+    /// Requests the graph rendered at `coordinates` (view space `-1..1`, see `ViewCoordinates`).
+    ///
+    /// Resolve the result with `GraphId::from_pick_id(handle.try_get()?)`.
+    pub fn pick(&mut self, coordinates: (f32, f32)) -> PickHandle {
+        let handle = self.view.borrow_mut().pick(coordinates);
+
+        if !self.pick_callbacks.is_empty() {
+            self.pending_picks.push((coordinates, handle.clone()));
+        }
+
+        handle
+    }
+
+    fn screen_constant(&self, value: f64) -> f32 {
+        let x_len = self.x_scale.forward_interval(&self.x_range).len();
+        let y_len = self.y_scale.forward_interval(&self.y_range).len();
+
+        (value * ((x_len + y_len) / 2.0)) as f32
+    }
+
+    fn display_refresh_required(&self) -> bool {
+        self.changes.any()
+            || self.view_resized()
+            || self.functions.iter().any(|entry| entry.layer.dirty)
+            || self.complex_functions.iter().any(|entry| entry.layer.dirty)
+            || self.parametric_graphs.iter().any(|entry| entry.layer.dirty)
+            || self.implicit_graphs.iter().any(|entry| entry.layer.dirty)
+            || self.point_series.iter().any(|entry| entry.layer.dirty)
+            || self.ellipses.iter().any(|entry| entry.layer.dirty)
+            || self
+                .layers
+                .iter()
+                .any(|entry| entry.vertices.dirty || entry.layer.is_dirty())
+    }
+
+    /// Pixel based sizes (e.g. label backgrounds) have to be recomputed when the view got resized.
+    fn view_resized(&self) -> bool {
+        self.view.borrow().texture_size() != self.view_size
+    }
+
+    /// Marks the layers which have to be tessellated again and resets the change tracking.
+    fn display_reset_refresh(&mut self) {
+        let changes = self.changes;
+        let resized = self.view_resized();
+
+        // pixel based sizes and the mapping of every vertex depend on range and view size
+        let redraw_all = changes.range || resized;
+
+        if resized {
+            self.cancel_range_token();
+        }
+
+        if changes.style {
+            self.display_style = match &self.accessibility {
+                Some(accessibility) => accessibility.apply(&self.style),
+                None => self.style.clone(),
+            };
+        }
+
+        if redraw_all || changes.style {
+            self.enviroment_layer.dirty = true;
+            self.label_background_layer.dirty = true;
+        }
+        for entry in self.functions.iter_mut() {
+            if redraw_all || changes.parameter {
+                // also cancels a pending refinement
+                entry.layer.dirty = true;
+                entry.sampled = 0;
+            }
+        }
+        for entry in self.complex_functions.iter_mut() {
+            entry.layer.dirty |= redraw_all || changes.parameter;
+        }
+        for entry in self.parametric_graphs.iter_mut() {
+            entry.layer.dirty |= redraw_all || changes.parameter;
+        }
+        for entry in self.implicit_graphs.iter_mut() {
+            entry.layer.dirty |= redraw_all || changes.parameter;
+        }
+        for entry in self.point_series.iter_mut() {
+            entry.layer.dirty |= redraw_all;
+        }
+        for entry in self.ellipses.iter_mut() {
+            entry.layer.dirty |= redraw_all;
+        }
+        for entry in self.layers.iter_mut() {
+            entry.vertices.dirty |= redraw_all
+                || (changes.parameter && entry.layer.uses_parameter())
+                || entry.layer.is_dirty();
+        }
+
+        self.view_size = self.view.borrow().texture_size();
+        self.changes = DisplayChanges::NONE;
+    }
+
+    pub fn display_clear(&mut self) {
+        let mut view = self.view.as_ref().borrow_mut();
+
+        view.clear_render_vertices();
+        view.clear_text_sections();
+    }
+
+    /// Tessellates every layer affected by the changes since the last call
+    /// and uploads the combined vertices to the view.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn display(&mut self) {
+        self.emit_events();
+
+        // changes wait for the next refresh of a view with a refresh divisor
+        if !self.view.borrow().is_refresh_due() {
+            return;
+        }
+
+        if !self.display_refresh_required() {
+            if self.refinement_pending() {
+                self.display_refine();
+            }
+            return;
+        }
+
+        self.display_reset_refresh();
+
+        if self.enviroment_layer.dirty {
+            self.view.borrow_mut().clear_text_sections();
+            self.curve_label_sections.clear();
+            self.readout_sections.clear();
+            self.crosshair_sections.clear();
+            self.measurement_sections.clear();
+            self.widget_sections.clear();
+
+            self.display_enviroment();
+            self.enviroment_layer.vertices = std::mem::take(&mut self.vertices);
+            self.enviroment_layer.dirty = false;
+
+            self.display_label_backgrounds();
+            self.label_background_layer.vertices = std::mem::take(&mut self.vertices);
+            self.label_background_layer.dirty = false;
+
+            // their labels were cleared with the other text sections
+            for entry in self.layers.iter_mut() {
+                entry.sections.clear();
+                entry.vertices.dirty = true;
+            }
+        }
+
+        self.display_complex_function_graphs();
+        self.display_function_graphs();
+        self.display_parametric_graphs();
+        self.display_implicit_graphs();
+        self.display_point_series();
+        self.display_ellipses();
+        self.display_layers();
+
+        // cheap enough to be redone on every change
+        self.display_curve_labels();
+        self.display_cursor_readout();
+        self.display_crosshair();
+        self.display_measurement();
+        self.display_widgets();
+
+        self.display_assemble_layers();
+    }
+
+    /// Resamples the function graphs which are not fully refined yet.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_refine(&mut self) {
+        for entry in self.functions.iter_mut() {
+            if entry.sampled < entry.graph.samples.max(1) {
+                entry.layer.dirty = true;
+            }
+        }
+
+        self.display_function_graphs();
+        self.display_assemble_layers();
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_assemble_layers(&mut self) {
+        let mut view = self.view.borrow_mut();
+        view.clear_render_vertices();
+
+        let layers = self
+            .complex_functions
+            .iter()
+            .map(|entry| &entry.layer)
+            .chain(std::iter::once(&self.enviroment_layer))
+            .chain(self.functions.iter().map(|entry| &entry.layer))
+            .chain(self.parametric_graphs.iter().map(|entry| &entry.layer))
+            .chain(self.implicit_graphs.iter().map(|entry| &entry.layer))
+            .chain(self.point_series.iter().map(|entry| &entry.layer))
+            .chain(self.ellipses.iter().map(|entry| &entry.layer))
+            .chain(self.layers.iter().map(|entry| &entry.vertices))
+            .chain(std::iter::once(&self.label_background_layer))
+            .chain(std::iter::once(&self.curve_label_layer))
+            .chain(std::iter::once(&self.readout_layer))
+            .chain(std::iter::once(&self.crosshair_layer))
+            .chain(std::iter::once(&self.measurement_layer))
+            .chain(std::iter::once(&self.widget_layer));
+
+        for layer in layers {
+            view.set_pick_id(layer.pick_id);
+            view.extend_render_vertices(&layer.vertices);
+            view.extend_glow_vertices(&layer.glow_vertices);
+        }
+
+        view.set_pick_id(0);
+
+        let glow_radius = self
+            .functions
+            .iter()
+            .filter_map(|entry| entry.graph.style.glow)
+            .map(|glow| glow.radius)
+            .fold(0.0, f32::max);
+        view.set_glow_radius(glow_radius);
+    }
+
+    /// Major steps and substeps of an axis with `scale` showing `range`.
+    ///
+    /// Logarithmic scales get ticks at powers of their base as long as at least two are
+    /// visible, everything else is divided linearly by `spacing`.
+    fn axis_ticks(range: &Interval, scale: AxisScale, spacing: &GridSpacing) -> AxisTicks {
+        if let GridSpacing::Dynamic { steps, .. } = spacing {
+            if let Some(ticks) = scale.ticks(range, *steps) {
+                return ticks;
+            }
+        }
+
+        //-- spacings in decimal representation
+
+        let (step_spacing, substeps) = Self::grid_spacing(spacing, range.len());
+        let substep_spacing = &step_spacing / (substeps + 1) as f64;
+
+        //-- floating point representations of the spacings
+
+        let step_spacing_f64 = step_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
+        let substep_spacing_f64 = substep_spacing.to_f64().expect(Self::ERROR_DEC_TO_F64);
+
+        //-- offset to make the range symetric to zero
+
+        let sym_index = (range.center() / step_spacing_f64).round();
+        let sym_offset = sym_index * step_spacing_f64;
+
+        //-- spacing indices
+
+        let substep_start_index =
+            ((range.start() - sym_offset) / substep_spacing_f64).ceil() as i64;
+        let substep_end_index = ((range.end() - sym_offset) / substep_spacing_f64).floor() as i64;
+
+        let step_start_index = ((range.start() - sym_offset) / step_spacing_f64).ceil() as i64;
+        let step_end_index = ((range.end() - sym_offset) / step_spacing_f64).floor() as i64;
+
+        AxisTicks {
+            steps: (step_start_index..=step_end_index)
+                .map(|i| {
+                    let index = i + sym_index as i64;
+
+                    AxisTick {
+                        index,
+                        value: (i as f64 * step_spacing_f64) + sym_offset,
+                        label: TickLabel::Number((&step_spacing * index).calc_precision(None)),
+                    }
+                })
+                .collect(),
+            substeps: (substep_start_index..=substep_end_index)
+                .map(|i| (i as f64 * substep_spacing_f64) + sym_offset)
+                .collect(),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_enviroment(&mut self) {
+        //-- screen mapping of the axis origins
+
+        let origin = (
+            self.x_scale.origin(&self.x_range),
+            self.y_scale.origin(&self.y_range),
+        );
+        let (sx0, sy0) = self.plane_to_screen(origin);
+
+        //-- grid and tick positions
+
+        let x_ticks = Self::axis_ticks(&self.x_range, self.x_scale, &self.display_style.x.spacing);
+        let y_ticks = Self::axis_ticks(&self.y_range, self.y_scale, &self.display_style.y.spacing);
+
+        //-- grid ---
+
+        if let Some(subgrid_style) = self.display_style.x.subgrid {
+            for &x in x_ticks.substeps.iter() {
+                let (sx, _) = self.plane_to_screen((x, origin.1));
+
+                self.vertices_add_line(
+                    [sx, -1.0],
+                    [sx, 1.0],
+                    subgrid_style.thickness,
+                    subgrid_style.color,
+                );
+            }
+        }
+
+        if let Some(subgrid_style) = self.display_style.y.subgrid {
+            for &y in y_ticks.substeps.iter() {
+                let (_, sy) = self.plane_to_screen((origin.0, y));
+
+                self.vertices_add_line(
+                    [-1.0, sy],
+                    [1.0, sy],
+                    subgrid_style.thickness,
+                    subgrid_style.color,
+                );
+            }
+        }
+
+        if let Some(grid_style) = self.display_style.x.grid {
+            for tick in x_ticks.steps.iter() {
+                let (sx, _) = self.plane_to_screen((tick.value, origin.1));
+
+                self.vertices_add_line(
+                    [sx, -1.0],
+                    [sx, 1.0],
+                    grid_style.thickness,
+                    grid_style.color,
+                );
+            }
+        }
+
+        if let Some(grid_style) = self.display_style.y.grid {
+            for tick in y_ticks.steps.iter() {
+                let (_, sy) = self.plane_to_screen((origin.0, tick.value));
+
+                self.vertices_add_line(
+                    [-1.0, sy],
+                    [1.0, sy],
+                    grid_style.thickness,
+                    grid_style.color,
+                );
+            }
+        }
+
+        //-----------
+
+        //-- axes ---
+
+        if let Some(axis_style) = self.display_style.x.axis {
+            self.vertices_add_line(
+                [-1.0, sy0],
+                [1.0, sy0],
+                axis_style.thickness,
+                axis_style.color,
+            );
+        }
+
+        if let Some(axis_style) = self.display_style.y.axis {
+            self.vertices_add_line(
+                [sx0, -1.0],
+                [sx0, 1.0],
+                axis_style.thickness,
+                axis_style.color,
+            );
+        }
+
+        //-----------
+
+        //-- ticks --
+
+        if let Some(subtick_style) = self.display_style.x.subtick {
+            for &x in x_ticks.substeps.iter() {
+                let (sx, sy) = self.plane_to_screen((x, origin.1));
+
+                self.vertices_add_polyline(
+                    &[
+                        [sx, sy + subtick_style.length / 2.0],
+                        [sx, sy - subtick_style.length / 2.0],
+                    ],
+                    self.screen_constant(subtick_style.thickness as f64),
+                    subtick_style.color,
+                );
+            }
+        }
+
+        if let Some(subtick_style) = self.display_style.y.subtick {
+            for &y in y_ticks.substeps.iter() {
+                let (sx, sy) = self.plane_to_screen((origin.0, y));
+
+                self.vertices_add_polyline(
+                    &[
+                        [sx + subtick_style.length / 2.0, sy],
+                        [sx - subtick_style.length / 2.0, sy],
+                    ],
+                    self.screen_constant(subtick_style.thickness as f64),
+                    subtick_style.color,
+                );
+            }
+        }
+
+        if let Some(tick_style) = self.display_style.x.tick {
+            for tick in x_ticks.steps.iter() {
+                let (sx, sy) = self.plane_to_screen((tick.value, origin.1));
+
+                self.vertices_add_polyline(
+                    &[
+                        [sx, sy + tick_style.length / 2.0],
+                        [sx, sy - tick_style.length / 2.0],
+                    ],
+                    self.screen_constant(tick_style.thickness as f64),
+                    tick_style.color,
+                );
+            }
+        }
+
+        if let Some(tick_style) = self.display_style.y.tick {
+            for tick in y_ticks.steps.iter() {
+                let (sx, sy) = self.plane_to_screen((origin.0, tick.value));
+
+                self.vertices_add_polyline(
+                    &[
+                        [sx + tick_style.length / 2.0, sy],
+                        [sx - tick_style.length / 2.0, sy],
+                    ],
+                    tick_style.thickness,
+                    tick_style.color,
+                );
+            }
+        }
+
+        //-----------
+
+        //-- text --
+
+        if let Some(text_style) = self.display_style.text.clone() {
+            let text_max_digits = text_style.max_digits;
+            let (px, py) = self.pixel_scale();
+
+            // uv space (`0..1`, y pointing down) of the axis origins
+            let (x0_uv, y0_uv) = ((sx0 + 1.0) / 2.0, (1.0 - sy0) / 2.0);
+
+            let superscript_exponents = text_style.superscript_exponents;
+            let geographic = self.geographic;
+
+            // `geographic_format` replaces `Auto` in geographic mode
+            let label_text = |tick: &AxisTick, format: TickLabelFormat, geographic_format| {
+                let format = match (geographic, format) {
+                    (true, TickLabelFormat::Auto) => geographic_format,
+                    (_, format) => format,
+                };
+                let text = match &tick.label {
+                    TickLabel::Number(value) => format.format(value, text_max_digits),
+                    TickLabel::Text(text) => text.clone(),
+                };
+                match superscript_exponents {
+                    true => superscript_exponent(&text).into_owned(),
+                    false => text,
+                }
+            };
+
+            if let Some(label_style) = self.display_style.x.label {
+                let every = label_style.every.max(1) as i64;
+
+                let rotation = label_style.rotation.0;
+                let h_align = match rotation {
+                    r if r > 0.0 => HorizontalAlign::Right,
+                    r if r < 0.0 => HorizontalAlign::Left,
+                    _ => HorizontalAlign::Center,
+                };
+                let v_align = match rotation == 0.0 {
+                    true => VerticalAlign::Top,
+                    false => VerticalAlign::Center,
+                };
+
+                for tick in x_ticks.steps.iter() {
+                    if tick.value == origin.0 || tick.index % every != 0 {
+                        continue;
+                    }
+
+                    let (sx, _) = self.plane_to_screen((tick.value, origin.1));
+
+                    let x_uv = (sx + 1.0) / 2.0;
+                    let y_uv = y0_uv + label_style.offset / (2.0 * py);
+
+                    let text = label_text(tick, label_style.format, TickLabelFormat::Longitude);
+                    let text = text_style.locale.localize(&text);
+
+                    self.text_add_rotated_label(
+                        &text,
+                        (x_uv, y_uv),
+                        Layout::default_single_line()
+                            .h_align(h_align)
+                            .v_align(v_align),
+                        &text_style,
+                        label_style.rotation.into(),
+                    );
+                }
+            }
+
+            if let Some(label_style) = self.display_style.y.label {
+                let every = label_style.every.max(1) as i64;
+
+                // steep labels are centered on their tick instead of starting at it
+                let rotation = label_style.rotation.0;
+                let (h_align, v_align) = match rotation {
+                    r if r >= 45.0 => (HorizontalAlign::Center, VerticalAlign::Top),
+                    r if r <= -45.0 => (HorizontalAlign::Center, VerticalAlign::Bottom),
+                    _ => (HorizontalAlign::Left, VerticalAlign::Center),
+                };
+
+                for tick in y_ticks.steps.iter() {
+                    if tick.value == origin.1 || tick.index % every != 0 {
+                        continue;
+                    }
+
+                    let (_, sy) = self.plane_to_screen((origin.0, tick.value));
+
+                    let x_uv = x0_uv + label_style.offset / (2.0 * px);
+                    let y_uv = (1.0 - sy) / 2.0;
+
+                    let text = format!(
+                        " {}",
+                        label_text(tick, label_style.format, TickLabelFormat::Latitude)
+                    );
+                    let text = text_style.locale.localize(&text);
+
+                    self.text_add_rotated_label(
+                        &text,
+                        (x_uv, y_uv),
+                        Layout::default_single_line()
+                            .h_align(h_align)
+                            .v_align(v_align),
+                        &text_style,
+                        label_style.rotation.into(),
+                    );
+                }
+            }
+
+            // alignments refer to the rotated text, rotated titles end at the edge
+            if let Some(title) = self.display_style.x.title.clone() {
+                let rotation = title.rotation.unwrap_or(Degrees(0.0));
+                let (h_align, v_align) = match rotation.0 {
+                    r if r >= 45.0 => (HorizontalAlign::Left, VerticalAlign::Center),
+                    r if r <= -45.0 => (HorizontalAlign::Right, VerticalAlign::Center),
+                    _ => (HorizontalAlign::Center, VerticalAlign::Bottom),
+                };
+
+                self.text_add_rotated_label(
+                    &title.text,
+                    (0.5, 1.0 - title.offset / (2.0 * py)),
+                    Layout::default_single_line()
+                        .h_align(h_align)
+                        .v_align(v_align),
+                    &text_style,
+                    rotation.into(),
+                );
+            }
+
+            if let Some(title) = self.display_style.y.title.clone() {
+                let rotation = title.rotation.unwrap_or(Degrees(90.0));
+                let (h_align, v_align) = match rotation.0 {
+                    r if r >= 45.0 => (HorizontalAlign::Center, VerticalAlign::Top),
+                    r if r <= -45.0 => (HorizontalAlign::Center, VerticalAlign::Bottom),
+                    _ => (HorizontalAlign::Left, VerticalAlign::Center),
+                };
+
+                self.text_add_rotated_label(
+                    &title.text,
+                    (title.offset / (2.0 * px), 0.5),
+                    Layout::default_single_line()
+                        .h_align(h_align)
+                        .v_align(v_align),
+                    &text_style,
+                    rotation.into(),
+                );
+            }
+        }
+
+        //-----------
+    }
+
+    /// Adds a text label at `position` (uv space `0..1`) including its halo copies,
+    /// and remembers its bounds for `display_label_backgrounds`.
+    pub(crate) fn text_add_label(
+        &mut self,
+        text: &str,
+        position: (f32, f32),
+        layout: Layout<BuiltInLineBreaker>,
+        text_style: &TextStyle,
+    ) -> Vec<Arc<RefCell<TextSection>>> {
+        self.text_add_rotated_label(text, position, layout, text_style, Radians(0.0))
+    }
+
+    /// Like `text_add_label`, but rotated counterclockwise around `position`.
+    pub(crate) fn text_add_rotated_label(
+        &mut self,
+        text: &str,
+        position: (f32, f32),
+        layout: Layout<BuiltInLineBreaker>,
+        text_style: &TextStyle,
+        rotation: Radians,
+    ) -> Vec<Arc<RefCell<TextSection>>> {
+        let mut sections = Vec::new();
+
+        // the text brush lays out left to right, so bidirectional text is reordered beforehand
+        let shaped = self
+            .shape_cache
+            .shape(text, &text_style.font, text_style.size);
+        let text = shaped.text.as_str();
+
+        if let Some(halo) = text_style.halo {
+            const HALO_DIRECTIONS: [(f32, f32); 8] = [
+                (1.0, 0.0),
+                (0.70710677, 0.70710677),
+                (0.0, 1.0),
+                (-0.70710677, 0.70710677),
+                (-1.0, 0.0),
+                (-0.70710677, -0.70710677),
+                (0.0, -1.0),
+                (0.70710677, -0.70710677),
+            ];
+
+            for (dx, dy) in HALO_DIRECTIONS {
+                sections.push(
+                    TextSection::RelativeOffset(
+                        SectionBuilder::default()
+                            .add_text(
+                                Text::new(text)
+                                    .with_scale(text_style.size)
+                                    .with_color(halo.color),
+                            )
+                            .with_screen_position(position)
+                            .with_layout(layout)
+                            .to_owned(),
+                        (dx * halo.width, dy * halo.width),
+                    )
+                    .into_arc_ref_cell(),
+                );
+            }
+        }
+
+        sections.push(
+            TextSection::Relative(
+                SectionBuilder::default()
+                    .add_text(
+                        Text::new(text)
+                            .with_scale(text_style.size)
+                            .with_color(text_style.color),
+                    )
+                    .with_screen_position(position)
+                    .with_layout(layout)
+                    .to_owned(),
+            )
+            .into_arc_ref_cell(),
+        );
+
+        {
+            let mut view = self.view.borrow_mut();
+
+            match rotation.0 == 0.0 {
+                true => {
+                    view.ensure_font(&text_style.font);
+
+                    for section in sections.iter() {
+                        view.add_text_section(section.clone(), &text_style.font.name)
+                            .unwrap();
+                    }
+                }
+                false => {
+                    for section in sections.iter() {
+                        view.add_rotated_text_section(section.clone(), &text_style.font, rotation);
+                    }
+                }
+            }
+        }
+
+        if let Some(background) = text_style.background {
+            if let Some(bounds) =
+                self.estimate_label_bounds(&shaped, position, layout, text_style, rotation)
+            {
+                self.label_backgrounds.push((bounds, background));
+            }
+        }
+
+        sections
+    }
+
+    /// Value of function graph `index` at `x`, `NaN` outside of its domain.
+    ///
+    /// `h` is the spacing used by derivatives and integrals of derived graphs.
+    fn evaluate_graph(&self, index: usize, x: f64, h: f64, depth: u32) -> Result<f64, String> {
+        const MAX_DEPTH: u32 = 16;
+        const MAX_INTEGRATION_STEPS: f64 = 100_000.0;
+
+        let entry = &self.functions[index];
+        if entry
+            .graph
+            .domain
+            .as_ref()
+            .is_some_and(|domain| !domain.contains(&x))
+        {
+            return Ok(f64::NAN);
+        }
+
+        let Some(derived) = entry.derived else {
+            return catch_evaluation(|| (entry.graph.function)(x, &self.parameter));
+        };
+        if depth > MAX_DEPTH {
+            return Err("derived graphs are nested too deeply".to_string());
+        }
+
+        let source = |id: GraphId, x: f64| match self.functions.iter().position(|e| e.id == id) {
+            Some(index) => self.evaluate_graph(index, x, h, depth + 1),
+            None => Err(format!("source graph {} was removed", id.pick_id())),
+        };
+
+        match derived {
+            DerivedGraph::Derivative(id) => {
+                Ok((source(id, x + h)? - source(id, x - h)?) / (2.0 * h))
+            }
+            DerivedGraph::Sum(a, b) => Ok(source(a, x)? + source(b, x)?),
+            DerivedGraph::Antiderivative(id, x0) => {
+                let steps = ((x - x0).abs() / h)
+                    .ceil()
+                    .clamp(1.0, MAX_INTEGRATION_STEPS);
+                let step = (x - x0) / steps;
+
+                let mut integral = 0.0;
+                let mut last = source(id, x0)?;
+                for i in 1..=steps as u32 {
+                    let value = source(id, x0 + step * i as f64)?;
+                    integral += (last + value) / 2.0 * step;
+                    last = value;
+                }
+
+                Ok(integral)
+            }
+        }
+    }
+
+    /// Graph space x of the `samples + 1` samples of a function graph between `x_start` and
+    /// `x_end`, evenly spaced on the x scale of the canvas.
+    fn sample_positions(
+        &self,
+        transform: GraphTransform,
+        x_start: f64,
+        x_end: f64,
+        samples: u32,
+    ) -> Vec<f64> {
+        let samples = samples.max(1);
+
+        if self.x_scale.is_linear() {
+            let step = (x_end - x_start) / samples as f64;
+            return (0..=samples).map(|i| x_start + step * i as f64).collect();
+        }
+
+        let scale = self.x_scale;
+        let (start, _) = transform.apply((x_start, 0.0));
+        let (end, _) = transform.apply((x_end, 0.0));
+        let scaled = Interval::new(scale.forward(start), scale.forward(end));
+
+        (0..=samples)
+            .map(|i| transform.inverse_x(scale.inverse(scaled.lerp(i as f64 / samples as f64))))
+            .collect()
+    }
+
+    /// Values of function graph `index` at the graph space positions `xs`.
+    ///
+    /// Antiderivatives are accumulated along `xs` instead of integrating every sample again.
+    fn sample_graph(&self, index: usize, xs: &[f64]) -> Vec<Result<f64, String>> {
+        // distance to the next sample, used as step of derived graphs
+        let h_at = |i: usize| {
+            let step = match (xs.get(i), xs.get(i + 1), i.checked_sub(1).map(|i| xs[i])) {
+                (Some(x), Some(next), _) => next - x,
+                (Some(x), None, Some(previous)) => x - previous,
+                _ => 0.0,
+            };
+            match step.abs() {
+                0.0 => f64::EPSILON,
+                step => step,
+            }
+        };
+
+        let Some(DerivedGraph::Antiderivative(id, _)) = self.functions[index].derived else {
+            return xs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| self.evaluate_graph(index, *x, h_at(i), 0))
+                .collect();
+        };
+        let Some(source) = self.functions.iter().position(|entry| entry.id == id) else {
+            return vec![Err(format!("source graph {} was removed", id.pick_id())); xs.len()];
+        };
+        let Some(&x_first) = xs.first() else {
+            return Vec::new();
+        };
+
+        let mut values = Vec::with_capacity(xs.len());
+        let mut integral = self.evaluate_graph(index, x_first, h_at(0), 0);
+        let mut last = self.evaluate_graph(source, x_first, h_at(0), 1);
+        values.push(integral.clone());
+
+        for i in 1..xs.len() {
+            let value = self.evaluate_graph(source, xs[i], h_at(i), 1);
+            let step = xs[i] - xs[i - 1];
+            integral = match (&integral, &last, &value) {
+                (Ok(integral), Ok(last), Ok(value)) => Ok(integral + (last + value) / 2.0 * step),
+                (Err(message), _, _) | (_, Err(message), _) | (_, _, Err(message)) => {
+                    Err(message.clone())
+                }
+            };
+            values.push(integral.clone());
+            last = value;
+        }
+
+        values
+    }
+
+    /// Screen space position of function graph `index` drawn at data space `x`, if it is
+    /// visible there.
+    fn graph_screen_position(&self, index: usize, x: f64) -> Option<(f32, f32)> {
+        let entry = &self.functions[index];
+        let h = self.x_range_len() / entry.graph.samples.max(1) as f64;
+
+        let source_x = entry.transform.inverse_x(x);
+        let y = self.evaluate_graph(index, source_x, h, 0).ok()?;
+        let (_, y) = entry.transform.apply((source_x, y));
+        if !y.is_finite() {
+            return None;
+        }
+
+        self.visible_screen_position((x, y))
+    }
+
+    /// Draws the label of every function graph which has one next to its curve.
+    ///
+    /// Candidate positions are tried above and below the curve until the label
+    /// doesn't overlap an already placed one.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_curve_labels(&mut self) {
+        const CANDIDATES: usize = 64;
+        const LABEL_OFFSET: f32 = 0.01;
+
+        let sections = std::mem::take(&mut self.curve_label_sections);
+        self.view.borrow_mut().remove_text_sections(&sections);
+
+        let Some(mut text_style) = self.display_style.text.clone() else {
+            self.curve_label_layer.vertices.clear();
+            return;
+        };
+        // labels cross grid lines and other curves
+        text_style.halo = text_style.halo.or(Some(TextHaloStyle::default()));
+
+        let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
+
+        // evenly spaced over the sampled domain, also on nonlinear x scales
+        let domain = self.projection.x_domain(self.x_range, self.y_range);
+        let scaled = self.x_scale.forward_interval(&domain);
+        let xs: Vec<f64> = (0..CANDIDATES)
+            .map(|i| {
+                self.x_scale
+                    .inverse(scaled.lerp((i as f64 + 0.5) / CANDIDATES as f64))
+            })
+            .collect();
+
+        // screen position of every graph at the candidate xs, only needed for `MaxSpacing`
+        let needs_spacing = self.functions.iter().any(|entry| {
+            entry
+                .graph
+                .label
+                .as_ref()
+                .is_some_and(|label| label.placement == LabelPlacement::MaxSpacing)
+        });
+        let screen_positions: Vec<Vec<Option<(f32, f32)>>> = match needs_spacing {
+            true => self
+                .functions
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| match entry.error {
+                    Some(_) => vec![None; CANDIDATES],
+                    None => xs
+                        .iter()
+                        .map(|x| self.graph_screen_position(index, *x))
+                        .collect(),
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+
+        let mut placed: Vec<Rect> = Vec::new();
+
+        for index in 0..self.functions.len() {
+            let f = self.functions[index].graph.clone();
+            let Some(label) = &f.label else {
+                continue;
+            };
+            if self.functions[index].error.is_some() {
+                continue;
+            }
+
+            let transform = self.functions[index].transform;
+            // screen positions on the curve
+            let candidates: Vec<(f32, f32)> = match label.placement {
+                LabelPlacement::At(x) => {
+                    let (x, _) = transform.apply((x, 0.0));
+                    let mut others: Vec<f64> = xs.clone();
+                    others.sort_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()));
+
+                    std::iter::once(x)
+                        .chain(others)
+                        .filter_map(|x| self.graph_screen_position(index, x))
+                        .collect()
+                }
+                LabelPlacement::MaxSpacing => {
+                    let mut candidates: Vec<((f32, f32), f32)> = (0..CANDIDATES)
+                        .filter_map(|i| {
+                            let (sx, sy) = screen_positions[index][i]?;
+                            let spacing = (0..self.functions.len())
+                                .filter(|other| *other != index)
+                                .filter_map(|other| screen_positions[other][i])
+                                .map(|(_, other_sy)| (other_sy - sy).abs())
+                                .fold(f32::INFINITY, f32::min);
+
+                            Some(((sx, sy), spacing))
+                        })
+                        .collect();
+                    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                    candidates
+                        .into_iter()
+                        .map(|(position, _)| position)
+                        .collect()
+                }
+            };
+
+            let mut text_style = text_style.clone();
+            text_style.color = f.style.color;
+
+            let shaped = self
+                .shape_cache
+                .shape(&label.text, &text_style.font, text_style.size);
+
+            let positions = candidates.iter().flat_map(|&(sx, sy)| {
+                let x_uv = (1.0 + sx) / 2.0;
+                let y_uv = (1.0 - sy) / 2.0;
+
+                [
+                    ((x_uv, y_uv - LABEL_OFFSET), VerticalAlign::Bottom),
+                    ((x_uv, y_uv + LABEL_OFFSET), VerticalAlign::Top),
+                ]
+            });
+
+            let mut chosen = None;
+            for (position, v_align) in positions {
+                let layout = Layout::default_single_line()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(v_align);
+
+                chosen.get_or_insert((position, layout, None));
+
+                let Some(bounds) = self.estimate_label_bounds(
+                    &shaped,
+                    position,
+                    layout,
+                    &text_style,
+                    Radians(0.0),
+                ) else {
+                    break;
+                };
+
+                if !placed.iter().any(|other| bounds.intersects(other)) {
+                    chosen = Some((position, layout, Some(bounds)));
+                    break;
+                }
+            }
+
+            // every candidate overlaps, the best one is used anyway
+            let Some((position, layout, bounds)) = chosen else {
+                continue;
+            };
+            placed.extend(bounds);
+
+            let sections = self.text_add_label(&label.text, position, layout, &text_style);
+            self.curve_label_sections.extend(sections);
+        }
+
+        for (bounds, color) in std::mem::replace(&mut self.label_backgrounds, label_backgrounds) {
+            self.vertices_add_rect(bounds.min, bounds.max, color);
+        }
+
+        self.curve_label_layer.vertices = std::mem::take(&mut self.vertices);
+        self.curve_label_layer.dirty = false;
+    }
+
+    /// Draws a dot and a value label where each function graph crosses the readout x.
+    fn display_cursor_readout(&mut self) {
+        const LABEL_OFFSET: f32 = 0.01;
+
+        let sections = std::mem::take(&mut self.readout_sections);
+        self.view.borrow_mut().remove_text_sections(&sections);
+
+        let Some(x) = self.cursor_readout.filter(|x| self.x_range.contains(*x)) else {
+            self.readout_layer.vertices.clear();
+            return;
+        };
+
+        let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
+
+        for index in 0..self.functions.len() {
+            let f = self.functions[index].graph.clone();
+            let transform = self.functions[index].transform;
+
+            let source_x = transform.inverse_x(x);
+            let h = self.x_range_len() / f.samples.max(1) as f64;
+            let Ok(y) = self.evaluate_graph(index, source_x, h, 0) else {
+                continue;
+            };
+            let (_, drawn_y) = transform.apply((source_x, y));
+            if !drawn_y.is_finite() || !self.y_range.contains(drawn_y) {
+                continue;
+            }
+
+            let (sx, sy) = self.global_to_screen((x, drawn_y));
+            let radius = self.line_thickness(f.style.thickness) * 3.0;
+            self.vertices_add_circle([sx, sy], radius, f.style.color, 16);
+
+            if let Some(text_style) = self.display_style.text.clone() {
+                let x_uv = (sx + 1.0) / 2.0 + LABEL_OFFSET;
+                let y_uv = (1.0 - sy) / 2.0 - LABEL_OFFSET;
+
+                let sections = self.text_add_label(
+                    &text_style.locale.localize(&format_readout_value(y)),
+                    (x_uv, y_uv),
+                    Layout::default_single_line()
+                        .h_align(HorizontalAlign::Left)
+                        .v_align(VerticalAlign::Bottom),
+                    &text_style,
+                );
+                self.readout_sections.extend(sections);
+            }
+        }
+
+        // backgrounds are drawn below the dots so they stay visible
+        let mut dots = std::mem::take(&mut self.vertices);
+        for (bounds, color) in std::mem::replace(&mut self.label_backgrounds, label_backgrounds) {
+            self.vertices_add_rect(bounds.min, bounds.max, color);
+        }
+        self.vertices.append(&mut dots);
+
+        self.readout_layer.vertices = std::mem::take(&mut self.vertices);
+        self.readout_layer.dirty = false;
+    }
+
+    /// Draws the lines of the crosshair and labels their intersection with its coordinates.
+    fn display_crosshair(&mut self) {
+        const LABEL_OFFSET: f32 = 0.01;
+
+        let sections = std::mem::take(&mut self.crosshair_sections);
+        self.view.borrow_mut().remove_text_sections(&sections);
+
+        let Some(readout) = self.crosshair_readout() else {
+            self.crosshair_layer.vertices.clear();
+            return;
+        };
+
+        let style = self.crosshair_style;
+        let text_style = self.display_style.text.clone();
+        let color = style.color.unwrap_or_else(|| {
+            text_style
+                .as_ref()
+                .map_or(RGBA::BLACK, |text_style| text_style.color)
+        });
+        let width = self.line_thickness(style.thickness);
+
+        let (sx, sy) = self.global_to_screen(readout.position);
+        for line in [[[sx, -1.0], [sx, 1.0]], [[-1.0, sy], [1.0, sy]]] {
+            match style.dash {
+                Some(dash) => self.vertices_add_dashed_polyline(&line, width, color, dash),
+                None => self.vertices_add_line(line[0], line[1], width, color),
+            }
+        }
 
-        let x_substep_start_index =
-            ((x_range_start - x_sym_offset) / x_substep_spacing_f64).ceil() as i32;
-        let x_substep_end_index =
-            ((x_range_end - x_sym_offset) / x_substep_spacing_f64).floor() as i32;
+        let graph = readout
+            .graph
+            .and_then(|id| self.functions.iter().find(|entry| entry.id == id))
+            .map(|entry| (entry.graph.style.clone(), entry.graph.label.clone()));
+        if let Some((graph_style, _)) = &graph {
+            let radius = self.line_thickness(graph_style.thickness) * 3.0;
+            self.vertices_add_circle([sx, sy], radius, graph_style.color, 16);
+        }
 
-        let x_step_start_index =
-            ((x_range_start - x_sym_offset) / x_step_spacing_f64).ceil() as i32;
-        let x_step_end_index = ((x_range_end - x_sym_offset) / x_step_spacing_f64).floor() as i32;
+        if let Some(text_style) = text_style.filter(|_| style.label) {
+            let (x, y) = readout.position;
+            let mut text = format!(
+                "x = {}   y = {}",
+                format_readout_value(x),
+                format_readout_value(y)
+            );
+            if let Some(label) = graph.and_then(|(_, label)| label) {
+                text = format!("{}: {}", label.text, text);
+            }
 
-        let x_substep_range = x_substep_start_index..=x_substep_end_index;
-        let x_step_range = x_step_start_index..=x_step_end_index;
+            // flipped to the other side of the intersection near the right and top edges
+            let (h_align, x_offset) = match sx > 0.5 {
+                true => (HorizontalAlign::Right, -LABEL_OFFSET),
+                false => (HorizontalAlign::Left, LABEL_OFFSET),
+            };
+            let (v_align, y_offset) = match sy > 0.5 {
+                true => (VerticalAlign::Top, LABEL_OFFSET),
+                false => (VerticalAlign::Bottom, -LABEL_OFFSET),
+            };
+
+            let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
+
+            let sections = self.text_add_label(
+                &text_style.locale.localize(&text),
+                ((sx + 1.0) / 2.0 + x_offset, (1.0 - sy) / 2.0 + y_offset),
+                Layout::default_single_line()
+                    .h_align(h_align)
+                    .v_align(v_align),
+                &text_style,
+            );
+            self.crosshair_sections.extend(sections);
 
-        //-- y spacing indices
+            // backgrounds are drawn above the lines so the label stays readable
+            for (bounds, color) in std::mem::replace(&mut self.label_backgrounds, label_backgrounds)
+            {
+                self.vertices_add_rect(bounds.min, bounds.max, color);
+            }
+        }
 
-        let y_substep_start_index =
-            ((y_range_start - y_sym_offset) / y_substep_spacing_f64).ceil() as i32;
-        let y_substep_end_index =
-            ((y_range_end - y_sym_offset) / y_substep_spacing_f64).floor() as i32;
+        self.crosshair_layer.vertices = std::mem::take(&mut self.vertices);
+        self.crosshair_layer.dirty = false;
+    }
 
-        let y_step_start_index =
-            ((y_range_start - y_sym_offset) / y_step_spacing_f64).ceil() as i32;
-        let y_step_end_index = ((y_range_end - y_sym_offset) / y_step_spacing_f64).floor() as i32;
+    /// Draws the measurement and the angle measurement picked with the measure tool.
+    fn display_measurement(&mut self) {
+        let sections = std::mem::take(&mut self.measurement_sections);
+        self.view.borrow_mut().remove_text_sections(&sections);
 
-        let y_substep_range = y_substep_start_index..=y_substep_end_index;
-        let y_step_range = y_step_start_index..=y_step_end_index;
+        let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
 
-        // println!(
-        //     "x-range: {{{:?}, len: {:?}}}",
-        //     self.x_range,
-        //     self.x_range_len()
-        // );
+        if let Some(measurement) = self.measurement {
+            self.vertices_add_measurement(measurement);
+        }
 
-        // println!("x-sym-offset: {:?}", x_sym_offset);
+        if let Some(mut angle) = self.angle_measurement {
+            let mut painter = LayerPainter::new(self);
+            angle.tessellate(&mut painter);
 
-        // println!(
-        //     "x-step: {:?}, x-substep: {:?}",
-        //     x_step_spacing_f64, x_substep_spacing_f64
-        // );
+            let sections = painter.into_sections();
+            self.measurement_sections.extend(sections);
+        }
 
-        // println!("x-substep-range {:?}", x_substep_range);
+        // backgrounds are drawn below the lines so they stay visible
+        let mut lines = std::mem::take(&mut self.vertices);
+        for (bounds, color) in std::mem::replace(&mut self.label_backgrounds, label_backgrounds) {
+            self.vertices_add_rect(bounds.min, bounds.max, color);
+        }
+        self.vertices.append(&mut lines);
 
-        //-- grid ---
+        self.measurement_layer.vertices = std::mem::take(&mut self.vertices);
+        self.measurement_layer.dirty = false;
+    }
 
-        if let Some(subgrid_style) = self.style.x.subgrid {
-            for i in x_substep_range.clone() {
-                let x = (i as f64 * x_substep_spacing_f64) + x_sym_offset;
-                let (sx, _) = self.global_to_screen((x, 0.0));
+    /// Adds the measured line with dashed legs along the axes and stacked labels of
+    /// Δx, Δy, the distance and the slope next to its end.
+    fn vertices_add_measurement(&mut self, measurement: Measurement) {
+        const DASH_LENGTH: f32 = 0.02;
+        const LABEL_OFFSET: f32 = 0.01;
+        const LINE_HEIGHT: f32 = 1.2;
 
-                self.vertices_add_line(
-                    [sx, -1.0],
-                    [sx, 1.0],
-                    subgrid_style.thickness,
-                    subgrid_style.color,
+        let color = self
+            .display_style
+            .text
+            .as_ref()
+            .map_or(RGBA::BLACK, |text_style| text_style.color);
+        let width = self.line_thickness(Thickness::THIN);
+
+        let (sx1, sy1) = self.global_to_screen(measurement.start);
+        let (sx2, sy2) = self.global_to_screen(measurement.end);
+
+        self.vertices_add_dashed_polyline(
+            &[[sx1, sy1], [sx2, sy1], [sx2, sy2]],
+            width,
+            RGBA {
+                a: color.a / 2,
+                ..color
+            },
+            DASH_LENGTH,
+        );
+        self.vertices_add_line([sx1, sy1], [sx2, sy2], width, color);
+
+        let radius = width * 3.0;
+        self.vertices_add_circle([sx1, sy1], radius, color, 16);
+        self.vertices_add_circle([sx2, sy2], radius, color, 16);
+
+        let Some(text_style) = self.display_style.text.clone() else {
+            return;
+        };
+
+        let slope = measurement
+            .slope()
+            .map_or("∞".to_string(), format_readout_value);
+        let lines = [
+            format!("Δx = {}", format_readout_value(measurement.dx())),
+            format!("Δy = {}", format_readout_value(measurement.dy())),
+            format!("d = {}", format_readout_value(measurement.distance())),
+            format!("m = {}", slope),
+        ];
+
+        let (_, py) = self.pixel_scale();
+        let line_height = text_style.size * LINE_HEIGHT / (2.0 * py);
+
+        let x_uv = (sx2 + 1.0) / 2.0 + LABEL_OFFSET;
+        let y_uv = (1.0 - sy2) / 2.0 + LABEL_OFFSET;
+
+        for (i, line) in lines.iter().enumerate() {
+            let sections = self.text_add_label(
+                &text_style.locale.localize(line),
+                (x_uv, y_uv + i as f32 * line_height),
+                Layout::default_single_line()
+                    .h_align(HorizontalAlign::Left)
+                    .v_align(VerticalAlign::Top),
+                &text_style,
+            );
+            self.measurement_sections.extend(sections);
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_widgets(&mut self) {
+        const TRACK_THICKNESS: f32 = Thickness::THIN;
+        const KNOB_RADIUS: f32 = Widget::<()>::HEIGHT / 3.0;
+
+        let sections = std::mem::take(&mut self.widget_sections);
+        self.view.borrow_mut().remove_text_sections(&sections);
+
+        let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
+
+        let locale = self
+            .display_style
+            .text
+            .as_ref()
+            .map(|text_style| text_style.locale)
+            .unwrap_or_default();
+
+        for index in 0..self.widgets.len() {
+            let widget = self.widgets[index].clone();
+            let (x, y) = widget.position_or_stacked(index);
+            let value = widget.value(&self.parameter);
+
+            let label = match &widget.kind {
+                WidgetKind::Slider { range } => {
+                    let t = saturate(inverse_lerp(value, range)) as f32;
+                    let knob_x = x + widget.width * t;
+
+                    self.vertices_add_line(
+                        [x, y],
+                        [x + widget.width, y],
+                        TRACK_THICKNESS,
+                        RGBA::GREY,
+                    );
+                    self.vertices_add_line(
+                        [x, y],
+                        [knob_x, y],
+                        TRACK_THICKNESS * 2.0,
+                        widget.color,
+                    );
+                    self.vertices_add_circle([knob_x, y], KNOB_RADIUS, widget.color, 16);
+
+                    format!(
+                        "{}: {}",
+                        widget.label,
+                        locale.localize(&format_readout_value(value))
+                    )
+                }
+                WidgetKind::Toggle => {
+                    let bounds = widget.bounds((x, y));
+                    let inset = Widget::<()>::HEIGHT / 5.0;
+
+                    self.vertices_add_rect(bounds.min, bounds.max, widget.color);
+                    let inner_color = match value != 0.0 {
+                        true => widget.color,
+                        false => RGBA::WHITE,
+                    };
+                    let inner = bounds.expand([-inset, -inset]);
+                    self.vertices_add_rect(inner.min, inner.max, inner_color);
+
+                    widget.label.clone()
+                }
+            };
+
+            if let Some(text_style) = self.display_style.text.clone() {
+                let (label_position, h_align, v_align) = match &widget.kind {
+                    WidgetKind::Slider { .. } => (
+                        (x, y + Widget::<()>::HEIGHT / 2.0),
+                        HorizontalAlign::Left,
+                        VerticalAlign::Bottom,
+                    ),
+                    WidgetKind::Toggle => (
+                        (x + widget.width + Widget::<()>::HEIGHT, y),
+                        HorizontalAlign::Left,
+                        VerticalAlign::Center,
+                    ),
+                };
+
+                let uv = (
+                    (label_position.0 + 1.0) / 2.0,
+                    (1.0 - label_position.1) / 2.0,
                 );
+                let sections = self.text_add_label(
+                    &label,
+                    uv,
+                    Layout::default_single_line()
+                        .h_align(h_align)
+                        .v_align(v_align),
+                    &text_style,
+                );
+                self.widget_sections.extend(sections);
             }
         }
 
-        if let Some(subgrid_style) = self.style.y.subgrid {
-            for i in y_substep_range.clone() {
-                let y = (i as f64 * y_substep_spacing_f64) + y_sym_offset;
-                let (_, sy) = self.global_to_screen((0.0, y));
+        // label backgrounds below the controls
+        let mut controls = std::mem::take(&mut self.vertices);
+        for (bounds, color) in std::mem::replace(&mut self.label_backgrounds, label_backgrounds) {
+            self.vertices_add_rect(bounds.min, bounds.max, color);
+        }
+        self.vertices.append(&mut controls);
 
-                self.vertices_add_line(
-                    [-1.0, sy],
-                    [1.0, sy],
-                    subgrid_style.thickness,
-                    subgrid_style.color,
-                );
+        self.widget_layer.vertices = std::mem::take(&mut self.vertices);
+        self.widget_layer.dirty = false;
+    }
+
+    /// Estimates the screen space (`-1..1`) bounds of a single line label, rotated labels
+    /// get the bounds of their rotated box.
+    ///
+    /// The glyph layout happens inside the text brush, so without the `shaping` feature
+    /// the width is approximated by the average advance of digits.
+    fn estimate_label_bounds(
+        &self,
+        text: &ShapedLine,
+        position: (f32, f32),
+        layout: Layout<BuiltInLineBreaker>,
+        text_style: &TextStyle,
+        rotation: Radians,
+    ) -> Option<Rect> {
+        const AVERAGE_ADVANCE: f32 = 0.6;
+
+        let (width, height) = self.view.borrow().texture_size()?;
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let (px, py) = self.pixel_scale();
+
+        let padding = text_style.halo.map(|halo| halo.width).unwrap_or(0.0);
+
+        let text_width = text.width.unwrap_or_else(|| {
+            text.text.chars().count() as f32 * text_style.size * AVERAGE_ADVANCE
+        });
+        let text_width = (text_width + 2.0 * padding) / px;
+        let text_height = (text_style.size + 2.0 * padding) / py;
+
+        let (h_align, v_align) = match layout {
+            Layout::SingleLine {
+                h_align, v_align, ..
+            } => (h_align, v_align),
+            Layout::Wrap {
+                h_align, v_align, ..
+            } => (h_align, v_align),
+        };
+
+        let sx = position.0 * 2.0 - 1.0;
+        let sy = 1.0 - position.1 * 2.0;
+
+        let x_start = match h_align {
+            HorizontalAlign::Left => sx,
+            HorizontalAlign::Center => sx - text_width / 2.0,
+            HorizontalAlign::Right => sx - text_width,
+        };
+        let y_start = match v_align {
+            VerticalAlign::Top => sy - text_height,
+            VerticalAlign::Center => sy - text_height / 2.0,
+            VerticalAlign::Bottom => sy,
+        };
+
+        let bounds = Rect::new(
+            [x_start, y_start],
+            [x_start + text_width, y_start + text_height],
+        );
+        if rotation.0 == 0.0 {
+            return Some(bounds);
+        }
+
+        // rotated around the anchor in pixels, screen space isn't square
+        let (sin, cos) = (rotation.0 as f32).sin_cos();
+        let corners = [
+            bounds.min,
+            [bounds.min[0], bounds.max[1]],
+            bounds.max,
+            [bounds.max[0], bounds.min[1]],
+        ]
+        .map(|[x, y]| {
+            let (dx, dy) = ((x - sx) * px, (y - sy) * py);
+            [
+                sx + (dx * cos - dy * sin) / px,
+                sy + (dx * sin + dy * cos) / py,
+            ]
+        });
+
+        Some(
+            corners[1..]
+                .iter()
+                .fold(Rect::new(corners[0], corners[0]), |bounds, &corner| {
+                    bounds.union(&Rect::new(corner, corner))
+                }),
+        )
+    }
+
+    /// Draws the label backgrounds collected by `text_add_label` on top of the data.
+    fn display_label_backgrounds(&mut self) {
+        let label_backgrounds = std::mem::take(&mut self.label_backgrounds);
+
+        for (bounds, color) in label_backgrounds {
+            self.vertices_add_rect(bounds.min, bounds.max, color);
+        }
+    }
+
+    /// Samples and tessellates every dirty function graph.
+    ///
+    /// Non finite values split the curve, panics are caught and mark the graph as failed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_function_graphs(&mut self) {
+        const DASH_LENGTH: f32 = 0.02;
+
+        // progressive rendering starts at `samples / REFINE_FACTOR^3`
+        const REFINE_FACTOR: u32 = 4;
+        const MIN_COARSE_SAMPLES: u32 = 16;
+
+        // derived graphs and fills between graphs follow their sources
+        loop {
+            let mut changed = false;
+
+            for index in 0..self.functions.len() {
+                let sources = self.functions[index].sources();
+                if sources.is_empty() || self.functions[index].layer.dirty {
+                    continue;
+                }
+
+                let dirty_sources: Vec<&GraphEntry<P>> = self
+                    .functions
+                    .iter()
+                    .filter(|entry| sources.contains(&entry.id) && entry.layer.dirty)
+                    .collect();
+                if dirty_sources.is_empty() {
+                    continue;
+                }
+
+                let restart = dirty_sources.iter().any(|entry| entry.sampled == 0);
+                let entry = &mut self.functions[index];
+                entry.layer.dirty = true;
+                if restart {
+                    entry.sampled = 0;
+                }
+                changed = true;
+            }
+
+            if !changed {
+                break;
             }
         }
 
-        if let Some(grid_style) = self.style.x.grid {
-            for i in x_step_range.clone() {
-                let x = (i as f64 * x_step_spacing_f64) + x_sym_offset;
-                let (sx, _) = self.global_to_screen((x, 0.0));
+        let mut segments: Vec<Vec<[f32; 2]>> = Vec::new();
+        // samples of `segments` in graph space
+        let mut graph_segments: Vec<Vec<(f64, f64)>> = Vec::new();
 
-                self.vertices_add_line(
-                    [sx, -1.0],
-                    [sx, 1.0],
-                    grid_style.thickness,
-                    grid_style.color,
+        for index in 0..self.functions.len() {
+            if !self.functions[index].layer.dirty {
+                continue;
+            }
+
+            let id = self.functions[index].id;
+            if let Some(style_override) = &self.functions[index].graph.style_override {
+                self.functions[index].graph.style = self.cascade_graph_style(id, style_override);
+            }
+            let f = self.functions[index].graph.clone();
+            let transform = self.functions[index].transform;
+
+            //-- visible part of the sampling domain
+
+            let domain = self.projection.x_domain(self.x_range, self.y_range);
+            let visible = transform.inverse_x_range(&domain.to_range());
+            let (x_start, x_end) = match &f.domain {
+                Some(domain) => (visible.start.max(domain.start), visible.end.min(domain.end)),
+                None => (visible.start, visible.end),
+            };
+
+            let samples = match (self.progressive_rendering, self.functions[index].sampled) {
+                (false, _) => f.samples.max(1),
+                (true, 0) => (f.samples / REFINE_FACTOR.pow(3)).max(MIN_COARSE_SAMPLES),
+                (true, sampled) => sampled.saturating_mul(REFINE_FACTOR),
+            }
+            .min(f.samples.max(1));
+            let step = (x_end - x_start) / samples as f64;
+
+            //-- sampling
+
+            segments.clear();
+            segments.push(Vec::new());
+            graph_segments.clear();
+            graph_segments.push(Vec::new());
+
+            // last sample before and first sample after every non finite stretch
+            let mut gap_ends: Vec<[f32; 2]> = Vec::new();
+            let mut after_gap = false;
+
+            let mut error: Option<GraphError> = None;
+
+            // skip domains outside of the visible range
+            if x_start <= x_end {
+                let xs = self.sample_positions(transform, x_start, x_end, samples);
+                let values = self.sample_graph(index, &xs);
+
+                for (x, value) in xs.into_iter().zip(values) {
+                    let y = match value {
+                        Ok(y) => Some(y),
+                        Err(message) => {
+                            match &mut error {
+                                Some(error) => error.failed_samples += 1,
+                                None => {
+                                    error = Some(GraphError {
+                                        message,
+                                        failed_samples: 1,
+                                    });
+                                }
+                            }
+                            None
+                        }
+                    };
+
+                    // values outside of logarithmic scales end up non finite as well
+                    let screen = y
+                        .filter(|y| y.is_finite())
+                        .map(|y| (y, self.global_to_screen(transform.apply((x, y)))))
+                        .filter(|(_, (sx, sy))| sx.is_finite() && sy.is_finite());
+
+                    match screen {
+                        Some((y, (sx, sy))) => {
+                            segments.last_mut().unwrap().push([sx, sy]);
+                            graph_segments.last_mut().unwrap().push((x, y));
+
+                            if std::mem::take(&mut after_gap) {
+                                gap_ends.push([sx, sy]);
+                            }
+                        }
+                        None => {
+                            if let Some(last) = segments.last().unwrap().last() {
+                                gap_ends.push(*last);
+                                segments.push(Vec::new());
+                                graph_segments.push(Vec::new());
+                            }
+                            after_gap = true;
+                        }
+                    }
+                }
+            }
+
+            //-- tessellation
+
+            let (segment_colors, segment_widths) = match &error {
+                None => (
+                    graph_segment_colors(&f.style, &graph_segments),
+                    self.graph_segment_widths(&f.style, &graph_segments),
+                ),
+                Some(_) => (None, None),
+            };
+
+            if let (Some(fill), None) = (f.style.fill, &error) {
+                for (segment, graph_segment) in segments.iter().zip(&graph_segments) {
+                    let baselines = self.fill_baselines(fill.baseline, transform, graph_segment);
+
+                    // the area is split where the baseline is undefined
+                    let mut start = 0;
+                    for end in 0..=segment.len() {
+                        if end < segment.len() && baselines[end].is_finite() {
+                            continue;
+                        }
+                        if end > start + 1 {
+                            self.vertices_add_area_fill(
+                                &segment[start..end],
+                                &baselines[start..end],
+                                fill,
+                            );
+                        }
+                        start = end + 1;
+                    }
+                }
+            }
+
+            let thickness = self.line_thickness(f.style.thickness);
+            for (i, segment) in segments.iter().enumerate() {
+                if error.is_some() {
+                    self.vertices_add_dashed_polyline(segment, thickness, RGBA::RED, DASH_LENGTH);
+                    continue;
+                }
+
+                let colors = match &segment_colors {
+                    Some(colors) => Cow::Borrowed(&colors[i]),
+                    None => Cow::Owned(vec![f.style.color; segment.len()]),
+                };
+                let widths = match &segment_widths {
+                    Some(widths) => Cow::Borrowed(&widths[i]),
+                    None => Cow::Owned(vec![thickness; segment.len()]),
+                };
+                self.vertices_add_stroke(segment, &widths, &colors, f.style.join, f.style.cap);
+            }
+
+            if error.is_none() {
+                self.vertices_add_endpoint_markers(index, thickness, step);
+                self.vertices_add_gap_markers(&gap_ends, &f.style, thickness);
+            }
+
+            let vertices = std::mem::take(&mut self.vertices);
+
+            if error.is_none() {
+                self.vertices_add_glow(
+                    &f.style,
+                    &segments,
+                    segment_colors.as_deref(),
+                    segment_widths.as_deref(),
+                    thickness,
                 );
             }
+            let glow_vertices = std::mem::take(&mut self.vertices);
+
+            if let (Some(error), Some(callback)) = (&error, &mut self.error_callback) {
+                callback(id, error);
+            }
+
+            let entry = &mut self.functions[index];
+            entry.error = error;
+            entry.sampled = samples;
+            entry.layer.vertices = vertices;
+            entry.layer.glow_vertices = glow_vertices;
+            entry.layer.dirty = false;
         }
+    }
 
-        if let Some(grid_style) = self.style.y.grid {
-            for i in y_step_range.clone() {
-                let y = (i as f64 * y_step_spacing_f64) + y_sym_offset;
-                let (_, sy) = self.global_to_screen((0.0, y));
+    /// Screen space y of the fill baseline at every sample of `graph_segment` (graph space),
+    /// `NaN` where it is undefined.
+    fn fill_baselines(
+        &self,
+        baseline: FillBaseline,
+        transform: GraphTransform,
+        graph_segment: &[(f64, f64)],
+    ) -> Vec<f32> {
+        let constant = |y: f64| {
+            let (_, baseline) = self.global_to_screen(transform.apply((0.0, y)));
+            // logarithmic y scales have no zero, areas start at the bottom instead
+            match baseline.is_finite() {
+                true => baseline,
+                false => -1.0,
+            }
+        };
 
-                self.vertices_add_line(
-                    [-1.0, sy],
-                    [1.0, sy],
-                    grid_style.thickness,
-                    grid_style.color,
+        let id = match baseline {
+            FillBaseline::Zero => return vec![constant(0.0); graph_segment.len()],
+            FillBaseline::Value(y) => return vec![constant(y); graph_segment.len()],
+            FillBaseline::Graph(id) => id,
+        };
+        let Some(index) = self.functions.iter().position(|entry| entry.id == id) else {
+            return vec![f32::NAN; graph_segment.len()];
+        };
+
+        // the other graph is sampled where the samples of this one are drawn, its errors
+        // are reported when it is tessellated itself
+        let other = self.functions[index].transform;
+        let xs: Vec<f64> = graph_segment
+            .iter()
+            .map(|&(x, _)| other.inverse_x(transform.apply((x, 0.0)).0))
+            .collect();
+
+        self.sample_graph(index, &xs)
+            .into_iter()
+            .zip(xs)
+            .map(|(value, x)| match value {
+                Ok(y) if y.is_finite() => self.global_to_screen(other.apply((x, y))).1,
+                _ => f32::NAN,
+            })
+            .collect()
+    }
+
+    /// Per point widths of `graph_segments` (data space) for the variable thickness of
+    /// `style`, `None` if it has a constant thickness.
+    fn graph_segment_widths(
+        &self,
+        style: &GraphStyle,
+        graph_segments: &[Vec<(f64, f64)>],
+    ) -> Option<Vec<Vec<f32>>> {
+        let variable = style.variable_thickness.as_ref()?;
+
+        Some(
+            graph_segments
+                .iter()
+                .map(|segment| {
+                    segment
+                        .iter()
+                        .map(|&(x, y)| match variable.thickness(x, y) {
+                            thickness if thickness.is_finite() => {
+                                self.line_thickness(thickness.max(0.0))
+                            }
+                            _ => 0.0,
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Blurred copy of the curve `segments` (view space) for the glow of `style`.
+    fn vertices_add_glow(
+        &mut self,
+        style: &GraphStyle,
+        segments: &[Vec<[f32; 2]>],
+        segment_colors: Option<&[Vec<RGBA>]>,
+        segment_widths: Option<&[Vec<f32>]>,
+        thickness: f32,
+    ) {
+        let Some(glow) = style.glow else {
+            return;
+        };
+
+        let alpha = (glow.intensity.clamp(0.0, 1.0) * 255.0) as u8;
+        let with_alpha = |mut color: RGBA| {
+            color.a = alpha;
+            color
+        };
+
+        for (i, segment) in segments.iter().enumerate() {
+            let colors: Vec<RGBA> = match segment_colors {
+                Some(colors) => colors[i].iter().copied().map(with_alpha).collect(),
+                None => vec![with_alpha(style.color); segment.len()],
+            };
+            let widths: Vec<f32> = match segment_widths {
+                Some(widths) => widths[i].iter().map(|w| w * glow.spread).collect(),
+                None => vec![thickness * glow.spread; segment.len()],
+            };
+            self.vertices_add_stroke(segment, &widths, &colors, style.join, style.cap);
+        }
+    }
+
+    /// Samples every dirty parametric graph over its whole `t_range` and draws it like a
+    /// function graph, non finite samples split the curve.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_parametric_graphs(&mut self) {
+        const DASH_LENGTH: f32 = 0.02;
+
+        for index in 0..self.parametric_graphs.len() {
+            if !self.parametric_graphs[index].layer.dirty {
+                continue;
+            }
+
+            let id = self.parametric_graphs[index].id;
+            if let Some(style_override) = &self.parametric_graphs[index].graph.style_override {
+                self.parametric_graphs[index].graph.style =
+                    self.cascade_graph_style(id, style_override);
+            }
+            let graph = self.parametric_graphs[index].graph.clone();
+
+            let samples = graph.samples.max(1);
+            let (t_start, t_end) = (graph.t_range.start, graph.t_range.end);
+            let step = (t_end - t_start) / samples as f64;
+
+            //-- sampling
+
+            let mut segments: Vec<Vec<[f32; 2]>> = vec![Vec::new()];
+            let mut graph_segments: Vec<Vec<(f64, f64)>> = vec![Vec::new()];
+
+            // last sample before and first sample after every non finite stretch
+            let mut gap_ends: Vec<[f32; 2]> = Vec::new();
+            let mut after_gap = false;
+
+            let mut error: Option<GraphError> = None;
+
+            for i in 0..=samples {
+                let t = t_start + step * i as f64;
+
+                let point = match catch_evaluation(|| (graph.function)(t, &self.parameter)) {
+                    Ok(point) => Some(point),
+                    Err(message) => {
+                        match &mut error {
+                            Some(error) => error.failed_samples += 1,
+                            None => {
+                                error = Some(GraphError {
+                                    message,
+                                    failed_samples: 1,
+                                });
+                            }
+                        }
+                        None
+                    }
+                };
+
+                // values outside of logarithmic scales end up non finite as well
+                let screen = point
+                    .filter(|(x, y)| x.is_finite() && y.is_finite())
+                    .map(|point| (point, self.global_to_screen(point)))
+                    .filter(|(_, (sx, sy))| sx.is_finite() && sy.is_finite());
+
+                match screen {
+                    Some((point, (sx, sy))) => {
+                        segments.last_mut().unwrap().push([sx, sy]);
+                        graph_segments.last_mut().unwrap().push(point);
+
+                        if std::mem::take(&mut after_gap) {
+                            gap_ends.push([sx, sy]);
+                        }
+                    }
+                    None => {
+                        if let Some(last) = segments.last().unwrap().last() {
+                            gap_ends.push(*last);
+                            segments.push(Vec::new());
+                            graph_segments.push(Vec::new());
+                        }
+                        after_gap = true;
+                    }
+                }
+            }
+
+            //-- tessellation
+
+            let (segment_colors, segment_widths) = match &error {
+                None => (
+                    graph_segment_colors(&graph.style, &graph_segments),
+                    self.graph_segment_widths(&graph.style, &graph_segments),
+                ),
+                Some(_) => (None, None),
+            };
+
+            let thickness = self.line_thickness(graph.style.thickness);
+            for (i, segment) in segments.iter().enumerate() {
+                if error.is_some() {
+                    self.vertices_add_dashed_polyline(segment, thickness, RGBA::RED, DASH_LENGTH);
+                    continue;
+                }
+
+                let colors = match &segment_colors {
+                    Some(colors) => Cow::Borrowed(&colors[i]),
+                    None => Cow::Owned(vec![graph.style.color; segment.len()]),
+                };
+                let widths = match &segment_widths {
+                    Some(widths) => Cow::Borrowed(&widths[i]),
+                    None => Cow::Owned(vec![thickness; segment.len()]),
+                };
+                self.vertices_add_stroke(
+                    segment,
+                    &widths,
+                    &colors,
+                    graph.style.join,
+                    graph.style.cap,
+                );
+            }
+            if error.is_none() {
+                self.vertices_add_gap_markers(&gap_ends, &graph.style, thickness);
+            }
+            let vertices = std::mem::take(&mut self.vertices);
+
+            if error.is_none() {
+                self.vertices_add_glow(
+                    &graph.style,
+                    &segments,
+                    segment_colors.as_deref(),
+                    segment_widths.as_deref(),
+                    thickness,
                 );
             }
+            let glow_vertices = std::mem::take(&mut self.vertices);
+
+            if let (Some(error), Some(callback)) = (&error, &mut self.error_callback) {
+                callback(id, error);
+            }
+
+            let entry = &mut self.parametric_graphs[index];
+            entry.error = error;
+            entry.layer.vertices = vertices;
+            entry.layer.glow_vertices = glow_vertices;
+            entry.layer.dirty = false;
         }
+    }
 
-        //-----------
+    /// Traces the zero contours of every dirty implicit graph on a grid over the view and
+    /// draws them like function graphs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_implicit_graphs(&mut self) {
+        const DASH_LENGTH: f32 = 0.02;
 
-        //-- axes ---
+        for index in 0..self.implicit_graphs.len() {
+            if !self.implicit_graphs[index].layer.dirty {
+                continue;
+            }
 
-        if let Some(axis_style) = self.style.x.axis {
-            self.vertices_add_line(
-                [-1.0, sy0],
-                [1.0, sy0],
-                axis_style.thickness,
-                axis_style.color,
+            let id = self.implicit_graphs[index].id;
+            if let Some(style_override) = &self.implicit_graphs[index].graph.style_override {
+                self.implicit_graphs[index].graph.style =
+                    self.cascade_graph_style(id, style_override);
+            }
+            let graph = self.implicit_graphs[index].graph.clone();
+
+            //-- sampling
+
+            // the grid is evenly spaced on screen, so it follows logarithmic scales
+            let (columns, rows) = (
+                graph.resolution.0.max(1) as usize,
+                graph.resolution.1.max(1) as usize,
             );
+            let to_screen = |(column, row): (f64, f64)| {
+                (
+                    column / columns as f64 * 2.0 - 1.0,
+                    row / rows as f64 * 2.0 - 1.0,
+                )
+            };
+
+            let mut error: Option<GraphError> = None;
+
+            let mut values = Vec::with_capacity((columns + 1) * (rows + 1));
+            for row in 0..=rows {
+                for column in 0..=columns {
+                    let (x, y) = self.screen_to_global(to_screen((column as f64, row as f64)));
+
+                    match catch_evaluation(|| (graph.function)(x, y, &self.parameter)) {
+                        Ok(value) => values.push(value),
+                        Err(message) => {
+                            match &mut error {
+                                Some(error) => error.failed_samples += 1,
+                                None => {
+                                    error = Some(GraphError {
+                                        message,
+                                        failed_samples: 1,
+                                    });
+                                }
+                            }
+                            values.push(f64::NAN);
+                        }
+                    }
+                }
+            }
+
+            let (segments, graph_segments): (Vec<Vec<[f32; 2]>>, Vec<Vec<(f64, f64)>>) =
+                zero_contours(&values, columns, rows)
+                    .into_iter()
+                    .map(|contour| {
+                        contour
+                            .into_iter()
+                            .map(|point| {
+                                let (sx, sy) = to_screen(point);
+                                ([sx as f32, sy as f32], self.screen_to_global((sx, sy)))
+                            })
+                            .unzip()
+                    })
+                    .unzip();
+
+            //-- tessellation
+
+            let (segment_colors, segment_widths) = match &error {
+                None => (
+                    graph_segment_colors(&graph.style, &graph_segments),
+                    self.graph_segment_widths(&graph.style, &graph_segments),
+                ),
+                Some(_) => (None, None),
+            };
+
+            let thickness = self.line_thickness(graph.style.thickness);
+            for (i, segment) in segments.iter().enumerate() {
+                if error.is_some() {
+                    self.vertices_add_dashed_polyline(segment, thickness, RGBA::RED, DASH_LENGTH);
+                    continue;
+                }
+
+                let colors = match &segment_colors {
+                    Some(colors) => Cow::Borrowed(&colors[i]),
+                    None => Cow::Owned(vec![graph.style.color; segment.len()]),
+                };
+                let widths = match &segment_widths {
+                    Some(widths) => Cow::Borrowed(&widths[i]),
+                    None => Cow::Owned(vec![thickness; segment.len()]),
+                };
+                self.vertices_add_stroke(
+                    segment,
+                    &widths,
+                    &colors,
+                    graph.style.join,
+                    graph.style.cap,
+                );
+            }
+            let vertices = std::mem::take(&mut self.vertices);
+
+            if error.is_none() {
+                self.vertices_add_glow(
+                    &graph.style,
+                    &segments,
+                    segment_colors.as_deref(),
+                    segment_widths.as_deref(),
+                    thickness,
+                );
+            }
+            let glow_vertices = std::mem::take(&mut self.vertices);
+
+            if let (Some(error), Some(callback)) = (&error, &mut self.error_callback) {
+                callback(id, error);
+            }
+
+            let entry = &mut self.implicit_graphs[index];
+            entry.error = error;
+            entry.layer.vertices = vertices;
+            entry.layer.glow_vertices = glow_vertices;
+            entry.layer.dirty = false;
         }
+    }
 
-        if let Some(axis_style) = self.style.y.axis {
-            self.vertices_add_line(
-                [sx0, -1.0],
-                [sx0, 1.0],
-                axis_style.thickness,
-                axis_style.color,
+    /// Draws the endpoint markers of function graph `index` at the ends of its domain.
+    fn vertices_add_endpoint_markers(&mut self, index: usize, thickness: f32, step: f64) {
+        let entry = &self.functions[index];
+        let Some(domain) = entry.graph.domain.clone() else {
+            return;
+        };
+        let (transform, color) = (entry.transform, entry.graph.style.color);
+
+        let ends = [
+            (domain.start, entry.graph.endpoints.0),
+            (domain.end, entry.graph.endpoints.1),
+        ];
+        let radius = thickness * 2.0;
+
+        for (x, marker) in ends {
+            let Some(marker) = marker.filter(|_| x.is_finite()) else {
+                continue;
+            };
+
+            // evaluated directly, the domain check would exclude the end of the range
+            let y = match self.functions[index].derived {
+                Some(_) => self.evaluate_graph(index, x, step, 0),
+                None => {
+                    catch_evaluation(|| (self.functions[index].graph.function)(x, &self.parameter))
+                }
+            };
+            let Some(y) = y.ok().filter(|y| y.is_finite()) else {
+                continue;
+            };
+
+            let Some((sx, sy)) = self.visible_screen_position(transform.apply((x, y))) else {
+                continue;
+            };
+
+            self.vertices_add_endpoint_marker([sx, sy], marker, radius, thickness, color);
+        }
+    }
+
+    /// Draws the `gap_marker` of `style` at the curve ends next to non finite samples.
+    fn vertices_add_gap_markers(
+        &mut self,
+        gap_ends: &[[f32; 2]],
+        style: &GraphStyle,
+        thickness: f32,
+    ) {
+        let Some(marker) = style.gap_marker else {
+            return;
+        };
+
+        for end in gap_ends {
+            self.vertices_add_endpoint_marker(
+                *end,
+                marker,
+                thickness * 2.0,
+                thickness,
+                style.color,
             );
         }
+    }
 
-        //-----------
+    fn vertices_add_endpoint_marker(
+        &mut self,
+        center: [f32; 2],
+        marker: EndpointMarker,
+        radius: f32,
+        thickness: f32,
+        color: RGBA,
+    ) {
+        self.vertices_add_circle(center, radius, color, 16);
+        if marker == EndpointMarker::Open {
+            self.vertices_add_circle(center, radius - thickness, self.clear_color, 16);
+        }
+    }
+
+    /// Samples every dirty complex function graph on a grid of pixel cells and draws
+    /// the cells with the domain coloring of the samples at their corners.
+    ///
+    /// Graphs with an expression are evaluated per pixel by their pass instead, unless the
+    /// axes are scaled or projected and the shader can't map pixels to data coordinates.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_complex_function_graphs(&mut self) {
+        let Some((width, height)) = self.view.borrow().texture_size() else {
+            return;
+        };
+
+        let linear =
+            self.x_scale.is_linear() && self.y_scale.is_linear() && self.projection.is_identity();
+
+        for index in 0..self.complex_functions.len() {
+            if !self.complex_functions[index].layer.dirty {
+                continue;
+            }
+
+            let id = self.complex_functions[index].id;
+            let graph = self.complex_functions[index].graph.clone();
+
+            {
+                let entry = &mut self.complex_functions[index];
+                let mut pass = entry.pass.borrow_mut();
+
+                pass.visible = false;
+                if let (true, Some(expression)) = (linear, &graph.expression) {
+                    pass.set(expression, &graph.coloring, self.x_range, self.y_range);
+                    pass.visible = true;
+
+                    entry.error = None;
+                    entry.layer.vertices.clear();
+                    entry.layer.dirty = false;
+                    continue;
+                }
+            }
+
+            let cell_size = graph.cell_size.max(1.0);
+            let columns = (width as f32 / cell_size).ceil().max(1.0) as usize;
+            let rows = (height as f32 / cell_size).ceil().max(1.0) as usize;
+
+            // view space of the grid lines, the last cell may extend past the view
+            let xs: Vec<f32> = (0..=columns)
+                .map(|i| -1.0 + 2.0 * (i as f32 * cell_size) / width as f32)
+                .collect();
+            let ys: Vec<f32> = (0..=rows)
+                .map(|j| -1.0 + 2.0 * (j as f32 * cell_size) / height as f32)
+                .collect();
+
+            //-- sampling
+
+            let mut error: Option<GraphError> = None;
+
+            let mut colors = Vec::with_capacity(xs.len() * ys.len());
+            for y in &ys {
+                for x in &xs {
+                    let (re, im) = self.view_to_data((*x, *y));
+                    let z = Complex::new(re, im);
+
+                    let value = match catch_evaluation(|| graph.eval(z, &self.parameter)) {
+                        Ok(value) => value,
+                        Err(message) => {
+                            match &mut error {
+                                Some(error) => error.failed_samples += 1,
+                                None => {
+                                    error = Some(GraphError {
+                                        message,
+                                        failed_samples: 1,
+                                    });
+                                }
+                            }
+                            Complex::new(f64::NAN, f64::NAN)
+                        }
+                    };
+                    colors.push(graph.coloring.color(value));
+                }
+            }
+
+            //-- tessellation
+
+            let color_at = |i: usize, j: usize| colors[j * xs.len() + i].into();
+            for j in 0..rows {
+                for i in 0..columns {
+                    let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+
+                    for corner in [0, 1, 2, 0, 2, 3] {
+                        let (ci, cj) = corners[corner];
+                        self.vertices.push(Vertex {
+                            position: [xs[ci], ys[cj], 0.0],
+                            color: color_at(ci, cj),
+                        });
+                    }
+                }
+            }
+
+            if let (Some(error), Some(callback)) = (&error, &mut self.error_callback) {
+                callback(id, error);
+            }
+
+            let entry = &mut self.complex_functions[index];
+            entry.error = error;
+            entry.layer.vertices = std::mem::take(&mut self.vertices);
+            entry.layer.dirty = false;
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_point_series(&mut self) {
+        for index in 0..self.point_series.len() {
+            if !self.point_series[index].layer.dirty {
+                continue;
+            }
+
+            let mut style = self.point_series[index].series.style;
+            style.radius = self.line_thickness(style.radius);
+            let transform = self.point_series[index].transform;
+
+            for i in 0..self.point_series[index].series.points.len() {
+                let point = self.point_series[index].series.points[i];
+                // missing values are left out instead of producing NaN vertices
+                if !point.0.is_finite() || !point.1.is_finite() {
+                    continue;
+                }
 
-        //-- ticks --
+                let (sx, sy) = self.global_to_screen(transform.apply(point));
+                // outside of a logarithmic scale
+                if !sx.is_finite() || !sy.is_finite() {
+                    continue;
+                }
 
-        if let Some(subtick_style) = self.style.x.subtick {
-            for i in x_substep_range.clone() {
-                let x = (i as f64 * x_substep_spacing_f64) + x_sym_offset;
+                self.vertices_add_marker([sx, sy], style.radius, style.shape, style.color);
+            }
 
-                let (sx, sy) = self.global_to_screen((x, 0.0));
+            let layer = &mut self.point_series[index].layer;
+            layer.vertices = std::mem::take(&mut self.vertices);
+            layer.dirty = false;
+        }
+    }
 
-                self.vertices_add_polyline(
-                    &[
-                        [sx, sy + subtick_style.length / 2.0],
-                        [sx, sy - subtick_style.length / 2.0],
-                    ],
-                    self.screen_constant(subtick_style.thickness as f64),
-                    subtick_style.color,
-                );
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_ellipses(&mut self) {
+        for index in 0..self.ellipses.len() {
+            if !self.ellipses[index].layer.dirty {
+                continue;
             }
+
+            let mut ellipse = self.ellipses[index].ellipse;
+            CanvasLayer::<P>::tessellate(&mut ellipse, &mut LayerPainter::new(self));
+
+            let layer = &mut self.ellipses[index].layer;
+            layer.vertices = std::mem::take(&mut self.vertices);
+            layer.dirty = false;
         }
+    }
 
-        if let Some(subtick_style) = self.style.y.subtick {
-            for i in y_substep_range.clone() {
-                let y = (i as f64 * y_substep_spacing_f64) + y_sym_offset;
-                let (sx, sy) = self.global_to_screen((0.0, y));
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn display_layers(&mut self) {
+        let mut layers = std::mem::take(&mut self.layers);
 
-                self.vertices_add_polyline(
-                    &[
-                        [sx + subtick_style.length / 2.0, sy],
-                        [sx - subtick_style.length / 2.0, sy],
-                    ],
-                    self.screen_constant(subtick_style.thickness as f64),
-                    subtick_style.color,
-                );
-            }
+        for entry in layers.iter_mut().filter(|entry| entry.vertices.dirty) {
+            self.view.borrow_mut().remove_text_sections(&entry.sections);
+
+            let mut painter = LayerPainter::new(self);
+            entry.layer.tessellate(&mut painter);
+            entry.sections = painter.into_sections();
+
+            entry.vertices.vertices = std::mem::take(&mut self.vertices);
+            entry.vertices.dirty = false;
         }
 
-        if let Some(tick_style) = self.style.x.tick {
-            for i in x_step_range.clone() {
-                let x = (i as f64 * x_step_spacing_f64) + x_sym_offset;
-                let (sx, sy) = self.global_to_screen((x, 0.0));
+        self.layers = layers;
+    }
 
-                self.vertices_add_polyline(
-                    &[
-                        [sx, sy + tick_style.length / 2.0],
-                        [sx, sy - tick_style.length / 2.0],
-                    ],
-                    self.screen_constant(tick_style.thickness as f64),
-                    tick_style.color,
-                );
+    fn vertices_add_polyline(&mut self, points: &[[f32; 2]], width: f32, color: RGBA) {
+        self.vertices_add_stroke(
+            points,
+            &vec![width; points.len()],
+            &vec![color; points.len()],
+            LineJoin::Round,
+            LineCap::Round,
+        );
+    }
+
+    /// Adds a polyline whose width and color are interpolated between the `widths` and
+    /// `colors` of its points.
+    ///
+    /// Every segment is a quad, the gaps at the outer side of the corners are filled
+    /// according to `join` and the ends are shaped according to `cap`. Neighbouring
+    /// segments share the intersection of their inner edges, so the geometry doesn't
+    /// overlap and translucent strokes get a uniform opacity (except where the curve
+    /// crosses itself).
+    pub(crate) fn vertices_add_stroke(
+        &mut self,
+        points: &[[f32; 2]],
+        widths: &[f32],
+        colors: &[RGBA],
+        join: LineJoin,
+        cap: LineCap,
+    ) {
+        // (point, half width, color), repeated points have no direction
+        let mut stroke: Vec<([f32; 2], f32, RGBA)> = Vec::with_capacity(points.len());
+        for ((point, width), color) in points.iter().zip(widths).zip(colors) {
+            if !(point[0].is_finite() && point[1].is_finite()) {
+                continue;
             }
+            if stroke.last().is_some_and(|last| last.0 == *point) {
+                continue;
+            }
+            stroke.push((*point, width / 2.0, *color));
         }
 
-        if let Some(tick_style) = self.style.y.tick {
-            for i in y_step_range.clone() {
-                let y = (i as f64 * y_step_spacing_f64) + y_sym_offset;
-                let (sx, sy) = self.global_to_screen((0.0, y));
+        if colors.iter().any(|color| color.a < u8::MAX) {
+            stroke = simplify_translucent_stroke(stroke);
+        }
 
-                self.vertices_add_polyline(
-                    &[
-                        [sx + tick_style.length / 2.0, sy],
-                        [sx - tick_style.length / 2.0, sy],
-                    ],
-                    tick_style.thickness,
-                    tick_style.color,
-                );
+        match stroke.len() {
+            0 => return,
+            1 => {
+                let (point, radius, color) = stroke[0];
+                match cap {
+                    LineCap::Butt => {}
+                    LineCap::Round => self.vertices_add_circle(point, radius, color, 16),
+                    LineCap::Square => self.vertices_add_rect(
+                        [point[0] - radius, point[1] - radius],
+                        [point[0] + radius, point[1] + radius],
+                        color,
+                    ),
+                }
+                return;
             }
+            _ => {}
         }
 
-        //-----------
-
-        //-- text --
+        let (directions, lengths): (Vec<[f32; 2]>, Vec<f32>) = stroke
+            .windows(2)
+            .map(|pair| {
+                let (start, end) = (pair[0].0, pair[1].0);
+                let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+                let len = (dx * dx + dy * dy).sqrt();
+                ([dx / len, dy / len], len)
+            })
+            .unzip();
 
-        if let Some(text_style) = &self.style.text {
-            let text_size = text_style.size;
-            let text_font = &text_style.font;
-            let text_max_digits = text_style.max_digits;
+        //-- corners
 
-            {
-                let start_index = (x_range_start / x_step_spacing_f64).ceil() as i128;
-                let end_index = (x_range_end / x_step_spacing_f64).floor() as i128;
+        let last_segment = directions.len() - 1;
+        // left and right corners at the start and end of every segment
+        let mut corners: Vec<[[f32; 2]; 4]> = directions
+            .iter()
+            .enumerate()
+            .map(|(k, direction)| {
+                let ((mut start, radius1, _), (mut end, radius2, _)) = (stroke[k], stroke[k + 1]);
 
-                for i in start_index..=end_index {
-                    if i == 0 {
-                        continue;
+                if cap == LineCap::Square {
+                    if k == 0 {
+                        start = offset(start, *direction, -radius1);
+                    }
+                    if k == last_segment {
+                        end = offset(end, *direction, radius2);
                     }
+                }
 
-                    let x = (&x_step_spacing * i).calc_precision(None);
-                    let x_f64 = x_step_spacing_f64 * i as f64;
+                let n = normal(*direction);
+                [
+                    offset(start, n, radius1),
+                    offset(start, n, -radius1),
+                    offset(end, n, radius2),
+                    offset(end, n, -radius2),
+                ]
+            })
+            .collect();
 
-                    let x_uv = lerp(x_f64, &self.x_range, &(0.0..1.0)) as f32;
-                    let y_uv = lerp(0.0, &self.y_range, &(1.0..0.0)) as f32;
+        //-- joins
 
-                    let text = format!("{}", decimal_format_scientific_when(&x, text_max_digits));
+        // the joins are collected first since they move the segment corners
+        let mut joins = Vec::with_capacity(directions.len());
+        for k in 1..directions.len() {
+            let (point, radius, color) = stroke[k];
+            let (d0, d1) = (directions[k - 1], directions[k]);
+            let (n0, n1) = (normal(d0), normal(d1));
 
-                    let text_section = TextSection::Relative(
-                        SectionBuilder::default()
-                            .add_text(Text::new(&text).with_scale(text_size))
-                            .with_screen_position((x_uv, y_uv))
-                            .with_layout(
-                                Layout::default_single_line()
-                                    .h_align(HorizontalAlign::Center)
-                                    .v_align(VerticalAlign::Top),
-                            )
-                            .to_owned(),
-                    )
-                    .into_arc_ref_cell();
-
-                    let mut view = self.view.borrow_mut();
-                    match view.add_text_section(text_section.clone(), &text_font.name) {
-                        Err(_) => {
-                            view.add_font(text_font.clone()).unwrap();
-                            view.add_text_section(text_section.clone(), &text_font.name)
-                                .unwrap();
-                        }
-                        _ => {}
-                    }
-                }
+            let cross = d0[0] * d1[1] - d0[1] * d1[0];
+            let dot = d0[0] * d1[0] + d0[1] * d1[1];
+            if approx_zero(cross, 1e-6) && dot > 0.0 {
+                continue;
             }
 
-            {
-                let start_index = (y_range_start / y_step_spacing_f64).ceil() as i128;
-                let end_index = (y_range_end / y_step_spacing_f64).floor() as i128;
+            // the gap opens on the right for left turns and vice versa
+            let side = if cross > 0.0 { -1.0 } else { 1.0 };
+            let outer0 = offset(point, n0, side * radius);
+            let outer1 = offset(point, n1, side * radius);
+
+            // 1 / cos² of half the turning angle
+            let ratio = 2.0 / (1.0 + n0[0] * n1[0] + n0[1] * n1[1]);
+            let bisector = [n0[0] + n1[0], n0[1] + n1[1]];
+
+            // The inner edges intersect within both segments unless the turn is too sharp.
+            // Segments between two joins are shared, the ones at the ends are not.
+            let inner_distance = radius * (ratio - 1.0).max(0.0).sqrt();
+            let available = |segment: usize| match segment == 0 || segment == last_segment {
+                true => lengths[segment],
+                false => lengths[segment] / 2.0,
+            };
+            let inner = (ratio.is_finite() && inner_distance <= available(k - 1).min(available(k)))
+                .then(|| offset(point, bisector, -side * radius * ratio / 2.0));
+
+            if let Some(inner) = inner {
+                let (end, start) = match side > 0.0 {
+                    true => (3, 1),
+                    false => (2, 0),
+                };
+                corners[k - 1][end] = inner;
+                corners[k][start] = inner;
+            }
 
-                for i in start_index..=end_index {
-                    if i == 0 {
-                        continue;
-                    }
+            let tip = match join {
+                LineJoin::Miter { limit } if ratio.is_finite() && ratio.sqrt() <= limit => {
+                    Some(offset(point, bisector, side * radius * ratio / 2.0))
+                }
+                _ => None,
+            };
 
-                    let y = (&y_step_spacing * i).calc_precision(None);
-                    let y_f64 = y_step_spacing_f64 * i as f64;
+            joins.push((point, radius, color, outer0, outer1, inner, tip));
+        }
 
-                    let x_uv = lerp(0.0, &self.x_range, &(0.0..1.0)) as f32;
-                    let y_uv = lerp(y_f64, &self.y_range, &(1.0..0.0)) as f32;
+        //-- segments
+
+        for (k, corners) in corners.iter().enumerate() {
+            let (color1, color2) = (stroke[k].2.into(), stroke[k + 1].2.into());
+            let corners = [
+                (corners[0], color1),
+                (corners[1], color1),
+                (corners[3], color2),
+                (corners[2], color2),
+            ];
+
+            for index in [0, 1, 2, 0, 2, 3] {
+                let (position, color) = corners[index];
+                self.vertices.push(Vertex {
+                    position: [position[0], position[1], 0.0],
+                    color,
+                });
+            }
+        }
 
-                    let text = format!(" {}", decimal_format_scientific_when(&y, text_max_digits));
+        for (point, radius, color, outer0, outer1, inner, tip) in joins {
+            // the triangle between the inner corner and the point belongs to the join
+            let center = inner.unwrap_or(point);
 
-                    let text_section = TextSection::Relative(
-                        SectionBuilder::default()
-                            .add_text(Text::new(&text).with_scale(text_size))
-                            .with_screen_position((x_uv, y_uv))
-                            .with_layout(
-                                Layout::default_single_line()
-                                    .h_align(HorizontalAlign::Left)
-                                    .v_align(VerticalAlign::Center),
-                            )
-                            .to_owned(),
-                    )
-                    .into_arc_ref_cell();
-
-                    let mut view = self.view.borrow_mut();
-                    match view.add_text_section(text_section.clone(), &text_font.name) {
-                        Err(_) => {
-                            view.add_font(text_font.clone()).unwrap();
-                            view.add_text_section(text_section.clone(), &text_font.name)
-                                .unwrap();
-                        }
-                        _ => {}
+            match (join, tip) {
+                (LineJoin::Round, _) => {
+                    if inner.is_some() {
+                        self.vertices_add_triangle([center, outer0, point], color);
+                        self.vertices_add_triangle([center, point, outer1], color);
                     }
+                    self.vertices_add_arc(point, radius, outer0, outer1, color);
                 }
+                (_, Some(tip)) => {
+                    self.vertices_add_triangle([center, outer0, tip], color);
+                    self.vertices_add_triangle([center, tip, outer1], color);
+                }
+                (_, None) => self.vertices_add_triangle([center, outer0, outer1], color),
             }
         }
 
-        //-----------
+        //-- caps
 
-        println!();
-    }
+        if cap == LineCap::Round {
+            let ends = [
+                (stroke[0], directions[0], -1.0),
+                (stroke[stroke.len() - 1], directions[last_segment], 1.0),
+            ];
 
-    fn display_function_graphs(&mut self) {
-        let mut points = Vec::new();
+            for ((point, radius, color), direction, sign) in ends {
+                let n = normal(direction);
+                let tip = offset(point, direction, sign * radius);
 
-        let sample_freq = 5000u32;
+                self.vertices_add_arc(point, radius, offset(point, n, radius), tip, color);
+                self.vertices_add_arc(point, radius, tip, offset(point, n, -radius), color);
+            }
+        }
+    }
 
-        let x_start = self.x_range.start;
-        let x_len = self.x_range.end - x_start;
+    /// Adds the circular sector around `center` between the points `from` and `to` on its
+    /// circle, taking the shorter way.
+    fn vertices_add_arc(
+        &mut self,
+        center: [f32; 2],
+        radius: f32,
+        from: [f32; 2],
+        to: [f32; 2],
+        color: RGBA,
+    ) {
+        let angle_from = (from[1] - center[1]).atan2(from[0] - center[0]);
+        let angle_to = (to[1] - center[1]).atan2(to[0] - center[0]);
+
+        let mut sweep = angle_to - angle_from;
+        if sweep > std::f32::consts::PI {
+            sweep -= std::f32::consts::TAU;
+        } else if sweep < -std::f32::consts::PI {
+            sweep += std::f32::consts::TAU;
+        }
 
-        let step = x_len / sample_freq as f64;
+        let steps = ((sweep.abs() / std::f32::consts::TAU * 32.0).ceil() as usize).max(1);
+
+        let mut last = from;
+        for step in 1..=steps {
+            let point = match step == steps {
+                true => to,
+                false => {
+                    let angle = angle_from + sweep * step as f32 / steps as f32;
+                    [
+                        center[0] + radius * angle.cos(),
+                        center[1] + radius * angle.sin(),
+                    ]
+                }
+            };
 
-        for index in 0..self.functions.len() {
-            let f = &self.functions[index];
+            self.vertices_add_triangle([center, last, point], color);
+            last = point;
+        }
+    }
 
-            points.clear();
-            for i in 0..=sample_freq {
-                let x = x_start + (step * i as f64);
-                let y = (f.function)(x, &self.parameter);
+    pub(crate) fn vertices_add_triangle(&mut self, corners: [[f32; 2]; 3], color: RGBA) {
+        let color = color.into();
 
-                let (sx, sy) = self.global_to_screen((x, y));
+        self.vertices.extend(corners.map(|corner| Vertex {
+            position: [corner[0], corner[1], 0.0],
+            color,
+        }));
+    }
 
-                points.push([sx, sy]);
+    /// Adds a polyline consisting of dashes of length `dash` (screen space).
+    fn vertices_add_dashed_polyline(
+        &mut self,
+        points: &[[f32; 2]],
+        width: f32,
+        color: RGBA,
+        dash: f32,
+    ) {
+        let mut distance = 0.0f32;
+
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+
+            let len = ((end[0] - start[0]).powi(2) + (end[1] - start[1]).powi(2)).sqrt();
+            if len == 0.0 || !len.is_finite() {
+                continue;
             }
 
-            self.vertices_add_polyline(&points, f.style.thickness, f.style.color);
-        }
-    }
-
-    fn vertices_add_polyline(&mut self, points: &[[f32; 2]], width: f32, color: RGBA) {
-        let mut last_point = None;
-        for point in points {
-            self.vertices_add_circle(*point, width / 2.0, color, 16);
+            let point_at = |t: f32| {
+                [
+                    start[0] + (end[0] - start[0]) * t / len,
+                    start[1] + (end[1] - start[1]) * t / len,
+                ]
+            };
+
+            let mut t = 0.0;
+            while t < len {
+                let dash_index = (distance / dash).floor();
+                let next_boundary = (dash_index + 1.0) * dash;
+                let step = (next_boundary - distance).max(dash * 0.001).min(len - t);
+
+                if dash_index as u64 % 2 == 0 {
+                    self.vertices_add_line(point_at(t), point_at(t + step), width, color);
+                }
 
-            if let Some(last_point) = last_point {
-                self.vertices_add_line(last_point, *point, width, color);
+                t += step;
+                distance += step;
             }
-
-            last_point = Some(*point);
         }
     }
 
     fn vertices_add_line(&mut self, end1: [f32; 2], end2: [f32; 2], width: f32, color: RGBA) {
-        let color = color.into();
+        self.vertices_add_varying_line(end1, end2, width, width, color, color);
+    }
 
-        let view = &mut self.view.as_ref().borrow_mut();
+    /// Adds a segment between circles of diameter `width1` and `width2` around its ends,
+    /// touching both of them tangentially.
+    fn vertices_add_varying_line(
+        &mut self,
+        end1: [f32; 2],
+        end2: [f32; 2],
+        width1: f32,
+        width2: f32,
+        color1: RGBA,
+        color2: RGBA,
+    ) {
+        let (color1, color2) = (color1.into(), color2.into());
+
+        let direction = [end2[0] - end1[0], end2[1] - end1[1]];
+        let len = (direction[0] * direction[0] + direction[1] * direction[1]).sqrt();
+        let (radius1, radius2) = (width1 / 2.0, width2 / 2.0);
+
+        // one circle contains the other, there is no outer tangent
+        if !(len > (radius1 - radius2).abs()) {
+            return;
+        }
 
-        let normal = [end2[1] - end1[1], -(end2[0] - end1[0])];
-        let normal_len = (normal[0] * normal[0] + normal[1] * normal[1]).sqrt();
-        let normal_norm = [normal[0] / normal_len, normal[1] / normal_len];
-        let normal_width = [normal_norm[0] * width, normal_norm[1] * width];
+        let direction_norm = [direction[0] / len, direction[1] / len];
+        let normal_norm = [direction_norm[1], -direction_norm[0]];
+
+        // the tangent points are rotated towards the smaller circle
+        let sin = (radius1 - radius2) / len;
+        let cos = (1.0 - sin * sin).sqrt();
+        let offset = |sign: f32| {
+            [
+                normal_norm[0] * cos * sign + direction_norm[0] * sin,
+                normal_norm[1] * cos * sign + direction_norm[1] * sin,
+            ]
+        };
+        let (offset1, offset2) = (offset(1.0), offset(-1.0));
 
         let corner11 = [
-            end1[0] + normal_width[0] / 2.0,
-            end1[1] + normal_width[1] / 2.0,
+            end1[0] + offset1[0] * radius1,
+            end1[1] + offset1[1] * radius1,
         ];
         let corner12 = [
-            end1[0] - normal_width[0] / 2.0,
-            end1[1] - normal_width[1] / 2.0,
+            end1[0] + offset2[0] * radius1,
+            end1[1] + offset2[1] * radius1,
         ];
         let corner21 = [
-            end2[0] + normal_width[0] / 2.0,
-            end2[1] + normal_width[1] / 2.0,
+            end2[0] + offset1[0] * radius2,
+            end2[1] + offset1[1] * radius2,
         ];
         let corner22 = [
-            end2[0] - normal_width[0] / 2.0,
-            end2[1] - normal_width[1] / 2.0,
+            end2[0] + offset2[0] * radius2,
+            end2[1] + offset2[1] * radius2,
         ];
 
-        view.append_render_vertices(&mut vec![
+        self.vertices.extend_from_slice(&[
             Vertex {
                 position: [corner11[0], corner11[1], 0.0],
-                color,
+                color: color1,
             },
             Vertex {
                 position: [corner12[0], corner12[1], 0.0],
-                color,
+                color: color1,
             },
             Vertex {
                 position: [corner21[0], corner21[1], 0.0],
-                color,
+                color: color2,
             },
             Vertex {
                 position: [corner12[0], corner12[1], 0.0],
-                color,
+                color: color1,
             },
             Vertex {
                 position: [corner21[0], corner21[1], 0.0],
-                color,
+                color: color2,
             },
             Vertex {
                 position: [corner22[0], corner22[1], 0.0],
+                color: color2,
+            },
+        ]);
+    }
+
+    /// Fills the area between the polyline `points` (sorted by x) and the polyline with
+    /// the y values `baselines` at the same x (screen space).
+    fn vertices_add_area_fill(&mut self, points: &[[f32; 2]], baselines: &[f32], fill: FillStyle) {
+        // everything outside of the view is cut, which also bounds the number of hatch lines
+        const LIMIT: f32 = 1.1;
+
+        // (x, curve y, baseline y), columns whose top and bottom are linear, split where the
+        // curve crosses the baseline
+        let mut columns: Vec<([f32; 3], [f32; 3])> = Vec::new();
+        for (pair, baseline) in points.windows(2).zip(baselines.windows(2)) {
+            let start = [pair[0][0], pair[0][1], baseline[0]];
+            let end = [pair[1][0], pair[1][1], baseline[1]];
+            let (start_side, end_side) = (start[1] - start[2], end[1] - end[2]);
+
+            if start_side * end_side < 0.0 {
+                let t = start_side / (start_side - end_side);
+                let y = start[1] + (end[1] - start[1]) * t;
+                let crossing = [start[0] + (end[0] - start[0]) * t, y, y];
+
+                columns.push((start, crossing));
+                columns.push((crossing, end));
+            } else {
+                columns.push((start, end));
+            }
+        }
+
+        // (x, bottom y, top y)
+        let columns: Vec<[[f32; 3]; 2]> = columns
+            .into_iter()
+            .map(|(start, end)| {
+                let bounds = |[x, y, baseline]: [f32; 3]| {
+                    let (y, baseline) = (y.clamp(-LIMIT, LIMIT), baseline.clamp(-LIMIT, LIMIT));
+                    [x, y.min(baseline), y.max(baseline)]
+                };
+                [bounds(start), bounds(end)]
+            })
+            .collect();
+
+        match fill.pattern {
+            FillPattern::Solid => {
+                for [start, end] in columns {
+                    self.vertices_add_quad(
+                        [
+                            [start[0], start[1]],
+                            [start[0], start[2]],
+                            [end[0], end[2]],
+                            [end[0], end[1]],
+                        ],
+                        fill.color,
+                    );
+                }
+            }
+            FillPattern::Hatch {
+                angle,
+                spacing,
+                thickness,
+            } => self.vertices_add_hatch(&columns, angle, spacing, thickness, fill.color),
+            FillPattern::CrossHatch {
+                angle,
+                spacing,
+                thickness,
+            } => {
+                self.vertices_add_hatch(&columns, angle, spacing, thickness, fill.color);
+                self.vertices_add_hatch(&columns, angle + 90.0, spacing, thickness, fill.color);
+            }
+            FillPattern::Dots { spacing, radius } => {
+                self.vertices_add_dots(&columns, spacing, radius, fill.color)
+            }
+        }
+    }
+
+    /// Pixels per screen space unit in x and y. These are multiview pixels, so pixel sizes
+    /// don't depend on the render scale of the view.
+    pub(crate) fn pixel_scale(&self) -> (f32, f32) {
+        let view = self.view.borrow();
+        match view.texture_size() {
+            Some((width, height)) if width > 0 && height > 0 => {
+                let density = view.pixel_density();
+                (width as f32 / 2.0 / density, height as f32 / 2.0 / density)
+            }
+            _ => (1.0, 1.0),
+        }
+    }
+
+    /// Adds parallel lines clipped to the `columns` of `vertices_add_area_fill`.
+    ///
+    /// The lines are laid out in pixel space so spacing and angle don't depend on the aspect ratio.
+    fn vertices_add_hatch(
+        &mut self,
+        columns: &[[[f32; 3]; 2]],
+        angle: f32,
+        spacing: f32,
+        thickness: f32,
+        color: RGBA,
+    ) {
+        if !spacing.is_finite() || spacing <= 0.0 || columns.is_empty() {
+            return;
+        }
+
+        let (scale_x, scale_y) = self.pixel_scale();
+        let to_pixel = |[x, bottom, top]: [f32; 3]| [x * scale_x, bottom * scale_y, top * scale_y];
+
+        // lines are `normal . p = k * spacing`
+        let (sin, cos) = Radians::from(Degrees(angle)).sin_cos();
+        let normal = [-sin, cos];
+
+        // line index to its pieces
+        let mut segments: BTreeMap<i64, Vec<([f32; 2], [f32; 2])>> = BTreeMap::new();
+
+        for [start, end] in columns.iter().copied().map(|column| column.map(to_pixel)) {
+            let (x0, x1) = (start[0], end[0]);
+
+            let corners = [[x0, start[1]], [x0, start[2]], [x1, end[1]], [x1, end[2]]];
+            let projections = corners.map(|p| normal[0] * p[0] + normal[1] * p[1]);
+            let k_start =
+                (projections.iter().copied().fold(f32::INFINITY, f32::min) / spacing).ceil();
+            let k_end = (projections
+                .iter()
+                .copied()
+                .fold(f32::NEG_INFINITY, f32::max)
+                / spacing)
+                .floor();
+
+            for k in k_start as i64..=k_end as i64 {
+                let c = k as f32 * spacing;
+
+                let segment = match normal[1].abs() > 1e-6 && (x1 - x0).abs() > 1e-6 {
+                    // line `y = a + b x` between the linear bottom and top of the column
+                    true => {
+                        let (a, b) = (c / normal[1], -normal[0] / normal[1]);
+                        let slope = |y0: f32, y1: f32| (y1 - y0) / (x1 - x0);
+
+                        let mut range = (x0.min(x1), x0.max(x1));
+                        for (y0, y1, sign) in [(start[1], end[1], 1.0), (start[2], end[2], -1.0)] {
+                            // sign * (a + b x - (y0 + m (x - x0))) >= 0
+                            let m = slope(y0, y1);
+                            let offset = sign * (a - y0 + m * x0);
+                            let factor = sign * (b - m);
+
+                            if approx_zero(factor, 1e-9) {
+                                if offset < 0.0 {
+                                    range = (1.0, 0.0);
+                                }
+                            } else if factor > 0.0 {
+                                range.0 = range.0.max(-offset / factor);
+                            } else {
+                                range.1 = range.1.min(-offset / factor);
+                            }
+                        }
+
+                        (range.0 < range.1)
+                            .then_some(([range.0, a + b * range.0], [range.1, a + b * range.1]))
+                    }
+                    // vertical line
+                    false if normal[0].abs() > 1e-6 => {
+                        let x = c / normal[0];
+                        let t = (x - x0) / (x1 - x0);
+
+                        (x0.min(x1) <= x && x <= x0.max(x1) && t.is_finite()).then(|| {
+                            let bottom = start[1] + (end[1] - start[1]) * t;
+                            let top = start[2] + (end[2] - start[2]) * t;
+                            ([x, bottom], [x, top])
+                        })
+                    }
+                    false => None,
+                };
+
+                let Some(segment) = segment else {
+                    continue;
+                };
+
+                // lines continue through neighbouring columns, merge them to save vertices
+                let line = segments.entry(k).or_default();
+                match line.last_mut() {
+                    Some(last) if approx_eq_points(last.1, segment.0, 1e-3) => last.1 = segment.1,
+                    _ => line.push(segment),
+                }
+            }
+        }
+
+        let half = [normal[0] * thickness / 2.0, normal[1] * thickness / 2.0];
+        let to_screen = |p: [f32; 2]| [p[0] / scale_x, p[1] / scale_y];
+
+        for (start, end) in segments.into_values().flatten() {
+            self.vertices_add_quad(
+                [
+                    to_screen([start[0] + half[0], start[1] + half[1]]),
+                    to_screen([start[0] - half[0], start[1] - half[1]]),
+                    to_screen([end[0] - half[0], end[1] - half[1]]),
+                    to_screen([end[0] + half[0], end[1] + half[1]]),
+                ],
+                color,
+            );
+        }
+    }
+
+    /// Adds a grid of dots clipped to the `columns` of `vertices_add_area_fill`.
+    fn vertices_add_dots(
+        &mut self,
+        columns: &[[[f32; 3]; 2]],
+        spacing: f32,
+        radius: f32,
+        color: RGBA,
+    ) {
+        if !spacing.is_finite() || spacing <= 0.0 {
+            return;
+        }
+
+        let (scale_x, scale_y) = self.pixel_scale();
+
+        for [start, end] in columns.iter().copied() {
+            let (x0, x1) = (start[0] * scale_x, end[0] * scale_x);
+            if approx_eq(x0, x1, 1e-6) {
+                continue;
+            }
+
+            // half open, so dots on a column border are only added once
+            let i_start = (x0.min(x1) / spacing).ceil() as i64;
+            let i_end = (x0.max(x1) / spacing).ceil() as i64;
+
+            for i in i_start..i_end {
+                let x = i as f32 * spacing;
+                let t = (x - x0) / (x1 - x0);
+                let bottom = (start[1] + (end[1] - start[1]) * t) * scale_y;
+                let top = (start[2] + (end[2] - start[2]) * t) * scale_y;
+
+                for j in (bottom / spacing).ceil() as i64..=(top / spacing).floor() as i64 {
+                    let center = [x / scale_x, j as f32 * spacing / scale_y];
+                    self.vertices_add_circle(center, radius / scale_x, color, 8);
+                }
+            }
+        }
+    }
+
+    /// Adds the quad `corners[0..4]` as two triangles.
+    fn vertices_add_quad(&mut self, corners: [[f32; 2]; 4], color: RGBA) {
+        let color = color.into();
+
+        for index in [0, 1, 2, 0, 2, 3] {
+            self.vertices.push(Vertex {
+                position: [corners[index][0], corners[index][1], 0.0],
+                color,
+            });
+        }
+    }
+
+    pub(crate) fn vertices_add_rect(&mut self, corner1: [f32; 2], corner2: [f32; 2], color: RGBA) {
+        let color = color.into();
+
+        self.vertices.extend_from_slice(&[
+            Vertex {
+                position: [corner1[0], corner1[1], 0.0],
+                color,
+            },
+            Vertex {
+                position: [corner2[0], corner1[1], 0.0],
+                color,
+            },
+            Vertex {
+                position: [corner2[0], corner2[1], 0.0],
+                color,
+            },
+            Vertex {
+                position: [corner1[0], corner1[1], 0.0],
+                color,
+            },
+            Vertex {
+                position: [corner2[0], corner2[1], 0.0],
+                color,
+            },
+            Vertex {
+                position: [corner1[0], corner2[1], 0.0],
                 color,
             },
         ]);
     }
 
-    fn vertices_add_circle(&mut self, center: [f32; 2], radius: f32, color: RGBA, resolution: u8) {
+    /// Fan of `resolution` triangles, the points get scaled from a cached unit circle.
+    ///
+    /// Measured with `benches/scatter.rs`.
+    pub(crate) fn vertices_add_circle(
+        &mut self,
+        center: [f32; 2],
+        radius: f32,
+        color: RGBA,
+        resolution: u8,
+    ) {
         let color = color.into();
+        let unit_circle = unit_circle(resolution.max(3));
+
+        let center_vertex = Vertex {
+            position: [center[0], center[1], 0.0],
+            color,
+        };
+        let vertex_at = |[cos, sin]: [f32; 2]| Vertex {
+            position: [center[0] + radius * cos, center[1] + radius * sin, 0.0],
+            color,
+        };
+
+        let first = vertex_at(unit_circle[0]);
+        let mut last = first;
+
+        self.vertices.reserve(unit_circle.len() * 3);
+        for point in &unit_circle[1..] {
+            let vertex = vertex_at(*point);
+            self.vertices
+                .extend_from_slice(&[last, center_vertex, vertex]);
+            last = vertex;
+        }
+        self.vertices
+            .extend_from_slice(&[last, center_vertex, first]);
+    }
+
+    /// Draws `shape` centered at `center`, `radius` is the distance of its outermost points
+    /// in x direction. The shape is stretched in y so it keeps its proportions in pixels.
+    pub(crate) fn vertices_add_marker(
+        &mut self,
+        center: [f32; 2],
+        radius: f32,
+        shape: MarkerShape,
+        color: RGBA,
+    ) {
+        let (px, py) = self.pixel_scale();
+        let aspect = px / py;
+
+        // offsets in x units, so the shapes can be laid out as if pixels were square
+        let point = |[dx, dy]: [f32; 2]| [center[0] + dx, center[1] + dy * aspect];
+        let polar = |angle: f32, distance: f32| {
+            let Radians(angle) = Degrees(angle).into();
+            point([distance * angle.cos(), distance * angle.sin()])
+        };
+        let quad = |canvas: &mut Self, corners: [[f32; 2]; 4]| {
+            canvas.vertices_add_triangle([corners[0], corners[1], corners[2]], color);
+            canvas.vertices_add_triangle([corners[0], corners[2], corners[3]], color);
+        };
+        // bar through the center along `angle`, for `Plus` and `Cross`
+        let bar = |canvas: &mut Self, angle: f32| {
+            let width = radius / 3.0;
+            let Radians(angle) = Degrees(angle).into();
+            let (dx, dy) = (radius * angle.cos(), radius * angle.sin());
+            let (nx, ny) = (-dy / radius * width / 2.0, dx / radius * width / 2.0);
+
+            quad(
+                canvas,
+                [
+                    point([-dx - nx, -dy - ny]),
+                    point([dx - nx, dy - ny]),
+                    point([dx + nx, dy + ny]),
+                    point([-dx + nx, -dy + ny]),
+                ],
+            );
+        };
+
+        match shape {
+            MarkerShape::Circle => {
+                let outline: Vec<[f32; 2]> = unit_circle(16)
+                    .iter()
+                    .map(|[cos, sin]| point([radius * cos, radius * sin]))
+                    .collect();
+
+                for pair in outline.windows(2) {
+                    self.vertices_add_triangle([center, pair[0], pair[1]], color);
+                }
+                self.vertices_add_triangle([center, outline[outline.len() - 1], outline[0]], color);
+            }
+            // the corners touch the circle of `radius`, like the tips of the other shapes
+            MarkerShape::Square => quad(
+                self,
+                [45.0, 135.0, 225.0, 315.0].map(|angle| polar(angle, radius)),
+            ),
+            MarkerShape::Diamond => quad(
+                self,
+                [0.0, 90.0, 180.0, 270.0].map(|angle| polar(angle, radius)),
+            ),
+            MarkerShape::Triangle => self.vertices_add_triangle(
+                [90.0, 210.0, 330.0].map(|angle| polar(angle, radius)),
+                color,
+            ),
+            MarkerShape::Plus => {
+                bar(self, 0.0);
+                bar(self, 90.0);
+            }
+            MarkerShape::Cross => {
+                bar(self, 45.0);
+                bar(self, 135.0);
+            }
+        }
+    }
 
-        let view = &mut self.view.as_ref().borrow_mut();
+    const ERROR_DEC_TO_F64: &'static str = "Error while trying to map BigDecimal to f64";
+}
+
+/// Per point colors of `graph_segments` (data space) for the gradient of `style`, which
+/// runs along the arclength of all segments together. `None` if it has a constant color.
+fn graph_segment_colors(
+    style: &GraphStyle,
+    graph_segments: &[Vec<(f64, f64)>],
+) -> Option<Vec<Vec<RGBA>>> {
+    let gradient = style.gradient.as_ref()?;
+
+    let total = graph_segments
+        .iter()
+        .flat_map(|segment| segment.windows(2))
+        .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+        .sum();
+
+    let mut arclength = 0.0;
+    Some(
+        graph_segments
+            .iter()
+            .map(|segment| gradient.segment_colors(segment, &mut arclength, total))
+            .collect(),
+    )
+}
 
-        let scale = u8::MAX as f32 / resolution as f32;
+/// Formats a value of the cursor readout with up to 4 decimal places (trailing zeros
+/// removed), or in scientific notation with 4 significant digits outside of `1e-3..1e5`.
+pub(crate) fn format_readout_value(value: f64) -> String {
+    let magnitude = value.abs();
+
+    match magnitude == 0.0 || (1e-3..1e5).contains(&magnitude) {
+        true => {
+            let text = format!("{:.4}", value);
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+        false => format!("{:.3e}", value),
+    }
+}
 
-        let mut last_point: Option<[f32; 2]> = None;
-        for i in (0..=resolution).chain([0].into_iter()) {
-            let index = i as f32 * scale;
+/// `[cos, sin]` of `resolution` evenly spaced angles, computed once per resolution.
+fn unit_circle(resolution: u8) -> &'static [[f32; 2]] {
+    static UNIT_CIRCLES: [OnceLock<Box<[[f32; 2]]>>; 256] = [const { OnceLock::new() }; 256];
 
-            let sin_cos = Self::CIRCLE_SIN_COS_LOOKUP[index as usize];
-            let sin = sin_cos[0];
-            let cos = sin_cos[1];
+    UNIT_CIRCLES[resolution as usize].get_or_init(|| {
+        (0..resolution)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / resolution as f32;
+                [angle.cos(), angle.sin()]
+            })
+            .collect()
+    })
+}
 
-            let x = center[0] + radius * cos;
-            let y = center[1] + radius * sin;
+fn normal(direction: [f32; 2]) -> [f32; 2] {
+    [-direction[1], direction[0]]
+}
 
-            let point = [x, y];
+fn offset(point: [f32; 2], vector: [f32; 2], scale: f32) -> [f32; 2] {
+    [point[0] + vector[0] * scale, point[1] + vector[1] * scale]
+}
 
-            if let Some(last_point) = last_point {
-                view.append_render_vertices(&mut vec![
-                    Vertex {
-                        position: [last_point[0], last_point[1], 0.0],
-                        color,
-                    },
-                    Vertex {
-                        position: [center[0], center[1], 0.0],
-                        color,
-                    },
-                    Vertex {
-                        position: [point[0], point[1], 0.0],
-                        color,
-                    },
-                ]);
+/// Intersection of the lines through `a` with direction `da` and through `b` with direction `db`,
+/// as distances along both directions.
+fn line_intersection(a: [f32; 2], da: [f32; 2], b: [f32; 2], db: [f32; 2]) -> Option<(f32, f32)> {
+    let denominator = da[0] * db[1] - da[1] * db[0];
+    if approx_zero(denominator, 1e-9) {
+        return None;
+    }
+
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let t = (dx * db[1] - dy * db[0]) / denominator;
+    let u = (dx * da[1] - dy * da[0]) / denominator;
+
+    Some((t, u))
+}
+
+/// Prepares the points of a translucent stroke so neighbouring segments can share their
+/// inner corner.
+///
+/// Nearly collinear points are dropped (deviating at most 1% of the width), the inner
+/// corner of a join can then reach beyond the short segments of a densely sampled curve.
+/// Short segments between two turns in the same direction (usually a sharp corner cut off
+/// by the sampling) get replaced by the intersection of their neighbours.
+fn simplify_translucent_stroke(stroke: Vec<([f32; 2], f32, RGBA)>) -> Vec<([f32; 2], f32, RGBA)> {
+    let direction = |from: [f32; 2], to: [f32; 2]| {
+        let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+        let len = (dx * dx + dy * dy).sqrt();
+        ([dx / len, dy / len], len)
+    };
+    let same_style =
+        |a: &([f32; 2], f32, RGBA), b: &([f32; 2], f32, RGBA)| a.1 == b.1 && a.2 == b.2;
+
+    //-- collinear points
+
+    let mut simplified: Vec<([f32; 2], f32, RGBA)> = Vec::with_capacity(stroke.len());
+    let mut skipped: Vec<[f32; 2]> = Vec::new();
+    for (i, current) in stroke.iter().enumerate() {
+        let (Some(anchor), Some(next)) = (simplified.last(), stroke.get(i + 1)) else {
+            simplified.push(*current);
+            continue;
+        };
+
+        let (d, len) = direction(anchor.0, next.0);
+        let tolerance = 0.01 * anchor.1;
+        let within = |point: &[f32; 2]| {
+            let (dx, dy) = (point[0] - anchor.0[0], point[1] - anchor.0[1]);
+            let along = dx * d[0] + dy * d[1];
+            (dx * d[1] - dy * d[0]).abs() <= tolerance && (0.0..=len).contains(&along)
+        };
+
+        match same_style(anchor, current)
+            && same_style(current, next)
+            && within(&current.0)
+            && skipped.iter().all(within)
+        {
+            true => skipped.push(current.0),
+            false => {
+                simplified.push(*current);
+                skipped.clear();
+            }
+        }
+    }
+    let mut stroke = simplified;
+
+    //-- short corner segments
+
+    // distance of the inner corner from the joint along the segments, `tan` of half the turn
+    let inner_distance = |d0: [f32; 2], d1: [f32; 2], radius: f32| {
+        let cross = d0[0] * d1[1] - d0[1] * d1[0];
+        let dot = d0[0] * d1[0] + d0[1] * d1[1];
+        radius * cross.abs() / (1.0 + dot)
+    };
+
+    let mut k = 1;
+    while k + 2 < stroke.len() {
+        let (before, a, b, after) = (stroke[k - 1], stroke[k], stroke[k + 1], stroke[k + 2]);
+        let (d0, len0) = direction(before.0, a.0);
+        let (d1, len) = direction(a.0, b.0);
+        let (d2, len2) = direction(b.0, after.0);
+
+        let turn1 = d0[0] * d1[1] - d0[1] * d1[0];
+        let turn2 = d1[0] * d2[1] - d1[1] * d2[0];
+        let overlapping = inner_distance(d0, d1, a.1) > len0.min(len) / 2.0
+            || inner_distance(d1, d2, b.1) > len.min(len2) / 2.0;
+        let radius = a.1.max(b.1);
+
+        let corner = (overlapping && len < 2.0 * radius && turn1 * turn2 > 0.0)
+            .then(|| line_intersection(a.0, d0, b.0, d2))
+            .flatten()
+            // the corner has to lie ahead of `a` and behind `b`, close to the short segment
+            .filter(|(t, u)| *t >= 0.0 && *u <= 0.0 && *t <= 2.0 * radius);
+
+        match corner {
+            Some((t, _)) => {
+                stroke[k].0 = offset(a.0, d0, t);
+                stroke.remove(k + 1);
             }
+            None => k += 1,
+        }
+    }
+
+    stroke
+}
 
-            last_point = Some(point);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64), tolerance: f64) {
+        assert!(
+            (a.0 - b.0).abs() <= tolerance && (a.1 - b.1).abs() <= tolerance,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn data_view_round_trip_far_from_origin() {
+        let mut canvas: GPUCanvas2D<()> = GPUCanvas2D::new(GPUViewFrame::Whole);
+        canvas.set_range(1.7e9..1.7e9 + 60.0, -1.0..1.0);
+
+        let data = (1.7e9 + 15.0, 0.5);
+        let view = canvas.data_to_view(data);
+        assert_close((view.0 as f64, view.1 as f64), (-0.5, 0.5), 1e-6);
+        assert_close(canvas.view_to_data(view), data, 1e-4);
+    }
+
+    #[test]
+    fn data_view_round_trip_on_log_scales() {
+        let mut canvas: GPUCanvas2D<()> = GPUCanvas2D::new(GPUViewFrame::Whole);
+        canvas.set_x_scale(AxisScale::Log10);
+        canvas.set_y_scale(AxisScale::Ln);
+        canvas.set_range(1.0..1000.0, 1.0..std::f64::consts::E.powi(2));
+
+        let view = canvas.data_to_view((10f64.powf(1.5), std::f64::consts::E));
+        assert_close((view.0 as f64, view.1 as f64), (0.0, 0.0), 1e-6);
+
+        for data in [(2.0, 1.5), (999.0, 7.0), (1.0, 1.0)] {
+            assert_close(canvas.view_to_data(canvas.data_to_view(data)), data, 1e-4);
         }
     }
 
-    const ERROR_DEC_TO_F64: &'static str = "Error while trying to map BigDecimal to f64";
+    #[test]
+    fn data_pixel_round_trip_with_frame_offset() {
+        let multiview = GPUMultiView::with_size(200, 100);
+        let mut canvas: GPUCanvas2D<()> = GPUCanvas2D::new(GPUViewFrame::UpperRightQuad);
+        canvas.set_range(0.0..10.0, 0.0..10.0);
+
+        assert_close(
+            canvas.data_to_pixel((0.0, 0.0), &multiview).unwrap(),
+            (100.0, 50.0),
+            1e-3,
+        );
+        assert_close(
+            canvas.data_to_pixel((10.0, 10.0), &multiview).unwrap(),
+            (200.0, 0.0),
+            1e-3,
+        );
+
+        let data = (2.5, 7.5);
+        let pixel = canvas.data_to_pixel(data, &multiview).unwrap();
+        assert_close(pixel, (125.0, 12.5), 1e-3);
+        assert_close(canvas.pixel_to_data(pixel, &multiview).unwrap(), data, 1e-4);
+
+        let view = canvas.pixel_to_view(pixel, &multiview).unwrap();
+        assert_close((view.0 as f64, view.1 as f64), (-0.5, 0.5), 1e-6);
+    }
+
+    fn stroke_vertex_count(points: &[[f32; 2]], join: LineJoin, cap: LineCap) -> usize {
+        let mut canvas: GPUCanvas2D<()> = GPUCanvas2D::new(GPUViewFrame::Whole);
+        let widths = vec![0.02; points.len()];
+        let colors = vec![RGBA::new(0, 0, 0, 255); points.len()];
+        canvas.vertices_add_stroke(points, &widths, &colors, join, cap);
+        canvas.vertices.len()
+    }
 
-    const CIRCLE_SIN_COS_LOOKUP: [[f32; 2]; 256] = [
-        [0.0, 1.0],
-        [0.024541229, 0.9996988],
-        [0.049067676, 0.99879545],
-        [0.07356457, 0.99729043],
-        [0.09801714, 0.9951847],
-        [0.12241068, 0.99247956],
-        [0.14673047, 0.9891765],
-        [0.1709619, 0.98527765],
-        [0.19509032, 0.98078525],
-        [0.21910124, 0.9757021],
-        [0.2429802, 0.97003126],
-        [0.26671278, 0.96377605],
-        [0.2902847, 0.95694035],
-        [0.31368175, 0.94952816],
-        [0.33688986, 0.94154406],
-        [0.35989505, 0.9329928],
-        [0.38268346, 0.9238795],
-        [0.40524134, 0.9142097],
-        [0.42755508, 0.9039893],
-        [0.44961134, 0.8932243],
-        [0.47139674, 0.88192123],
-        [0.49289823, 0.87008697],
-        [0.51410276, 0.8577286],
-        [0.5349976, 0.8448536],
-        [0.55557024, 0.8314696],
-        [0.5758082, 0.8175848],
-        [0.5956993, 0.8032075],
-        [0.61523163, 0.7883464],
-        [0.63439333, 0.77301043],
-        [0.65317285, 0.7572088],
-        [0.671559, 0.7409511],
-        [0.68954057, 0.7242471],
-        [0.70710677, 0.70710677],
-        [0.7242471, 0.6895405],
-        [0.7409512, 0.6715589],
-        [0.7572089, 0.6531728],
-        [0.77301043, 0.6343933],
-        [0.7883464, 0.6152316],
-        [0.8032075, 0.5956993],
-        [0.8175848, 0.57580817],
-        [0.83146966, 0.5555702],
-        [0.8448536, 0.53499764],
-        [0.85772866, 0.5141027],
-        [0.87008697, 0.49289817],
-        [0.8819213, 0.47139665],
-        [0.8932243, 0.4496113],
-        [0.9039893, 0.4275551],
-        [0.9142098, 0.40524128],
-        [0.9238795, 0.38268343],
-        [0.9329928, 0.35989496],
-        [0.94154406, 0.33688983],
-        [0.9495282, 0.31368166],
-        [0.95694035, 0.29028463],
-        [0.96377605, 0.26671275],
-        [0.97003126, 0.24298012],
-        [0.9757021, 0.21910124],
-        [0.9807853, 0.19509023],
-        [0.98527765, 0.17096186],
-        [0.9891765, 0.1467305],
-        [0.99247956, 0.122410625],
-        [0.9951847, 0.098017134],
-        [0.99729043, 0.07356449],
-        [0.99879545, 0.04906765],
-        [0.9996988, 0.024541136],
-        [1.0, -4.371139e-8],
-        [0.9996988, -0.024541223],
-        [0.99879545, -0.04906774],
-        [0.99729043, -0.073564574],
-        [0.9951847, -0.09801722],
-        [0.9924795, -0.12241071],
-        [0.9891765, -0.14673057],
-        [0.98527765, -0.17096195],
-        [0.98078525, -0.19509032],
-        [0.9757021, -0.21910131],
-        [0.97003126, -0.2429802],
-        [0.96377605, -0.26671284],
-        [0.9569403, -0.29028472],
-        [0.94952816, -0.31368172],
-        [0.94154406, -0.33688992],
-        [0.9329928, -0.35989505],
-        [0.9238795, -0.38268352],
-        [0.9142097, -0.40524134],
-        [0.9039893, -0.42755508],
-        [0.8932243, -0.44961137],
-        [0.88192123, -0.47139683],
-        [0.870087, -0.49289817],
-        [0.8577286, -0.51410276],
-        [0.8448535, -0.5349977],
-        [0.83146954, -0.55557036],
-        [0.8175848, -0.57580817],
-        [0.8032075, -0.59569937],
-        [0.78834635, -0.6152317],
-        [0.7730105, -0.6343933],
-        [0.7572088, -0.65317285],
-        [0.74095106, -0.67155904],
-        [0.724247, -0.6895407],
-        [0.70710677, -0.70710677],
-        [0.6895405, -0.72424716],
-        [0.67155886, -0.74095124],
-        [0.65317285, -0.7572088],
-        [0.6343933, -0.7730105],
-        [0.6152315, -0.78834647],
-        [0.59569913, -0.80320764],
-        [0.57580817, -0.8175848],
-        [0.5555702, -0.83146966],
-        [0.53499746, -0.84485364],
-        [0.51410276, -0.8577286],
-        [0.49289814, -0.870087],
-        [0.47139663, -0.88192135],
-        [0.44961137, -0.8932243],
-        [0.42755505, -0.9039893],
-        [0.40524122, -0.9142098],
-        [0.38268328, -0.9238796],
-        [0.35989505, -0.9329928],
-        [0.3368898, -0.9415441],
-        [0.3136816, -0.9495282],
-        [0.29028472, -0.95694035],
-        [0.26671273, -0.96377605],
-        [0.24298008, -0.97003126],
-        [0.21910107, -0.97570217],
-        [0.19509031, -0.9807853],
-        [0.17096181, -0.98527765],
-        [0.14673033, -0.9891765],
-        [0.1224107, -0.9924795],
-        [0.0980171, -0.9951847],
-        [0.07356445, -0.9972905],
-        [0.049067486, -0.99879545],
-        [0.02454121, -0.9996988],
-        [-8.742278e-8, -1.0],
-        [-0.024541385, -0.9996988],
-        [-0.04906766, -0.99879545],
-        [-0.07356462, -0.99729043],
-        [-0.09801727, -0.9951847],
-        [-0.12241087, -0.9924795],
-        [-0.1467305, -0.9891765],
-        [-0.17096199, -0.98527765],
-        [-0.19509049, -0.98078525],
-        [-0.21910124, -0.9757021],
-        [-0.24298024, -0.97003126],
-        [-0.2667129, -0.96377605],
-        [-0.29028487, -0.9569403],
-        [-0.31368178, -0.94952816],
-        [-0.33688995, -0.94154406],
-        [-0.3598952, -0.93299276],
-        [-0.38268343, -0.9238795],
-        [-0.4052414, -0.9142097],
-        [-0.42755523, -0.90398926],
-        [-0.4496115, -0.8932242],
-        [-0.47139677, -0.88192123],
-        [-0.4928983, -0.8700869],
-        [-0.5141029, -0.85772854],
-        [-0.53499764, -0.8448536],
-        [-0.5555703, -0.83146954],
-        [-0.57580835, -0.8175847],
-        [-0.5956993, -0.8032075],
-        [-0.61523163, -0.7883464],
-        [-0.6343934, -0.7730104],
-        [-0.65317297, -0.7572087],
-        [-0.671559, -0.7409511],
-        [-0.6895406, -0.72424704],
-        [-0.7071069, -0.70710665],
-        [-0.7242471, -0.68954057],
-        [-0.7409512, -0.6715589],
-        [-0.75720876, -0.6531729],
-        [-0.77301043, -0.63439333],
-        [-0.78834647, -0.6152316],
-        [-0.8032076, -0.5956992],
-        [-0.81758493, -0.57580805],
-        [-0.8314698, -0.55557],
-        [-0.84485376, -0.53499734],
-        [-0.85772854, -0.5141028],
-        [-0.87008697, -0.4928982],
-        [-0.8819213, -0.47139668],
-        [-0.89322436, -0.44961122],
-        [-0.9039894, -0.42755494],
-        [-0.91420984, -0.40524107],
-        [-0.9238797, -0.38268313],
-        [-0.93299276, -0.3598951],
-        [-0.94154406, -0.33688986],
-        [-0.9495282, -0.3136817],
-        [-0.95694035, -0.29028454],
-        [-0.9637761, -0.26671258],
-        [-0.9700313, -0.24297991],
-        [-0.9757022, -0.2191009],
-        [-0.98078525, -0.19509038],
-        [-0.98527765, -0.17096189],
-        [-0.9891765, -0.14673041],
-        [-0.99247956, -0.122410536],
-        [-0.9951847, -0.09801693],
-        [-0.9972905, -0.07356428],
-        [-0.99879545, -0.049067326],
-        [-0.9996988, -0.024541287],
-        [-1.0, 1.1924881e-8],
-        [-0.9996988, 0.02454131],
-        [-0.99879545, 0.049067825],
-        [-0.99729043, 0.07356478],
-        [-0.9951847, 0.09801743],
-        [-0.9924795, 0.122411035],
-        [-0.9891765, 0.14673042],
-        [-0.98527765, 0.17096192],
-        [-0.98078525, 0.19509041],
-        [-0.9757021, 0.2191014],
-        [-0.9700312, 0.2429804],
-        [-0.963776, 0.26671305],
-        [-0.95694023, 0.29028502],
-        [-0.9495282, 0.3136817],
-        [-0.94154406, 0.3368899],
-        [-0.93299276, 0.35989514],
-        [-0.92387944, 0.3826836],
-        [-0.91420966, 0.40524155],
-        [-0.90398914, 0.42755538],
-        [-0.8932241, 0.44961166],
-        [-0.8819213, 0.4713967],
-        [-0.87008697, 0.49289823],
-        [-0.85772854, 0.5141028],
-        [-0.84485346, 0.53499776],
-        [-0.8314695, 0.5555704],
-        [-0.81758463, 0.57580847],
-        [-0.8032076, 0.59569925],
-        [-0.7883464, 0.6152316],
-        [-0.77301043, 0.63439333],
-        [-0.75720876, 0.6531729],
-        [-0.740951, 0.6715591],
-        [-0.7242469, 0.68954074],
-        [-0.70710653, 0.707107],
-        [-0.6895406, 0.72424704],
-        [-0.671559, 0.7409511],
-        [-0.6531728, 0.7572089],
-        [-0.63439316, 0.77301055],
-        [-0.61523145, 0.7883465],
-        [-0.5956991, 0.8032077],
-        [-0.5758079, 0.817585],
-        [-0.5555703, 0.8314696],
-        [-0.53499764, 0.8448536],
-        [-0.5141027, 0.85772866],
-        [-0.49289808, 0.8700871],
-        [-0.47139654, 0.88192135],
-        [-0.44961107, 0.8932244],
-        [-0.4275548, 0.90398943],
-        [-0.40524137, 0.9142097],
-        [-0.38268343, 0.92387956],
-        [-0.35989496, 0.9329928],
-        [-0.3368897, 0.9415441],
-        [-0.31368154, 0.9495283],
-        [-0.2902844, 0.9569404],
-        [-0.2667124, 0.9637762],
-        [-0.24298023, 0.97003126],
-        [-0.21910122, 0.9757021],
-        [-0.19509023, 0.9807853],
-        [-0.17096172, 0.98527765],
-        [-0.14673024, 0.9891766],
-        [-0.12241037, 0.99247956],
-        [-0.09801677, 0.9951848],
-        [-0.0735646, 0.99729043],
-        [-0.04906764, 0.99879545],
-        [-0.024541123, 0.9996988],
-    ];
+    #[test]
+    fn stroke_joins_add_geometry_only_at_corners() {
+        // right angle turns at every inner point
+        let zigzag: Vec<[f32; 2]> = (0..100)
+            .map(|i| [i as f32 * 0.01, (i % 2) as f32 * 0.01])
+            .collect();
+        let segments = zigzag.len() - 1;
+        let corners = zigzag.len() - 2;
+
+        let bevel = stroke_vertex_count(&zigzag, LineJoin::Bevel, LineCap::Butt);
+        assert_eq!(bevel, 6 * segments + 3 * corners);
+        let miter = stroke_vertex_count(&zigzag, LineJoin::MITER, LineCap::Butt);
+        assert_eq!(miter, 6 * segments + 6 * corners);
+
+        // collinear points need no join at all
+        let straight: Vec<[f32; 2]> = (0..100).map(|i| [i as f32 * 0.01, 0.0]).collect();
+        let round = stroke_vertex_count(&straight, LineJoin::Round, LineCap::Butt);
+        assert_eq!(round, 6 * segments);
+    }
+
+    #[test]
+    fn stroke_round_joins_are_cheaper_than_circles() {
+        let zigzag: Vec<[f32; 2]> = (0..100)
+            .map(|i| [i as f32 * 0.01, (i % 2) as f32 * 0.01])
+            .collect();
+
+        // a 16 segment circle at every point was 48 vertices per point on top of the quads
+        let circles = 6 * (zigzag.len() - 1) + 48 * zigzag.len();
+        let round = stroke_vertex_count(&zigzag, LineJoin::Round, LineCap::Round);
+        assert!(round * 3 < circles * 2, "{round} vertices");
+    }
 }
@@ -0,0 +1,179 @@
+use anyhow::Context;
+
+/// Configuration of the adapter that is requested from a `wgpu::Instance`.
+#[derive(Debug, Clone)]
+pub struct AdapterConfig {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    /// Retry with a software (fallback) adapter if no hardware adapter could be found.
+    pub allow_fallback: bool,
+    /// Skip hardware adapters entirely and only request a software (fallback) adapter.
+    pub force_fallback: bool,
+}
+
+impl Default for AdapterConfig {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            allow_fallback: true,
+            force_fallback: false,
+        }
+    }
+}
+
+impl AdapterConfig {
+    pub fn create_instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    pub async fn request_adapter(
+        &self,
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+    ) -> anyhow::Result<wgpu::Adapter> {
+        let request = |force_fallback_adapter: bool| {
+            instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.power_preference,
+                compatible_surface,
+                force_fallback_adapter,
+            })
+        };
+
+        let adapter = match self.force_fallback {
+            true => request(true).await,
+            false => match request(false).await {
+                Some(adapter) => Some(adapter),
+                None if self.allow_fallback => request(true).await,
+                None => None,
+            },
+        };
+
+        adapter.context("GPU Adapter Request Failed.")
+    }
+}
+
+/// Set of device features split into features without which a canvas can not work at all
+/// and features which only enable additional functionality.
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureRequest {
+    pub required: wgpu::Features,
+    pub optional: wgpu::Features,
+}
+
+impl FeatureRequest {
+    pub const NONE: Self = Self {
+        required: wgpu::Features::empty(),
+        optional: wgpu::Features::empty(),
+    };
+
+    /// Features used by `GPUView` and `GPUCanvas2D`.
+    ///
+    /// `POLYGON_MODE_LINE`/`POLYGON_MODE_POINT` are only needed for wireframe rendering
+    /// and get disabled on adapters which do not support them.
+    pub const CANVAS_2D: Self = Self {
+        required: wgpu::Features::empty(),
+        optional: wgpu::Features::POLYGON_MODE_LINE
+            .union(wgpu::Features::POLYGON_MODE_POINT)
+            .union(wgpu::Features::CLEAR_TEXTURE),
+    };
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            required: self.required | other.required,
+            optional: self.optional | other.optional,
+        }
+    }
+}
+
+/// Features and adapter information of a created device.
+#[derive(Debug, Clone)]
+pub struct DeviceCapabilities {
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub adapter_info: wgpu::AdapterInfo,
+}
+
+impl DeviceCapabilities {
+    pub fn from_device(device: &wgpu::Device, adapter: &wgpu::Adapter) -> Self {
+        Self {
+            features: device.features(),
+            limits: device.limits(),
+            adapter_info: adapter.get_info(),
+        }
+    }
+
+    pub fn supports(&self, features: wgpu::Features) -> bool {
+        self.features.contains(features)
+    }
+
+    pub fn polygon_mode_line(&self) -> bool {
+        self.supports(wgpu::Features::POLYGON_MODE_LINE)
+    }
+
+    pub fn polygon_mode_point(&self) -> bool {
+        self.supports(wgpu::Features::POLYGON_MODE_POINT)
+    }
+
+    pub fn bgra8unorm_storage(&self) -> bool {
+        self.supports(wgpu::Features::BGRA8UNORM_STORAGE)
+    }
+
+    pub fn is_software(&self) -> bool {
+        self.adapter_info.device_type == wgpu::DeviceType::Cpu
+    }
+}
+
+/// Requests a device from `adapter` with all `features.required` and every feature of
+/// `features.optional` that the adapter supports.
+///
+/// Setting `COMPLEX_VIS_WGPU_TRACE` to a directory records an API trace of the device there
+/// (requires wgpu-core built with its `trace` feature).
+pub async fn request_device(
+    adapter: &wgpu::Adapter,
+    features: FeatureRequest,
+    limits: Option<wgpu::Limits>,
+) -> anyhow::Result<(wgpu::Device, wgpu::Queue, DeviceCapabilities)> {
+    let adapter_features = adapter.features();
+
+    let missing = features.required - adapter_features;
+    if !missing.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "GPU Adapter is missing required features: {:?}",
+            missing
+        )));
+    }
+
+    let required_features = features.required | (features.optional & adapter_features);
+
+    let required_limits = limits.unwrap_or_else(|| {
+        if cfg!(target_arch = "wasm32") {
+            wgpu::Limits::downlevel_webgl2_defaults()
+        } else {
+            wgpu::Limits::default()
+        }
+    });
+    let required_limits = required_limits.using_resolution(adapter.limits());
+
+    let trace_path = std::env::var_os("COMPLEX_VIS_WGPU_TRACE").map(std::path::PathBuf::from);
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                required_features,
+                required_limits,
+                label: Some("Renderer Created Device"),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            trace_path.as_deref(),
+        )
+        .await
+        .context("GPU Device Request Failed.")?;
+
+    let capabilities = DeviceCapabilities::from_device(&device, adapter);
+
+    Ok((device, queue, capabilities))
+}
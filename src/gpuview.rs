@@ -1,8 +1,12 @@
 use anyhow::Context;
 use std::{
     cell::RefCell,
-    ops::{Add, Div, Mul, Range, Sub},
-    sync::Arc,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::{Add, Div, Mul, Range, RangeInclusive, Sub},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 use wgpu::util::DeviceExt;
 use wgpu_text::{
@@ -10,10 +14,19 @@ use wgpu_text::{
         ab_glyph::{FontArc, FontRef, FontVec},
         OwnedSection,
     },
-    BrushBuilder, TextBrush,
+    Matrix,
 };
 
-use crate::math::lerp;
+use crate::color::ColorVisionFilter;
+use crate::geometry::Rect;
+use crate::glow::GlowPass;
+use crate::math::Radians;
+use crate::postprocess::{PostEffect, PostProcessChain};
+use crate::resources::{
+    FontRegistry, PipelineCache, SharedTextBrush, TextBrushRegistry, TextureRegistry,
+};
+use crate::svg::{SvgRect, SvgWriter};
+use crate::texture_copy::{read_texture_rgba, texture_bytes};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -22,7 +35,7 @@ pub struct Vertex {
     pub color: [f32; 4],
 }
 impl Vertex {
-    fn desc() -> wgpu::VertexBufferLayout<'static> {
+    pub(crate) fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
@@ -42,6 +55,33 @@ impl Vertex {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickVertex {
+    pub position: [f32; 3],
+    pub id: u32,
+}
+impl PickVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<PickVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+            ],
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct FrameVertex {
@@ -104,7 +144,8 @@ impl FrameVertex {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GPUViewFrame {
     Whole,
     UpperLeftQuad,
@@ -115,19 +156,126 @@ pub enum GPUViewFrame {
         upper_left: (f32, f32),
         lower_right: (f32, f32),
     },
+    /// Frame given in physical pixels, resolved against the surface size whenever the
+    /// multiview is resized.
+    Anchored {
+        anchor: FrameAnchor,
+        insets: FrameInsets,
+    },
+}
+
+/// Placement of a `GPUViewFrame::Anchored` frame on the surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameAnchor {
+    /// Area in screen space (`-1..1`), like the non anchored frames.
+    Region(Rect),
+    /// Full height strip of this many pixels along the left edge.
+    Left(f32),
+    /// Full height strip of this many pixels along the right edge.
+    Right(f32),
+    /// Full width strip of this many pixels along the top edge.
+    Top(f32),
+    /// Full width strip of this many pixels along the bottom edge.
+    Bottom(f32),
+    /// Largest centered area with this width / height ratio.
+    AspectLocked(f32),
+}
+
+/// Distances in physical pixels an anchored frame is moved away from its edges.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameInsets {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl FrameInsets {
+    pub fn uniform(inset: f32) -> Self {
+        Self::symmetric(inset, inset)
+    }
+
+    pub fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self {
+            left: horizontal,
+            right: horizontal,
+            top: vertical,
+            bottom: vertical,
+        }
+    }
+
+    pub fn with_left(mut self, left: f32) -> Self {
+        self.left = left;
+        self
+    }
+
+    pub fn with_right(mut self, right: f32) -> Self {
+        self.right = right;
+        self
+    }
+
+    pub fn with_top(mut self, top: f32) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn with_bottom(mut self, bottom: f32) -> Self {
+        self.bottom = bottom;
+        self
+    }
+
+    fn add(self, other: FrameInsets) -> Self {
+        Self {
+            left: self.left + other.left,
+            right: self.right + other.right,
+            top: self.top + other.top,
+            bottom: self.bottom + other.bottom,
+        }
+    }
 }
 
 impl GPUViewFrame {
+    /// Margin in screen space (`-1..1`), so it scales with the surface.
+    ///
+    /// Anchored frames are laid out in pixels and are returned unchanged, use
+    /// `with_pixel_margin` for them.
     pub fn with_margin(self, margin: (f32, f32)) -> Self {
-        let upper_left = self.upper_left();
-        let lower_right = self.lower_right();
+        match self.fraction_rect() {
+            Some(rect) => Self::from_rect(rect.expand([-margin.0, -margin.1])),
+            None => self,
+        }
+    }
 
-        let upper_left = (upper_left.0 + margin.0, upper_left.1 - margin.1);
-        let lower_right = (lower_right.0 - margin.0, lower_right.1 + margin.1);
+    /// Margin in physical pixels, which keeps its size when the surface is resized.
+    pub fn with_pixel_margin(self, insets: FrameInsets) -> Self {
+        match self {
+            GPUViewFrame::Anchored {
+                anchor,
+                insets: current,
+            } => GPUViewFrame::Anchored {
+                anchor,
+                insets: current.add(insets),
+            },
+            frame => GPUViewFrame::Anchored {
+                anchor: FrameAnchor::Region(frame.fraction_rect().unwrap()),
+                insets,
+            },
+        }
+    }
 
+    pub fn from_rect(rect: Rect) -> Self {
         Self::Custom {
-            upper_left,
-            lower_right,
+            upper_left: rect.upper_left().into(),
+            lower_right: rect.lower_right().into(),
+        }
+    }
+
+    pub fn anchored(anchor: FrameAnchor) -> Self {
+        Self::Anchored {
+            anchor,
+            insets: FrameInsets::default(),
         }
     }
 
@@ -148,50 +296,72 @@ impl GPUViewFrame {
         (1.0, -1.0),  // 8
     ];
 
-    fn upper_left(&self) -> (f32, f32) {
-        match self {
-            GPUViewFrame::Whole => Self::QUAD_VERTS_POS[0],
-            GPUViewFrame::UpperLeftQuad => Self::QUAD_VERTS_POS[0],
-            GPUViewFrame::UpperRightQuad => Self::QUAD_VERTS_POS[1],
-            GPUViewFrame::LowerLeftQuad => Self::QUAD_VERTS_POS[3],
-            GPUViewFrame::LowerRightQuad => Self::QUAD_VERTS_POS[4],
+    /// Area in screen space of the frames which don't depend on the surface size.
+    fn fraction_rect(&self) -> Option<Rect> {
+        let (upper_left, lower_right) = match self {
+            GPUViewFrame::Whole => (Self::QUAD_VERTS_POS[0], Self::QUAD_VERTS_POS[8]),
+            GPUViewFrame::UpperLeftQuad => (Self::QUAD_VERTS_POS[0], Self::QUAD_VERTS_POS[4]),
+            GPUViewFrame::UpperRightQuad => (Self::QUAD_VERTS_POS[1], Self::QUAD_VERTS_POS[5]),
+            GPUViewFrame::LowerLeftQuad => (Self::QUAD_VERTS_POS[3], Self::QUAD_VERTS_POS[7]),
+            GPUViewFrame::LowerRightQuad => (Self::QUAD_VERTS_POS[4], Self::QUAD_VERTS_POS[8]),
             GPUViewFrame::Custom {
                 upper_left,
-                lower_right: _,
-            } => *upper_left,
-        }
-    }
-
-    fn lower_right(&self) -> (f32, f32) {
-        match self {
-            GPUViewFrame::Whole => Self::QUAD_VERTS_POS[8],
-            GPUViewFrame::UpperLeftQuad => Self::QUAD_VERTS_POS[4],
-            GPUViewFrame::UpperRightQuad => Self::QUAD_VERTS_POS[5],
-            GPUViewFrame::LowerLeftQuad => Self::QUAD_VERTS_POS[7],
-            GPUViewFrame::LowerRightQuad => Self::QUAD_VERTS_POS[8],
-            GPUViewFrame::Custom {
-                upper_left: _,
                 lower_right,
-            } => *lower_right,
-        }
-    }
+            } => (*upper_left, *lower_right),
+            GPUViewFrame::Anchored { .. } => return None,
+        };
 
-    fn frame_vertices(&self) -> Vec<FrameVertex> {
-        FrameVertex::vertices_from_rect(self.upper_left(), self.lower_right())
+        Some(Rect::new(upper_left.into(), lower_right.into()))
     }
 
-    fn relative_dimensions(&self) -> (f32, f32) {
-        match self {
-            GPUViewFrame::Whole => (1.0, 1.0),
-            GPUViewFrame::UpperLeftQuad => (0.5, 0.5),
-            GPUViewFrame::UpperRightQuad => (0.5, 0.5),
-            GPUViewFrame::LowerLeftQuad => (0.5, 0.5),
-            GPUViewFrame::LowerRightQuad => (0.5, 0.5),
-            GPUViewFrame::Custom {
-                upper_left,
-                lower_right,
-            } => (lower_right.0 - upper_left.0, upper_left.1 - lower_right.1),
+    /// Area of the frame in multiview screen space (`-1..1`) on a surface of `surface_size`
+    /// physical pixels.
+    ///
+    /// Anchored frames which don't fit collapse to zero size instead of flipping.
+    pub fn resolve(&self, surface_size: (u32, u32)) -> Rect {
+        let GPUViewFrame::Anchored { anchor, insets } = *self else {
+            return self.fraction_rect().unwrap();
+        };
+
+        let (width, height) = (surface_size.0.max(1) as f32, surface_size.1.max(1) as f32);
+
+        // pixels with the origin in the lower left corner, so y points up like screen space
+        let pixels = match anchor {
+            FrameAnchor::Region(rect) => {
+                let to_pixels = Rect::new([0.0, 0.0], [width, height]);
+                Rect::new(to_pixels.from_ndc(rect.min), to_pixels.from_ndc(rect.max))
+            }
+            FrameAnchor::Left(size) => Rect::new([0.0, 0.0], [size, height]),
+            FrameAnchor::Right(size) => Rect::new([width - size, 0.0], [width, height]),
+            FrameAnchor::Top(size) => Rect::new([0.0, height - size], [width, height]),
+            FrameAnchor::Bottom(size) => Rect::new([0.0, 0.0], [width, size]),
+            FrameAnchor::AspectLocked(_) => Rect::new([0.0, 0.0], [width, height]),
+        };
+
+        let mut pixels = Rect {
+            min: [pixels.min[0] + insets.left, pixels.min[1] + insets.bottom],
+            max: [pixels.max[0] - insets.right, pixels.max[1] - insets.top],
+        };
+        for axis in 0..2 {
+            if pixels.min[axis] > pixels.max[axis] {
+                let center = (pixels.min[axis] + pixels.max[axis]) / 2.0;
+                pixels.min[axis] = center;
+                pixels.max[axis] = center;
+            }
+        }
+
+        if let FrameAnchor::AspectLocked(aspect) = anchor {
+            let aspect = aspect.max(f32::MIN_POSITIVE);
+            let [available_width, available_height] = pixels.size();
+            let half_size = match available_width > available_height * aspect {
+                true => [available_height * aspect / 2.0, available_height / 2.0],
+                false => [available_width / 2.0, available_width / aspect / 2.0],
+            };
+            pixels = Rect::from_center(pixels.center(), half_size);
         }
+
+        let surface = Rect::new([0.0, 0.0], [width, height]);
+        Rect::new(surface.to_ndc(pixels.min), surface.to_ndc(pixels.max))
     }
 }
 
@@ -199,12 +369,41 @@ impl GPUViewFrame {
 pub struct Font {
     pub name: String,
     pub font: FontArc,
+    /// Shared by all clones of this font, used by `FontRegistry` to find unused fonts.
+    usage: Arc<()>,
+}
+
+impl Font {
+    pub fn new(name: &str, font: FontArc) -> Self {
+        Self {
+            name: name.to_string(),
+            font,
+            usage: Arc::new(()),
+        }
+    }
+
+    pub fn from_file(name: &str, path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let data = std::fs::read(path.as_ref())
+            .with_context(|| format!("Failed to read font file {:?}.", path.as_ref()))?;
+        let font = FontArc::try_from_vec(data)?;
+
+        Ok(Self::new(name, font))
+    }
+
+    /// Number of live clones of this font.
+    pub fn usage_count(&self) -> usize {
+        Arc::strong_count(&self.usage)
+    }
 }
 
+/// Plain sections are copied for the text brush again every frame, so they can be changed
+/// through their shared handle. Labels (see `LabelHandle`) are only copied when they changed.
 #[derive(Debug)]
 pub enum TextSection {
     Absolute(OwnedSection),
     Relative(OwnedSection),
+    /// Relative screen position with an additional offset in pixels.
+    RelativeOffset(OwnedSection, (f32, f32)),
 }
 
 impl TextSection {
@@ -212,9 +411,46 @@ impl TextSection {
         Arc::new(RefCell::new(self))
     }
 
-    fn create_section(&self, render_width: u32, render_height: u32) -> OwnedSection {
+    fn section(&self) -> &OwnedSection {
         match self {
-            TextSection::Absolute(section) => section.clone(),
+            TextSection::Absolute(section)
+            | TextSection::Relative(section)
+            | TextSection::RelativeOffset(section, _) => section,
+        }
+    }
+
+    fn section_mut(&mut self) -> &mut OwnedSection {
+        match self {
+            TextSection::Absolute(section)
+            | TextSection::Relative(section)
+            | TextSection::RelativeOffset(section, _) => section,
+        }
+    }
+
+    /// Replaces the text of the first part, further parts are dropped.
+    fn set_text(&mut self, text: &str) {
+        let section = self.section_mut();
+        section.text.truncate(1);
+        if let Some(part) = section.text.first_mut() {
+            part.text = text.to_string();
+        }
+    }
+
+    /// Sizes, absolute positions and offsets are in multiview pixels and multiplied by
+    /// `pixel_density` to get texture pixels.
+    fn create_section(
+        &self,
+        render_width: u32,
+        render_height: u32,
+        pixel_density: f32,
+    ) -> OwnedSection {
+        let mut section = match self {
+            TextSection::Absolute(section) => {
+                let position = section.screen_position;
+                section
+                    .clone()
+                    .with_screen_position((position.0 * pixel_density, position.1 * pixel_density))
+            }
             TextSection::Relative(section) => {
                 let relative_pos = section.screen_position;
                 section.clone().with_screen_position((
@@ -222,15 +458,119 @@ impl TextSection {
                     relative_pos.1 * render_height as f32,
                 ))
             }
+            TextSection::RelativeOffset(section, offset) => {
+                let relative_pos = section.screen_position;
+                section.clone().with_screen_position((
+                    relative_pos.0 * render_width as f32 + offset.0 * pixel_density,
+                    relative_pos.1 * render_height as f32 + offset.1 * pixel_density,
+                ))
+            }
+        };
+
+        if pixel_density != 1.0 {
+            for text in &mut section.text {
+                text.scale.x *= pixel_density;
+                text.scale.y *= pixel_density;
+            }
+        }
+
+        section
+    }
+}
+
+/// Thread safe handle to the text of a section added with `GPUView::add_label` or
+/// `GPUMultiView::add_label`.
+///
+/// Changes are applied on the next render, only the changed labels are rebuilt and queued to
+/// the text brush again.
+#[derive(Debug, Clone)]
+pub struct LabelHandle {
+    text: Arc<Mutex<String>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl LabelHandle {
+    fn new(section: &TextSection) -> Self {
+        let text = section
+            .section()
+            .text
+            .iter()
+            .map(|part| part.text.as_str())
+            .collect();
+
+        Self {
+            text: Arc::new(Mutex::new(text)),
+            changed: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    pub fn set_text(&self, text: impl Into<String>) {
+        let text = text.into();
+
+        let mut current = self.text.lock().unwrap();
+        if *current != text {
+            *current = text;
+            self.changed.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text.lock().unwrap().clone()
+    }
+
+    /// The current text if it changed since the last call.
+    fn take_change(&self) -> Option<String> {
+        self.changed
+            .swap(false, Ordering::AcqRel)
+            .then(|| self.text())
+    }
+
+    fn is_same(&self, other: &LabelHandle) -> bool {
+        Arc::ptr_eq(&self.changed, &other.changed)
+    }
+}
+
+/// Sections as they were last queued to a text brush.
+struct QueuedSections {
+    sections: Vec<OwnedSection>,
+    /// Render width, height and pixel density they were created for
+    target: (u32, u32, f32),
+    /// Of the content and positions of `sections`
+    hash: u64,
+}
+
+impl QueuedSections {
+    fn new(sections: Vec<OwnedSection>, target: (u32, u32, f32)) -> Self {
+        let mut queued = Self {
+            sections,
+            target,
+            hash: 0,
+        };
+        queued.rehash();
+        queued
+    }
+
+    fn rehash(&mut self) {
+        let mut hasher = DefaultHasher::new();
+        for section in &self.sections {
+            section.to_borrowed().hash(&mut hasher);
+        }
+        self.hash = hasher.finish();
+    }
 }
 
 pub struct TextPrimitive {
     font: Font,
     sections: Vec<Arc<RefCell<TextSection>>>,
+    /// Sections of `sections` whose text is changed through a handle
+    labels: Vec<(Arc<RefCell<TextSection>>, LabelHandle)>,
+    /// Counterclockwise rotation of all sections around their positions in radians.
+    rotation: f32,
+    /// `None` if sections were added or removed since they were last queued.
+    queued: Option<QueuedSections>,
 
-    brush: Option<TextBrush<FontArc>>,
+    /// Borrowed from the `TextBrushRegistry` of the view.
+    brush: Option<Arc<Mutex<SharedTextBrush>>>,
 
     is_initialized: bool,
 }
@@ -240,6 +580,9 @@ impl TextPrimitive {
         Self {
             font,
             sections,
+            labels: Vec::new(),
+            rotation: 0.0,
+            queued: None,
             brush: None,
             is_initialized: false,
         }
@@ -247,32 +590,297 @@ impl TextPrimitive {
 
     pub fn initialize(
         &mut self,
+        brushes: &TextBrushRegistry,
+        device: &wgpu::Device,
+        render_width: u32,
+        render_height: u32,
+        multisample_state: wgpu::MultisampleState,
+    ) -> anyhow::Result<()> {
+        // deferred until the target has a size again, the brush rejects empty targets
+        if render_width == 0 || render_height == 0 {
+            return Ok(());
+        }
+
+        self.brush = Some(brushes.get_or_create(
+            &self.font,
+            self.rotation,
+            wgpu::TextureFormat::Bgra8Unorm,
+            multisample_state,
+            device,
+            (render_width, render_height),
+        ));
+        self.is_initialized = true;
+        self.queued = None;
+
+        Ok(())
+    }
+
+    /// Borrows the brush on first use and writes the projection of the target to it, the
+    /// brush is shared with other views which may have drawn into targets of other sizes.
+    fn prepare(
+        &mut self,
+        brushes: &TextBrushRegistry,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         render_width: u32,
         render_height: u32,
         multisample_state: wgpu::MultisampleState,
     ) -> anyhow::Result<()> {
-        let brush = BrushBuilder::using_font(self.font.font.clone())
-            .with_multisample(multisample_state)
-            .build(
+        if !self.is_initialized {
+            self.initialize(
+                brushes,
                 device,
                 render_width,
                 render_height,
-                wgpu::TextureFormat::Bgra8Unorm,
-            );
+                multisample_state,
+            )?;
+        }
 
-        self.brush = Some(brush);
-        self.is_initialized = true;
+        if let Some(brush) = &self.brush {
+            let matrix = self.matrix(render_width, render_height);
+            brush.lock().unwrap().set_matrix(matrix, queue);
+        }
+
+        Ok(())
+    }
+
+    /// Releases the brush while there are no sections to draw, its glyph atlas is dropped
+    /// once no other view uses it either (see `TextBrushRegistry::remove_unused`).
+    fn evict_unused(&mut self) -> bool {
+        if !self.sections.is_empty() || self.brush.is_none() {
+            return false;
+        }
+
+        self.brush = None;
+        self.is_initialized = false;
+
+        true
+    }
+
+    fn push_section(&mut self, section: Arc<RefCell<TextSection>>) {
+        self.sections.push(section);
+        self.queued = None;
+    }
+
+    fn clear_sections(&mut self) {
+        self.sections.clear();
+        self.labels.clear();
+        self.queued = None;
+    }
+
+    fn remove_sections(&mut self, removed: &[Arc<RefCell<TextSection>>]) {
+        let is_removed = |section: &Arc<RefCell<TextSection>>| {
+            removed.iter().any(|removed| Arc::ptr_eq(section, removed))
+        };
+
+        let count = self.sections.len();
+        self.sections.retain(|section| !is_removed(section));
+        self.labels.retain(|(section, _)| !is_removed(section));
+
+        if self.sections.len() != count {
+            self.queued = None;
+        }
+    }
+
+    fn add_label(&mut self, section: TextSection) -> LabelHandle {
+        let label = LabelHandle::new(&section);
+        let section = section.into_arc_ref_cell();
+
+        self.push_section(section.clone());
+        self.labels.push((section, label.clone()));
+
+        label
+    }
+
+    fn remove_label(&mut self, label: &LabelHandle) {
+        let removed = self
+            .labels
+            .iter()
+            .filter(|(_, other)| other.is_same(label))
+            .map(|(section, _)| section.clone())
+            .collect::<Vec<_>>();
+
+        self.remove_sections(&removed);
+    }
+
+    /// Queues the sections to the brush unless it already holds the current ones.
+    ///
+    /// Plain sections are rebuilt every time since they can be edited through their shared
+    /// handle, labels only if their text changed. All sections are rebuilt after sections were
+    /// added or removed or the target changed. Nothing is queued if the brush holds sections
+    /// hashing the same as the rebuilt ones.
+    fn queue_sections(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        render_width: u32,
+        render_height: u32,
+        pixel_density: f32,
+    ) -> anyhow::Result<()> {
+        let target = (render_width, render_height, pixel_density);
+
+        let changed = self
+            .labels
+            .iter()
+            .filter_map(|(section, label)| {
+                let text = label.take_change()?;
+                section.borrow_mut().set_text(&text);
+                self.sections
+                    .iter()
+                    .position(|other| Arc::ptr_eq(other, section))
+            })
+            .collect::<Vec<_>>();
+
+        let Some(brush) = self.brush.clone() else {
+            return Ok(());
+        };
+
+        let previous = self.queued.take();
+
+        let only_labels = self.labels.len() == self.sections.len();
+
+        let queued = match previous {
+            Some(queued) if queued.target == target && only_labels && changed.is_empty() => queued,
+            Some(mut queued) if queued.target == target => {
+                let is_label = |section: &Arc<RefCell<TextSection>>| {
+                    self.labels
+                        .iter()
+                        .any(|(label, _)| Arc::ptr_eq(label, section))
+                };
+
+                for (index, section) in self.sections.iter().enumerate() {
+                    if is_label(section) && !changed.contains(&index) {
+                        continue;
+                    }
+                    queued.sections[index] = self.create_section(
+                        &section.borrow(),
+                        render_width,
+                        render_height,
+                        pixel_density,
+                    );
+                }
+                queued.rehash();
+                queued
+            }
+            _ => QueuedSections::new(
+                self.create_sections(render_width, render_height, pixel_density),
+                target,
+            ),
+        };
+
+        // sections which got removed and added again unchanged (e.g. by a redraw of the
+        // canvas) are still queued, unless another view queued its own in the meantime
+        brush
+            .lock()
+            .unwrap()
+            .queue(device, queue, &queued.sections, queued.hash)?;
+        self.queued = Some(queued);
 
         Ok(())
     }
 
-    fn create_sections(&self, render_width: u32, render_height: u32) -> Vec<OwnedSection> {
+    fn create_sections(
+        &self,
+        render_width: u32,
+        render_height: u32,
+        pixel_density: f32,
+    ) -> Vec<OwnedSection> {
         self.sections
             .iter()
-            .map(|section| section.borrow().create_section(render_width, render_height))
+            .map(|section| {
+                self.create_section(
+                    &section.borrow(),
+                    render_width,
+                    render_height,
+                    pixel_density,
+                )
+            })
             .collect::<Vec<_>>()
     }
+
+    fn create_section(
+        &self,
+        section: &TextSection,
+        render_width: u32,
+        render_height: u32,
+        pixel_density: f32,
+    ) -> OwnedSection {
+        let section = section.create_section(render_width, render_height, pixel_density);
+
+        if self.rotation == 0.0 {
+            return section;
+        }
+
+        // the brush rotates everything around the origin, so the positions are
+        // rotated back beforehand to end up in place
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = section.screen_position;
+        section.with_screen_position((x * cos - y * sin, x * sin + y * cos))
+    }
+
+    /// Appends the sections to `writer`, positioned in `rect`.
+    fn write_svg(&self, writer: &mut SvgWriter, rect: SvgRect) {
+        let (width, height) = (rect.width.round() as u32, rect.height.round() as u32);
+
+        for section in &self.sections {
+            let mut created = section.borrow().create_section(width, height, 1.0);
+
+            // label changes are only applied to the sections when queued
+            let label = self
+                .labels
+                .iter()
+                .find(|(label, _)| Arc::ptr_eq(label, section));
+            if let Some((_, label)) = label {
+                created.text.truncate(1);
+                if let Some(part) = created.text.first_mut() {
+                    part.text = label.text();
+                }
+            }
+
+            writer.text(rect, &created, &self.font.name, self.rotation);
+        }
+    }
+
+    /// Projection of the brush, pixels (y pointing down) rotated counterclockwise on screen.
+    fn matrix(&self, render_width: u32, render_height: u32) -> Matrix {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (width, height) = (render_width as f32, render_height as f32);
+
+        [
+            [2.0 * cos / width, 2.0 * sin / height, 0.0, 0.0],
+            [2.0 * sin / width, -2.0 * cos / height, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0, 1.0],
+        ]
+    }
+}
+
+/// Handle to a pending `GPUView::pick` request.
+///
+/// The request gets resolved asynchronously after the next rendered frame.
+#[derive(Debug, Clone)]
+pub struct PickHandle {
+    result: Arc<Mutex<Option<u32>>>,
+}
+
+impl PickHandle {
+    /// Returns `None` while the readback is still pending, otherwise the picked id (`0` if nothing was hit).
+    pub fn try_get(&self) -> Option<u32> {
+        *self.result.lock().unwrap()
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.try_get().is_some()
+    }
+}
+
+struct PickReadback {
+    texel: (u32, u32),
+    buffer: Option<wgpu::Buffer>,
+    map_requested: bool,
+    /// Set by the `map_async` callback, `false` if mapping the buffer failed.
+    mapped: Arc<Mutex<Option<bool>>>,
+    result: Arc<Mutex<Option<u32>>>,
 }
 
 pub trait ShaderDescriptor {
@@ -285,37 +893,193 @@ pub trait ShaderDescriptor {
     ) -> anyhow::Result<(wgpu::BindGroup, wgpu::BindGroupLayout)>;
 }
 
-pub struct GPUView {
-    frame: GPUViewFrame,
+/// GPU work of a view besides its render vertices, e.g. the compute passes of a
+/// `CanvasLayer` (see `CanvasLayer::gpu_pass`).
+pub trait GPUPass {
+    /// Records the work which has to be done before the render pass of the view, called
+    /// on every refresh. `size` is the render size of the view and `multisample_state`
+    /// the one of its render pass.
+    fn prepare(
+        &mut self,
+        size: (u32, u32),
+        multisample_state: wgpu::MultisampleState,
+        pipeline_cache: &PipelineCache,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<()>;
 
-    multisample_state: wgpu::MultisampleState,
-    clear_color: wgpu::Color,
+    /// Draws into the render pass of the view, below the render vertices.
+    fn draw(&self, render_pass: &mut wgpu::RenderPass);
 
-    shader_descriptor: Arc<RefCell<dyn ShaderDescriptor>>,
-    render_vertices: Vec<Vertex>,
+    /// Called once the frame was submitted, e.g. to map readback buffers.
+    fn after_submit(&mut self) {}
 
-    text_primitives: Vec<TextPrimitive>,
+    /// Bytes of the buffers and textures owned by the pass.
+    fn memory_bytes(&self) -> u64 {
+        0
+    }
+}
+
+/// Invocations per workgroup of a compute pass, injected into its shader as the constants
+/// `WORKGROUP_SIZE_X` and `WORKGROUP_SIZE_Y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkgroupSize {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl WorkgroupSize {
+    pub const SMALL: Self = Self { x: 8, y: 8 };
+    /// The most invocations the default limits allow.
+    pub const LARGE: Self = Self { x: 16, y: 16 };
+
+    pub fn new(x: u32, y: u32) -> Self {
+        Self {
+            x: x.max(1),
+            y: y.max(1),
+        }
+    }
+
+    pub fn invocations(&self) -> u32 {
+        self.x * self.y
+    }
+
+    /// Workgroups covering `width` x `height` invocations, the shader has to skip the
+    /// invocations beyond.
+    pub fn dispatch_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (width.div_ceil(self.x), height.div_ceil(self.y))
+    }
+
+    /// WGSL declarations of the size, to be prepended to the shader source.
+    pub fn wgsl_constants(&self) -> String {
+        format!(
+            "const WORKGROUP_SIZE_X: u32 = {}u;\nconst WORKGROUP_SIZE_Y: u32 = {}u;\n",
+            self.x, self.y
+        )
+    }
+
+    /// Fails if `device` doesn't support workgroups of this size.
+    pub fn validate(&self, device: &wgpu::Device) -> anyhow::Result<()> {
+        let limits = device.limits();
+
+        if self.x > limits.max_compute_workgroup_size_x
+            || self.y > limits.max_compute_workgroup_size_y
+            || self.invocations() > limits.max_compute_invocations_per_workgroup
+        {
+            return Err(anyhow::anyhow!(
+                "Workgroup size {}x{} exceeds the limits of the device.",
+                self.x,
+                self.y
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for WorkgroupSize {
+    fn default() -> Self {
+        Self::SMALL
+    }
+}
+
+/// Stable handle of a `GPUView`, unique for the whole process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ViewId(u64);
+
+impl ViewId {
+    fn next() -> Self {
+        static NEXT_VIEW_ID: AtomicU64 = AtomicU64::new(1);
+
+        Self(NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub struct GPUView {
+    id: ViewId,
+    label: Option<String>,
+    frame: GPUViewFrame,
+    /// `frame` resolved against the multiview size of the last resize.
+    frame_rect: Rect,
+    /// Texture pixels per frame pixel, the texture is scaled to the frame when composited.
+    render_scale: f32,
+    /// Pixel density of the surface the frame is resolved against, i.e. the product of the
+    /// render scales of all parents.
+    surface_scale: f32,
+    visible: bool,
+
+    multisample_state: wgpu::MultisampleState,
+    polygon_mode: wgpu::PolygonMode,
+    clear_color: wgpu::Color,
+
+    shader_descriptor: Arc<RefCell<dyn ShaderDescriptor>>,
+    render_vertices: Vec<Vertex>,
+    /// Triangle list indices into `render_vertices`, which are drawn in order if `None`.
+    render_indices: Option<Vec<u32>>,
+    /// Vertices blurred and added below `render_vertices`
+    glow_vertices: Vec<Vertex>,
+    /// Blur radius of the glow in pixels
+    glow_radius: f32,
+    glow: Option<GlowPass>,
+    gpu_passes: Vec<Arc<RefCell<dyn GPUPass>>>,
+
+    text_primitives: Vec<TextPrimitive>,
+    /// Taken from the multiview (or the parent view) on initialization.
+    text_brushes: TextBrushRegistry,
+
+    pick_enabled: bool,
+    pick_id: u32,
+    pick_vertices: Vec<PickVertex>,
+    pick_readbacks: Vec<PickReadback>,
 
     texture_width: Option<u32>,
     texture_height: Option<u32>,
+    /// Set while the frame covers zero pixels, the textures keep their last valid size meanwhile.
+    resize_deferred: bool,
+    /// Allocated size of the textures, the view renders into the upper left
+    /// `texture_width` x `texture_height` part of them.
+    texture_capacity: Option<(u32, u32)>,
+    textures_outdated: bool,
+    /// Renders the texture only every `refresh_divisor`th frame, see `set_refresh_divisor`.
+    refresh_divisor: u32,
+    frames_since_refresh: u32,
+    /// Set once the textures hold a complete frame, cleared when they are (re)allocated.
+    content_valid: bool,
     resolve_texture: Option<wgpu::Texture>,
     msaa_texture: Option<wgpu::Texture>,
 
     shader_bind_group: Option<wgpu::BindGroup>,
     render_vertices_buffer: Option<wgpu::Buffer>,
+    /// Created on the first upload of non empty `render_indices`.
+    render_indices_buffer: Option<wgpu::Buffer>,
     frame_vertices_buffer: Option<wgpu::Buffer>,
-    render_pipeline: Option<wgpu::RenderPipeline>,
+    render_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+    /// Taken from the multiview (or the parent view) on initialization.
+    pipeline_cache: PipelineCache,
 
     resolve_texture_sampler: Option<wgpu::Sampler>,
     frame_bind_group_layout: Option<wgpu::BindGroupLayout>,
     frame_bind_group: Option<wgpu::BindGroup>,
 
+    pick_texture: Option<wgpu::Texture>,
+    pick_vertices_buffer: Option<wgpu::Buffer>,
+    pick_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+
+    /// Views composited on top of this one, their frames are relative to this view.
+    child_views: Vec<Arc<RefCell<GPUView>>>,
+    child_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+
     is_initialized: bool,
     render_vertices_changed: bool,
+    render_indices_changed: bool,
+    pick_vertices_changed: bool,
     frame_changed: bool,
 }
 
 impl GPUView {
+    pub const RENDER_SCALE_RANGE: RangeInclusive<f32> = 0.5..=2.0;
+
     const FRAME_BIND_GROUP_LAYOUT_DESCIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
         wgpu::BindGroupLayoutDescriptor {
             label: Some("GPUView Bind Group Layout"),
@@ -349,25 +1113,57 @@ impl GPUView {
         let clear_color = wgpu::Color::TRANSPARENT;
 
         Self {
+            id: ViewId::next(),
             frame,
+            frame_rect: frame.resolve((1, 1)),
+            render_scale: 1.0,
+            surface_scale: 1.0,
+            label: None,
+            visible: true,
             multisample_state,
+            polygon_mode: wgpu::PolygonMode::Fill,
             clear_color,
             shader_descriptor,
             render_vertices: Vec::new(),
+            render_indices: None,
+            glow_vertices: Vec::new(),
+            glow_radius: 8.0,
+            glow: None,
+            gpu_passes: Vec::new(),
             text_primitives: Vec::new(),
+            text_brushes: TextBrushRegistry::new(),
+            pick_enabled: false,
+            pick_id: 0,
+            pick_vertices: Vec::new(),
+            pick_readbacks: Vec::new(),
             texture_width: None,
+            resize_deferred: false,
             texture_height: None,
             msaa_texture: None,
+            texture_capacity: None,
+            textures_outdated: false,
+            refresh_divisor: 1,
+            frames_since_refresh: 0,
+            content_valid: false,
             resolve_texture: None,
             shader_bind_group: None,
             render_vertices_buffer: None,
+            render_indices_buffer: None,
             frame_vertices_buffer: None,
             render_pipeline: None,
+            pipeline_cache: PipelineCache::new(),
             resolve_texture_sampler: None,
             frame_bind_group_layout: None,
             frame_bind_group: None,
+            pick_texture: None,
+            pick_vertices_buffer: None,
+            pick_pipeline: None,
+            child_views: Vec::new(),
+            child_pipeline: None,
             is_initialized: false,
             render_vertices_changed: false,
+            render_indices_changed: false,
+            pick_vertices_changed: false,
             frame_changed: false,
         }
     }
@@ -383,15 +1179,122 @@ impl GPUView {
         device: &wgpu::Device,
     ) {
         self.frame = frame;
+        self.frame_rect = frame.resolve((
+            multiview.width().unwrap_or(1),
+            multiview.height().unwrap_or(1),
+        ));
         self.frame_changed = true;
 
         let _ = self.resize(multiview, device);
     }
 
+    /// Renders the view into a texture of `render_scale` times the frame size (clamped to
+    /// `RENDER_SCALE_RANGE`), which is filtered to the frame size when composited.
+    ///
+    /// Values above `1` supersample the view, e.g. for exports, values below `1` make it
+    /// cheaper to render on weak GPUs. Text sizes and pixel offsets stay in multiview pixels
+    /// (see `pixel_density`).
+    pub fn set_render_scale(
+        &mut self,
+        render_scale: f32,
+        multiview: &GPUMultiView,
+        device: &wgpu::Device,
+    ) {
+        let render_scale = match render_scale.is_finite() {
+            true => render_scale.clamp(
+                *Self::RENDER_SCALE_RANGE.start(),
+                *Self::RENDER_SCALE_RANGE.end(),
+            ),
+            false => 1.0,
+        };
+        if render_scale == self.render_scale {
+            return;
+        }
+
+        self.render_scale = render_scale;
+        let _ = self.resize(multiview, device);
+    }
+
+    /// Renders the view only on every `divisor`th frame of its multiview (or parent view)
+    /// and composites the last rendered texture in between, e.g. a statistics panel at
+    /// 5 Hz next to a plot at 60 Hz.
+    ///
+    /// Resizes refresh the view immediately. Canvases skip their `display` until the view
+    /// is due again, so their changes are batched.
+    pub fn set_refresh_divisor(&mut self, divisor: u32) {
+        self.refresh_divisor = divisor.max(1);
+    }
+
+    pub fn refresh_divisor(&self) -> u32 {
+        self.refresh_divisor
+    }
+
+    /// True if the next render draws the view instead of reusing its last frame.
+    pub fn is_refresh_due(&self) -> bool {
+        !self.content_valid
+            || self.textures_outdated
+            || self.frames_since_refresh + 1 >= self.refresh_divisor
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Texture pixels per multiview pixel, which includes the render scales of parent views.
+    pub fn pixel_density(&self) -> f32 {
+        self.surface_scale * self.render_scale
+    }
+
+    /// Changing the sample count of an initialized view recreates its resources on the next render.
     pub fn set_multisample_state(&mut self, multisample_state: wgpu::MultisampleState) {
+        if self.is_initialized && self.multisample_state.count != multisample_state.count {
+            self.is_initialized = false;
+            self.glow = None;
+            for text_primitive in &mut self.text_primitives {
+                text_primitive.is_initialized = false;
+            }
+        }
+
         self.multisample_state = multisample_state;
     }
 
+    /// Single samples this view and its children, see `GPUMultiView::set_deterministic`.
+    fn disable_multisampling(&mut self) {
+        if self.multisample_state.count != 1 {
+            self.set_multisample_state(wgpu::MultisampleState::default());
+        }
+
+        for child in &self.child_views {
+            child.borrow_mut().disable_multisampling();
+        }
+    }
+
+    pub fn multisample_state(&self) -> wgpu::MultisampleState {
+        self.multisample_state
+    }
+
+    /// Sets the polygon mode used for the render vertices.
+    ///
+    /// `Line` and `Point` fall back to `Fill` if the device was created without
+    /// `POLYGON_MODE_LINE`/`POLYGON_MODE_POINT`.
+    pub fn set_polygon_mode(&mut self, polygon_mode: wgpu::PolygonMode) {
+        self.polygon_mode = polygon_mode;
+    }
+
+    fn supported_polygon_mode(&self, device: &wgpu::Device) -> wgpu::PolygonMode {
+        let features = device.features();
+
+        match self.polygon_mode {
+            wgpu::PolygonMode::Line if !features.contains(wgpu::Features::POLYGON_MODE_LINE) => {
+                wgpu::PolygonMode::Fill
+            }
+            wgpu::PolygonMode::Point if !features.contains(wgpu::Features::POLYGON_MODE_POINT) => {
+                wgpu::PolygonMode::Fill
+            }
+            polygon_mode => polygon_mode,
+        }
+    }
+
     pub fn set_clear_color(&mut self, clear_color: wgpu::Color) {
         self.clear_color = clear_color;
     }
@@ -399,29 +1302,424 @@ impl GPUView {
     pub fn clear_render_vertices(&mut self) {
         self.render_vertices.clear();
         self.render_vertices_changed;
+        self.clear_render_indices();
+
+        self.glow_vertices.clear();
+
+        self.pick_vertices.clear();
+        self.pick_vertices_changed = true;
     }
 
     pub fn set_render_vertices(&mut self, vertices: Vec<Vertex>) {
+        self.pick_vertices.clear();
+        self.push_pick_vertices(&vertices);
+
         self.render_vertices = vertices;
         self.render_vertices_changed = true;
+        self.clear_render_indices();
+    }
+
+    /// Draws the render vertices indexed by `indices` (a triangle list) instead of in order,
+    /// so vertices shared by several triangles are only uploaded once.
+    ///
+    /// The indices are dropped by `set_render_vertices` and `clear_render_vertices`, vertices
+    /// added later by `extend_render_vertices` are only drawn if `indices` refer to them.
+    /// Indexed picking requires picking to be enabled before the render vertices are set.
+    pub fn set_render_indices(&mut self, indices: Vec<u32>) {
+        self.render_indices = Some(indices);
+        self.render_indices_changed = true;
+    }
+
+    /// Returns to drawing the render vertices in order.
+    pub fn clear_render_indices(&mut self) {
+        if self.render_indices.take().is_some() {
+            self.render_indices_changed = true;
+        }
+    }
+
+    pub fn render_indices(&self) -> Option<&[u32]> {
+        self.render_indices.as_deref()
     }
 
     pub fn append_render_vertices(&mut self, vertices: &mut Vec<Vertex>) {
+        self.push_pick_vertices(vertices);
+
         self.render_vertices.append(vertices);
         self.render_vertices_changed = true;
     }
 
+    pub fn extend_render_vertices(&mut self, vertices: &[Vertex]) {
+        self.push_pick_vertices(vertices);
+
+        self.render_vertices.extend_from_slice(vertices);
+        self.render_vertices_changed = true;
+    }
+
+    /// Adds vertices to the glow layer, which is blurred and added below the render vertices.
+    pub fn extend_glow_vertices(&mut self, vertices: &[Vertex]) {
+        self.glow_vertices.extend_from_slice(vertices);
+    }
+
+    pub fn set_glow_radius(&mut self, radius: f32) {
+        self.glow_radius = radius;
+    }
+
+    /// Enables the additional id render pass which is required for `pick`.
+    /// Passes are prepared and drawn in order, below the render vertices.
+    pub fn add_gpu_pass(&mut self, pass: Arc<RefCell<dyn GPUPass>>) {
+        self.gpu_passes.push(pass);
+    }
+
+    pub fn remove_gpu_pass(&mut self, pass: &Arc<RefCell<dyn GPUPass>>) {
+        self.gpu_passes
+            .retain(|existing| !Arc::ptr_eq(existing, pass));
+    }
+
+    pub fn set_picking_enabled(&mut self, enabled: bool) {
+        self.pick_enabled = enabled;
+        self.pick_vertices_changed = true;
+    }
+
+    /// Sets the id which is written into the pick texture for all following render vertices.
+    /// `0` is reserved for "nothing", those vertices are discarded in the pick pass.
+    pub fn set_pick_id(&mut self, id: u32) {
+        self.pick_id = id;
+    }
+
+    /// One pick vertex per render vertex, so the render indices fit the pick vertices as well.
+    fn push_pick_vertices(&mut self, vertices: &[Vertex]) {
+        if !self.pick_enabled {
+            return;
+        }
+
+        let id = self.pick_id;
+        self.pick_vertices
+            .extend(vertices.iter().map(|vertex| PickVertex {
+                position: vertex.position,
+                id,
+            }));
+        self.pick_vertices_changed = true;
+    }
+
+    /// Requests the id rendered at `coordinates` (view space `-1..1`).
+    ///
+    /// Requires picking to be enabled. The returned handle is resolved after the next frame.
+    pub fn pick(&mut self, coordinates: (f32, f32)) -> PickHandle {
+        let result = Arc::new(Mutex::new(None));
+
+        let (Some(width), Some(height)) = (self.texture_width, self.texture_height) else {
+            *result.lock().unwrap() = Some(0);
+            return PickHandle { result };
+        };
+
+        if !self.pick_enabled || width == 0 || height == 0 {
+            *result.lock().unwrap() = Some(0);
+            return PickHandle { result };
+        }
+
+        let tx = ((coordinates.0 + 1.0) * 0.5 * width as f32) as i64;
+        let ty = ((1.0 - coordinates.1) * 0.5 * height as f32) as i64;
+
+        let texel = (
+            tx.clamp(0, width as i64 - 1) as u32,
+            ty.clamp(0, height as i64 - 1) as u32,
+        );
+
+        self.pick_readbacks.push(PickReadback {
+            texel,
+            buffer: None,
+            map_requested: false,
+            mapped: Arc::new(Mutex::new(None)),
+            result: result.clone(),
+        });
+
+        PickHandle { result }
+    }
+
     pub fn get_render_vertices_len(&self) -> usize {
         self.render_vertices.len()
     }
 
+    /// Reads back the content of the view rendered by the last `GPUMultiView::render`
+    /// as tightly packed RGBA rows of `texture_size()`.
+    pub fn read_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<Vec<u8>> {
+        let texture = self
+            .resolve_texture
+            .as_ref()
+            .context("GPUView is not initialized.")?;
+
+        let (width, height) = self.texture_size().unwrap();
+        let data = read_texture_rgba(texture, device, queue)?;
+
+        if texture.width() == width && texture.height() == height {
+            return Ok(data);
+        }
+
+        let row_len = width as usize * 4;
+        let texture_row_len = texture.width() as usize * 4;
+
+        Ok(data
+            .chunks_exact(texture_row_len)
+            .take(height as usize)
+            .flat_map(|row| &row[..row_len])
+            .copied()
+            .collect())
+    }
+
+    pub fn id(&self) -> ViewId {
+        self.id
+    }
+
+    /// Name used for the debug groups and pass labels of this view in GPU captures.
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn debug_label(&self) -> String {
+        match &self.label {
+            Some(label) => format!("GPUView {:?} ({})", label, self.id.0),
+            None => format!("GPUView {}", self.id.0),
+        }
+    }
+
+    pub fn frame(&self) -> GPUViewFrame {
+        self.frame
+    }
+
+    /// Area of the frame in multiview screen space (`-1..1`), updated on resize.
+    pub fn frame_rect(&self) -> Rect {
+        self.frame_rect
+    }
+
+    /// Maps multiview screen space (`-1..1`) to view space (`-1..1` inside the frame).
+    pub fn screen_to_view(&self, point: (f32, f32)) -> (f32, f32) {
+        self.frame_rect.to_ndc(point.into()).into()
+    }
+
+    /// Maps view space (`-1..1` inside the frame) to multiview screen space (`-1..1`).
+    pub fn view_to_screen(&self, point: (f32, f32)) -> (f32, f32) {
+        self.frame_rect.from_ndc(point.into()).into()
+    }
+
+    fn frame_vertices(&self) -> Vec<FrameVertex> {
+        let rect = self.frame_rect;
+        FrameVertex::vertices_from_rect(rect.upper_left().into(), rect.lower_right().into())
+    }
+
+    /// Texture size for a multiview of `multiview_size` physical pixels.
+    fn frame_texture_size(&self, multiview_size: (u32, u32)) -> (u32, u32) {
+        let [width, height] = self.frame_rect.size();
+        let scale = self.render_scale;
+
+        (
+            (multiview_size.0 as f32 * width / 2.0 * scale).round() as u32,
+            (multiview_size.1 as f32 * height / 2.0 * scale).round() as u32,
+        )
+    }
+
+    /// Appends the view and its visible children to `writer`, clipped to `rect`.
+    ///
+    /// GPU passes are raster only and left out.
+    fn write_svg(&self, writer: &mut SvgWriter, rect: SvgRect) {
+        writer.begin_clip(rect);
+        writer.fill_rect(rect, self.clear_color);
+
+        if !self.glow_vertices.is_empty() {
+            writer.begin_blur(self.glow_radius);
+            writer.triangles(rect, &self.glow_vertices, None);
+            writer.end_group();
+        }
+        writer.triangles(rect, &self.render_vertices, self.render_indices.as_deref());
+
+        for primitive in &self.text_primitives {
+            primitive.write_svg(writer, rect);
+        }
+
+        let size = (rect.width.round() as u32, rect.height.round() as u32);
+        for child in &self.child_views {
+            let child = child.borrow();
+            if child.visible {
+                child.write_svg(writer, rect.sub_rect(child.frame.resolve(size)));
+            }
+        }
+
+        writer.end_group();
+    }
+
+    /// Adds a view which is rendered after this one and composited into its texture,
+    /// e.g. a legend embedded in a canvas.
+    ///
+    /// The frame of the child is resolved against the texture size of this view, so its
+    /// screen space is the view space of this view. A view must not contain itself.
+    pub fn add_child_view(&mut self, view: Arc<RefCell<GPUView>>) {
+        self.child_views.push(view);
+    }
+
+    /// Children are drawn in order, so later views are on top.
+    pub fn set_child_views(&mut self, views: Vec<Arc<RefCell<GPUView>>>) {
+        self.child_views = views;
+    }
+
+    pub fn remove_child_view(&mut self, id: ViewId) -> Option<Arc<RefCell<GPUView>>> {
+        let index = self
+            .child_views
+            .iter()
+            .position(|view| view.borrow().id == id)?;
+
+        Some(self.child_views.remove(index))
+    }
+
+    pub fn child_views(&self) -> &[Arc<RefCell<GPUView>>] {
+        &self.child_views
+    }
+
+    /// View `id` among the children of this view and their children.
+    pub fn find_child_view(&self, id: ViewId) -> Option<Arc<RefCell<GPUView>>> {
+        self.child_views
+            .iter()
+            .find_map(|view| match view.borrow().id == id {
+                true => Some(view.clone()),
+                false => view.borrow().find_child_view(id),
+            })
+    }
+
+    /// Visible children (and their children) at `point` (view space of this view),
+    /// topmost first.
+    fn child_views_under(&self, point: (f32, f32)) -> Vec<ViewCoordinates> {
+        let mut views = Vec::new();
+
+        for child in self.child_views.iter().rev() {
+            let child = child.borrow();
+            if !child.visible {
+                continue;
+            }
+
+            let (view_x, view_y) = child.screen_to_view(point);
+            if !Rect::ndc().contains([view_x, view_y]) {
+                continue;
+            }
+
+            views.extend(child.child_views_under((view_x, view_y)));
+            views.push(ViewCoordinates {
+                view: child.id,
+                coordinates: (view_x, view_y),
+            });
+        }
+
+        views
+    }
+
+    /// Hidden views are neither rendered nor found by `GPUMultiView::view_at`.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Size of the render texture, available after the view was initialized.
+    pub fn texture_size(&self) -> Option<(u32, u32)> {
+        Some((self.texture_width?, self.texture_height?))
+    }
+
+    /// GPU memory allocated by the textures and vertex buffers of this view.
+    ///
+    /// Glyph atlases of the text brushes are shared through the `TextBrushRegistry` and not
+    /// included.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let textures = [
+            &self.resolve_texture,
+            &self.msaa_texture,
+            &self.pick_texture,
+        ];
+        let buffers = [
+            &self.render_vertices_buffer,
+            &self.render_indices_buffer,
+            &self.frame_vertices_buffer,
+            &self.pick_vertices_buffer,
+        ];
+
+        let usage = MemoryUsage {
+            texture_bytes: textures
+                .into_iter()
+                .flatten()
+                .map(texture_bytes)
+                .sum::<u64>()
+                + self.glow.as_ref().map_or(0, |glow| glow.memory_bytes()),
+            buffer_bytes: buffers
+                .into_iter()
+                .flatten()
+                .map(|buffer| buffer.size())
+                .sum::<u64>()
+                + self
+                    .gpu_passes
+                    .iter()
+                    .map(|pass| pass.borrow().memory_bytes())
+                    .sum::<u64>(),
+        };
+
+        self.child_views
+            .iter()
+            .fold(usage, |usage, child| usage + child.borrow().memory_usage())
+    }
+
+    /// Releases cache like resources which the current frame doesn't need: texture capacity
+    /// beyond the current size, the glow pass without glowing graphs and text brushes without
+    /// sections. Returns the number of evicted resources.
+    fn evict_caches(&mut self, device: &wgpu::Device) -> usize {
+        if !self.is_initialized {
+            return 0;
+        }
+
+        let mut evicted = 0;
+
+        if let (Some(size), Some(capacity)) = (self.texture_size(), self.texture_capacity) {
+            if size != capacity {
+                self.allocate_textures(device, size.0, size.1);
+                evicted += 1;
+            }
+        }
+
+        if self.glow_vertices.is_empty() && self.glow.take().is_some() {
+            evicted += 1;
+        }
+
+        for text_primitive in &mut self.text_primitives {
+            if text_primitive.evict_unused() {
+                evicted += 1;
+            }
+        }
+
+        for child in &self.child_views {
+            evicted += child.borrow_mut().evict_caches(device);
+        }
+
+        evicted
+    }
+
     pub fn clear_text_sections_and_fonts(&mut self) {
         self.text_primitives.clear();
     }
 
     pub fn clear_text_sections(&mut self) {
         for primitive in &mut self.text_primitives {
-            primitive.sections.clear();
+            primitive.clear_sections();
+        }
+    }
+
+    /// Removes previously added `sections`, compared by identity.
+    pub fn remove_text_sections(&mut self, sections: &[Arc<RefCell<TextSection>>]) {
+        for primitive in &mut self.text_primitives {
+            primitive.remove_sections(sections);
         }
     }
 
@@ -452,6 +1750,18 @@ impl GPUView {
         Ok(())
     }
 
+    /// Adds `font` unless a font with the same name was already added.
+    pub fn ensure_font(&mut self, font: &Font) {
+        if !self
+            .text_primitives
+            .iter()
+            .any(|p| p.font.name == font.name && p.rotation == 0.0)
+        {
+            self.text_primitives
+                .push(TextPrimitive::new(font.clone(), Vec::new()));
+        }
+    }
+
     pub fn add_text_section(
         &mut self,
         text_section: Arc<RefCell<TextSection>>,
@@ -460,18 +1770,210 @@ impl GPUView {
         let text_primitive = self
             .text_primitives
             .iter_mut()
-            .find(|p| p.font.name == font_name)
+            .find(|p| p.font.name == font_name && p.rotation == 0.0)
             .context("Specified font was not added yet.")?;
 
-        text_primitive.sections.push(text_section);
+        text_primitive.push_section(text_section);
 
         Ok(())
     }
 
+    /// Adds `text_section` like `add_text_section` and returns a handle to change its text
+    /// from any thread.
+    pub fn add_label(
+        &mut self,
+        text_section: TextSection,
+        font_name: &str,
+    ) -> anyhow::Result<LabelHandle> {
+        let text_primitive = self
+            .text_primitives
+            .iter_mut()
+            .find(|p| p.font.name == font_name && p.rotation == 0.0)
+            .context("Specified font was not added yet.")?;
+
+        Ok(text_primitive.add_label(text_section))
+    }
+
+    pub fn remove_label(&mut self, label: &LabelHandle) {
+        for primitive in &mut self.text_primitives {
+            primitive.remove_label(label);
+        }
+    }
+
+    /// Adds a section drawn rotated counterclockwise by `rotation` around its position,
+    /// alignments refer to the rotated text.
+    ///
+    /// Sections with the same font and rotation share a text brush (with the views of the
+    /// same `TextBrushRegistry`), which is released again while it has no sections.
+    pub fn add_rotated_text_section(
+        &mut self,
+        text_section: Arc<RefCell<TextSection>>,
+        font: &Font,
+        rotation: Radians,
+    ) {
+        let rotation = rotation.0 as f32;
+
+        let index = match self
+            .text_primitives
+            .iter()
+            .position(|p| p.font.name == font.name && p.rotation == rotation)
+        {
+            Some(index) => index,
+            None => {
+                let mut text_primitive = TextPrimitive::new(font.clone(), Vec::new());
+                text_primitive.rotation = rotation;
+                self.text_primitives.push(text_primitive);
+                self.text_primitives.len() - 1
+            }
+        };
+
+        self.text_primitives[index].push_section(text_section);
+    }
+
+    /// Single sampled views render directly into the resolve texture and have none.
+    fn create_msaa_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        multisample_state: wgpu::MultisampleState,
+    ) -> Option<wgpu::Texture> {
+        if multisample_state.count <= 1 {
+            return None;
+        }
+
+        Some(device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("GPUView MSAA Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: multisample_state.count,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Bgra8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        }))
+    }
+
+    const PICK_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    fn create_pick_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("GPUView Pick Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::PICK_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    fn initialize_picking(&mut self, device: &wgpu::Device) {
+        let pick_pipeline = self
+            .pipeline_cache
+            .get_or_create("GPUView Pick Pipeline", || {
+                Self::create_pick_pipeline(device)
+            });
+
+        let pick_vertices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("GPUView Pick Vertices Buffer"),
+            contents: bytemuck::cast_slice(self.pick_vertices.as_slice()),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let (capacity_width, capacity_height) = self.texture_capacity.unwrap();
+        self.pick_texture = Some(Self::create_pick_texture(
+            device,
+            capacity_width,
+            capacity_height,
+        ));
+        self.pick_vertices_buffer = Some(pick_vertices_buffer);
+        self.pick_pipeline = Some(pick_pipeline);
+        self.pick_vertices_changed = false;
+    }
+
+    fn create_pick_pipeline(device: &wgpu::Device) -> wgpu::RenderPipeline {
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("GPUView Pick Shader Module"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("pick.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("GPUView Pick Pipeline Layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("GPUView Pick Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[PickVertex::desc()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::PICK_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+            cache: None,
+        })
+    }
+
     pub fn initialize(
         &mut self,
         multiview: &GPUMultiView,
         device: &wgpu::Device,
+    ) -> anyhow::Result<()> {
+        let multiview_width = multiview
+            .width()
+            .context("Provided multiview was not initialized correctly.")?;
+        let multiview_height = multiview
+            .height()
+            .context("Provided multiview was not initialized correctly.")?;
+
+        self.pipeline_cache = multiview.pipeline_cache().clone();
+        self.text_brushes = multiview.text_brushes().clone();
+        self.initialize_for_surface((multiview_width, multiview_height), device)
+    }
+
+    /// Initializes the view with its frame resolved against `surface_size`, which is the
+    /// multiview size for top level views and the texture size of the parent for children.
+    fn initialize_for_surface(
+        &mut self,
+        surface_size: (u32, u32),
+        device: &wgpu::Device,
     ) -> anyhow::Result<()> {
         self.shader_descriptor.borrow_mut().initialize(device)?;
 
@@ -486,7 +1988,9 @@ impl GPUView {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        let frame_vertices = self.frame.frame_vertices();
+        self.frame_rect = self.frame.resolve(surface_size);
+
+        let frame_vertices = self.frame_vertices();
 
         let frame_vertices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("GPUView Frame Vertices Buffer"),
@@ -494,16 +1998,11 @@ impl GPUView {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        let multiview_width = multiview
-            .width()
-            .context("Provided multiview was not initialized correctly.")?;
-        let multiview_height = multiview
-            .height()
-            .context("Provided multiview was not initialized correctly.")?;
-        let (frame_relative_width, frame_relative_height) = self.frame.relative_dimensions();
+        let (texture_width, texture_height) = self.frame_texture_size(surface_size);
 
-        let texture_width = (multiview_width as f32 * frame_relative_width) as u32;
-        let texture_height = (multiview_height as f32 * frame_relative_height) as u32;
+        // textures can't be empty, a zero sized frame gets resized properly once it has an area
+        let texture_width = texture_width.max(1);
+        let texture_height = texture_height.max(1);
 
         let resolve_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("GPUView Resolve Texture"),
@@ -516,24 +2015,18 @@ impl GPUView {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Bgra8Unorm,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
 
-        let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("GPUView MSAA Texture"),
-            size: wgpu::Extent3d {
-                width: texture_width,
-                height: texture_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: self.multisample_state.count,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Bgra8Unorm,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
+        let msaa_texture = Self::create_msaa_texture(
+            device,
+            texture_width,
+            texture_height,
+            self.multisample_state,
+        );
 
         let resolve_texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -565,9 +2058,70 @@ impl GPUView {
             ],
         });
 
+        let render_pipeline = self.render_pipeline(device, &shader_bind_group_layout);
+
+        self.texture_width = Some(texture_width);
+        self.texture_height = Some(texture_height);
+        self.texture_capacity = Some((texture_width, texture_height));
+        self.resolve_texture = Some(resolve_texture);
+        self.msaa_texture = msaa_texture;
+        self.shader_bind_group = Some(shader_bind_group);
+        self.render_vertices_buffer = Some(render_vertices_buffer);
+        self.frame_vertices_buffer = Some(frame_vertices_buffer);
+        self.render_pipeline = Some(render_pipeline);
+        self.resolve_texture_sampler = Some(resolve_texture_sampler);
+        self.frame_bind_group_layout = Some(frame_bind_group_layout);
+        self.frame_bind_group = Some(frame_bind_group);
+        self.content_valid = false;
+        self.is_initialized = true;
+
+        Ok(())
+    }
+
+    /// Pipeline drawing the render vertices, shared through the pipeline cache by all views
+    /// with the same shader, polygon mode and multisample state.
+    fn render_pipeline(
+        &self,
+        device: &wgpu::Device,
+        shader_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let shader_descriptor = self.shader_descriptor.borrow();
+        let source = shader_descriptor.shader_source();
+        let polygon_mode = self.supported_polygon_mode(device);
+
+        let key = match &source {
+            wgpu::ShaderSource::Wgsl(wgsl) => {
+                let mut hasher = DefaultHasher::new();
+                wgsl.hash(&mut hasher);
+
+                Some(format!(
+                    "GPUView Render Pipeline {:x} {:?} {:?}",
+                    hasher.finish(),
+                    polygon_mode,
+                    self.multisample_state
+                ))
+            }
+            _ => None,
+        };
+        let create =
+            || self.create_render_pipeline(device, shader_bind_group_layout, source, polygon_mode);
+
+        match key {
+            Some(key) => self.pipeline_cache.get_or_create(&key, create),
+            None => Arc::new(create()),
+        }
+    }
+
+    fn create_render_pipeline(
+        &self,
+        device: &wgpu::Device,
+        shader_bind_group_layout: &wgpu::BindGroupLayout,
+        source: wgpu::ShaderSource,
+        polygon_mode: wgpu::PolygonMode,
+    ) -> wgpu::RenderPipeline {
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("GPUView Shader Module"),
-            source: self.shader_descriptor.borrow().shader_source(),
+            source,
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -576,7 +2130,7 @@ impl GPUView {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("GPUView Render Pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
@@ -590,42 +2144,28 @@ impl GPUView {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
+                polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
-            multisample: self.multisample_state,
-            fragment: Some(wgpu::FragmentState {
-                module: &shader_module,
-                entry_point: Some("fs_main"),
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-            cache: None,
-        });
-
-        self.texture_width = Some(texture_width);
-        self.texture_height = Some(texture_height);
-        self.resolve_texture = Some(resolve_texture);
-        self.msaa_texture = Some(msaa_texture);
-        self.shader_bind_group = Some(shader_bind_group);
-        self.render_vertices_buffer = Some(render_vertices_buffer);
-        self.frame_vertices_buffer = Some(frame_vertices_buffer);
-        self.render_pipeline = Some(render_pipeline);
-        self.resolve_texture_sampler = Some(resolve_texture_sampler);
-        self.frame_bind_group_layout = Some(frame_bind_group_layout);
-        self.frame_bind_group = Some(frame_bind_group);
-        self.is_initialized = true;
-
-        Ok(())
+            depth_stencil: None,
+            multisample: self.multisample_state,
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Bgra8Unorm,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+            cache: None,
+        })
     }
 
+    /// Only records the new size, the textures are updated lazily before the next render.
     pub fn resize(
         &mut self,
         multiview: &GPUMultiView,
@@ -637,10 +2177,90 @@ impl GPUView {
             ));
         }
 
-        let (frame_relative_width, frame_relative_height) = self.frame.relative_dimensions();
+        let multiview_size = (multiview.width().unwrap(), multiview.height().unwrap());
+        self.resize_for_surface(multiview_size, device);
+
+        Ok(())
+    }
+
+    /// See `initialize_for_surface`.
+    fn resize_for_surface(&mut self, surface_size: (u32, u32), device: &wgpu::Device) {
+        let frame_rect = self.frame.resolve(surface_size);
+        if frame_rect != self.frame_rect {
+            self.frame_rect = frame_rect;
+            self.frame_changed = true;
+        }
+
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        let (texture_width, texture_height) = self.frame_texture_size(surface_size);
+        let texture_width = texture_width.min(max_dimension);
+        let texture_height = texture_height.min(max_dimension);
+
+        if texture_width == 0 || texture_height == 0 {
+            self.resize_deferred = true;
+            return;
+        }
+        self.resize_deferred = false;
+
+        if self.texture_size() != Some((texture_width, texture_height)) {
+            self.texture_width = Some(texture_width);
+            self.texture_height = Some(texture_height);
+            self.textures_outdated = true;
+            self.frame_changed = true;
+        }
+    }
+
+    /// Textures grow in steps of this many pixels, so a live-resize doesn't reallocate them
+    /// on every event.
+    const TEXTURE_CAPACITY_STEP: u32 = 256;
+
+    /// Makes sure the textures can hold the current size. They are only reallocated when
+    /// growing beyond or shrinking well below (a quarter of the area) their capacity,
+    /// smaller sizes render into the upper left part.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(view = self.id.0))
+    )]
+    fn update_textures(&mut self, device: &wgpu::Device) {
+        if !self.textures_outdated {
+            return;
+        }
+        self.textures_outdated = false;
+
+        let (width, height) = self.texture_size().unwrap();
+        let (capacity_width, capacity_height) = self.texture_capacity.unwrap();
+
+        let fits = width <= capacity_width && height <= capacity_height;
+        let wasteful =
+            (width as u64 * height as u64) * 4 < capacity_width as u64 * capacity_height as u64;
+        if fits && !wasteful {
+            return;
+        }
+
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        let round_up = |size: u32| {
+            size.div_ceil(Self::TEXTURE_CAPACITY_STEP)
+                .saturating_mul(Self::TEXTURE_CAPACITY_STEP)
+                .min(max_dimension)
+                .max(size)
+        };
+        let (texture_width, texture_height) = match wasteful {
+            true => (width, height),
+            false => (round_up(width), round_up(height)),
+        };
+
+        self.allocate_textures(device, texture_width, texture_height);
+    }
 
-        let texture_width = (multiview.width().unwrap() as f32 * frame_relative_width) as u32;
-        let texture_height = (multiview.height().unwrap() as f32 * frame_relative_height) as u32;
+    /// Replaces resolve, MSAA and pick textures by ones with a capacity of
+    /// `texture_width` x `texture_height`.
+    fn allocate_textures(
+        &mut self,
+        device: &wgpu::Device,
+        texture_width: u32,
+        texture_height: u32,
+    ) {
+        self.content_valid = false;
 
         let resolve_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("GPUView Resolve Texture"),
@@ -653,24 +2273,18 @@ impl GPUView {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Bgra8Unorm,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
 
-        let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("GPUView MSAA Texture"),
-            size: wgpu::Extent3d {
-                width: texture_width,
-                height: texture_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: self.multisample_state.count,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Bgra8Unorm,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
+        let msaa_texture = Self::create_msaa_texture(
+            device,
+            texture_width,
+            texture_height,
+            self.multisample_state,
+        );
 
         let frame_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("GPUView Frame Bind Group"),
@@ -692,26 +2306,31 @@ impl GPUView {
         });
 
         self.resolve_texture.as_ref().unwrap().destroy();
-        self.msaa_texture.as_ref().unwrap().destroy();
-
-        self.texture_width = Some(texture_width);
-        self.texture_height = Some(texture_height);
-        self.resolve_texture = Some(resolve_texture);
-        self.msaa_texture = Some(msaa_texture);
-        self.frame_bind_group = Some(frame_bind_group);
+        if let Some(msaa_texture) = &self.msaa_texture {
+            msaa_texture.destroy();
+        }
 
-        for text_primitive in &mut self.text_primitives {
-            text_primitive.initialize(
+        if let Some(pick_texture) = &self.pick_texture {
+            pick_texture.destroy();
+            self.pick_texture = Some(Self::create_pick_texture(
                 device,
                 texture_width,
                 texture_height,
-                self.multisample_state,
-            )?;
+            ));
         }
 
-        Ok(())
+        self.texture_capacity = Some((texture_width, texture_height));
+        self.resolve_texture = Some(resolve_texture);
+        self.msaa_texture = msaa_texture;
+        self.frame_bind_group = Some(frame_bind_group);
+
+        self.frame_changed = true;
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(view = self.id.0))
+    )]
     pub fn update_buffers(
         &mut self,
         device: &wgpu::Device,
@@ -749,8 +2368,82 @@ impl GPUView {
             self.render_vertices_changed = false;
         }
 
+        if self.render_indices_changed {
+            let indices = self.render_indices.as_deref().unwrap_or_default();
+
+            let vertex_count = self.render_vertices.len();
+            if let Some(index) = indices
+                .iter()
+                .find(|&&index| index as usize >= vertex_count)
+            {
+                return Err(anyhow::anyhow!(
+                    "Render index {} is out of bounds for {} render vertices.",
+                    index,
+                    vertex_count
+                ));
+            }
+
+            let new_data: &[u8] = bytemuck::cast_slice(indices);
+
+            match &self.render_indices_buffer {
+                Some(buffer) if buffer.size() as usize == new_data.len() => {
+                    queue.write_buffer(buffer, 0, new_data);
+                }
+                buffer => {
+                    if let Some(buffer) = buffer {
+                        buffer.destroy();
+                    }
+                    // empty buffers can't be bound, so no indices are kept as `None`
+                    self.render_indices_buffer = (!new_data.is_empty()).then(|| {
+                        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("GPUView Render Indices Buffer"),
+                            contents: new_data,
+                            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+                        })
+                    });
+                }
+            }
+
+            self.render_indices_changed = false;
+        }
+
+        if self.pick_vertices_changed && self.pick_vertices_buffer.is_some() {
+            let new_data = bytemuck::cast_slice(self.pick_vertices.as_slice());
+
+            let buffer = self.pick_vertices_buffer.as_ref().unwrap();
+
+            match buffer.size() as usize == new_data.len() {
+                true => {
+                    queue.write_buffer(buffer, 0, new_data);
+                }
+                false => {
+                    buffer.destroy();
+                    self.pick_vertices_buffer = Some(device.create_buffer_init(
+                        &wgpu::util::BufferInitDescriptor {
+                            label: Some("GPUView Pick Vertices Buffer"),
+                            contents: new_data,
+                            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                        },
+                    ));
+                }
+            }
+
+            self.pick_vertices_changed = false;
+        }
+
         if self.frame_changed {
-            let frame_vertices = self.frame.frame_vertices();
+            let (width, height) = self.texture_size().unwrap();
+            let (capacity_width, capacity_height) = self.texture_capacity.unwrap();
+            let used = (
+                width as f32 / capacity_width as f32,
+                height as f32 / capacity_height as f32,
+            );
+
+            let mut frame_vertices = self.frame_vertices();
+            for vertex in &mut frame_vertices {
+                vertex.tex_coords[0] *= used.0;
+                vertex.tex_coords[1] *= used.1;
+            }
             let new_data = bytemuck::cast_slice(frame_vertices.as_slice());
 
             let buffer = self.frame_vertices_buffer.as_ref().unwrap();
@@ -777,6 +2470,10 @@ impl GPUView {
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(view = self.id.0))
+    )]
     fn render(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
@@ -787,8 +2484,49 @@ impl GPUView {
             return Err(anyhow::Error::msg("Cannot render uninitialized view."));
         }
 
+        self.resolve_pick_readbacks();
+
+        // the texture of the last refresh gets composited again
+        if !self.is_refresh_due() {
+            self.frames_since_refresh += 1;
+            return Ok(());
+        }
+        self.frames_since_refresh = 0;
+
+        if self.pick_enabled && self.pick_pipeline.is_none() {
+            self.initialize_picking(device);
+        }
+
+        self.update_textures(device);
         self.update_buffers(device, queue)?;
 
+        let debug_label = self.debug_label();
+        encoder.push_debug_group(&debug_label);
+        let result = self.render_passes(&debug_label, encoder, device, queue);
+        encoder.pop_debug_group();
+
+        let result = result.and_then(|(resolve_texture_view, drew_text)| {
+            // the shared text brushes get the sections of the next view queued
+            if drew_text {
+                Self::flush_encoder(encoder, device, queue);
+            }
+
+            self.render_child_views(&resolve_texture_view, encoder, device, queue)
+        });
+
+        self.content_valid = result.is_ok();
+        result
+    }
+
+    /// Returns the view of the resolve texture and whether text was drawn.
+
+    fn render_passes(
+        &mut self,
+        debug_label: &str,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<(wgpu::TextureView, bool)> {
         let render_width = self.texture_width.unwrap();
         let render_height = self.texture_height.unwrap();
 
@@ -798,45 +2536,94 @@ impl GPUView {
             .unwrap()
             .create_view(&wgpu::TextureViewDescriptor::default());
 
+        #[cfg(feature = "tracing")]
+        let text_span = tracing::debug_span!("queue_text", view = self.id.0).entered();
+
+        let pixel_density = self.pixel_density();
         for text_primitive in &mut self.text_primitives {
-            if !text_primitive.is_initialized {
-                text_primitive.initialize(
-                    device,
-                    render_width,
-                    render_height,
-                    self.multisample_state,
-                )?;
+            if !text_primitive.is_initialized && text_primitive.sections.is_empty() {
+                continue;
             }
 
-            let sections = text_primitive.create_sections(render_width, render_height);
-            let sections = sections.iter().map(|section| section).collect::<Vec<_>>();
+            text_primitive.prepare(
+                &self.text_brushes,
+                device,
+                queue,
+                render_width,
+                render_height,
+                self.multisample_state,
+            )?;
 
             text_primitive
-                .brush
-                .as_mut()
-                .unwrap()
-                .queue(device, queue, sections)
+                .queue_sections(device, queue, render_width, render_height, pixel_density)
                 .unwrap();
         }
 
+        #[cfg(feature = "tracing")]
+        drop(text_span);
+
+        let glowing = !self.glow_vertices.is_empty();
+        if glowing {
+            if self
+                .glow
+                .as_ref()
+                .is_none_or(|glow| glow.size() != (render_width, render_height))
+            {
+                self.glow = Some(GlowPass::new(
+                    (render_width, render_height),
+                    &*self.shader_descriptor.borrow(),
+                    self.multisample_state,
+                    device,
+                )?);
+            }
+
+            encoder.push_debug_group("Glow");
+            let glow_radius = self.glow_radius * self.pixel_density();
+            self.glow.as_mut().unwrap().render(
+                &self.glow_vertices,
+                glow_radius,
+                encoder,
+                device,
+                queue,
+            );
+            encoder.pop_debug_group();
+        }
+
+        for pass in &self.gpu_passes {
+            pass.borrow_mut().prepare(
+                (render_width, render_height),
+                self.multisample_state,
+                &self.pipeline_cache,
+                encoder,
+                device,
+                queue,
+            )?;
+        }
+
+        let text_brushes = self
+            .text_primitives
+            .iter()
+            .filter_map(|primitive| primitive.brush.as_ref())
+            .map(|brush| brush.lock().unwrap())
+            .collect::<Vec<_>>();
+
         {
             let shader_bind_group = self.shader_bind_group.as_ref().unwrap();
 
             let msaa_texture_view = self
                 .msaa_texture
                 .as_ref()
-                .unwrap()
-                .create_view(&wgpu::TextureViewDescriptor::default());
+                .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
 
             let render_pipeline = self.render_pipeline.as_ref().unwrap();
 
             let render_vertices_buffer = self.render_vertices_buffer.as_ref().unwrap();
 
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("GPUView Render Pass"),
+                label: Some(&format!("{} Render Pass", debug_label)),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &msaa_texture_view,
-                    resolve_target: Some(&resolve_texture_view),
+                    view: msaa_texture_view.as_ref().unwrap_or(&resolve_texture_view),
+                    resolve_target: msaa_texture_view.as_ref().map(|_| &resolve_texture_view),
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(self.clear_color),
                         store: wgpu::StoreOp::Store,
@@ -847,30 +2634,356 @@ impl GPUView {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(render_pipeline);
-            render_pass.set_bind_group(0, shader_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, render_vertices_buffer.slice(..));
-            render_pass.draw(0..self.render_vertices.len() as u32, 0..1);
-
-            for text_primitive in &self.text_primitives {
-                text_primitive
-                    .brush
-                    .as_ref()
-                    .unwrap()
-                    .draw(&mut render_pass);
+            render_pass.set_viewport(
+                0.0,
+                0.0,
+                render_width as f32,
+                render_height as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_scissor_rect(0, 0, render_width, render_height);
+
+            if let (true, Some(glow)) = (glowing, &self.glow) {
+                glow.composite(&mut render_pass);
+            }
+
+            for pass in &self.gpu_passes {
+                pass.borrow().draw(&mut render_pass);
+            }
+
+            // empty buffers can't be bound, e.g. a child view rendered before its first display
+            if !self.render_vertices.is_empty() {
+                render_pass.set_pipeline(render_pipeline);
+                render_pass.set_bind_group(0, shader_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, render_vertices_buffer.slice(..));
+
+                match (&self.render_indices, &self.render_indices_buffer) {
+                    (Some(indices), Some(buffer)) => {
+                        render_pass.set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+                    }
+                    (Some(_), None) => {}
+                    (None, _) => render_pass.draw(0..self.render_vertices.len() as u32, 0..1),
+                }
+            }
+
+            for brush in &text_brushes {
+                brush.draw(&mut render_pass);
+            }
+        }
+
+        let drew_text = !text_brushes.is_empty();
+        drop(text_brushes);
+
+        if self.pick_enabled {
+            self.render_pick(debug_label, encoder, device);
+        }
+
+        Ok((resolve_texture_view, drew_text))
+    }
+
+    /// Submits the commands recorded so far and continues with a new encoder, so buffer
+    /// writes afterwards don't affect them.
+    fn flush_encoder(
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let recorded = std::mem::replace(
+            encoder,
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Multiview Frame Encoder"),
+            }),
+        );
+        queue.submit(std::iter::once(recorded.finish()));
+    }
+
+    /// Renders the visible children and composites them into `target`, the resolve texture.
+    fn render_child_views(
+        &mut self,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<()> {
+        if self.child_views.is_empty() {
+            return Ok(());
+        }
+
+        let render_width = self.texture_width.unwrap();
+        let render_height = self.texture_height.unwrap();
+        let pixel_density = self.pixel_density();
+
+        // view textures are premultiplied, they were cleared transparent and blended onto
+        let pipeline = self.child_pipeline.get_or_insert_with(|| {
+            GPUMultiView::composite_pipeline(
+                &self.pipeline_cache,
+                device,
+                wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            )
+        });
+
+        for child in &self.child_views {
+            let mut child = child.borrow_mut();
+            if !child.visible {
+                continue;
+            }
+
+            child.surface_scale = pixel_density;
+            match child.is_initialized {
+                true => child.resize_for_surface((render_width, render_height), device),
+                false => {
+                    child.pipeline_cache = self.pipeline_cache.clone();
+                    child.text_brushes = self.text_brushes.clone();
+                    child.initialize_for_surface((render_width, render_height), device)?
+                }
+            }
+            if child.resize_deferred {
+                continue;
+            }
+
+            child.render(encoder, device, queue)?;
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&format!("Composite {} Pass", child.debug_label())),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_viewport(
+                0.0,
+                0.0,
+                render_width as f32,
+                render_height as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_scissor_rect(0, 0, render_width, render_height);
+
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, child.frame_bind_group.as_ref().unwrap(), &[]);
+            render_pass
+                .set_vertex_buffer(0, child.frame_vertices_buffer.as_ref().unwrap().slice(..));
+            render_pass.draw(0..child.frame_vertices().len() as u32, 0..1);
+        }
+
+        Ok(())
+    }
+
+    fn render_pick(
+        &mut self,
+        debug_label: &str,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+    ) {
+        let pick_texture = self.pick_texture.as_ref().unwrap();
+
+        {
+            let pick_texture_view =
+                pick_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&format!("{} Pick Pass", debug_label)),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &pick_texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            let (width, height) = self.texture_size().unwrap();
+            render_pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+            render_pass.set_scissor_rect(0, 0, width, height);
+
+            // empty buffers can't be bound, the pick texture is only cleared then
+            if !self.pick_vertices.is_empty() {
+                render_pass.set_pipeline(self.pick_pipeline.as_ref().unwrap());
+                render_pass
+                    .set_vertex_buffer(0, self.pick_vertices_buffer.as_ref().unwrap().slice(..));
+
+                match (&self.render_indices, &self.render_indices_buffer) {
+                    // vertices added before picking was enabled have no pick vertex
+                    (Some(indices), Some(buffer))
+                        if self.pick_vertices.len() == self.render_vertices.len() =>
+                    {
+                        render_pass.set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+                    }
+                    (Some(_), _) => {}
+                    (None, _) => render_pass.draw(0..self.pick_vertices.len() as u32, 0..1),
+                }
+            }
+        }
+
+        for readback in self
+            .pick_readbacks
+            .iter_mut()
+            .filter(|r| r.buffer.is_none())
+        {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GPUView Pick Readback Buffer"),
+                size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture: pick_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: readback.texel.0,
+                        y: readback.texel.1,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            readback.buffer = Some(buffer);
+        }
+    }
+
+    /// Maps the readback buffers (of picks and GPU passes) which were written by the last
+    /// submitted frame.
+    fn after_submit(&mut self) {
+        for child in &self.child_views {
+            child.borrow_mut().after_submit();
+        }
+
+        for pass in &self.gpu_passes {
+            pass.borrow_mut().after_submit();
+        }
+
+        for readback in &mut self.pick_readbacks {
+            let Some(buffer) = &readback.buffer else {
+                continue;
+            };
+
+            if readback.map_requested {
+                continue;
             }
+
+            let mapped = readback.mapped.clone();
+            buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    *mapped.lock().unwrap() = Some(result.is_ok());
+                });
+
+            readback.map_requested = true;
         }
+    }
+
+    fn resolve_pick_readbacks(&mut self) {
+        self.pick_readbacks.retain(|readback| {
+            let Some(mapped) = *readback.mapped.lock().unwrap() else {
+                return true;
+            };
+
+            let buffer = readback.buffer.as_ref().unwrap();
+            // a failed readback resolves as if nothing was hit
+            let id = match mapped {
+                true => {
+                    let id = {
+                        let data = buffer.slice(..).get_mapped_range();
+                        bytemuck::pod_read_unaligned::<u32>(&data[0..4])
+                    };
+                    buffer.unmap();
+                    id
+                }
+                false => 0,
+            };
+            buffer.destroy();
+
+            *readback.result.lock().unwrap() = Some(id);
 
-        Ok(())
+            false
+        });
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct ViewCoordinates {
-    pub view_index: usize,
+    pub view: ViewId,
     pub coordinates: (f32, f32),
 }
 
+/// Bytes of GPU memory allocated for textures and buffers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub texture_bytes: u64,
+    pub buffer_bytes: u64,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> u64 {
+        self.texture_bytes + self.buffer_bytes
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            texture_bytes: self.texture_bytes + rhs.texture_bytes,
+            buffer_bytes: self.buffer_bytes + rhs.buffer_bytes,
+        }
+    }
+}
+
+/// Memory accounting of the last frame rendered by a `GPUMultiView`.
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    /// Memory of every rendered view
+    pub views: Vec<(ViewId, MemoryUsage)>,
+    /// Target and post process textures of the multiview itself
+    pub multiview: MemoryUsage,
+    /// Textures of the shared `TextureRegistry`
+    pub registry: MemoryUsage,
+    /// Resources released to stay within the memory budget
+    pub evicted: usize,
+}
+
+impl FrameStats {
+    pub fn total(&self) -> MemoryUsage {
+        self.views
+            .iter()
+            .map(|(_, usage)| *usage)
+            .fold(self.multiview + self.registry, Add::add)
+    }
+}
+
 pub struct GPUMultiView<'a> {
     clear_color: wgpu::Color,
 
@@ -878,9 +2991,29 @@ pub struct GPUMultiView<'a> {
 
     text_primitives: Vec<TextPrimitive>,
 
+    font_registry: FontRegistry,
+    texture_registry: TextureRegistry,
+    pipeline_cache: PipelineCache,
+    text_brushes: TextBrushRegistry,
+
     surface: Option<wgpu::Surface<'a>>,
     surface_config: Option<wgpu::SurfaceConfiguration>,
-    render_pipeline: Option<wgpu::RenderPipeline>,
+    offscreen_texture: Option<wgpu::Texture>,
+    render_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+
+    post_effects: Vec<PostEffect>,
+    color_vision_filter: ColorVisionFilter,
+    post_process: PostProcessChain,
+
+    /// Set while the surface has a zero dimension (e.g. minimized window), rendering is skipped.
+    suspended: bool,
+    /// Set between `release_surface` and `restore_surface`, rendering is skipped.
+    surface_released: bool,
+    capture_next_frame: bool,
+    deterministic: bool,
+
+    memory_budget: Option<u64>,
+    frame_stats: FrameStats,
 
     is_initialized: bool,
 }
@@ -927,17 +3060,88 @@ impl<'a> GPUMultiView<'a> {
             clear_color,
             render_views: Vec::new(),
             text_primitives: Vec::new(),
+            font_registry: FontRegistry::shared(),
+            texture_registry: TextureRegistry::new(),
+            pipeline_cache: PipelineCache::new(),
+            text_brushes: TextBrushRegistry::new(),
             surface: None,
             surface_config: None,
+            offscreen_texture: None,
             render_pipeline: None,
+            post_effects: Vec::new(),
+            color_vision_filter: ColorVisionFilter::None,
+            post_process: PostProcessChain::new(),
+            suspended: false,
+            surface_released: false,
+            capture_next_frame: false,
+            deterministic: false,
+            memory_budget: None,
+            frame_stats: FrameStats::default(),
             is_initialized: false,
         }
     }
 
+    /// Multiview which only knows its size, for testing the coordinate conversions without
+    /// a device.
+    #[cfg(test)]
+    pub(crate) fn with_size(width: u32, height: u32) -> Self {
+        let mut multiview = Self::new();
+        multiview.surface_config = Some(wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8Unorm,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        });
+
+        multiview
+    }
+
     pub fn width(&self) -> Option<u32> {
         Some(self.surface_config.as_ref()?.width)
     }
 
+    pub fn font_registry(&self) -> &FontRegistry {
+        &self.font_registry
+    }
+
+    pub fn set_font_registry(&mut self, font_registry: FontRegistry) {
+        self.font_registry = font_registry;
+    }
+
+    pub fn set_texture_registry(&mut self, texture_registry: TextureRegistry) {
+        self.texture_registry = texture_registry;
+    }
+
+    pub fn texture_registry(&self) -> &TextureRegistry {
+        &self.texture_registry
+    }
+
+    /// Cache the pipelines of the multiview and its views are created through.
+    ///
+    /// Pipelines are only valid for one device, so views of a cache must share it.
+    pub fn set_pipeline_cache(&mut self, pipeline_cache: PipelineCache) {
+        self.pipeline_cache = pipeline_cache;
+    }
+
+    pub fn pipeline_cache(&self) -> &PipelineCache {
+        &self.pipeline_cache
+    }
+
+    /// Registry the text brushes of the multiview and its views are borrowed from.
+    ///
+    /// Like pipelines, brushes are only valid for one device.
+    pub fn set_text_brushes(&mut self, text_brushes: TextBrushRegistry) {
+        self.text_brushes = text_brushes;
+    }
+
+    pub fn text_brushes(&self) -> &TextBrushRegistry {
+        &self.text_brushes
+    }
+
     pub fn height(&self) -> Option<u32> {
         Some(self.surface_config.as_ref()?.height)
     }
@@ -948,6 +3152,206 @@ impl<'a> GPUMultiView<'a> {
         surface_config: wgpu::SurfaceConfiguration,
         device: &wgpu::Device,
     ) {
+        self.suspended = surface_config.width == 0 || surface_config.height == 0;
+        self.surface = Some(surface);
+        self.surface_config = Some(surface_config);
+        self.render_pipeline = Some(Self::composite_pipeline(
+            &self.pipeline_cache,
+            device,
+            wgpu::BlendState::REPLACE,
+        ));
+        self.is_initialized = true;
+    }
+
+    /// Initializes the multiview to render into an owned texture instead of a surface.
+    /// The result can be read back with `read_pixels`.
+    pub fn initialize_offscreen(&mut self, width: u32, height: u32, device: &wgpu::Device) {
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Bgra8Unorm,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        self.offscreen_texture = Some(Self::create_offscreen_texture(&surface_config, device));
+        self.surface = None;
+        self.surface_config = Some(surface_config);
+        self.render_pipeline = Some(Self::composite_pipeline(
+            &self.pipeline_cache,
+            device,
+            wgpu::BlendState::REPLACE,
+        ));
+        self.is_initialized = true;
+    }
+
+    pub fn is_offscreen(&self) -> bool {
+        self.offscreen_texture.is_some()
+    }
+
+    /// Whether rendering is paused because the target has a zero dimension or the surface was released.
+    ///
+    /// The next `resize` to a non zero size or `restore_surface` resumes it.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended || self.surface_released
+    }
+
+    /// Renders every view single sampled and waits for each frame to finish before returning,
+    /// so repeated renders of the same content produce identical pixels.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Once the memory of a frame exceeds `budget` bytes, cache like resources are released:
+    /// unused registry textures and fonts, spare texture capacity of the views, unused glow
+    /// passes and post process textures and text brushes without sections.
+    pub fn set_memory_budget(&mut self, budget: Option<u64>) {
+        self.memory_budget = budget;
+    }
+
+    pub fn memory_budget(&self) -> Option<u64> {
+        self.memory_budget
+    }
+
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.frame_stats
+    }
+
+    fn update_frame_stats(&mut self) {
+        let target_bytes = self.offscreen_texture.as_ref().map_or(0, texture_bytes);
+
+        self.frame_stats = FrameStats {
+            views: self
+                .render_views
+                .iter()
+                .map(|view| {
+                    let view = view.borrow();
+                    (view.id, view.memory_usage())
+                })
+                .collect(),
+            multiview: MemoryUsage {
+                texture_bytes: target_bytes + self.post_process.memory_bytes(),
+                buffer_bytes: 0,
+            },
+            registry: MemoryUsage {
+                texture_bytes: self.texture_registry.memory_bytes(),
+                buffer_bytes: 0,
+            },
+            evicted: 0,
+        };
+    }
+
+    /// Releases cache like resources until the frame fits into the memory budget again.
+    fn enforce_memory_budget(&mut self, device: &wgpu::Device) {
+        let Some(budget) = self.memory_budget else {
+            return;
+        };
+
+        if self.frame_stats.total().total() <= budget {
+            return;
+        }
+
+        let mut evicted = self.texture_registry.remove_unused();
+        evicted += self.font_registry.remove_unused();
+        evicted += self.post_process.evict_unused() as usize;
+
+        for text_primitive in &mut self.text_primitives {
+            evicted += text_primitive.evict_unused() as usize;
+        }
+
+        for render_view in &self.render_views {
+            evicted += render_view.borrow_mut().evict_caches(device);
+        }
+
+        // after the views released the brushes they don't draw with anymore
+        evicted += self.text_brushes.remove_unused();
+
+        self.update_frame_stats();
+        self.frame_stats.evicted = evicted;
+    }
+
+    /// Wraps the next rendered frame in a GPU capture, picked up by an attached
+    /// graphics debugger (RenderDoc, Xcode, PIX).
+    pub fn capture_next_frame(&mut self) {
+        self.capture_next_frame = true;
+    }
+
+    /// Drops the window surface, e.g. when the application gets suspended
+    /// (Android destroys the native window on suspend).
+    ///
+    /// Views, text and post effects are kept, rendering is skipped until `restore_surface`.
+    pub fn release_surface(&mut self) {
+        if self.surface.take().is_some() {
+            self.surface_released = true;
+        }
+    }
+
+    /// Presents to a newly created surface after `release_surface`.
+    pub fn restore_surface(
+        &mut self,
+        surface: wgpu::Surface<'a>,
+        surface_config: wgpu::SurfaceConfiguration,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<()> {
+        if !self.is_initialized {
+            return Err(anyhow::Error::msg(
+                "Cannot restore surface of uninitialized multiview.",
+            ));
+        }
+
+        let (width, height) = (surface_config.width, surface_config.height);
+
+        self.surface = Some(surface);
+        self.surface_config = Some(surface_config);
+        self.surface_released = false;
+
+        // configures the surface and resizes the views in case the window size changed meanwhile
+        self.resize(width, height, device)
+    }
+
+    fn create_offscreen_texture(
+        surface_config: &wgpu::SurfaceConfiguration,
+        device: &wgpu::Device,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multiview Offscreen Texture"),
+            size: wgpu::Extent3d {
+                width: surface_config.width,
+                height: surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: surface_config.format,
+            usage: surface_config.usage,
+            view_formats: &[],
+        })
+    }
+
+    /// Pipeline drawing the texture of a view into its frame, cached per blend state.
+    fn composite_pipeline(
+        pipeline_cache: &PipelineCache,
+        device: &wgpu::Device,
+        blend: wgpu::BlendState,
+    ) -> Arc<wgpu::RenderPipeline> {
+        pipeline_cache.get_or_create(
+            &format!("GPUMultiView Composite Pipeline {:?}", blend),
+            || Self::create_composite_pipeline(device, blend),
+        )
+    }
+
+    fn create_composite_pipeline(
+        device: &wgpu::Device,
+        blend: wgpu::BlendState,
+    ) -> wgpu::RenderPipeline {
         let bind_group_layout =
             device.create_bind_group_layout(&GPUView::FRAME_BIND_GROUP_LAYOUT_DESCIPTOR);
 
@@ -963,7 +3367,7 @@ impl<'a> GPUMultiView<'a> {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Render Pipeline"),
             layout: Some(&render_pipeline_layout),
             vertex: wgpu::VertexState {
@@ -977,7 +3381,7 @@ impl<'a> GPUMultiView<'a> {
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
@@ -999,12 +3403,7 @@ impl<'a> GPUMultiView<'a> {
             },
             multiview: None,
             cache: None,
-        });
-
-        self.surface = Some(surface);
-        self.surface_config = Some(surface_config);
-        self.render_pipeline = Some(render_pipeline);
-        self.is_initialized = true;
+        })
     }
 
     pub fn resize(
@@ -1017,31 +3416,32 @@ impl<'a> GPUMultiView<'a> {
             return Err(anyhow::Error::msg("Cannot resize uninitialized multiview."));
         }
 
+        // surfaces and textures can't be configured empty, keep the last valid size until restored
+        if new_width == 0 || new_height == 0 {
+            self.suspended = true;
+            return Ok(());
+        }
+        self.suspended = false;
+
         let surface_config = self.surface_config.as_mut().unwrap();
 
         surface_config.width = new_width;
         surface_config.height = new_height;
 
-        self.surface
-            .as_ref()
-            .unwrap()
-            .configure(device, surface_config);
+        if self.surface_released {
+            return Ok(());
+        }
 
-        for render_view in &self.render_views {
-            render_view.borrow_mut().resize(self, device)?;
+        match &self.surface {
+            Some(surface) => surface.configure(device, surface_config),
+            None => {
+                self.offscreen_texture =
+                    Some(Self::create_offscreen_texture(surface_config, device))
+            }
         }
 
-        for text_primitive in &mut self.text_primitives {
-            text_primitive.initialize(
-                device,
-                new_width,
-                new_height,
-                wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-            )?;
+        for render_view in &self.render_views {
+            render_view.borrow_mut().resize(self, device)?;
         }
 
         Ok(())
@@ -1051,6 +3451,37 @@ impl<'a> GPUMultiView<'a> {
         self.clear_color = clear_color;
     }
 
+    /// Sets the chain of full screen passes applied (in order) to the composed views and text
+    /// before presenting.
+    pub fn set_post_effects(&mut self, effects: Vec<PostEffect>) {
+        self.post_effects = effects;
+        self.update_post_process();
+    }
+
+    pub fn post_effects(&self) -> &[PostEffect] {
+        &self.post_effects
+    }
+
+    /// Previews the whole multiview as seen with a color vision deficiency,
+    /// applied after all other post effects.
+    pub fn set_color_vision_filter(&mut self, filter: ColorVisionFilter) {
+        self.color_vision_filter = filter;
+        self.update_post_process();
+    }
+
+    pub fn color_vision_filter(&self) -> ColorVisionFilter {
+        self.color_vision_filter
+    }
+
+    fn update_post_process(&mut self) {
+        let mut effects = self.post_effects.clone();
+        if self.color_vision_filter != ColorVisionFilter::None {
+            effects.push(PostEffect::ColorVision(self.color_vision_filter));
+        }
+
+        self.post_process.set_effects(effects);
+    }
+
     pub fn set_render_views(&mut self, views: Vec<Arc<RefCell<GPUView>>>) {
         self.render_views = views;
     }
@@ -1061,7 +3492,7 @@ impl<'a> GPUMultiView<'a> {
 
     pub fn clear_text_sections(&mut self) {
         for primitive in &mut self.text_primitives {
-            primitive.sections.clear();
+            primitive.clear_sections();
         }
     }
 
@@ -1100,42 +3531,105 @@ impl<'a> GPUMultiView<'a> {
         let text_primitive = self
             .text_primitives
             .iter_mut()
-            .find(|p| p.font.name == font_name)
+            .find(|p| p.font.name == font_name && p.rotation == 0.0)
             .context("Specified font was not added yet.")?;
 
-        text_primitive.sections.push(text_section);
+        text_primitive.push_section(text_section);
 
         Ok(())
     }
 
-    pub fn get_view_coords_behind(&self, point: (f32, f32)) -> Option<ViewCoordinates> {
-        let x = point.0;
-        let y = point.1;
+    /// Adds `text_section` like `add_text_section` and returns a handle to change its text
+    /// from any thread.
+    pub fn add_label(
+        &mut self,
+        text_section: TextSection,
+        font_name: &str,
+    ) -> anyhow::Result<LabelHandle> {
+        let text_primitive = self
+            .text_primitives
+            .iter_mut()
+            .find(|p| p.font.name == font_name && p.rotation == 0.0)
+            .context("Specified font was not added yet.")?;
+
+        Ok(text_primitive.add_label(text_section))
+    }
 
-        let mut view_coords = None;
-        for i in 0..self.render_views.len() {
-            let view = self.render_views[i].borrow();
+    pub fn remove_label(&mut self, label: &LabelHandle) {
+        for primitive in &mut self.text_primitives {
+            primitive.remove_label(label);
+        }
+    }
 
-            let frame_ul = view.frame.upper_left();
-            let frame_lr = view.frame.lower_right();
+    /// Maps physical pixel coordinates (origin in the upper left corner) to screen space (`-1..1`).
+    pub fn pixel_to_screen(&self, pixel: (f64, f64)) -> Option<(f32, f32)> {
+        let (width, height) = (self.width()?, self.height()?);
+        if width == 0 || height == 0 {
+            return None;
+        }
 
-            let view_x = lerp(x, &(frame_ul.0..frame_lr.0), &(-1.0..1.0));
-            let view_y = lerp(y, &(frame_ul.1..frame_lr.1), &(1.0..-1.0));
+        let x = (pixel.0 / width as f64) * 2.0 - 1.0;
+        let y = -((pixel.1 / height as f64) * 2.0 - 1.0);
 
-            if view_x >= -1.0 && view_x <= 1.0 && view_y >= -1.0 && view_y <= 1.0 {
-                view_coords = Some(ViewCoordinates {
-                    view_index: i,
-                    coordinates: (view_x, view_y),
-                })
+        Some((x as f32, y as f32))
+    }
+
+    /// Maps screen space (`-1..1`) to physical pixel coordinates (origin in the upper left corner).
+    pub fn screen_to_pixel(&self, point: (f32, f32)) -> Option<(f64, f64)> {
+        let (width, height) = (self.width()?, self.height()?);
+
+        let x = (point.0 as f64 + 1.0) / 2.0 * width as f64;
+        let y = (1.0 - point.1 as f64) / 2.0 * height as f64;
+
+        Some((x, y))
+    }
+
+    /// View `id` among the render views and their children.
+    pub fn view(&self, id: ViewId) -> Option<Arc<RefCell<GPUView>>> {
+        self.render_views
+            .iter()
+            .find_map(|view| match view.borrow().id == id {
+                true => Some(view.clone()),
+                false => view.borrow().find_child_view(id),
+            })
+    }
+
+    /// Topmost visible view at `point` (screen space `-1..1`).
+    pub fn view_at(&self, point: (f32, f32)) -> Option<ViewCoordinates> {
+        self.views_under(point).into_iter().next()
+    }
+
+    /// All visible views at `point` (screen space `-1..1`), topmost first.
+    ///
+    /// Views are drawn in the order of `set_render_views`, so later views are on top.
+    /// Child views are on top of their parent and reported before it.
+    pub fn views_under(&self, point: (f32, f32)) -> Vec<ViewCoordinates> {
+        let mut views = Vec::new();
+
+        for view in self.render_views.iter().rev() {
+            let view = view.borrow();
+            if !view.visible {
+                continue;
+            }
+
+            let (view_x, view_y) = view.screen_to_view(point);
+            if !Rect::ndc().contains([view_x, view_y]) {
+                continue;
             }
+
+            views.extend(view.child_views_under((view_x, view_y)));
+            views.push(ViewCoordinates {
+                view: view.id,
+                coordinates: (view_x, view_y),
+            });
         }
 
-        view_coords
+        views
     }
 
     fn clear_surface(&self, view: &wgpu::TextureView, encoder: &mut wgpu::CommandEncoder) {
         let _clear_render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("GPUView Render Pass"),
+            label: Some("Multiview Clear Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: &view,
                 resolve_target: None,
@@ -1167,7 +3661,10 @@ impl<'a> GPUMultiView<'a> {
         let frame_vertices_buffer = render_view.frame_vertices_buffer.as_ref().unwrap();
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Multiview Render Pass"),
+            label: Some(&format!(
+                "Multiview Composite {} Pass",
+                render_view.debug_label()
+            )),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: &view,
                 resolve_target: None,
@@ -1184,11 +3681,12 @@ impl<'a> GPUMultiView<'a> {
         render_pass.set_pipeline(self.render_pipeline.as_ref().unwrap());
         render_pass.set_bind_group(0, frame_bind_group, &[]);
         render_pass.set_vertex_buffer(0, frame_vertices_buffer.slice(..));
-        render_pass.draw(0..render_view.frame.frame_vertices().len() as u32, 0..1);
+        render_pass.draw(0..render_view.frame_vertices().len() as u32, 0..1);
 
         Ok(())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     fn render_text(
         &mut self,
         view: &wgpu::TextureView,
@@ -1206,32 +3704,36 @@ impl<'a> GPUMultiView<'a> {
         let render_height = self.height().unwrap();
 
         for text_primitive in &mut self.text_primitives {
-            if !text_primitive.is_initialized {
-                text_primitive.initialize(
-                    device,
-                    render_width,
-                    render_height,
-                    wgpu::MultisampleState {
-                        count: 1,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                )?;
+            if !text_primitive.is_initialized && text_primitive.sections.is_empty() {
+                continue;
             }
 
-            let sections = text_primitive.create_sections(render_width, render_height);
-            let sections = sections.iter().map(|section| section).collect::<Vec<_>>();
+            text_primitive.prepare(
+                &self.text_brushes,
+                device,
+                queue,
+                render_width,
+                render_height,
+                wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+            )?;
 
-            text_primitive
-                .brush
-                .as_mut()
-                .unwrap()
-                .queue(device, queue, sections)?;
+            text_primitive.queue_sections(device, queue, render_width, render_height, 1.0)?;
         }
 
+        let text_brushes = self
+            .text_primitives
+            .iter()
+            .filter_map(|primitive| primitive.brush.as_ref())
+            .map(|brush| brush.lock().unwrap())
+            .collect::<Vec<_>>();
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("GPUView Render Pass"),
+                label: Some("Multiview Text Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
                     resolve_target: None,
@@ -1245,41 +3747,68 @@ impl<'a> GPUMultiView<'a> {
                 occlusion_query_set: None,
             });
 
-            for text_primitive in &self.text_primitives {
-                text_primitive
-                    .brush
-                    .as_ref()
-                    .unwrap()
-                    .draw(&mut render_pass);
+            for brush in &text_brushes {
+                brush.draw(&mut render_pass);
             }
         }
 
         Ok(())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> anyhow::Result<()> {
         if !self.is_initialized {
             return Err(anyhow::Error::msg("Cannot render uninitialized multiview."));
         }
 
-        let output = self
-            .surface
-            .as_ref()
-            .unwrap()
-            .get_current_texture()
-            .unwrap();
+        if self.is_suspended() {
+            return Ok(());
+        }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let output = match &self.surface {
+            Some(surface) => Some(surface.get_current_texture()?),
+            None => None,
+        };
+
+        let target_texture = match &output {
+            Some(output) => &output.texture,
+            None => self.offscreen_texture.as_ref().unwrap(),
+        };
+
+        let target_view = target_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // with post effects everything is composed into an intermediate texture first
+        let post_processing = !self.post_process.is_empty();
+        let view = match post_processing {
+            true => {
+                self.post_process.prepare(target_texture, device, queue);
+                self.post_process.input_view()
+            }
+            false => target_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        };
+
+        let capture = std::mem::take(&mut self.capture_next_frame);
+        if capture {
+            device.start_capture();
+        }
 
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Command Encoder"),
+            label: Some("Multiview Frame Encoder"),
         });
 
         self.clear_surface(&view, &mut encoder);
 
+        // views with text submit the encoder (see `GPUView::render`), so unlike the text
+        // and the post effects they are not grouped, debug groups can't span submissions
         for render_view in &self.render_views {
+            if !render_view.borrow().visible || render_view.borrow().resize_deferred {
+                continue;
+            }
+
+            if self.deterministic {
+                render_view.borrow_mut().disable_multisampling();
+            }
+
             if !render_view.borrow().is_initialized {
                 render_view.borrow_mut().initialize(self, device)?;
             }
@@ -1292,11 +3821,90 @@ impl<'a> GPUMultiView<'a> {
             self.render_view(&render_view.borrow(), &view, &mut encoder)?;
         }
 
+        encoder.push_debug_group("Text");
         self.render_text(&view, &mut encoder, device, queue)?;
+        encoder.pop_debug_group();
+
+        if post_processing {
+            encoder.push_debug_group("Post Process");
+            self.post_process.render(&target_view, &mut encoder, device);
+            encoder.pop_debug_group();
+        }
+
+        #[cfg(feature = "tracing")]
+        let _submit_span = tracing::debug_span!("submit").entered();
 
         queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if capture {
+            device.stop_capture();
+        }
+        if let Some(output) = output {
+            output.present();
+        }
+
+        for render_view in &self.render_views {
+            render_view.borrow_mut().after_submit();
+        }
+        device.poll(match self.deterministic {
+            true => wgpu::Maintain::Wait,
+            false => wgpu::Maintain::Poll,
+        });
+
+        self.update_frame_stats();
+        self.enforce_memory_budget(device);
 
         Ok(())
     }
+
+    /// The visible views and the text as SVG document, painted in the order they are rendered.
+    ///
+    /// Vertices become filled paths and text sections `text` elements in the font named like
+    /// the `Font`. GPU passes of the views (e.g. of a `DensityLayer`) and post effects are
+    /// raster only and not included.
+    pub fn to_svg(&self) -> anyhow::Result<String> {
+        let (Some(width), Some(height)) = (self.width(), self.height()) else {
+            return Err(anyhow::Error::msg(
+                "Cannot export uninitialized multiview as SVG.",
+            ));
+        };
+
+        let document = SvgRect {
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+        };
+        let mut writer = SvgWriter::new(width, height);
+        writer.fill_rect(document, self.clear_color);
+
+        for render_view in &self.render_views {
+            let view = render_view.borrow();
+            if view.visible {
+                view.write_svg(
+                    &mut writer,
+                    document.sub_rect(view.frame.resolve((width, height))),
+                );
+            }
+        }
+
+        for primitive in &self.text_primitives {
+            primitive.write_svg(&mut writer, document);
+        }
+
+        Ok(writer.finish())
+    }
+
+    /// Reads back the last rendered frame of an offscreen multiview as tightly packed RGBA rows.
+    pub fn read_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<Vec<u8>> {
+        let texture = self
+            .offscreen_texture
+            .as_ref()
+            .context("Only offscreen multiviews can be read back.")?;
+
+        read_texture_rgba(texture, device, queue)
+    }
 }
@@ -1,23 +1,714 @@
-use wgpu_text::glyph_brush::ab_glyph::FontArc;
+use std::ops::Range;
+use std::sync::Arc;
 
-use crate::decimal_math::Decimal;
-use crate::{color::RGBA, gpuview::Font};
+use fraction::ToPrimitive;
+
+use crate::decimal_math::{decimal_format_scientific_when, decimal_from_f64, Decimal};
+use crate::math::Degrees;
+use crate::resources::FontRegistry;
+use crate::text_layout::NumberLocale;
+use crate::{
+    color::{Palette, RGBA},
+    gpuview::Font,
+};
+
+/// Stable handle of a graph added to a canvas.
+///
+/// Ids are assigned in the order graphs are added starting at `1` and never reused, so
+/// building a canvas the same way always gives the same ids. The raw value is also used as
+/// id in the pick pass, `0` is reserved for "nothing".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GraphId(u32);
+
+impl GraphId {
+    pub(crate) fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub fn pick_id(&self) -> u32 {
+        self.0
+    }
+
+    /// Converts the result of a pick request back into a `GraphId`.
+    pub fn from_pick_id(pick_id: u32) -> Option<Self> {
+        match pick_id {
+            0 => None,
+            id => Some(Self(id)),
+        }
+    }
+}
 
 /// Structure respresenting the graph of a function.
 ///
 /// `I`: Input;
 /// `P`: Parameter;
 /// `O`: Output;
-#[derive(Debug, Clone)]
 pub struct FunctionGraph<I, P, O> {
-    pub function: fn(I, &P) -> O,
+    pub function: Arc<dyn Fn(I, &P) -> O + Send + Sync>,
+    pub style: GraphStyle,
+    /// Number of segments the sampled part of the visible range is divided into.
+    pub samples: u32,
+    /// Only sample inside this domain instead of the whole visible range.
+    pub domain: Option<Range<f64>>,
+    /// Name drawn along the curve.
+    pub label: Option<CurveLabel>,
+    /// Markers at the start and end of `domain`.
+    pub endpoints: (Option<EndpointMarker>, Option<EndpointMarker>),
+    /// Takes the style from the cascade of the canvas instead of `style`, with these
+    /// overrides on top (see `GPUCanvas2D::set_canvas_graph_style`).
+    pub style_override: Option<GraphStyleOverride>,
+}
+
+impl<I, P, O> FunctionGraph<I, P, O> {
+    pub const DEFAULT_SAMPLES: u32 = 5000;
+
+    pub fn new(function: impl Fn(I, &P) -> O + Send + Sync + 'static, style: GraphStyle) -> Self {
+        Self {
+            function: Arc::new(function),
+            style,
+            samples: Self::DEFAULT_SAMPLES,
+            domain: None,
+            label: None,
+            endpoints: (None, None),
+            style_override: None,
+        }
+    }
+
+    /// Graph styled entirely by the cascade of the canvas it gets added to.
+    pub fn inheriting(function: impl Fn(I, &P) -> O + Send + Sync + 'static) -> Self {
+        Self::new(function, GraphStyle::default())
+            .with_style_override(GraphStyleOverride::default())
+    }
+
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn with_style_override(mut self, style_override: GraphStyleOverride) -> Self {
+        self.style_override = Some(style_override);
+        self
+    }
+
+    pub fn with_domain(mut self, domain: Range<f64>) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    pub fn with_label(mut self, label: CurveLabel) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Only drawn if the graph has a finite `domain` end.
+    pub fn with_endpoints(
+        mut self,
+        start: Option<EndpointMarker>,
+        end: Option<EndpointMarker>,
+    ) -> Self {
+        self.endpoints = (start, end);
+        self
+    }
+}
+
+impl<I, P, O> Clone for FunctionGraph<I, P, O> {
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            style: self.style.clone(),
+            samples: self.samples,
+            domain: self.domain.clone(),
+            label: self.label.clone(),
+            endpoints: self.endpoints,
+            style_override: self.style_override.clone(),
+        }
+    }
+}
+
+impl<I, P, O> std::fmt::Debug for FunctionGraph<I, P, O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionGraph")
+            .field("style", &self.style)
+            .field("samples", &self.samples)
+            .field("domain", &self.domain)
+            .field("label", &self.label)
+            .field("endpoints", &self.endpoints)
+            .field("style_override", &self.style_override)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Curve `t -> (x(t), y(t))` sampled at `samples + 1` evenly spaced values of `t_range`,
+/// e.g. spirals, Lissajous figures or circles which can't be written as `y = f(x)`.
+///
+/// Drawn with `style` like a function graph, except for `fill`.
+pub struct ParametricGraph<P> {
+    pub function: Arc<dyn Fn(f64, &P) -> (f64, f64) + Send + Sync>,
+    pub t_range: Range<f64>,
+    /// Number of segments `t_range` is divided into.
+    pub samples: u32,
+    pub style: GraphStyle,
+    /// See `FunctionGraph::style_override`.
+    pub style_override: Option<GraphStyleOverride>,
+}
+
+impl<P> ParametricGraph<P> {
+    pub const DEFAULT_SAMPLES: u32 = 1000;
+
+    pub fn new(
+        function: impl Fn(f64, &P) -> (f64, f64) + Send + Sync + 'static,
+        t_range: Range<f64>,
+        style: GraphStyle,
+    ) -> Self {
+        Self {
+            function: Arc::new(function),
+            t_range,
+            samples: Self::DEFAULT_SAMPLES,
+            style,
+            style_override: None,
+        }
+    }
+
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn with_style_override(mut self, style_override: GraphStyleOverride) -> Self {
+        self.style_override = Some(style_override);
+        self
+    }
+}
+
+impl<P> Clone for ParametricGraph<P> {
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            t_range: self.t_range.clone(),
+            samples: self.samples,
+            style: self.style.clone(),
+            style_override: self.style_override.clone(),
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for ParametricGraph<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParametricGraph")
+            .field("t_range", &self.t_range)
+            .field("samples", &self.samples)
+            .field("style", &self.style)
+            .field("style_override", &self.style_override)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Zero level set `f(x, y) = 0` over the visible range, e.g. circles, ellipses or level
+/// curves of `f(x, y) - c`, traced with marching squares on a grid of `resolution` cells.
+///
+/// Curves closer together than a cell can be missed and sign changes at poles are drawn as
+/// well. Drawn with `style` like a function graph, except for `fill` and `gap_marker`.
+pub struct ImplicitGraph<P> {
+    pub function: Arc<dyn Fn(f64, f64, &P) -> f64 + Send + Sync>,
+    /// Number of grid cells along x and y.
+    pub resolution: (u32, u32),
     pub style: GraphStyle,
+    /// See `FunctionGraph::style_override`.
+    pub style_override: Option<GraphStyleOverride>,
+}
+
+impl<P> ImplicitGraph<P> {
+    pub const DEFAULT_RESOLUTION: (u32, u32) = (256, 256);
+
+    pub fn new(
+        function: impl Fn(f64, f64, &P) -> f64 + Send + Sync + 'static,
+        style: GraphStyle,
+    ) -> Self {
+        Self {
+            function: Arc::new(function),
+            resolution: Self::DEFAULT_RESOLUTION,
+            style,
+            style_override: None,
+        }
+    }
+
+    pub fn with_resolution(mut self, columns: u32, rows: u32) -> Self {
+        self.resolution = (columns.max(1), rows.max(1));
+        self
+    }
+
+    pub fn with_style_override(mut self, style_override: GraphStyleOverride) -> Self {
+        self.style_override = Some(style_override);
+        self
+    }
+}
+
+impl<P> Clone for ImplicitGraph<P> {
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            resolution: self.resolution,
+            style: self.style.clone(),
+            style_override: self.style_override.clone(),
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for ImplicitGraph<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImplicitGraph")
+            .field("resolution", &self.resolution)
+            .field("style", &self.style)
+            .field("style_override", &self.style_override)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Marker at an end of the domain of a function graph or of a gap in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointMarker {
+    /// The end is excluded, drawn as a ring.
+    Open,
+    /// The end is included, drawn as a dot.
+    Closed,
+}
+
+impl EndpointMarker {
+    pub fn from_closed(closed: bool) -> Self {
+        match closed {
+            true => Self::Closed,
+            false => Self::Open,
+        }
+    }
+}
+
+/// Function made of pieces defined on separate intervals, each piece is drawn as its own
+/// function graph.
+pub struct PiecewiseGraph<P> {
+    pub pieces: Vec<FunctionGraph<f64, P, f64>>,
+    pub style: GraphStyle,
+    /// Marks the finite ends of the pieces with open and closed circles.
+    pub endpoint_markers: bool,
+}
+
+impl<P> PiecewiseGraph<P> {
+    pub fn new(style: GraphStyle) -> Self {
+        Self {
+            pieces: Vec::new(),
+            style,
+            endpoint_markers: true,
+        }
+    }
+
+    /// Adds a piece defined on `domain`, `closed` tells whether its start and end belong to it.
+    pub fn piece(
+        mut self,
+        domain: Range<f64>,
+        closed: (bool, bool),
+        function: impl Fn(f64, &P) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let endpoints = (
+            Some(EndpointMarker::from_closed(closed.0)).filter(|_| domain.start.is_finite()),
+            Some(EndpointMarker::from_closed(closed.1)).filter(|_| domain.end.is_finite()),
+        );
+
+        self.pieces.push(
+            FunctionGraph::new(function, self.style.clone())
+                .with_domain(domain)
+                .with_endpoints(endpoints.0, endpoints.1),
+        );
+        self
+    }
+
+    pub fn with_endpoint_markers(mut self, enabled: bool) -> Self {
+        self.endpoint_markers = enabled;
+        self
+    }
+
+    /// Function graphs of the pieces.
+    pub fn graphs(&self) -> Vec<FunctionGraph<f64, P, f64>> {
+        self.pieces
+            .iter()
+            .map(|piece| match self.endpoint_markers {
+                true => piece.clone(),
+                false => piece.clone().with_endpoints(None, None),
+            })
+            .collect()
+    }
+}
+
+impl<P> Clone for PiecewiseGraph<P> {
+    fn clone(&self) -> Self {
+        Self {
+            pieces: self.pieces.clone(),
+            style: self.style.clone(),
+            endpoint_markers: self.endpoint_markers,
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for PiecewiseGraph<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PiecewiseGraph")
+            .field("pieces", &self.pieces)
+            .field("style", &self.style)
+            .field("endpoint_markers", &self.endpoint_markers)
+            .finish()
+    }
+}
+
+/// Ids of graphs which were added together, e.g. by `GPUCanvas2D::add_function_family`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphGroup {
+    pub ids: Vec<GraphId>,
+}
+
+impl GraphGroup {
+    pub fn contains(&self, id: GraphId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// One function graph per value of `values`, passed as second argument to `function`.
+///
+/// `style` gets the value and a color interpolated along `Palette::VIRIDIS` in the order of `values`.
+pub fn function_family<P>(
+    function: impl Fn(f64, f64, &P) -> f64 + Send + Sync + 'static,
+    values: &[f64],
+    style: impl Fn(f64, RGBA) -> GraphStyle,
+) -> Vec<FunctionGraph<f64, P, f64>> {
+    let function = Arc::new(function);
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            let t = match values.len() {
+                1 => 0.5,
+                len => index as f32 / (len - 1) as f32,
+            };
+            let color = Palette::interpolate(&Palette::VIRIDIS, t);
+
+            let function = function.clone();
+            FunctionGraph::new(
+                move |x, parameter: &P| function(x, value, parameter),
+                style(value, color),
+            )
+        })
+        .collect()
+}
+
+/// Function graph computed numerically from other function graphs of the same canvas,
+/// see `GPUCanvas2D::add_derived_graph`.
+///
+/// Derived graphs can be sources of other derived graphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DerivedGraph {
+    /// Central difference with the sample spacing of the derived graph.
+    Derivative(GraphId),
+    /// Trapezoidal integral starting with `0` at the given x.
+    Antiderivative(GraphId, f64),
+    Sum(GraphId, GraphId),
+}
+
+impl DerivedGraph {
+    pub fn sources(&self) -> Vec<GraphId> {
+        match *self {
+            Self::Derivative(id) | Self::Antiderivative(id, _) => vec![id],
+            Self::Sum(a, b) => vec![a, b],
+        }
+    }
+}
+
+/// Graph algebra, builds `DerivedGraph`s from graph ids.
+pub struct Graph;
+impl Graph {
+    pub fn derivative(id: GraphId) -> DerivedGraph {
+        DerivedGraph::Derivative(id)
+    }
+
+    pub fn antiderivative(id: GraphId, x0: f64) -> DerivedGraph {
+        DerivedGraph::Antiderivative(id, x0)
+    }
+
+    pub fn sum(id_a: GraphId, id_b: GraphId) -> DerivedGraph {
+        DerivedGraph::Sum(id_a, id_b)
+    }
+}
+
+/// Visual offset and scale of a graph, applied when it is tessellated.
+///
+/// A sample `(x, y)` is drawn at `(x * sx + dx, y * sy + dy)`, while style closures,
+/// labels and readouts keep using the untransformed values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphTransform {
+    pub dx: f64,
+    pub dy: f64,
+    pub sx: f64,
+    pub sy: f64,
+}
+
+impl Default for GraphTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl GraphTransform {
+    pub const IDENTITY: Self = Self {
+        dx: 0.0,
+        dy: 0.0,
+        sx: 1.0,
+        sy: 1.0,
+    };
+
+    pub fn new(dx: f64, dy: f64, sx: f64, sy: f64) -> Self {
+        Self { dx, dy, sx, sy }
+    }
+
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        (point.0 * self.sx + self.dx, point.1 * self.sy + self.dy)
+    }
+
+    /// Untransformed x which is drawn at `x`.
+    pub fn inverse_x(&self, x: f64) -> f64 {
+        (x - self.dx) / self.sx
+    }
+
+    /// Untransformed x range which is drawn inside `range`.
+    pub fn inverse_x_range(&self, range: &Range<f64>) -> Range<f64> {
+        let (start, end) = (self.inverse_x(range.start), self.inverse_x(range.end));
+        start.min(end)..start.max(end)
+    }
+}
+
+/// Where the label of a function graph is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelPlacement {
+    /// On the curve at this x, moved along the curve if it overlaps another label.
+    At(f64),
+    /// Where the curve is farthest away from the other graphs.
+    MaxSpacing,
+}
+
+/// Name of a function graph drawn next to the curve in its color, instead of a legend entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveLabel {
+    pub text: String,
+    pub placement: LabelPlacement,
+}
+
+impl CurveLabel {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            placement: LabelPlacement::MaxSpacing,
+        }
+    }
+
+    pub fn at(text: &str, x: f64) -> Self {
+        Self {
+            text: text.to_string(),
+            placement: LabelPlacement::At(x),
+        }
+    }
+}
+
+/// Failure of a function graph while it got sampled.
+#[derive(Debug, Clone)]
+pub struct GraphError {
+    /// Message of the first panic.
+    pub message: String,
+    /// Number of samples at which the function panicked.
+    pub failed_samples: u32,
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "function panicked at {} samples: {}",
+            self.failed_samples, self.message
+        )
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Evaluates `function`, converting a panic into its message.
+///
+/// The process wide panic hook is left alone (other threads may panic meanwhile), so it
+/// still reports the panic as usual.
+pub(crate) fn catch_evaluation<O>(function: impl FnOnce() -> O) -> Result<O, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(function)).map_err(
+        |payload| match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| "unknown panic".to_string()),
+        },
+    )
+}
+
+/// Structure representing a set of data points.
+#[derive(Debug, Clone)]
+pub struct PointSeries {
+    pub points: Vec<(f64, f64)>,
+    pub style: PointStyle,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PointStyle {
+    pub color: RGBA,
+    /// Distance from the center to the outermost point of the marker
+    pub radius: f32,
+    pub shape: MarkerShape,
+}
+
+impl Default for PointStyle {
+    fn default() -> Self {
+        Self {
+            color: RGBA::BLACK,
+            radius: Thickness::BOLD,
+            shape: MarkerShape::Circle,
+        }
+    }
+}
+
+/// Filled shape drawn at every point of a `PointSeries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    /// Pointing up.
+    Triangle,
+    /// `+`
+    Plus,
+    /// `×`
+    Cross,
 }
 
+/// `n`-σ ellipse of the covariance of a 2D point cloud, drawn as an annotation.
 #[derive(Debug, Clone, Copy)]
+pub struct CovarianceEllipse {
+    pub mean: (f64, f64),
+    /// Semi axes, already scaled by `sigma`.
+    pub semi_axes: (f64, f64),
+    /// Angle of the first semi axis to the x axis in radians.
+    pub angle: f64,
+    pub style: EllipseStyle,
+}
+
+impl CovarianceEllipse {
+    /// Computes the sample covariance of `points` and the ellipse of `sigma` standard
+    /// deviations along its eigenvectors.
+    ///
+    /// Returns `None` for less than two finite points.
+    pub fn from_points(points: &[(f64, f64)], sigma: f64, style: EllipseStyle) -> Option<Self> {
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .copied()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean = (
+            points.iter().map(|p| p.0).sum::<f64>() / n,
+            points.iter().map(|p| p.1).sum::<f64>() / n,
+        );
+
+        let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+        for (x, y) in points.iter() {
+            let (dx, dy) = (x - mean.0, y - mean.1);
+            xx += dx * dx;
+            xy += dx * dy;
+            yy += dy * dy;
+        }
+        let (xx, xy, yy) = (xx / (n - 1.0), xy / (n - 1.0), yy / (n - 1.0));
+
+        // eigenvalues of the symmetric 2x2 matrix, the first one is the larger
+        let center = (xx + yy) / 2.0;
+        let radius = (((xx - yy) / 2.0).powi(2) + xy * xy).sqrt();
+        let eigenvalues = (center + radius, (center - radius).max(0.0));
+        let angle = 0.5 * (2.0 * xy).atan2(xx - yy);
+
+        Some(Self {
+            mean,
+            semi_axes: (sigma * eigenvalues.0.sqrt(), sigma * eigenvalues.1.sqrt()),
+            angle,
+            style,
+        })
+    }
+
+    /// Points of the outline in graph space, the first point is repeated at the end.
+    pub fn outline(&self, resolution: usize) -> Vec<(f64, f64)> {
+        let (sin, cos) = self.angle.sin_cos();
+
+        (0..=resolution)
+            .map(|i| {
+                let t = i as f64 / resolution as f64 * std::f64::consts::TAU;
+                let (u, v) = (self.semi_axes.0 * t.cos(), self.semi_axes.1 * t.sin());
+
+                (
+                    self.mean.0 + u * cos - v * sin,
+                    self.mean.1 + u * sin + v * cos,
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EllipseStyle {
+    pub color: RGBA,
+    pub thickness: f32,
+    /// Color inside of the outline
+    pub fill: Option<RGBA>,
+    /// Radius of the dot at the mean, `None` hides it
+    pub mean_marker: Option<f32>,
+}
+
+impl Default for EllipseStyle {
+    fn default() -> Self {
+        Self {
+            color: RGBA::BLACK,
+            thickness: Thickness::MEDIUM,
+            fill: None,
+            mean_marker: Some(Thickness::BOLD),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct GraphStyle {
     pub color: RGBA,
     pub thickness: f32,
+    /// Blurred copy of the curve drawn below it, mostly useful on dark backgrounds
+    pub glow: Option<GlowStyle>,
+    /// Varies the color along the curve, replaces `color`
+    pub gradient: Option<ColorGradient>,
+    /// Varies the width along the curve, replaces `thickness`
+    pub variable_thickness: Option<VariableThickness>,
+    /// Fills the area between the curve and the baseline of the fill
+    pub fill: Option<FillStyle>,
+    /// Marks both ends of every gap where the function is NaN or infinite
+    pub gap_marker: Option<EndpointMarker>,
+    pub join: LineJoin,
+    pub cap: LineCap,
 }
 
 impl Default for GraphStyle {
@@ -25,6 +716,323 @@ impl Default for GraphStyle {
         Self {
             color: RGBA::BLACK,
             thickness: Thickness::MEDIUM,
+            glow: None,
+            gradient: None,
+            variable_thickness: None,
+            fill: None,
+            gap_marker: None,
+            join: LineJoin::Round,
+            cap: LineCap::Round,
+        }
+    }
+}
+
+/// Partial `GraphStyle` of one level of the style cascade of a canvas, `None` fields are
+/// inherited from the level below.
+///
+/// The levels are the graph style of the theme, the canvas, the groups containing the graph
+/// and the graph itself, see `GPUCanvas2D::set_canvas_graph_style`. Optional parts of the
+/// style are switched off with `Some(None)`.
+#[derive(Debug, Clone, Default)]
+pub struct GraphStyleOverride {
+    pub color: Option<RGBA>,
+    pub thickness: Option<f32>,
+    pub glow: Option<Option<GlowStyle>>,
+    pub gradient: Option<Option<ColorGradient>>,
+    pub variable_thickness: Option<Option<VariableThickness>>,
+    pub fill: Option<Option<FillStyle>>,
+    pub gap_marker: Option<Option<EndpointMarker>>,
+    pub join: Option<LineJoin>,
+    pub cap: Option<LineCap>,
+}
+
+impl GraphStyleOverride {
+    pub fn with_color(mut self, color: RGBA) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+
+    /// Replaces the fields of `style` which are set.
+    pub fn apply(&self, style: &mut GraphStyle) {
+        fn set<T: Clone>(field: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *field = value.clone();
+            }
+        }
+
+        set(&mut style.color, &self.color);
+        set(&mut style.thickness, &self.thickness);
+        set(&mut style.glow, &self.glow);
+        set(&mut style.gradient, &self.gradient);
+        set(&mut style.variable_thickness, &self.variable_thickness);
+        set(&mut style.fill, &self.fill);
+        set(&mut style.gap_marker, &self.gap_marker);
+        set(&mut style.join, &self.join);
+        set(&mut style.cap, &self.cap);
+    }
+}
+
+/// Shape of the outer corner where two segments of a line meet.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LineJoin {
+    /// Sharp corner, replaced by a bevel if the miter gets longer than `limit` times the width.
+    Miter {
+        limit: f32,
+    },
+    #[default]
+    Round,
+    Bevel,
+}
+
+impl LineJoin {
+    /// Miter with the same default limit as SVG.
+    pub const MITER: Self = Self::Miter { limit: 4.0 };
+}
+
+/// Shape of the two ends of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point.
+    Butt,
+    #[default]
+    Round,
+    /// Extends half the width beyond the end point.
+    Square,
+}
+
+/// Pattern of a filled region, made of geometry so it stays distinguishable in grayscale exports.
+///
+/// Sizes are in pixels, angles in degrees counterclockwise from the x axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillPattern {
+    Solid,
+    Hatch {
+        angle: f32,
+        spacing: f32,
+        thickness: f32,
+    },
+    /// Two hatches perpendicular to each other.
+    CrossHatch {
+        angle: f32,
+        spacing: f32,
+        thickness: f32,
+    },
+    Dots {
+        spacing: f32,
+        radius: f32,
+    },
+}
+
+impl FillPattern {
+    pub const DIAGONAL_HATCH: Self = Self::Hatch {
+        angle: 45.0,
+        spacing: 10.0,
+        thickness: 1.5,
+    };
+
+    pub const CROSS_HATCH: Self = Self::CrossHatch {
+        angle: 45.0,
+        spacing: 10.0,
+        thickness: 1.5,
+    };
+
+    pub const DOTS: Self = Self::Dots {
+        spacing: 10.0,
+        radius: 2.0,
+    };
+}
+
+/// Other side of the area filled by a `FillStyle`, transformed like the filled graph.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FillBaseline {
+    /// `y = 0`, the bottom of the view on logarithmic y scales
+    #[default]
+    Zero,
+    Value(f64),
+    /// Another function graph of the same canvas, the area is only filled where both are
+    /// defined
+    Graph(GraphId),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FillStyle {
+    pub color: RGBA,
+    pub pattern: FillPattern,
+    pub baseline: FillBaseline,
+}
+
+impl FillStyle {
+    pub fn new(color: RGBA, pattern: FillPattern) -> Self {
+        Self {
+            color,
+            pattern,
+            baseline: FillBaseline::Zero,
+        }
+    }
+
+    pub fn solid(color: RGBA) -> Self {
+        Self::new(color, FillPattern::Solid)
+    }
+
+    /// Fills the area between the graph and the function graph `id`.
+    pub fn between(id: GraphId, color: RGBA) -> Self {
+        Self::solid(color).with_baseline(FillBaseline::Graph(id))
+    }
+
+    pub fn with_baseline(mut self, baseline: FillBaseline) -> Self {
+        self.baseline = baseline;
+        self
+    }
+}
+
+/// Thickness of a graph at the sample `(x, y)`, e.g. to show the uncertainty of a value.
+#[derive(Clone)]
+pub struct VariableThickness(pub Arc<dyn Fn(f64, f64) -> f32 + Send + Sync>);
+
+impl VariableThickness {
+    pub fn by_x(thickness: impl Fn(f64) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move |x, _| thickness(x)))
+    }
+
+    pub fn by_value(thickness: impl Fn(f64) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move |_, y| thickness(y)))
+    }
+
+    pub fn thickness(&self, x: f64, y: f64) -> f32 {
+        (self.0)(x, y)
+    }
+}
+
+impl std::fmt::Debug for VariableThickness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VariableThickness(..)")
+    }
+}
+
+/// Quantity the color of a `ColorGradient` depends on.
+#[derive(Clone)]
+pub enum GradientSource {
+    X,
+    Y,
+    /// Fraction `0..1` of the length (in graph units) of the visible curve.
+    Arclength,
+    /// Color of the sample at `(x, y)`, the stops of the gradient are not used.
+    Custom(Arc<dyn Fn(f64, f64) -> RGBA + Send + Sync>),
+}
+
+impl std::fmt::Debug for GradientSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::X => write!(f, "X"),
+            Self::Y => write!(f, "Y"),
+            Self::Arclength => write!(f, "Arclength"),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Per vertex color of a graph, linearly interpolated between color stops.
+#[derive(Debug, Clone)]
+pub struct ColorGradient {
+    pub source: GradientSource,
+    /// `(value, color)` sorted by value, values outside get the color of the nearest stop.
+    pub stops: Vec<(f64, RGBA)>,
+}
+
+impl ColorGradient {
+    pub fn new(source: GradientSource, stops: &[(f64, RGBA)]) -> Self {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self { source, stops }
+    }
+
+    pub fn custom(color: impl Fn(f64, f64) -> RGBA + Send + Sync + 'static) -> Self {
+        Self {
+            source: GradientSource::Custom(Arc::new(color)),
+            stops: Vec::new(),
+        }
+    }
+
+    /// Color of the stops at `value`.
+    pub fn color_at(&self, value: f64) -> RGBA {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return RGBA::BLACK;
+        };
+
+        if !(value > first.0) {
+            return first.1;
+        }
+
+        for pair in self.stops.windows(2) {
+            let ((start, start_color), (end, end_color)) = (pair[0], pair[1]);
+            if value <= end {
+                let t = match end - start {
+                    0.0 => 1.0,
+                    len => (value - start) / len,
+                };
+                return start_color.mix(end_color, t as f32);
+            }
+        }
+
+        last.1
+    }
+
+    /// Colors of the samples of one segment of a curve.
+    ///
+    /// `arclength` is the length of the curve before the segment and gets advanced,
+    /// `total` is the length of the whole visible curve.
+    pub(crate) fn segment_colors(
+        &self,
+        segment: &[(f64, f64)],
+        arclength: &mut f64,
+        total: f64,
+    ) -> Vec<RGBA> {
+        let mut last: Option<(f64, f64)> = None;
+
+        segment
+            .iter()
+            .map(|&(x, y)| {
+                if let Some((last_x, last_y)) = last {
+                    *arclength += (x - last_x).hypot(y - last_y);
+                }
+                last = Some((x, y));
+
+                match &self.source {
+                    GradientSource::X => self.color_at(x),
+                    GradientSource::Y => self.color_at(y),
+                    GradientSource::Arclength => match total {
+                        0.0 => self.color_at(0.0),
+                        total => self.color_at(*arclength / total),
+                    },
+                    GradientSource::Custom(color) => color(x, y),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GlowStyle {
+    /// Blur radius in pixels, the largest radius of all graphs of a canvas is used
+    pub radius: f32,
+    /// Opacity of the glow `0..1`
+    pub intensity: f32,
+    /// Width of the blurred curve relative to the graph thickness
+    pub spread: f32,
+}
+
+impl Default for GlowStyle {
+    fn default() -> Self {
+        Self {
+            radius: 12.0,
+            intensity: 0.8,
+            spread: 3.0,
         }
     }
 }
@@ -55,6 +1063,10 @@ pub struct DimensionStyle {
     pub subtick: Option<TickStyle>,
     pub grid: Option<GridStyle>,
     pub subgrid: Option<GridStyle>,
+    /// Values at the ticks, drawn with the text style of the enviroment
+    pub label: Option<AxisLabelStyle>,
+    /// Name of the axis at the edge of the view, drawn with the text style of the enviroment
+    pub title: Option<AxisTitleStyle>,
 }
 
 impl Default for DimensionStyle {
@@ -69,6 +1081,8 @@ impl Default for DimensionStyle {
                 color: RGBA::grey(240),
                 thickness: Thickness::EXTRATHIN,
             }),
+            label: Some(AxisLabelStyle::default()),
+            title: None,
         }
     }
 }
@@ -120,6 +1134,136 @@ impl Default for TickStyle {
     }
 }
 
+/// Tick value labels of one axis.
+#[derive(Debug, Clone, Copy)]
+pub struct AxisLabelStyle {
+    pub format: TickLabelFormat,
+    /// Only labels every `every`th tick, counted from zero so the labeled ticks don't
+    /// change while panning.
+    pub every: u32,
+    /// Counterclockwise rotation around the anchor at the axis, rotated x labels end at
+    /// their tick.
+    pub rotation: Degrees,
+    /// Distance from the axis in pixels.
+    pub offset: f32,
+}
+
+impl Default for AxisLabelStyle {
+    fn default() -> Self {
+        Self {
+            format: TickLabelFormat::Auto,
+            every: 1,
+            rotation: Degrees(0.0),
+            offset: 0.0,
+        }
+    }
+}
+
+impl AxisLabelStyle {
+    pub fn with_format(mut self, format: TickLabelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_every(mut self, every: u32) -> Self {
+        self.every = every.max(1);
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Degrees) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+/// Name of an axis, centered below the view for x and along its left edge for y.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisTitleStyle {
+    pub text: String,
+    /// Counterclockwise rotation, `None` reads along the axis (bottom to top for y).
+    pub rotation: Option<Degrees>,
+    /// Distance from the edge of the view in pixels.
+    pub offset: f32,
+}
+
+impl Default for AxisTitleStyle {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl AxisTitleStyle {
+    pub const DEFAULT_OFFSET: f32 = 4.0;
+
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            rotation: None,
+            offset: Self::DEFAULT_OFFSET,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Degrees) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+/// Number format of tick labels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TickLabelFormat {
+    /// As many decimals as the tick spacing needs, scientific notation beyond
+    /// `TextStyle::max_digits` digits.
+    #[default]
+    Auto,
+    /// Always `decimals` decimals.
+    Fixed { decimals: u32 },
+    /// Scientific notation with `decimals` decimals in the mantissa.
+    Scientific { decimals: u32 },
+    /// Degrees east or west (`30°E`), wrapped into `-180..=180`.
+    Longitude,
+    /// Degrees north or south (`45°N`).
+    Latitude,
+}
+
+impl TickLabelFormat {
+    pub fn format(&self, value: &Decimal, max_digits: u32) -> String {
+        let value_f64 = || value.to_f64().unwrap_or(f64::NAN);
+        let degrees = |value: &Decimal, positive: &str, negative: &str| {
+            let magnitude = decimal_format_scientific_when(&value.abs(), max_digits);
+            match value.to_f64().unwrap_or(0.0) {
+                value if value > 0.0 => format!("{}°{}", magnitude, positive),
+                value if value < 0.0 => format!("{}°{}", magnitude, negative),
+                _ => format!("{}°", magnitude),
+            }
+        };
+
+        match *self {
+            Self::Auto => decimal_format_scientific_when(value, max_digits),
+            Self::Fixed { decimals } => format!("{:.*}", decimals as usize, value_f64()),
+            Self::Scientific { decimals } => format!("{:.*e}", decimals as usize, value_f64()),
+            Self::Longitude => match value_f64().abs() <= 180.0 {
+                true => degrees(value, "E", "W"),
+                false => {
+                    let wrapped = (value_f64() + 180.0).rem_euclid(360.0) - 180.0;
+                    degrees(&decimal_from_f64(wrapped), "E", "W")
+                }
+            },
+            Self::Latitude => degrees(value, "N", "S"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GridStyle {
     pub color: RGBA,
@@ -139,24 +1283,131 @@ impl Default for GridStyle {
 pub struct TextStyle {
     pub size: f32,
     pub font: Font,
+    pub color: RGBA,
     /// Maximum number of digits before switching to scientific notation
     pub max_digits: u32,
+    /// Outline drawn behind the text to keep it readable on top of data
+    pub halo: Option<TextHaloStyle>,
+    /// Translucent box drawn over the data underneath labels
+    pub background: Option<RGBA>,
+    /// Digits and signs used for numbers
+    pub locale: NumberLocale,
+    /// Writes exponents of scientific tick labels with superscript digits (`1.5×10⁻³`)
+    /// instead of `1.5e-3`, see `superscript_exponent`
+    pub superscript_exponents: bool,
 }
 
 impl Default for TextStyle {
     fn default() -> Self {
         Self {
             size: 32.0,
-            font: Font {
-                name: "Default".to_string(),
-                font: FontArc::try_from_vec(std::fs::read("fonts/DejaVuSans.ttf").unwrap())
-                    .unwrap(),
-            },
+            font: FontRegistry::shared().default_font(),
+            color: RGBA::BLACK,
             max_digits: 4,
+            halo: None,
+            background: None,
+            locale: NumberLocale::LATIN,
+            superscript_exponents: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextHaloStyle {
+    pub color: RGBA,
+    /// Width in pixels
+    pub width: f32,
+}
+
+impl Default for TextHaloStyle {
+    fn default() -> Self {
+        Self {
+            color: RGBA::WHITE,
+            width: 2.0,
+        }
+    }
+}
+
+/// Adjustments applied on top of the style of a canvas for presentations and low vision,
+/// see `GPUCanvas2D::set_accessibility`.
+#[derive(Debug, Clone, Copy)]
+pub struct AccessibilityStyle {
+    /// Draws the enviroment in pure black with darker grids and outlined labels.
+    pub high_contrast: bool,
+    /// Minimum text size in pixels, regardless of `TextStyle::size`.
+    pub min_text_size: f32,
+    /// Minimum thickness of every line, thickening hairline grids, axes and graphs.
+    pub min_thickness: f32,
+}
+
+impl Default for AccessibilityStyle {
+    fn default() -> Self {
+        Self {
+            high_contrast: true,
+            min_text_size: 40.0,
+            min_thickness: Thickness::THIN,
         }
     }
 }
 
+impl AccessibilityStyle {
+    pub fn thickness(&self, thickness: f32) -> f32 {
+        thickness.max(self.min_thickness)
+    }
+
+    /// Returns `style` with the adjustments applied.
+    pub fn apply(&self, style: &EnviromentStyle) -> EnviromentStyle {
+        let mut style = style.clone();
+
+        for dimension in [&mut style.x, &mut style.y] {
+            if let Some(axis) = dimension.axis.as_mut() {
+                axis.thickness = self.thickness(axis.thickness);
+            }
+            for tick in [&mut dimension.tick, &mut dimension.subtick] {
+                if let Some(tick) = tick.as_mut() {
+                    tick.thickness = self.thickness(tick.thickness);
+                }
+            }
+            for grid in [&mut dimension.grid, &mut dimension.subgrid] {
+                if let Some(grid) = grid.as_mut() {
+                    grid.thickness = self.thickness(grid.thickness);
+                }
+            }
+
+            if self.high_contrast {
+                if let Some(axis) = dimension.axis.as_mut() {
+                    axis.color = RGBA::BLACK;
+                }
+                for tick in [&mut dimension.tick, &mut dimension.subtick] {
+                    if let Some(tick) = tick.as_mut() {
+                        tick.color = RGBA::BLACK;
+                    }
+                }
+                if let Some(grid) = dimension.grid.as_mut() {
+                    grid.color = RGBA::grey(110);
+                }
+                if let Some(subgrid) = dimension.subgrid.as_mut() {
+                    subgrid.color = RGBA::grey(180);
+                }
+            }
+        }
+
+        if let Some(text) = style.text.as_mut() {
+            text.size = text.size.max(self.min_text_size);
+
+            if self.high_contrast {
+                text.color = RGBA::BLACK;
+                text.halo = Some(TextHaloStyle {
+                    color: RGBA::WHITE,
+                    width: 3.0,
+                });
+            }
+        }
+
+        style
+    }
+}
+
 pub struct Thickness;
 impl Thickness {
     pub const EXTRATHIN: f32 = 0.001;
@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use crate::interval::Interval;
+
+/// Visible x and y range of a canvas.
+pub type ViewRange = (Interval, Interval);
+
+/// Ranges a canvas was navigated away from, see `GPUCanvas2D::undo_range`.
+///
+/// Changes following each other within `MERGE_WINDOW` (dragging, scrolling) are recorded as
+/// a single step.
+#[derive(Debug, Clone)]
+pub struct NavigationHistory {
+    undo: Vec<ViewRange>,
+    redo: Vec<ViewRange>,
+    /// Range restored by `GPUCanvas2D::reset_range`, the range before the first recorded
+    /// change unless set explicitly
+    home: Option<ViewRange>,
+    /// Maximum number of undo steps, the oldest ones are dropped beyond it
+    capacity: usize,
+    last_change: Option<Instant>,
+}
+
+impl Default for NavigationHistory {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            home: None,
+            capacity: Self::DEFAULT_CAPACITY,
+            last_change: None,
+        }
+    }
+}
+
+impl NavigationHistory {
+    pub const DEFAULT_CAPACITY: usize = 100;
+    pub const MERGE_WINDOW: Duration = Duration::from_millis(500);
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn home(&self) -> Option<ViewRange> {
+        self.home
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+
+    pub(crate) fn set_home(&mut self, home: ViewRange) {
+        self.home = Some(home);
+    }
+
+    /// Records `previous` before a navigation, unless the last one is still ongoing.
+    pub(crate) fn record_navigation(&mut self, previous: ViewRange) {
+        let now = Instant::now();
+        let ongoing = self
+            .last_change
+            .is_some_and(|last_change| now.duration_since(last_change) < Self::MERGE_WINDOW);
+
+        if !ongoing {
+            self.push(previous);
+        }
+        self.last_change = Some(now);
+    }
+
+    /// Records `previous` as its own step.
+    pub(crate) fn push(&mut self, previous: ViewRange) {
+        self.home.get_or_insert(previous);
+        self.last_change = None;
+
+        if self.undo.last() != Some(&previous) {
+            self.undo.push(previous);
+            self.truncate();
+        }
+        self.redo.clear();
+    }
+
+    /// Range to go back to from `current`.
+    pub(crate) fn undo(&mut self, current: ViewRange) -> Option<ViewRange> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.last_change = None;
+
+        Some(previous)
+    }
+
+    /// Range to go forward to from `current`.
+    pub(crate) fn redo(&mut self, current: ViewRange) -> Option<ViewRange> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.last_change = None;
+
+        Some(next)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_change = None;
+    }
+
+    fn truncate(&mut self) {
+        if self.undo.len() > self.capacity {
+            self.undo.drain(..self.undo.len() - self.capacity);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Socket server through which external processes push data points into a shown plot.
+//!
+//! Every line received is one command:
+//!
+//! ```text
+//! <series_name> <x> <y>    append a point to the series (created on first use)
+//! clear <series_name>      remove all points of the series
+//! # comment
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{TcpListener, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::data::StreamingSeries;
+use crate::graph::{PointSeries, PointStyle, Thickness};
+use crate::plot::{Plot, PlotHandle};
+
+#[derive(Debug, Clone, PartialEq)]
+enum IngestCommand {
+    Push(String, (f64, f64)),
+    Clear(String),
+}
+
+fn parse_line(line: &str) -> anyhow::Result<Option<IngestCommand>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+
+    match parts.as_slice() {
+        ["clear", name] => Ok(Some(IngestCommand::Clear(name.to_string()))),
+        [name, x, y] => Ok(Some(IngestCommand::Push(
+            name.to_string(),
+            (
+                x.parse().context("Invalid x value.")?,
+                y.parse().context("Invalid y value.")?,
+            ),
+        ))),
+        _ => Err(anyhow::Error::msg(format!(
+            "Invalid line \"{}\" (expected \"<series_name> <x> <y>\").",
+            line
+        ))),
+    }
+}
+
+#[derive(Default)]
+struct StreamEntry {
+    series: StreamingSeries,
+    /// Points pushed since the last flush, at most `capacity`
+    pending: Vec<(f64, f64)>,
+    /// The whole series has to be sent again, e.g. after `clear`
+    reset: bool,
+}
+
+#[derive(Default)]
+struct StreamStore {
+    /// Series in order of their first appearance
+    entries: Vec<(String, StreamEntry)>,
+    capacity: Option<usize>,
+}
+
+impl StreamStore {
+    fn entry_mut(&mut self, name: &str) -> &mut StreamEntry {
+        let index = match self
+            .entries
+            .iter()
+            .position(|(entry_name, _)| entry_name == name)
+        {
+            Some(index) => index,
+            None => {
+                let series = match self.capacity {
+                    Some(capacity) => StreamingSeries::with_capacity(capacity),
+                    None => StreamingSeries::new(),
+                };
+
+                self.entries.push((
+                    name.to_string(),
+                    StreamEntry {
+                        series,
+                        pending: Vec::new(),
+                        reset: true,
+                    },
+                ));
+                self.entries.len() - 1
+            }
+        };
+
+        &mut self.entries[index].1
+    }
+
+    fn apply(&mut self, command: IngestCommand) {
+        let capacity = self.capacity;
+
+        match command {
+            IngestCommand::Push(name, point) => {
+                let entry = self.entry_mut(&name);
+                entry.series.push(point);
+
+                entry.pending.push(point);
+                if let Some(capacity) = capacity {
+                    let excess = entry.pending.len().saturating_sub(capacity);
+                    entry.pending.drain(..excess);
+                }
+            }
+            IngestCommand::Clear(name) => {
+                let entry = self.entry_mut(&name);
+                entry.series.clear();
+                entry.pending.clear();
+                entry.reset = true;
+            }
+        }
+    }
+}
+
+/// What a flush sends for one series.
+enum StreamUpdate {
+    Append(Vec<(f64, f64)>),
+    Replace(PointSeries),
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+type ErrorCallback = Arc<dyn Fn(&anyhow::Error) + Send + Sync>;
+
+/// Listens on a local socket for data points and forwards them to a detached plot.
+pub struct IngestServer {
+    listener: Listener,
+    capacity: Option<usize>,
+    flush_interval: Duration,
+    error_callback: Option<ErrorCallback>,
+}
+
+impl IngestServer {
+    pub fn bind_tcp(address: impl ToSocketAddrs) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(address).context("Failed to bind TCP socket.")?;
+
+        Ok(Self::new(Listener::Tcp(listener)))
+    }
+
+    #[cfg(unix)]
+    pub fn bind_unix(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let listener = UnixListener::bind(path).context("Failed to bind Unix socket.")?;
+
+        Ok(Self::new(Listener::Unix(listener)))
+    }
+
+    fn new(listener: Listener) -> Self {
+        Self {
+            listener,
+            capacity: None,
+            flush_interval: Duration::from_millis(16),
+            error_callback: None,
+        }
+    }
+
+    /// Only keep the most recent `capacity` points of every series.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Interval in which received points get sent to the plot.
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Called with the error of every line a client sent that could not be parsed. The line
+    /// is skipped in any case.
+    pub fn with_error_callback(
+        mut self,
+        callback: impl Fn(&anyhow::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.error_callback = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn local_address(&self) -> Option<String> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr().ok().map(|addr| addr.to_string()),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .local_addr()
+                .ok()?
+                .as_pathname()
+                .map(|path| path.display().to_string()),
+        }
+    }
+
+    fn handle_connection(
+        stream: impl Read,
+        store: Arc<Mutex<StreamStore>>,
+        error_callback: Option<ErrorCallback>,
+    ) {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            match parse_line(&line) {
+                Ok(Some(command)) => store.lock().unwrap().apply(command),
+                Ok(None) => (),
+                Err(error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("Skipped ingest line: {:#}", error);
+
+                    if let Some(callback) = &error_callback {
+                        callback(&error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn accept_connections(
+        listener: Listener,
+        store: Arc<Mutex<StreamStore>>,
+        error_callback: Option<ErrorCallback>,
+    ) {
+        match listener {
+            Listener::Tcp(listener) => {
+                for stream in listener.incoming().flatten() {
+                    let (store, error_callback) = (store.clone(), error_callback.clone());
+                    std::thread::spawn(move || {
+                        Self::handle_connection(stream, store, error_callback)
+                    });
+                }
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                for stream in listener.incoming().flatten() {
+                    let (store, error_callback) = (store.clone(), error_callback.clone());
+                    std::thread::spawn(move || {
+                        Self::handle_connection(stream, store, error_callback)
+                    });
+                }
+            }
+        }
+    }
+
+    /// Accepts connections and forwards the received points to the plot of `handle` until
+    /// its window gets closed. New series are appended to the plot on their first point,
+    /// afterwards only the points received since the last flush are sent.
+    pub fn serve<P: 'static>(self, handle: &PlotHandle<P>) -> anyhow::Result<()> {
+        let store = Arc::new(Mutex::new(StreamStore {
+            capacity: self.capacity,
+            ..Default::default()
+        }));
+
+        {
+            let store = store.clone();
+            let listener = self.listener;
+            let error_callback = self.error_callback.clone();
+            // the accepting thread is detached as it blocks on `accept`
+            std::thread::spawn(move || Self::accept_connections(listener, store, error_callback));
+        }
+
+        // position of every streamed series in the plot
+        let mut series_indices: HashMap<String, usize> = HashMap::new();
+
+        while handle.is_open() {
+            std::thread::sleep(self.flush_interval);
+
+            let updates = {
+                let mut store = store.lock().unwrap();
+
+                store
+                    .entries
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(_, (_, entry))| entry.reset || !entry.pending.is_empty())
+                    .map(|(order, (name, entry))| {
+                        let pending = std::mem::take(&mut entry.pending);
+                        if !std::mem::take(&mut entry.reset) && series_indices.contains_key(name) {
+                            return (name.clone(), StreamUpdate::Append(pending));
+                        }
+
+                        let style = PointStyle {
+                            color: Plot::DEFAULT_COLORS[order % Plot::DEFAULT_COLORS.len()],
+                            radius: Thickness::EXTRABOLD,
+                            ..Default::default()
+                        };
+
+                        (
+                            name.clone(),
+                            StreamUpdate::Replace(entry.series.to_point_series(style)),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            for (name, update) in updates {
+                match (series_indices.get(&name), update) {
+                    (Some(index), StreamUpdate::Append(points)) => {
+                        handle.append_points(*index, points, self.capacity)?
+                    }
+                    (Some(index), StreamUpdate::Replace(series)) => {
+                        handle.replace_series(*index, series)?
+                    }
+                    (None, StreamUpdate::Replace(series)) => {
+                        let index = handle.add_series(series)?;
+                        series_indices.insert(name, index);
+                    }
+                    (None, StreamUpdate::Append(_)) => unreachable!(),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
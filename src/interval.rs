@@ -0,0 +1,160 @@
+use std::ops::Range;
+
+/// Closed interval of data coordinates stored as center and half width, e.g. the visible
+/// ranges of a `GPUCanvas2D`.
+///
+/// Converts from and into `Range<f64>`, so APIs can keep accepting `a..b`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interval {
+    center: f64,
+    half_width: f64,
+}
+
+impl Default for Interval {
+    fn default() -> Self {
+        Self::UNIT
+    }
+}
+
+impl Interval {
+    /// `-1..1`
+    pub const UNIT: Self = Self {
+        center: 0.0,
+        half_width: 1.0,
+    };
+
+    pub fn new(start: f64, end: f64) -> Self {
+        Self {
+            center: start + (end - start) / 2.0,
+            half_width: (end - start) / 2.0,
+        }
+    }
+
+    pub fn from_center(center: f64, half_width: f64) -> Self {
+        Self { center, half_width }
+    }
+
+    pub fn center(&self) -> f64 {
+        self.center
+    }
+
+    pub fn half_width(&self) -> f64 {
+        self.half_width
+    }
+
+    pub fn start(&self) -> f64 {
+        self.center - self.half_width
+    }
+
+    pub fn end(&self) -> f64 {
+        self.center + self.half_width
+    }
+
+    pub fn len(&self) -> f64 {
+        self.half_width * 2.0
+    }
+
+    pub fn to_range(&self) -> Range<f64> {
+        self.start()..self.end()
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        (x - self.center).abs() <= self.half_width
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.center.is_finite() && self.half_width.is_finite()
+    }
+
+    /// True for empty, reversed or non finite intervals, which can't be mapped onto a view.
+    pub fn is_degenerate(&self) -> bool {
+        !self.is_finite() || self.half_width <= 0.0
+    }
+
+    /// Overlap of both intervals, `None` if they are disjoint.
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let start = self.start().max(other.start());
+        let end = self.end().min(other.end());
+
+        (start <= end).then(|| Self::new(start, end))
+    }
+
+    /// Smallest interval containing both.
+    pub fn union(&self, other: &Interval) -> Interval {
+        Self::new(self.start().min(other.start()), self.end().max(other.end()))
+    }
+
+    /// Grows the interval by `amount` on both sides.
+    pub fn expand(&self, amount: f64) -> Self {
+        Self::from_center(self.center, self.half_width + amount)
+    }
+
+    /// Scales the width by `factor` around the center.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::from_center(self.center, self.half_width * factor)
+    }
+
+    pub fn offset(&self, offset: f64) -> Self {
+        Self::from_center(self.center + offset, self.half_width)
+    }
+
+    /// Value at `t`, `0` is the start and `1` the end.
+    pub fn lerp(&self, t: f64) -> f64 {
+        self.start() + t * self.len()
+    }
+
+    /// Position of `x`, `0` at the start and `1` at the end.
+    pub fn inverse_lerp(&self, x: f64) -> f64 {
+        (x - self.start()) / self.len()
+    }
+
+    /// Maps `x` onto `-1..1`, the center is subtracted first so large coordinates keep
+    /// their precision.
+    pub fn to_ndc(&self, x: f64) -> f64 {
+        (x - self.center) / self.half_width
+    }
+
+    pub fn from_ndc(&self, ndc: f64) -> f64 {
+        self.center + ndc * self.half_width
+    }
+
+    /// Smallest interval containing this one whose bounds are multiples of a "nice" step
+    /// (1, 2 or 5 times a power of ten) dividing it into about `steps` parts.
+    pub fn nice_round(&self, steps: u32) -> Self {
+        if self.is_degenerate() {
+            return *self;
+        }
+
+        let raw_step = self.len() / steps.max(1) as f64;
+        let magnitude = 10f64.powf(raw_step.log10().floor());
+        let step = [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|factor| factor * magnitude)
+            .find(|step| *step >= raw_step)
+            .unwrap_or(10.0 * magnitude);
+
+        Self::new(
+            (self.start() / step).floor() * step,
+            (self.end() / step).ceil() * step,
+        )
+    }
+}
+
+impl From<Range<f64>> for Interval {
+    fn from(range: Range<f64>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<&Range<f64>> for Interval {
+    fn from(range: &Range<f64>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<Interval> for Range<f64> {
+    fn from(interval: Interval) -> Self {
+        interval.to_range()
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Shared flag used to abandon work whose result became stale.
+///
+/// `GPUCanvas2D::range_token` hands out tokens which get cancelled on the next range change.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    parent: Option<Box<CancellationToken>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token which is cancelled together with `self`, but can also be cancelled alone.
+    pub fn child(&self) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            parent: Some(Box::new(self.clone())),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|parent| parent.is_cancelled())
+    }
+}
+
+#[derive(Debug)]
+pub enum JobState<T> {
+    Running,
+    Finished(T),
+    /// The job got cancelled or its result was already taken.
+    Abandoned,
+}
+
+/// Heavy computation (e.g. of a layer for the current viewport) running on its own thread.
+///
+/// The work function should check the token regularly and return `None` once it is cancelled.
+pub struct Job<T> {
+    token: CancellationToken,
+    receiver: Receiver<T>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T> Job<T>
+where
+    T: Send + 'static,
+{
+    pub fn spawn(
+        token: CancellationToken,
+        work: impl FnOnce(&CancellationToken) -> Option<T> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        // own token, so dropping the job doesn't cancel other work sharing `token`
+        let token = token.child();
+
+        let thread_token = token.clone();
+        let thread = std::thread::spawn(move || {
+            if thread_token.is_cancelled() {
+                return;
+            }
+
+            if let Some(result) = work(&thread_token) {
+                if !thread_token.is_cancelled() {
+                    let _ = sender.send(result);
+                }
+            }
+        });
+
+        Self {
+            token,
+            receiver,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Takes the result if the job finished, results of cancelled jobs are dropped.
+    pub fn poll(&mut self) -> JobState<T> {
+        if self.token.is_cancelled() {
+            return JobState::Abandoned;
+        }
+
+        match self.receiver.try_recv() {
+            Ok(result) => JobState::Finished(result),
+            Err(TryRecvError::Empty) => JobState::Running,
+            Err(TryRecvError::Disconnected) => JobState::Abandoned,
+        }
+    }
+
+    /// Blocks until the job is done and returns its result unless it got cancelled.
+    pub fn wait(mut self) -> Option<T> {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        match self.token.is_cancelled() {
+            true => None,
+            false => self.receiver.try_recv().ok(),
+        }
+    }
+}
+
+/// Dropping a job abandons it, the thread stops at its next cancellation check.
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
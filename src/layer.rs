@@ -0,0 +1,299 @@
+use std::{
+    cell::RefCell,
+    f64::consts::{PI, TAU},
+    sync::Arc,
+};
+
+use wgpu_text::glyph_brush::{HorizontalAlign, Layout, VerticalAlign};
+
+use crate::color::RGBA;
+use crate::gpucanvas_2d::{format_readout_value, GPUCanvas2D};
+use crate::graph::{CovarianceEllipse, LineCap, LineJoin, TextStyle};
+use crate::interval::Interval;
+use crate::math::{Degrees, Radians};
+use crate::measure::{AngleAnnotation, AngleUnit};
+use crate::{GPUPass, TextSection};
+
+/// Custom content of a `GPUCanvas2D`, added with `GPUCanvas2D::add_layer`.
+///
+/// A layer gets tessellated again whenever the range or the view size changed, the
+/// parameter changed (if `uses_parameter`) or `is_dirty` returns true. The resulting
+/// vertices and labels are cached in between.
+///
+/// Annotations (`AngleAnnotation`, `ScaleBar`, `NorthArrow`, `CovarianceEllipse`) and the
+/// point heatmap `DensityLayer` are drawn through this trait. The grid, the function,
+/// parametric, implicit and complex graphs and the point series are still built into the
+/// canvas. They depend on canvas state a layer can't reach: tick labels and their
+/// backgrounds, progressive refinement, the error callback, picking, snapping and the CSV
+/// export.
+pub trait CanvasLayer<P>
+where
+    P: Default,
+{
+    /// Draws the layer through `painter`, which maps data coordinates onto the canvas.
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>);
+
+    /// Requests a tessellation independent of range and parameter changes, e.g. after new
+    /// data arrived. Should be reset by `tessellate`.
+    fn is_dirty(&self) -> bool {
+        false
+    }
+
+    fn uses_parameter(&self) -> bool {
+        true
+    }
+
+    /// GPU work of the layer, added to the view of the canvas along with the layer. It is
+    /// drawn below the vertices of the canvas, `tessellate` is still called to update it.
+    fn gpu_pass(&self) -> Option<Arc<RefCell<dyn GPUPass>>> {
+        None
+    }
+}
+
+/// Drawing API handed to `CanvasLayer::tessellate`.
+///
+/// Positions are in data coordinates, thicknesses and radii in the units of
+/// `GraphStyle::thickness` (see `Thickness`).
+pub struct LayerPainter<'a, P>
+where
+    P: Default,
+{
+    canvas: &'a mut GPUCanvas2D<P>,
+    sections: Vec<Arc<RefCell<TextSection>>>,
+}
+
+impl<'a, P> LayerPainter<'a, P>
+where
+    P: Default,
+{
+    pub(crate) fn new(canvas: &'a mut GPUCanvas2D<P>) -> Self {
+        Self {
+            canvas,
+            sections: Vec::new(),
+        }
+    }
+
+    /// Text sections added by `label`, so they can be removed before the next tessellation.
+    pub(crate) fn into_sections(self) -> Vec<Arc<RefCell<TextSection>>> {
+        self.sections
+    }
+
+    pub fn parameter(&self) -> &P {
+        self.canvas.parameter()
+    }
+
+    pub fn x_range(&self) -> Interval {
+        self.canvas.x_range()
+    }
+
+    pub fn y_range(&self) -> Interval {
+        self.canvas.y_range()
+    }
+
+    /// Text style of the canvas labels, `None` if the canvas hides text.
+    pub fn text_style(&self) -> Option<&TextStyle> {
+        self.canvas.text_style()
+    }
+
+    /// Size of a physical pixel in data coordinates.
+    pub fn pixel_size(&self) -> (f64, f64) {
+        let (px, py) = self.canvas.pixel_scale();
+
+        (
+            self.canvas.x_range_len() / 2.0 / px as f64,
+            self.canvas.y_range_len() / 2.0 / py as f64,
+        )
+    }
+
+    fn screen(&self, point: (f64, f64)) -> [f32; 2] {
+        let (sx, sy) = self.canvas.data_to_view(point);
+        [sx, sy]
+    }
+
+    pub fn polyline(&mut self, points: &[(f64, f64)], thickness: f32, color: RGBA) {
+        self.stroke(points, thickness, color, LineJoin::Round, LineCap::Round);
+    }
+
+    pub fn stroke(
+        &mut self,
+        points: &[(f64, f64)],
+        thickness: f32,
+        color: RGBA,
+        join: LineJoin,
+        cap: LineCap,
+    ) {
+        let points: Vec<[f32; 2]> = points.iter().map(|point| self.screen(*point)).collect();
+        let width = self.canvas.line_thickness(thickness);
+
+        self.canvas.vertices_add_stroke(
+            &points,
+            &vec![width; points.len()],
+            &vec![color; points.len()],
+            join,
+            cap,
+        );
+    }
+
+    pub fn triangle(&mut self, corners: [(f64, f64); 3], color: RGBA) {
+        let corners = corners.map(|corner| self.screen(corner));
+        self.canvas.vertices_add_triangle(corners, color);
+    }
+
+    /// Fills the convex polygon `points` as a triangle fan.
+    pub fn convex_polygon(&mut self, points: &[(f64, f64)], color: RGBA) {
+        let Some(first) = points.first() else {
+            return;
+        };
+
+        for pair in points[1..].windows(2) {
+            self.triangle([*first, pair[0], pair[1]], color);
+        }
+    }
+
+    pub fn rect(&mut self, corner1: (f64, f64), corner2: (f64, f64), color: RGBA) {
+        let (corner1, corner2) = (self.screen(corner1), self.screen(corner2));
+        self.canvas.vertices_add_rect(corner1, corner2, color);
+    }
+
+    pub fn circle(&mut self, center: (f64, f64), radius: f32, color: RGBA) {
+        let center = self.screen(center);
+        let radius = self.canvas.line_thickness(radius);
+        self.canvas.vertices_add_circle(center, radius, color, 16);
+    }
+
+    /// Adds `text` centered at `position`.
+    pub fn label(&mut self, text: &str, position: (f64, f64), text_style: &TextStyle) {
+        self.label_aligned(
+            text,
+            position,
+            HorizontalAlign::Center,
+            VerticalAlign::Center,
+            text_style,
+        );
+    }
+
+    /// Adds `text` aligned to `position`, e.g. `VerticalAlign::Bottom` puts it above.
+    pub fn label_aligned(
+        &mut self,
+        text: &str,
+        position: (f64, f64),
+        h_align: HorizontalAlign,
+        v_align: VerticalAlign,
+        text_style: &TextStyle,
+    ) {
+        let [sx, sy] = self.screen(position);
+        let uv = ((sx + 1.0) / 2.0, (1.0 - sy) / 2.0);
+
+        let sections = self.canvas.text_add_label(
+            text,
+            uv,
+            Layout::default_single_line()
+                .h_align(h_align)
+                .v_align(v_align),
+            text_style,
+        );
+        self.sections.extend(sections);
+    }
+}
+
+impl<P> CanvasLayer<P> for CovarianceEllipse
+where
+    P: Default,
+{
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>) {
+        const RESOLUTION: usize = 128;
+
+        let outline = self.outline(RESOLUTION);
+
+        // the ellipse is convex, so a fan around the mean covers it
+        if let Some(fill) = self.style.fill {
+            for pair in outline.windows(2) {
+                painter.triangle([self.mean, pair[0], pair[1]], fill);
+            }
+        }
+
+        painter.polyline(&outline, self.style.thickness, self.style.color);
+
+        if let Some(radius) = self.style.mean_marker {
+            painter.circle(self.mean, radius, self.style.color);
+        }
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+}
+
+impl<P> CanvasLayer<P> for AngleAnnotation
+where
+    P: Default,
+{
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>) {
+        const RESOLUTION: usize = 48;
+        const LABEL_DISTANCE: f64 = 1.6;
+
+        painter.stroke(
+            &[self.rays[0], self.vertex, self.rays[1]],
+            self.style.thickness,
+            self.style.color,
+            LineJoin::MITER,
+            LineCap::Butt,
+        );
+
+        if !self.is_valid() {
+            return;
+        }
+
+        // the arc is laid out in pixels so it stays circular and ends on the drawn rays
+        // independent of the aspect ratio of the ranges
+        let (ux, uy) = painter.pixel_size();
+        let pixel_direction = |(x, y): (f64, f64)| {
+            Radians(((y - self.vertex.1) / uy).atan2((x - self.vertex.0) / ux))
+        };
+
+        let first = pixel_direction(self.rays[0]);
+        let sweep = (pixel_direction(self.rays[1]) - first).normalized();
+        let (start, sweep) = match self.directed || sweep.0 <= PI {
+            true => (first, sweep),
+            false => (first + sweep, Radians(TAU) - sweep),
+        };
+
+        let radius = self.style.radius as f64;
+        let at = |angle: Radians, radius: f64| {
+            let (sin, cos) = angle.sin_cos();
+            (
+                self.vertex.0 + radius * cos * ux,
+                self.vertex.1 + radius * sin * uy,
+            )
+        };
+        let arc: Vec<(f64, f64)> = (0..=RESOLUTION)
+            .map(|i| at(start + sweep * (i as f64 / RESOLUTION as f64), radius))
+            .collect();
+
+        if let Some(fill) = self.style.fill {
+            for pair in arc.windows(2) {
+                painter.triangle([self.vertex, pair[0], pair[1]], fill);
+            }
+        }
+        painter.polyline(&arc, self.style.thickness, self.style.color);
+
+        let Some(text_style) = painter.text_style().cloned() else {
+            return;
+        };
+        let angle = self.angle();
+        let text = match self.style.unit {
+            AngleUnit::Degrees => format!("{}°", format_readout_value(Degrees::from(angle).0)),
+            AngleUnit::Radians => format!("{} rad", format_readout_value(angle.0)),
+        };
+
+        painter.label(
+            &text_style.locale.localize(&text),
+            at(start + sweep * 0.5, radius * LABEL_DISTANCE),
+            &text_style,
+        );
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,139 @@
+pub mod math;
+pub use math::*;
+
+pub mod interval;
+pub use interval::*;
+
+pub mod history;
+pub use history::*;
+
+pub mod axis_scale;
+pub use axis_scale::*;
+
+pub mod projection;
+pub use projection::*;
+
+pub mod geometry;
+pub use geometry::*;
+
+mod contour;
+
+pub mod decimal_math;
+pub use decimal_math::*;
+
+pub mod color;
+pub use color::*;
+
+pub mod complex;
+pub use complex::*;
+
+mod complex_pass;
+
+pub mod gpudevice;
+pub use gpudevice::*;
+
+pub mod texture_copy;
+pub use texture_copy::*;
+
+mod glow;
+
+mod svg;
+
+pub mod postprocess;
+pub use postprocess::*;
+
+pub mod gpuview;
+pub use gpuview::{Font, *};
+
+pub mod text_layout;
+pub use text_layout::*;
+
+pub mod graph;
+pub use graph::*;
+
+pub mod theme;
+pub use theme::*;
+
+pub mod resources;
+pub use resources::*;
+
+pub mod timing;
+pub use timing::*;
+
+pub mod job;
+pub use job::*;
+
+pub mod context;
+pub use context::*;
+
+pub mod gpucanvas_2d;
+pub use gpucanvas_2d::*;
+
+pub mod widget;
+pub use widget::*;
+
+pub mod colorbar;
+pub use colorbar::*;
+
+pub mod density;
+pub use density::*;
+
+pub mod layer;
+pub use layer::*;
+
+pub mod snap;
+pub use snap::*;
+
+pub mod measure;
+pub use measure::*;
+
+pub mod scale_bar;
+pub use scale_bar::*;
+
+pub mod compass;
+pub use compass::*;
+
+pub mod crosshair;
+pub use crosshair::*;
+
+pub mod params;
+pub use params::*;
+
+pub mod controller;
+pub use controller::*;
+
+pub mod plot;
+pub use plot::*;
+
+pub mod export;
+pub use export::*;
+
+pub mod expr;
+pub use expr::*;
+
+pub mod data;
+pub use data::*;
+
+pub mod batch;
+pub use batch::*;
+
+pub mod watch;
+pub use watch::*;
+
+pub mod style_file;
+pub use style_file::*;
+
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "scripting")]
+pub use script::*;
+
+#[cfg(feature = "server")]
+pub mod ingest;
+#[cfg(feature = "server")]
+pub use ingest::*;
+
+#[cfg(feature = "evcxr")]
+pub mod notebook;
+#[cfg(feature = "evcxr")]
+pub use notebook::*;
@@ -1,20 +1,4 @@
-mod math;
-use math::*;
-
-mod decimal_math;
-use decimal_math::*;
-
-mod color;
-use color::*;
-
-mod gpuview;
-use gpuview::{Font, *};
-
-mod graph;
-use graph::*;
-
-mod gpucanvas_2d;
-use gpucanvas_2d::*;
+use complex_vis::*;
 
 use wgpu_text::glyph_brush::ab_glyph::{FontArc, PxScale};
 use wgpu_text::glyph_brush::{
@@ -41,100 +25,29 @@ struct GraphParam {
     a: f64,
 }
 
-struct App<'a> {
-    window: Option<Arc<Window>>,
-
-    device: Option<Arc<wgpu::Device>>,
-    queue: Option<Arc<wgpu::Queue>>,
+struct App {
+    context: Option<VisContext>,
 
-    multiview: GPUMultiView<'a>,
+    multiview: GPUMultiView<'static>,
     canvas: GPUCanvas2D<GraphParam>,
 
-    mouse_pos: PhysicalPosition<f64>,
-    mouse_delta: PhysicalPosition<f64>,
-    mouse_left: bool,
-
-    prev_t: std::time::Instant,
-    delta_t: std::time::Duration,
+    controller: CanvasController,
 }
 
-impl<'a> App<'a> {
+impl App {
     pub fn new() -> Self {
         Self {
-            window: None,
-            device: None,
-            queue: None,
+            context: None,
             multiview: GPUMultiView::new(),
             canvas: GPUCanvas2D::new(GPUViewFrame::Whole.with_margin((0.1, 0.1))),
-            mouse_pos: PhysicalPosition { x: 0.0, y: 0.0 },
-            mouse_delta: PhysicalPosition { x: 0.0, y: 0.0 },
-            mouse_left: false,
-            prev_t: std::time::Instant::now(),
-            delta_t: std::time::Duration::ZERO,
+            controller: CanvasController::new(),
         }
     }
 
-    pub async fn initialize(&mut self, window: Window) -> anyhow::Result<()> {
-        let window = Arc::new(window);
-
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
-
-        let surface = instance.create_surface(window.clone()).unwrap();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .context("GPU Adapter Request Failed.")?;
-
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let surface_format = wgpu::TextureFormat::Bgra8Unorm;
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: surface_format.required_features()
-                        | wgpu::Features::BGRA8UNORM_STORAGE
-                        | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-                        | wgpu::Features::TEXTURE_BINDING_ARRAY
-                        | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-                        | wgpu::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING
-                        | wgpu::Features::POLYGON_MODE_LINE
-                        | wgpu::Features::POLYGON_MODE_POINT
-                        | wgpu::Features::CLEAR_TEXTURE,
-                    required_limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: Some("Renderer Created Device"),
-                    memory_hints: wgpu::MemoryHints::Performance,
-                },
-                None, // Trace path
-            )
-            .await
-            .context("GPU Device Request Failed.")?;
-
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: window.inner_size().width,
-            height: window.inner_size().height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-        surface.configure(&device, &surface_config);
-
-        self.multiview.initialize(surface, surface_config, &device);
+    pub fn initialize(&mut self, window: Window) -> anyhow::Result<()> {
+        let mut context = VisContext::from_window(window)?;
+
+        self.multiview = context.create_multiview()?;
         self.multiview.set_clear_color(wgpu::Color::WHITE);
 
         {
@@ -143,50 +56,57 @@ impl<'a> App<'a> {
             // canvas_style.text = None;
         }
 
-        let square = FunctionGraph {
-            function: |x: f64, p: &GraphParam| (x - p.a).powi(2),
-            style: GraphStyle {
+        let square = FunctionGraph::new(
+            |x: f64, p: &GraphParam| (x - p.a).powi(2),
+            GraphStyle {
                 color: RGBA::new(131, 39, 196, 255),
                 thickness: Thickness::MEDIUM,
+                ..Default::default()
             },
-        };
+        );
 
-        let exp = FunctionGraph {
-            function: |x: f64, p: &GraphParam| (x * p.a).exp(),
-            style: GraphStyle {
+        let exp = FunctionGraph::new(
+            |x: f64, p: &GraphParam| (x * p.a).exp(),
+            GraphStyle {
                 color: RGBA::new(39, 187, 204, 255),
                 thickness: Thickness::EXTRABOLD,
+                ..Default::default()
             },
-        };
+        );
 
-        let cos = FunctionGraph {
-            function: |x: f64, p: &GraphParam| x.cos() - p.a,
-            style: GraphStyle {
+        let cos = FunctionGraph::new(
+            |x: f64, p: &GraphParam| x.cos() - p.a,
+            GraphStyle {
                 color: RGBA::new(230, 178, 57, 255),
                 thickness: Thickness::THIN,
+                ..Default::default()
             },
-        };
+        );
 
         self.canvas.add_function_graph(square);
         self.canvas.add_function_graph(exp);
         self.canvas.add_function_graph(cos);
 
-        self.window = Some(window);
-        self.device = Some(Arc::new(device));
-        self.queue = Some(Arc::new(queue));
+        self.canvas.add_widget(Widget::slider(
+            "a",
+            -2.0..2.0,
+            |p: &GraphParam| p.a,
+            |p, a| p.a = a,
+        ));
+
+        self.context = Some(context);
 
         Ok(())
     }
 }
 
-impl ApplicationHandler for App<'_> {
+impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         self.initialize(
             event_loop
                 .create_window(Window::default_attributes().with_transparent(true))
                 .unwrap(),
         )
-        .block_on()
         .unwrap();
 
         self.canvas.set_clear_color(RGBA::WHITE);
@@ -197,6 +117,13 @@ impl ApplicationHandler for App<'_> {
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        if self
+            .controller
+            .handle_window_event(&event, &mut self.canvas, &self.multiview)
+        {
+            return;
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 println!("The close button was pressed; stopping");
@@ -207,36 +134,35 @@ impl ApplicationHandler for App<'_> {
                 let _ = self.multiview.resize(
                     new_size.width,
                     new_size.height,
-                    self.device.as_ref().unwrap(),
+                    self.context.as_ref().unwrap().device(),
                 );
             }
             WindowEvent::RedrawRequested => {
-                let now = std::time::Instant::now();
-                self.delta_t = now - self.prev_t;
-                self.prev_t = now;
-
-                let num_vertices = self
-                    .canvas
-                    .get_view()
-                    .as_ref()
-                    .borrow()
-                    .get_render_vertices_len();
-
-                println!(
-                    "{}ms with {} vertices",
-                    self.delta_t.as_micros() as f32 / 1000.0,
-                    num_vertices
-                );
+                let context = self.context.as_mut().unwrap();
+                context.clock_mut().tick();
+
+                #[cfg(feature = "tracing")]
+                {
+                    let num_vertices = self
+                        .canvas
+                        .get_view()
+                        .as_ref()
+                        .borrow()
+                        .get_render_vertices_len();
+
+                    tracing::debug!(
+                        delta_ms = context.clock().delta_ms(),
+                        vertices = num_vertices,
+                        "frame"
+                    );
+                }
 
                 self.canvas.display();
 
-                let x =
-                    (self.mouse_pos.x as f32 / self.multiview.width().unwrap() as f32) * 2.0 - 1.0;
-                let y = -((self.mouse_pos.y as f32 / self.multiview.height().unwrap() as f32)
-                    * 2.0
-                    - 1.0);
-
-                let view_coords = self.multiview.get_view_coords_behind((x, y));
+                let view_coords = self
+                    .controller
+                    .mouse_position_screen(&self.multiview)
+                    .and_then(|pos| self.multiview.view_at(pos));
 
                 // println!(
                 //     "{:?}, {:?}, {:?}, {:?}",
@@ -246,87 +172,23 @@ impl ApplicationHandler for App<'_> {
                 //     self.canvas.y_range_len()
                 // );
 
-                let _ = self
-                    .multiview
-                    .render(self.device.as_ref().unwrap(), self.queue.as_ref().unwrap());
+                let context = self.context.as_ref().unwrap();
 
-                self.window.as_ref().unwrap().request_redraw();
+                let _ = self.multiview.render(context.device(), context.queue());
+
+                context.request_redraw();
             }
             WindowEvent::KeyboardInput { event, .. } => match event.physical_key {
                 PhysicalKey::Code(key_code) => match key_code {
                     KeyCode::Escape => {
                         event_loop.exit();
                     }
-                    KeyCode::KeyD => self.canvas.offset_range((0.2, 0.0)),
-                    KeyCode::KeyA => self.canvas.offset_range((-0.2, 0.0)),
-                    KeyCode::KeyW => self.canvas.offset_range((0.0, 0.2)),
-                    KeyCode::KeyS => self.canvas.offset_range((0.0, -0.2)),
-
                     KeyCode::ArrowUp => self.canvas.parameter_get_mut().a += 0.1,
                     KeyCode::ArrowDown => self.canvas.parameter_get_mut().a -= 0.1,
                     _ => {}
                 },
                 _ => (),
             },
-            WindowEvent::MouseInput { button, state, .. } => match (button, state) {
-                (MouseButton::Left, ElementState::Pressed) => self.mouse_left = true,
-                (MouseButton::Left, ElementState::Released) => self.mouse_left = false,
-                _ => {}
-            },
-            WindowEvent::CursorMoved { position, .. } => {
-                self.mouse_delta = PhysicalPosition {
-                    x: position.x - self.mouse_pos.x,
-                    y: position.y - self.mouse_pos.y,
-                };
-
-                if self.mouse_left {
-                    let x =
-                        (position.x as f32 / self.multiview.width().unwrap() as f32) * 2.0 - 1.0;
-                    let y = -((position.y as f32 / self.multiview.height().unwrap() as f32) * 2.0
-                        - 1.0);
-
-                    let prev_x = (self.mouse_pos.x as f32 / self.multiview.width().unwrap() as f32)
-                        * 2.0
-                        - 1.0;
-                    let prev_y = -((self.mouse_pos.y as f32
-                        / self.multiview.height().unwrap() as f32)
-                        * 2.0
-                        - 1.0);
-
-                    let view_pos = self.multiview.get_view_coords_behind((x, y));
-
-                    let prev_view_pos = self.multiview.get_view_coords_behind((prev_x, prev_y));
-
-                    match (view_pos, prev_view_pos) {
-                        (Some(view_pos), Some(prev_view_pos)) => {
-                            if view_pos.view_index != prev_view_pos.view_index {
-                                return;
-                            }
-
-                            let dx = view_pos.coordinates.0 - prev_view_pos.coordinates.0;
-                            let dy = view_pos.coordinates.1 - prev_view_pos.coordinates.1;
-
-                            let x_rng_len = self.canvas.x_range_len();
-                            let y_rng_len = self.canvas.y_range_len();
-
-                            self.canvas.offset_range((
-                                -dx as f64 * x_rng_len * 0.5,
-                                -dy as f64 * y_rng_len * 0.5,
-                            ));
-                        }
-                        _ => {}
-                    }
-                }
-
-                self.mouse_pos = position;
-            }
-            WindowEvent::MouseWheel { delta, .. } => match delta {
-                event::MouseScrollDelta::LineDelta(x, y) => {
-                    let scale = 1.0 - (y * 0.05);
-                    self.canvas.scale_range((scale as f64, scale as f64))
-                }
-                event::MouseScrollDelta::PixelDelta(amt) => (),
-            },
             _ => (),
         }
     }
@@ -338,6 +200,14 @@ fn main() {
     env_logger::init();
     println!("Hello, world!");
 
+    #[cfg(feature = "scripting")]
+    if let Some(path) = std::env::args().nth(1) {
+        if let Err(error) = run_script(path, |error| eprintln!("{:?}", error)) {
+            eprintln!("{:?}", error);
+        }
+        return;
+    }
+
     let event_loop = EventLoop::new().unwrap();
 
     // ControlFlow::Wait pauses the event loop if no events are available to process.
@@ -1,4 +1,4 @@
-use std::ops::{Add, Div, Mul, Range, Sub};
+use std::ops::{Add, Div, Mul, Neg, Range, Sub};
 
 pub fn lerp<T>(x: T, from: &Range<T>, to: &Range<T>) -> T
 where
@@ -13,3 +13,187 @@ where
     let res = to.start + to_from_ratio * (x - from.start);
     res
 }
+
+/// Floating point type the helpers below work with.
+pub trait Scalar:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+}
+
+macro_rules! impl_scalar {
+    ($t:ty) => {
+        impl Scalar for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn min(self, other: Self) -> Self {
+                <$t>::min(self, other)
+            }
+
+            fn max(self, other: Self) -> Self {
+                <$t>::max(self, other)
+            }
+        }
+    };
+}
+
+impl_scalar!(f32);
+impl_scalar!(f64);
+
+//-- interpolation
+
+/// Position of `x` in `range`, `0` at its start and `1` at its end.
+pub fn inverse_lerp<T: Scalar>(x: T, range: &Range<T>) -> T {
+    (x - range.start) / (range.end - range.start)
+}
+
+/// Clamps `x` into `range`, which may also be descending.
+pub fn clamp_to<T: Scalar>(x: T, range: &Range<T>) -> T {
+    let (min, max) = match range.start <= range.end {
+        true => (range.start, range.end),
+        false => (range.end, range.start),
+    };
+
+    match x {
+        x if x < min => min,
+        x if x > max => max,
+        x => x,
+    }
+}
+
+/// Clamps `x` into `0..=1`.
+pub fn saturate<T: Scalar>(x: T) -> T {
+    clamp_to(x, &(T::ZERO..T::ONE))
+}
+
+/// `lerp` with `x` clamped to `from`, so the result stays inside of `to`.
+pub fn remap_clamped<T: Scalar>(x: T, from: &Range<T>, to: &Range<T>) -> T {
+    lerp(saturate(inverse_lerp(x, from)), &(T::ZERO..T::ONE), to)
+}
+
+/// Smooth Hermite step from `0` at `edges.start` to `1` at `edges.end`.
+pub fn smoothstep<T: Scalar>(x: T, edges: &Range<T>) -> T {
+    let t = saturate(inverse_lerp(x, edges));
+    let three = T::ONE + T::ONE + T::ONE;
+
+    t * t * (three - (T::ONE + T::ONE) * t)
+}
+
+//-- approximate comparisons
+
+pub fn approx_eq<T: Scalar>(a: T, b: T, tolerance: T) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+pub fn approx_zero<T: Scalar>(x: T, tolerance: T) -> bool {
+    approx_eq(x, T::ZERO, tolerance)
+}
+
+/// Component wise `approx_eq` of two points.
+pub fn approx_eq_points<T: Scalar>(a: [T; 2], b: [T; 2], tolerance: T) -> bool {
+    approx_eq(a[0], b[0], tolerance) && approx_eq(a[1], b[1], tolerance)
+}
+
+//-- angles
+
+/// Angle in degrees, converts from and into `Radians`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Degrees<T = f64>(pub T);
+
+/// Angle in radians, converts from and into `Degrees`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Radians<T = f64>(pub T);
+
+macro_rules! impl_angle_ops {
+    ($angle:ident) => {
+        impl<T: Scalar> Add for $angle<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0 + other.0)
+            }
+        }
+
+        impl<T: Scalar> Sub for $angle<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0 - other.0)
+            }
+        }
+
+        impl<T: Scalar> Neg for $angle<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl<T: Scalar> Mul<T> for $angle<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                Self(self.0 * factor)
+            }
+        }
+    };
+}
+
+impl_angle_ops!(Degrees);
+impl_angle_ops!(Radians);
+
+macro_rules! impl_angle_conversions {
+    ($t:ident) => {
+        impl From<Degrees<$t>> for Radians<$t> {
+            fn from(degrees: Degrees<$t>) -> Self {
+                Self(degrees.0.to_radians())
+            }
+        }
+
+        impl From<Radians<$t>> for Degrees<$t> {
+            fn from(radians: Radians<$t>) -> Self {
+                Self(radians.0.to_degrees())
+            }
+        }
+
+        impl Degrees<$t> {
+            /// Same angle in `0..360`.
+            pub fn normalized(self) -> Self {
+                Self(self.0.rem_euclid(360.0))
+            }
+        }
+
+        impl Radians<$t> {
+            /// Same angle in `0..2π`.
+            pub fn normalized(self) -> Self {
+                Self(self.0.rem_euclid(std::$t::consts::TAU))
+            }
+
+            pub fn sin_cos(self) -> ($t, $t) {
+                self.0.sin_cos()
+            }
+        }
+    };
+}
+
+impl_angle_conversions!(f32);
+impl_angle_conversions!(f64);
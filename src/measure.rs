@@ -0,0 +1,144 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::color::RGBA;
+use crate::graph::Thickness;
+use crate::math::Radians;
+
+/// Two points picked with the measure tool, drawn by `GPUCanvas2D::set_measurement`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub start: (f64, f64),
+    pub end: (f64, f64),
+}
+
+impl Measurement {
+    pub fn new(start: (f64, f64), end: (f64, f64)) -> Self {
+        Self { start, end }
+    }
+
+    pub fn dx(&self) -> f64 {
+        self.end.0 - self.start.0
+    }
+
+    pub fn dy(&self) -> f64 {
+        self.end.1 - self.start.1
+    }
+
+    /// Euclidean distance in data coordinates.
+    pub fn distance(&self) -> f64 {
+        self.dx().hypot(self.dy())
+    }
+
+    /// `dy / dx`, `None` for vertical (or zero length) measurements.
+    pub fn slope(&self) -> Option<f64> {
+        match self.dx() {
+            0.0 => None,
+            dx => Some(self.dy() / dx),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MeasureTool {
+    /// Two clicks, see `Measurement`.
+    Distance,
+    /// Three clicks (vertex and one point on each ray), see `AngleAnnotation`.
+    Angle,
+}
+
+impl MeasureTool {
+    /// Number of clicks needed to complete a measurement.
+    pub fn points(&self) -> usize {
+        match self {
+            MeasureTool::Distance => 2,
+            MeasureTool::Angle => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AngleStyle {
+    pub color: RGBA,
+    pub thickness: f32,
+    /// Radius of the arc in physical pixels
+    pub radius: f32,
+    /// Color of the sector enclosed by the arc
+    pub fill: Option<RGBA>,
+    pub unit: AngleUnit,
+}
+
+impl Default for AngleStyle {
+    fn default() -> Self {
+        Self {
+            color: RGBA::BLACK,
+            thickness: Thickness::THIN,
+            radius: 40.0,
+            fill: Some(RGBA::new(0, 0, 0, 32)),
+            unit: AngleUnit::default(),
+        }
+    }
+}
+
+/// Angle between two rays from `vertex` through `rays`, drawn as an arc with a label.
+///
+/// Add it with `GPUCanvas2D::add_layer` or pick it interactively with `MeasureTool::Angle`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AngleAnnotation {
+    pub vertex: (f64, f64),
+    /// A point on the first and on the second ray.
+    pub rays: [(f64, f64); 2],
+    /// Measures counterclockwise from the first to the second ray instead of the smaller angle.
+    pub directed: bool,
+    pub style: AngleStyle,
+}
+
+impl AngleAnnotation {
+    pub fn new(vertex: (f64, f64), first: (f64, f64), second: (f64, f64)) -> Self {
+        Self {
+            vertex,
+            rays: [first, second],
+            directed: false,
+            style: AngleStyle::default(),
+        }
+    }
+
+    pub fn with_directed(mut self, directed: bool) -> Self {
+        self.directed = directed;
+        self
+    }
+
+    pub fn with_style(mut self, style: AngleStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Direction of ray `index` to the x axis.
+    pub fn direction(&self, index: usize) -> Radians {
+        let (x, y) = self.rays[index];
+        Radians((y - self.vertex.1).atan2(x - self.vertex.0))
+    }
+
+    /// `0..2π` if `directed` and `0..=π` otherwise.
+    pub fn angle(&self) -> Radians {
+        let angle = (self.direction(1) - self.direction(0)).normalized();
+
+        match self.directed || angle.0 <= PI {
+            true => angle,
+            false => Radians(TAU) - angle,
+        }
+    }
+
+    /// False if a ray has no direction because its point equals the vertex.
+    pub fn is_valid(&self) -> bool {
+        self.rays.iter().all(|ray| *ray != self.vertex)
+    }
+}
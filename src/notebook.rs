@@ -0,0 +1,61 @@
+//! Inline output for Rust notebooks using the evcxr display protocol.
+//!
+//! ```ignore
+//! :dep complex-vis = { path = "...", features = ["evcxr"] }
+//! use complex_vis::*;
+//!
+//! Plot::new().line(|x| x.sin()).x_range(-5.0..5.0)
+//! ```
+
+use base64::Engine;
+
+use crate::context::VisContext;
+use crate::export::{encode_png, render_canvas_offscreen};
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::plot::Plot;
+
+/// Emits `content` with the given mime type as notebook output.
+pub fn evcxr_display_content(mime_type: &str, content: &str) {
+    println!(
+        "EVCXR_BEGIN_CONTENT {}\n{}\nEVCXR_END_CONTENT",
+        mime_type, content
+    );
+}
+
+/// Emits a PNG image as notebook output.
+pub fn evcxr_display_png(png: &[u8]) {
+    evcxr_display_content(
+        "image/png",
+        &base64::engine::general_purpose::STANDARD.encode(png),
+    );
+}
+
+fn display_result(result: anyhow::Result<Vec<u8>>) {
+    match result {
+        Ok(png) => evcxr_display_png(&png),
+        Err(error) => evcxr_display_content("text/plain", &format!("{:#}", error)),
+    }
+}
+
+/// Renders `canvas` offscreen and emits it as notebook output.
+pub fn evcxr_display_canvas<P: Default>(canvas: &mut GPUCanvas2D<P>, width: u32, height: u32) {
+    display_result((|| {
+        let context = VisContext::headless()?;
+        let mut multiview = context.create_offscreen_multiview(width, height);
+        multiview.set_clear_color(wgpu::Color::WHITE);
+
+        let pixels = render_canvas_offscreen(&context, &mut multiview, canvas)?;
+
+        encode_png(width, height, &pixels)
+    })());
+}
+
+impl<P> Plot<P>
+where
+    P: Default + Clone + Send + 'static,
+{
+    /// Called by evcxr to display a plot which is the result of a notebook cell.
+    pub fn evcxr_display(&self) {
+        display_result(self.render_png());
+    }
+}
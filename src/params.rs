@@ -0,0 +1,170 @@
+use std::ops::RangeInclusive;
+
+use crate::widget::Widget;
+
+#[cfg(feature = "derive")]
+pub use complex_vis_derive::CanvasParams;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Float,
+    Integer,
+    Bool,
+}
+
+/// Metadata of a single named parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub range: Option<RangeInclusive<f64>>,
+}
+
+impl ParamSpec {
+    /// Clamps `value` into the range and rounds it to the kind of the parameter.
+    pub fn sanitize(&self, value: f64) -> anyhow::Result<f64> {
+        if !value.is_finite() {
+            anyhow::bail!("Value of parameter \"{}\" is not finite.", self.name);
+        }
+
+        let value = match &self.range {
+            Some(range) => value.clamp(*range.start(), *range.end()),
+            None => value,
+        };
+
+        Ok(match self.kind {
+            ParamKind::Float => value,
+            ParamKind::Integer => value.round(),
+            ParamKind::Bool => (value != 0.0) as u8 as f64,
+        })
+    }
+}
+
+/// Runtime description of the parameters of a `CanvasParams` struct.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParameterSet {
+    specs: Vec<ParamSpec>,
+}
+
+impl ParameterSet {
+    pub fn new(specs: Vec<ParamSpec>) -> Self {
+        Self { specs }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParamSpec> {
+        self.specs.iter().find(|spec| spec.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ParamSpec> {
+        self.specs.iter()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.specs.iter().map(|spec| spec.name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+}
+
+/// Parameter struct whose fields can be read and written by name, usually implemented with
+/// `#[derive(CanvasParams)]` (feature `derive`).
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use complex_vis::CanvasParams;
+///
+/// #[derive(Default, CanvasParams)]
+/// struct Wave {
+///     #[param(range = 0.0..=10.0)]
+///     frequency: f64,
+///     harmonics: u32,
+///     inverted: bool,
+///     #[param(skip)]
+///     label: String,
+/// }
+///
+/// let mut wave = Wave::default();
+/// assert_eq!(wave.set_param("frequency", 12.0).unwrap(), 10.0);
+/// assert_eq!(wave.set_param("harmonics", 2.6).unwrap(), 3.0);
+/// assert!(wave.set_param("label", 1.0).is_err());
+/// assert_eq!(Wave::parameter_set().len(), 3);
+/// # }
+/// ```
+pub trait CanvasParams {
+    fn parameter_set() -> ParameterSet;
+
+    fn get_param(&self, name: &str) -> Option<f64>;
+
+    /// Writes `value` to the field `name` without any checks, returns false for unknown names.
+    fn write_param(&mut self, name: &str, value: f64) -> bool;
+
+    /// Writes `value` clamped to the range of the parameter and returns the written value.
+    fn set_param(&mut self, name: &str, value: f64) -> anyhow::Result<f64> {
+        let set = Self::parameter_set();
+        let Some(spec) = set.get(name) else {
+            anyhow::bail!("Unknown parameter \"{name}\".");
+        };
+
+        let value = spec.sanitize(value)?;
+        self.write_param(name, value);
+
+        Ok(value)
+    }
+
+    fn values(&self) -> Vec<(&'static str, f64)> {
+        Self::parameter_set()
+            .names()
+            .filter_map(|name| Some((name, self.get_param(name)?)))
+            .collect()
+    }
+
+    #[cfg(feature = "scripting")]
+    fn to_script_params(&self) -> crate::script::ScriptParams {
+        crate::script::ScriptParams(
+            self.values()
+                .into_iter()
+                .map(|(name, value)| (name.into(), rhai::Dynamic::from_float(value)))
+                .collect(),
+        )
+    }
+
+    /// A slider for every parameter with a range and a toggle for every bool parameter.
+    fn widgets() -> Vec<Widget<Self>>
+    where
+        Self: Sized + 'static,
+    {
+        Self::parameter_set()
+            .iter()
+            .filter_map(|spec| {
+                let name = spec.name;
+                let get = move |parameter: &Self| parameter.get_param(name).unwrap_or_default();
+
+                match (spec.kind, &spec.range) {
+                    (ParamKind::Bool, _) => Some(Widget::toggle(
+                        name,
+                        move |parameter: &Self| get(parameter) != 0.0,
+                        move |parameter, value| {
+                            let _ = parameter.set_param(name, value as u8 as f64);
+                        },
+                    )),
+                    (_, Some(range)) => Some(Widget::slider(
+                        name,
+                        *range.start()..*range.end(),
+                        get,
+                        move |parameter, value| {
+                            let _ = parameter.set_param(name, value);
+                        },
+                    )),
+                    (_, None) => None,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,28 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) id: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) id: u32,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.id = model.id;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) u32 {
+    // id 0 is not pickable and must not hide what is below
+    if (in.id == 0u) {
+        discard;
+    }
+    return in.id;
+}
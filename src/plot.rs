@@ -0,0 +1,1111 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Context;
+use wgpu_text::glyph_brush::{HorizontalAlign, Layout, SectionBuilder, Text, VerticalAlign};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
+
+use crate::axis_scale::AxisScale;
+use crate::color::{ColorVisionFilter, RGBA};
+use crate::context::VisContext;
+use crate::controller::{CanvasController, KeyBindings};
+use crate::crosshair::CrosshairStyle;
+use crate::export::{
+    encode_png, render_canvas_offscreen, render_canvas_svg, write_png, write_svg, HtmlExport,
+    SeriesMetadata,
+};
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::gpuview::{GPUMultiView, GPUViewFrame, TextSection};
+use crate::graph::*;
+use crate::postprocess::PostEffect;
+use crate::projection::CoordinateTransform;
+use crate::theme::Theme;
+use crate::timing::TickScheduler;
+use crate::widget::Widget;
+
+/// A series which can be added to a `Plot`.
+#[derive(Debug)]
+pub enum PlotSeries<P> {
+    Line(FunctionGraph<f64, P, f64>),
+    Scatter(PointSeries),
+    Ellipse(CovarianceEllipse),
+}
+
+impl<P> Clone for PlotSeries<P> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Line(graph) => Self::Line(graph.clone()),
+            Self::Scatter(series) => Self::Scatter(series.clone()),
+            Self::Ellipse(ellipse) => Self::Ellipse(*ellipse),
+        }
+    }
+}
+
+impl<P> PlotSeries<P> {
+    fn metadata(&self) -> SeriesMetadata {
+        match self {
+            Self::Line(graph) => SeriesMetadata {
+                kind: "line",
+                color: graph.style.color,
+                points: None,
+            },
+            Self::Scatter(series) => SeriesMetadata {
+                kind: "scatter",
+                color: series.style.color,
+                points: Some(series.points.len()),
+            },
+            Self::Ellipse(ellipse) => SeriesMetadata {
+                kind: "ellipse",
+                color: ellipse.style.color,
+                points: None,
+            },
+        }
+    }
+}
+
+impl<P> From<FunctionGraph<f64, P, f64>> for PlotSeries<P> {
+    fn from(value: FunctionGraph<f64, P, f64>) -> Self {
+        Self::Line(value)
+    }
+}
+
+impl<P> From<PointSeries> for PlotSeries<P> {
+    fn from(value: PointSeries) -> Self {
+        Self::Scatter(value)
+    }
+}
+
+impl<P> From<CovarianceEllipse> for PlotSeries<P> {
+    fn from(value: CovarianceEllipse) -> Self {
+        Self::Ellipse(value)
+    }
+}
+
+/// Commands sent from a `PlotHandle` to the render loop of a detached plot.
+#[derive(Debug)]
+pub enum PlotCommand<P> {
+    /// Adds a series at the given position, reserved by the sender so concurrent senders
+    /// can't swap positions.
+    AddSeries(usize, PlotSeries<P>),
+    /// Replaces the series at the given position (in the order the series were added).
+    ReplaceSeries(usize, PlotSeries<P>),
+    /// Appends points to the point series at the given position, keeping only the most
+    /// recent ones if a capacity is given.
+    AppendPoints(usize, Vec<(f64, f64)>, Option<usize>),
+    ClearSeries,
+    SetParameter(P),
+    SetStyle(EnviromentStyle),
+    SetTheme(Theme),
+    SetRange(Range<f64>, Range<f64>),
+    /// Stops (`true`) or restarts (`false`) the redraw loop.
+    SetPaused(bool),
+    Close,
+}
+
+/// One-call facade for quick plots.
+///
+/// ```no_run
+/// use complex_vis::Plot;
+///
+/// Plot::new()
+///     .line(|x| x.sin())
+///     .scatter(vec![(0.0, 0.0), (1.0, 0.5)])
+///     .title("sin")
+///     .show()
+///     .unwrap();
+/// ```
+pub struct Plot<P = ()>
+where
+    P: Default,
+{
+    title: Option<String>,
+    window_size: (u32, u32),
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+    x_scale: AxisScale,
+    y_scale: AxisScale,
+    projection: Option<Arc<dyn CoordinateTransform>>,
+    style: EnviromentStyle,
+    /// Clear color and graph style of the theme, its style is kept in `style`
+    theme: Option<Theme>,
+
+    series: Vec<PlotSeries<P>>,
+    parameter: P,
+    palette: Vec<RGBA>,
+    color_index: usize,
+    progressive: bool,
+    deterministic: bool,
+    cursor_readout: bool,
+    crosshair: Option<CrosshairStyle>,
+    accessibility: Option<AccessibilityStyle>,
+    key_bindings: KeyBindings,
+    color_vision_filter: ColorVisionFilter,
+    post_effects: Vec<PostEffect>,
+    widgets: Vec<Widget<P>>,
+    ticks: TickScheduler<GPUCanvas2D<P>>,
+}
+
+impl Default for Plot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plot {
+    /// Colors assigned to series which are added without an explicit style.
+    pub const DEFAULT_COLORS: [RGBA; 6] = [
+        RGBA::new(131, 39, 196, 255),
+        RGBA::new(39, 187, 204, 255),
+        RGBA::new(230, 178, 57, 255),
+        RGBA::new(214, 69, 65, 255),
+        RGBA::new(60, 170, 90, 255),
+        RGBA::new(90, 90, 90, 255),
+    ];
+
+    pub fn new() -> Self {
+        Self::with_parameter(())
+    }
+}
+
+impl<P> Plot<P>
+where
+    P: Default + Send + 'static,
+{
+    /// Creates a plot whose functions get evaluated with `parameter`.
+    pub fn with_parameter(parameter: P) -> Self {
+        Self {
+            title: None,
+            window_size: (1024, 768),
+            x_range: -1.0..1.0,
+            y_range: -1.0..1.0,
+            x_scale: AxisScale::Linear,
+            y_scale: AxisScale::Linear,
+            projection: None,
+            style: EnviromentStyle::default(),
+            theme: None,
+            series: Vec::new(),
+            parameter,
+            palette: Plot::DEFAULT_COLORS.to_vec(),
+            color_index: 0,
+            progressive: false,
+            deterministic: false,
+            cursor_readout: false,
+            crosshair: None,
+            accessibility: None,
+            key_bindings: KeyBindings::default(),
+            color_vision_filter: ColorVisionFilter::None,
+            post_effects: Vec::new(),
+            widgets: Vec::new(),
+            ticks: TickScheduler::new(),
+        }
+    }
+
+    fn next_color(&mut self) -> RGBA {
+        let color = self.palette[self.color_index % self.palette.len()];
+        self.color_index += 1;
+
+        color
+    }
+
+    pub fn line(self, function: impl Fn(f64) -> f64 + Send + Sync + 'static) -> Self {
+        self.line_param(move |x, _| function(x))
+    }
+
+    pub fn line_param(mut self, function: impl Fn(f64, &P) -> f64 + Send + Sync + 'static) -> Self {
+        let style = GraphStyle {
+            color: self.next_color(),
+            thickness: Thickness::MEDIUM,
+            ..Default::default()
+        };
+
+        self.line_styled(function, style)
+    }
+
+    pub fn line_styled(
+        self,
+        function: impl Fn(f64, &P) -> f64 + Send + Sync + 'static,
+        style: GraphStyle,
+    ) -> Self {
+        self.series(FunctionGraph::new(function, style))
+    }
+
+    /// Adds a line series for every parameter value, see `function_family`.
+    pub fn function_family(
+        self,
+        function: impl Fn(f64, f64, &P) -> f64 + Send + Sync + 'static,
+        param_values: Vec<f64>,
+        style_fn: impl Fn(f64, RGBA) -> GraphStyle,
+    ) -> Self {
+        function_family(function, &param_values, style_fn)
+            .into_iter()
+            .fold(self, |plot, graph| plot.series(graph))
+    }
+
+    /// Adds every piece as its own line series.
+    pub fn piecewise(self, piecewise_graph: PiecewiseGraph<P>) -> Self {
+        piecewise_graph
+            .graphs()
+            .into_iter()
+            .fold(self, |plot, graph| plot.series(graph))
+    }
+
+    pub fn scatter(mut self, points: Vec<(f64, f64)>) -> Self {
+        let style = PointStyle {
+            color: self.next_color(),
+            ..Default::default()
+        };
+
+        self.scatter_styled(points, style)
+    }
+
+    pub fn scatter_styled(self, points: Vec<(f64, f64)>, style: PointStyle) -> Self {
+        self.series(PointSeries { points, style })
+    }
+
+    pub fn series(mut self, series: impl Into<PlotSeries<P>>) -> Self {
+        self.series.push(series.into());
+        self
+    }
+
+    pub fn series_count(&self) -> usize {
+        self.series.len()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn x_range(mut self, x_range: Range<f64>) -> Self {
+        self.x_range = x_range;
+        self
+    }
+
+    pub fn y_range(mut self, y_range: Range<f64>) -> Self {
+        self.y_range = y_range;
+        self
+    }
+
+    /// See `GPUCanvas2D::set_x_scale`.
+    pub fn x_scale(mut self, x_scale: AxisScale) -> Self {
+        self.x_scale = x_scale;
+        self
+    }
+
+    pub fn y_scale(mut self, y_scale: AxisScale) -> Self {
+        self.y_scale = y_scale;
+        self
+    }
+
+    /// See `GPUCanvas2D::set_projection`.
+    pub fn projection(mut self, projection: impl CoordinateTransform + 'static) -> Self {
+        self.projection = Some(Arc::new(projection));
+        self
+    }
+
+    /// Titles the x axis below its labels, see `AxisTitleStyle`.
+    pub fn x_title(mut self, title: &str) -> Self {
+        self.style.x.title = Some(AxisTitleStyle::new(title));
+        self
+    }
+
+    /// Titles the y axis vertically along the left edge.
+    pub fn y_title(mut self, title: &str) -> Self {
+        self.style.y.title = Some(AxisTitleStyle::new(title));
+        self
+    }
+
+    /// Draws function graphs coarsely first and refines them while the window is idle.
+    pub fn progressive(mut self, enabled: bool) -> Self {
+        self.progressive = enabled;
+        self
+    }
+
+    /// Renders with a fixed frame clock, without multisampling and with serialized frames,
+    /// see `VisContext::set_deterministic`. Exports become bit-stable across runs.
+    pub fn deterministic(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        self
+    }
+
+    /// Adds a slider or toggle bound to the plot parameter.
+    pub fn widget(mut self, widget: Widget<P>) -> Self {
+        self.widgets.push(widget);
+        self
+    }
+
+    /// Calls `callback` every `interval` while the window is open, independent of redraws
+    /// (e.g. to stream data or animate the parameter). The canvas gets redrawn after ticks.
+    pub fn tick(
+        mut self,
+        interval: Duration,
+        callback: impl FnMut(&mut GPUCanvas2D<P>, Duration) + Send + 'static,
+    ) -> Self {
+        self.ticks.add_task(interval, callback);
+        self
+    }
+
+    /// Shows the values of all function graphs at the mouse cursor.
+    pub fn cursor_readout(mut self, enabled: bool) -> Self {
+        self.cursor_readout = enabled;
+        self
+    }
+
+    /// Draws a crosshair with the coordinates under the mouse cursor.
+    pub fn crosshair(mut self, style: CrosshairStyle) -> Self {
+        self.crosshair = Some(style);
+        self
+    }
+
+    /// Enforces high contrast colors, a minimum text size and thicker lines.
+    pub fn accessibility(mut self, accessibility: AccessibilityStyle) -> Self {
+        self.accessibility = Some(accessibility);
+        self
+    }
+
+    /// Colors assigned to series added afterwards without an explicit style,
+    /// e.g. one of the color blind safe `Palette`s.
+    pub fn palette(mut self, palette: &[RGBA]) -> Self {
+        if !palette.is_empty() {
+            self.palette = palette.to_vec();
+            self.color_index = 0;
+        }
+        self
+    }
+
+    /// Previews the plot as seen with a color vision deficiency.
+    pub fn color_vision_filter(mut self, filter: ColorVisionFilter) -> Self {
+        self.color_vision_filter = filter;
+        self
+    }
+
+    /// Appends a full screen pass applied to the rendered plot.
+    pub fn post_effect(mut self, effect: PostEffect) -> Self {
+        self.post_effects.push(effect);
+        self
+    }
+
+    /// Replaces the default WASD and `+`/`-` navigation keys.
+    pub fn key_bindings(mut self, key_bindings: KeyBindings) -> Self {
+        self.key_bindings = key_bindings;
+        self
+    }
+
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = (width, height);
+        self
+    }
+
+    pub fn style_get_mut(&mut self) -> &mut EnviromentStyle {
+        &mut self.style
+    }
+
+    /// Replaces the style and sets the clear color and graph style of `theme`, see
+    /// `GPUCanvas2D::set_theme`. Later changes through `style_get_mut` still apply.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.style = theme.enviroment.clone();
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Renders the plot offscreen (without opening a window) and returns the
+    /// tightly packed RGBA pixels of the image.
+    pub fn render_image(&self) -> anyhow::Result<Vec<u8>>
+    where
+        P: Clone,
+    {
+        let mut context = VisContext::headless()?;
+        context.set_deterministic(self.deterministic);
+
+        self.render_image_in(&context)
+    }
+
+    /// Like `render_image`, but renders with an existing (headless) context, e.g. to share
+    /// one device between many images. The deterministic mode of the context is used.
+    pub fn render_image_in(&self, context: &VisContext) -> anyhow::Result<Vec<u8>>
+    where
+        P: Clone,
+    {
+        let (mut multiview, mut canvas) = self.offscreen_canvas(context)?;
+
+        render_canvas_offscreen(context, &mut multiview, &mut canvas)
+    }
+
+    /// Like `render_image_in`, but returns the plot as SVG document, see
+    /// `GPUMultiView::to_svg`. Post effects and the color vision filter are not applied.
+    pub fn render_svg_in(&self, context: &VisContext) -> anyhow::Result<String>
+    where
+        P: Clone,
+    {
+        let (mut multiview, mut canvas) = self.offscreen_canvas(context)?;
+
+        render_canvas_svg(context, &mut multiview, &mut canvas)
+    }
+
+    pub fn export_svg(&self, path: impl AsRef<Path>) -> anyhow::Result<()>
+    where
+        P: Clone,
+    {
+        let mut context = VisContext::headless()?;
+        context.set_deterministic(self.deterministic);
+
+        write_svg(path, &self.render_svg_in(&context)?)
+    }
+
+    /// Offscreen multiview of the window size and the canvas showing the plot in it.
+    fn offscreen_canvas<'a>(
+        &self,
+        context: &VisContext,
+    ) -> anyhow::Result<(GPUMultiView<'a>, GPUCanvas2D<P>)>
+    where
+        P: Clone,
+    {
+        let (width, height) = self.window_size;
+
+        let mut multiview = context.create_offscreen_multiview(width, height);
+        multiview.set_clear_color(wgpu::Color::WHITE);
+        multiview.set_color_vision_filter(self.color_vision_filter);
+        multiview.set_post_effects(self.post_effects.clone());
+
+        let (mut canvas, _) = create_canvas(
+            self.style.clone(),
+            self.x_range.clone(),
+            self.y_range.clone(),
+            self.parameter.clone(),
+            self.series.clone(),
+        );
+        canvas.set_x_scale(self.x_scale);
+        canvas.set_y_scale(self.y_scale);
+        if let Some(projection) = &self.projection {
+            canvas.set_projection(projection.clone());
+        }
+        if let Some(theme) = &self.theme {
+            canvas.set_theme(&Theme {
+                enviroment: self.style.clone(),
+                ..theme.clone()
+            });
+        }
+        canvas.set_accessibility(self.accessibility);
+
+        if let Some(title) = &self.title {
+            add_title(&mut multiview, context, title)?;
+        }
+
+        Ok((multiview, canvas))
+    }
+
+    pub fn render_png(&self) -> anyhow::Result<Vec<u8>>
+    where
+        P: Clone,
+    {
+        let (width, height) = self.window_size;
+
+        encode_png(width, height, &self.render_image()?)
+    }
+
+    pub fn export_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()>
+    where
+        P: Clone,
+    {
+        let (width, height) = self.window_size;
+
+        write_png(path, width, height, &self.render_image()?)
+    }
+
+    /// Writes a self-contained HTML file embedding the rendered plot as PNG together
+    /// with its range and series metadata.
+    pub fn export_html(&self, path: impl AsRef<Path>) -> anyhow::Result<()>
+    where
+        P: Clone,
+    {
+        let (width, height) = self.window_size;
+
+        let export = HtmlExport {
+            title: self.title.clone().unwrap_or("Plot".to_string()),
+            width,
+            height,
+            png: self.render_png()?,
+            x_range: self.x_range.clone(),
+            y_range: self.y_range.clone(),
+            series: self.series.iter().map(PlotSeries::metadata).collect(),
+        };
+
+        export.write(path)
+    }
+
+    /// Opens a window showing the plot and blocks until it gets closed.
+    pub fn show(self) -> anyhow::Result<()> {
+        let event_loop = EventLoop::<PlotCommand<P>>::with_user_event().build()?;
+
+        Self::run(self, event_loop)
+    }
+
+    /// Opens the plot window on a separate thread and returns a handle through which the
+    /// plot can be updated while it stays interactive.
+    ///
+    /// Only supported on platforms which allow running an event loop outside of the main
+    /// thread (Windows and X11/Wayland).
+    pub fn show_detached(self) -> anyhow::Result<PlotHandle<P>> {
+        let (proxy_sender, proxy_receiver) = mpsc::channel();
+        let series_count = self.series_count();
+
+        let thread = std::thread::spawn(move || {
+            let event_loop = match Self::build_detached_event_loop() {
+                Ok(event_loop) => event_loop,
+                Err(error) => {
+                    let _ = proxy_sender.send(None);
+                    return Err(error);
+                }
+            };
+
+            let _ = proxy_sender.send(Some(event_loop.create_proxy()));
+
+            Self::run(self, event_loop)
+        });
+
+        match proxy_receiver.recv() {
+            Ok(Some(proxy)) => Ok(PlotHandle {
+                proxy,
+                thread: Some(thread),
+                series_count: AtomicUsize::new(series_count),
+            }),
+            _ => Err(thread
+                .join()
+                .map_err(|_| anyhow::Error::msg("Plot thread panicked."))?
+                .err()
+                .unwrap_or_else(|| anyhow::Error::msg("Plot thread exited unexpectedly."))),
+        }
+    }
+
+    fn build_detached_event_loop() -> anyhow::Result<EventLoop<PlotCommand<P>>> {
+        let mut builder = EventLoop::<PlotCommand<P>>::with_user_event();
+
+        #[cfg(all(unix, not(target_vendor = "apple"), not(target_os = "android")))]
+        winit::platform::x11::EventLoopBuilderExtX11::with_any_thread(&mut builder, true);
+        #[cfg(target_os = "windows")]
+        winit::platform::windows::EventLoopBuilderExtWindows::with_any_thread(&mut builder, true);
+
+        builder
+            .build()
+            .context("Failed to create an event loop outside of the main thread.")
+    }
+
+    fn run(self, event_loop: EventLoop<PlotCommand<P>>) -> anyhow::Result<()> {
+        event_loop.set_control_flow(ControlFlow::Wait);
+
+        let mut app = PlotApp::new(self);
+        event_loop.run_app(&mut app)?;
+
+        app.error.map_or(Ok(()), Err)
+    }
+}
+
+/// Handle to a plot shown with `Plot::show_detached`.
+pub struct PlotHandle<P: 'static> {
+    proxy: EventLoopProxy<PlotCommand<P>>,
+    thread: Option<JoinHandle<anyhow::Result<()>>>,
+    /// Number of series added through the `Plot` and this handle
+    series_count: AtomicUsize,
+}
+
+impl<P: 'static> PlotHandle<P> {
+    pub fn send(&self, command: PlotCommand<P>) -> anyhow::Result<()> {
+        self.proxy
+            .send_event(command)
+            .map_err(|_| anyhow::Error::msg("Plot window was closed."))
+    }
+
+    /// Adds a series and returns its position, usable with `replace_series`.
+    pub fn add_series(&self, series: impl Into<PlotSeries<P>>) -> anyhow::Result<usize> {
+        let index = self.series_count.fetch_add(1, Ordering::SeqCst);
+        self.send(PlotCommand::AddSeries(index, series.into()))?;
+
+        Ok(index)
+    }
+
+    pub fn series_count(&self) -> usize {
+        self.series_count.load(Ordering::SeqCst)
+    }
+
+    fn check_series_index(&self, index: usize) -> anyhow::Result<()> {
+        match index < self.series_count() {
+            true => Ok(()),
+            false => Err(anyhow::Error::msg(format!(
+                "Plot has no series at position {}.",
+                index
+            ))),
+        }
+    }
+
+    pub fn set_param(&self, parameter: P) -> anyhow::Result<()> {
+        self.send(PlotCommand::SetParameter(parameter))
+    }
+
+    /// Fails if no series was added at `index`, a series of another kind is left unchanged.
+    pub fn replace_series(
+        &self,
+        index: usize,
+        series: impl Into<PlotSeries<P>>,
+    ) -> anyhow::Result<()> {
+        self.check_series_index(index)?;
+        self.send(PlotCommand::ReplaceSeries(index, series.into()))
+    }
+
+    /// Appends to a scatter series without sending its existing points again.
+    pub fn append_points(
+        &self,
+        index: usize,
+        points: Vec<(f64, f64)>,
+        capacity: Option<usize>,
+    ) -> anyhow::Result<()> {
+        self.check_series_index(index)?;
+        self.send(PlotCommand::AppendPoints(index, points, capacity))
+    }
+
+    pub fn clear_series(&self) -> anyhow::Result<()> {
+        self.send(PlotCommand::ClearSeries)?;
+        self.series_count.store(0, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    pub fn set_style(&self, style: EnviromentStyle) -> anyhow::Result<()> {
+        self.send(PlotCommand::SetStyle(style))
+    }
+
+    /// See `GPUCanvas2D::set_theme`.
+    pub fn set_theme(&self, theme: Theme) -> anyhow::Result<()> {
+        self.send(PlotCommand::SetTheme(theme))
+    }
+
+    pub fn set_range(&self, x_range: Range<f64>, y_range: Range<f64>) -> anyhow::Result<()> {
+        self.send(PlotCommand::SetRange(x_range, y_range))
+    }
+
+    /// Stops redrawing while the plot is hidden, commands are still applied meanwhile.
+    pub fn set_paused(&self, paused: bool) -> anyhow::Result<()> {
+        self.send(PlotCommand::SetPaused(paused))
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    pub fn close(self) -> anyhow::Result<()> {
+        let _ = self.send(PlotCommand::Close);
+        self.join()
+    }
+
+    /// Blocks until the plot window gets closed.
+    pub fn join(mut self) -> anyhow::Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .map_err(|_| anyhow::Error::msg("Plot thread panicked."))?,
+            None => Ok(()),
+        }
+    }
+}
+
+fn title_section(title: &str) -> TextSection {
+    TextSection::Relative(
+        SectionBuilder::default()
+            .add_text(Text::new(title).with_scale(40.0))
+            .with_screen_position((0.5, 0.02))
+            .with_layout(
+                Layout::default_single_line()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Top),
+            )
+            .to_owned(),
+    )
+}
+
+fn add_title(
+    multiview: &mut GPUMultiView,
+    context: &VisContext,
+    title: &str,
+) -> anyhow::Result<()> {
+    let font = context.fonts().default_font();
+
+    multiview.add_font(font.clone())?;
+    multiview.add_text_section(title_section(title).into_arc_ref_cell(), &font.name)
+}
+
+fn create_canvas<P: Default>(
+    style: EnviromentStyle,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+    parameter: P,
+    series: Vec<PlotSeries<P>>,
+) -> (GPUCanvas2D<P>, Vec<GraphId>) {
+    let mut canvas = GPUCanvas2D::new(GPUViewFrame::Whole.with_margin((0.1, 0.1)));
+    canvas.set_clear_color(RGBA::WHITE);
+    canvas.set_style(style);
+    canvas.set_range(x_range, y_range);
+    *canvas.parameter_get_mut() = parameter;
+
+    let series_ids = series
+        .into_iter()
+        .map(|series| add_series(&mut canvas, series))
+        .collect();
+
+    (canvas, series_ids)
+}
+
+fn add_series<P: Default>(canvas: &mut GPUCanvas2D<P>, series: PlotSeries<P>) -> GraphId {
+    match series {
+        PlotSeries::Line(graph) => canvas.add_function_graph(graph),
+        PlotSeries::Scatter(points) => canvas.add_point_series(points),
+        PlotSeries::Ellipse(ellipse) => canvas.add_covariance_ellipse(ellipse),
+    }
+}
+
+/// Appends to the point series with the given id, `false` if there is none.
+fn append_points<P: Default>(
+    canvas: &mut GPUCanvas2D<P>,
+    id: GraphId,
+    points: Vec<(f64, f64)>,
+    capacity: Option<usize>,
+) -> bool {
+    let Some(series) = canvas.point_series_get_mut(id) else {
+        return false;
+    };
+
+    series.points.extend(points);
+    if let Some(capacity) = capacity {
+        let excess = series.points.len().saturating_sub(capacity);
+        series.points.drain(..excess);
+    }
+
+    true
+}
+
+/// Replaces the series with the given id in place if it is of the same kind.
+fn replace_series<P: Default>(
+    canvas: &mut GPUCanvas2D<P>,
+    id: GraphId,
+    series: PlotSeries<P>,
+) -> bool {
+    match series {
+        PlotSeries::Line(graph) => match canvas.function_graph_get_mut(id) {
+            Some(current) => *current = graph,
+            None => return false,
+        },
+        PlotSeries::Scatter(points) => match canvas.point_series_get_mut(id) {
+            Some(current) => *current = points,
+            None => return false,
+        },
+        PlotSeries::Ellipse(ellipse) => match canvas.covariance_ellipse_get_mut(id) {
+            Some(current) => *current = ellipse,
+            None => return false,
+        },
+    }
+
+    true
+}
+
+struct PlotApp<P>
+where
+    P: Default,
+{
+    title: Option<String>,
+    window_size: (u32, u32),
+    color_vision_filter: ColorVisionFilter,
+    post_effects: Vec<PostEffect>,
+    deterministic: bool,
+
+    context: Option<VisContext>,
+    multiview: GPUMultiView<'static>,
+    canvas: GPUCanvas2D<P>,
+    /// By position, `None` for positions whose series didn't arrive yet
+    series_ids: Vec<Option<GraphId>>,
+    controller: CanvasController,
+    ticks: TickScheduler<GPUCanvas2D<P>>,
+
+    /// Paused through `PlotHandle::set_paused`.
+    paused: bool,
+    /// The window is fully hidden by other windows.
+    occluded: bool,
+
+    error: Option<anyhow::Error>,
+}
+
+impl<P> PlotApp<P>
+where
+    P: Default + 'static,
+{
+    fn new(plot: Plot<P>) -> Self {
+        let theme = plot.theme.map(|theme| Theme {
+            enviroment: plot.style.clone(),
+            ..theme
+        });
+        let (mut canvas, series_ids) = create_canvas(
+            plot.style,
+            plot.x_range,
+            plot.y_range,
+            plot.parameter,
+            plot.series,
+        );
+        canvas.set_x_scale(plot.x_scale);
+        canvas.set_y_scale(plot.y_scale);
+        if let Some(projection) = plot.projection {
+            canvas.set_projection(projection);
+        }
+        if let Some(theme) = &theme {
+            canvas.set_theme(theme);
+        }
+        canvas.set_progressive_rendering(plot.progressive);
+        canvas.set_accessibility(plot.accessibility);
+        for widget in plot.widgets {
+            canvas.add_widget(widget);
+        }
+        if let Some(style) = plot.crosshair {
+            canvas.set_crosshair_style(style);
+        }
+
+        let mut controller = CanvasController::new();
+        controller.cursor_readout = plot.cursor_readout;
+        controller.crosshair = plot.crosshair.is_some();
+        controller.key_bindings = plot.key_bindings;
+
+        Self {
+            title: plot.title,
+            window_size: plot.window_size,
+            color_vision_filter: plot.color_vision_filter,
+            post_effects: plot.post_effects,
+            deterministic: plot.deterministic,
+            context: None,
+            multiview: GPUMultiView::new(),
+            canvas,
+            series_ids: series_ids.into_iter().map(Some).collect(),
+            controller,
+            ticks: plot.ticks,
+            paused: false,
+            occluded: false,
+            error: None,
+        }
+    }
+
+    fn initialize(&mut self, event_loop: &ActiveEventLoop) -> anyhow::Result<()> {
+        let attributes = Window::default_attributes()
+            .with_title(self.title.clone().unwrap_or("Plot".to_string()))
+            .with_inner_size(LogicalSize::new(self.window_size.0, self.window_size.1));
+
+        let mut context = VisContext::from_window(event_loop.create_window(attributes)?)?;
+        context.set_deterministic(self.deterministic);
+
+        self.multiview = context.create_multiview()?;
+        self.multiview.set_clear_color(wgpu::Color::WHITE);
+        self.multiview
+            .set_color_vision_filter(self.color_vision_filter);
+        self.multiview.set_post_effects(self.post_effects.clone());
+        self.multiview
+            .set_render_views(vec![self.canvas.get_view()]);
+
+        if let Some(title) = &self.title {
+            add_title(&mut self.multiview, &context, title)?;
+        }
+
+        self.context = Some(context);
+
+        Ok(())
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused || self.occluded
+    }
+
+    fn request_redraw(&self) {
+        if self.is_paused() {
+            return;
+        }
+
+        if let Some(context) = &self.context {
+            context.request_redraw();
+        }
+    }
+
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: anyhow::Error) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
+}
+
+impl<P> ApplicationHandler<PlotCommand<P>> for PlotApp<P>
+where
+    P: Default + 'static,
+{
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(context) = &self.context else {
+            if let Err(error) = self.initialize(event_loop) {
+                self.fail(event_loop, error);
+            }
+            return;
+        };
+
+        if !self.multiview.is_suspended() {
+            return;
+        }
+
+        let restored = context
+            .recreate_surface()
+            .and_then(|(surface, surface_config)| {
+                self.multiview
+                    .restore_surface(surface, surface_config, context.device())
+            });
+
+        match restored {
+            Ok(()) => self.request_redraw(),
+            Err(error) => self.fail(event_loop, error),
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        self.multiview.release_surface();
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.ticks.is_empty() {
+            return;
+        }
+
+        // ticks keep running while paused, only the redraw is skipped
+        if self.ticks.run_due(&mut self.canvas) > 0 {
+            self.request_redraw();
+        }
+
+        event_loop.set_control_flow(match self.ticks.next_deadline() {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        });
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, command: PlotCommand<P>) {
+        match command {
+            PlotCommand::AddSeries(index, series) => {
+                let id = add_series(&mut self.canvas, series);
+                if self.series_ids.len() <= index {
+                    self.series_ids.resize(index + 1, None);
+                }
+                self.series_ids[index] = Some(id);
+            }
+            PlotCommand::ReplaceSeries(index, series) => {
+                let replaced = self
+                    .series_ids
+                    .get(index)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|id| replace_series(&mut self.canvas, id, series));
+
+                // the position is checked by the handle, only the kind can mismatch here
+                if !replaced {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        index,
+                        "Plot has no series of the same kind at this position."
+                    );
+                }
+            }
+            PlotCommand::AppendPoints(index, points, capacity) => {
+                let appended = self
+                    .series_ids
+                    .get(index)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|id| append_points(&mut self.canvas, id, points, capacity));
+
+                if !appended {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(index, "Plot has no scatter series at this position.");
+                }
+            }
+            PlotCommand::ClearSeries => {
+                self.canvas.clear_graphs();
+                self.series_ids.clear();
+            }
+            PlotCommand::SetParameter(parameter) => *self.canvas.parameter_get_mut() = parameter,
+            PlotCommand::SetStyle(style) => self.canvas.set_style(style),
+            PlotCommand::SetTheme(theme) => self.canvas.set_theme(&theme),
+            PlotCommand::SetRange(x_range, y_range) => self.canvas.set_range(x_range, y_range),
+            PlotCommand::SetPaused(paused) => self.paused = paused,
+            PlotCommand::Close => {
+                event_loop.exit();
+                return;
+            }
+        }
+
+        self.request_redraw();
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        let Some(context) = &mut self.context else {
+            return;
+        };
+
+        if self
+            .controller
+            .handle_window_event(&event, &mut self.canvas, &self.multiview)
+        {
+            self.request_redraw();
+            return;
+        }
+
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::KeyboardInput { event, .. }
+                if event.physical_key == PhysicalKey::Code(KeyCode::Escape) =>
+            {
+                event_loop.exit()
+            }
+            WindowEvent::KeyboardInput { event, .. }
+                if event.physical_key == PhysicalKey::Code(KeyCode::F12)
+                    && event.state.is_pressed() =>
+            {
+                self.multiview.capture_next_frame();
+                self.request_redraw();
+            }
+            WindowEvent::Resized(new_size) => {
+                let _ = self
+                    .multiview
+                    .resize(new_size.width, new_size.height, context.device());
+                self.request_redraw();
+            }
+            WindowEvent::Occluded(occluded) => {
+                self.occluded = occluded;
+                self.request_redraw();
+            }
+            WindowEvent::RedrawRequested => {
+                if self.paused || self.occluded {
+                    return;
+                }
+
+                context.clock_mut().tick();
+
+                self.canvas.advance_animations(context.clock().delta());
+                self.canvas.display();
+
+                if let Err(error) = self.multiview.render(context.device(), context.queue()) {
+                    self.fail(event_loop, error);
+                    return;
+                }
+
+                if self.canvas.refinement_pending() || self.canvas.is_animating() {
+                    context.request_redraw();
+                }
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,557 @@
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+
+use crate::color::ColorVisionFilter;
+use crate::texture_copy::texture_bytes;
+
+/// Full screen pass applied to the composed multiview before it is presented,
+/// see `GPUMultiView::set_post_effects`.
+#[derive(Debug, Clone)]
+pub enum PostEffect {
+    /// Fast approximate anti-aliasing, also smoothing text edges.
+    Fxaa,
+    /// Adds a blurred copy of everything brighter than `threshold`, mostly useful on dark backgrounds.
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        /// Distance between blur taps in pixels
+        radius: f32,
+    },
+    /// Darkens the corners, `strength` `0..1`.
+    Vignette {
+        strength: f32,
+    },
+    ColorGrading(Arc<ColorLut>),
+    ColorVision(ColorVisionFilter),
+}
+
+/// 3D color lookup table with `size`³ RGBA entries, red varying fastest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLut {
+    pub size: u32,
+    pub data: Vec<u8>,
+}
+
+impl ColorLut {
+    pub fn identity(size: u32) -> Self {
+        Self::from_fn(size, |rgb| rgb)
+    }
+
+    /// Samples `grade` (RGB `0..1` to RGB `0..1`) at every entry.
+    pub fn from_fn(size: u32, grade: impl Fn([f32; 3]) -> [f32; 3]) -> Self {
+        let size = size.max(2);
+        let max = (size - 1) as f32;
+
+        let mut data = Vec::with_capacity(size.pow(3) as usize * 4);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let color = grade([r as f32 / max, g as f32 / max, b as f32 / max]);
+                    for channel in color {
+                        data.push((channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    }
+                    data.push(255);
+                }
+            }
+        }
+
+        Self { size, data }
+    }
+}
+
+const SHADER: &str = r#"
+    struct VertexOutput {
+        @builtin(position) clip_position: vec4<f32>,
+        @location(0) tex_coords: vec2<f32>,
+    };
+
+    struct Parameters {
+        values: array<vec4<f32>, 3>,
+    };
+
+    @group(0) @binding(0)
+    var texture: texture_2d<f32>;
+
+    @group(0) @binding(1)
+    var texture_sampler: sampler;
+
+    @group(0) @binding(2)
+    var<uniform> parameters: Parameters;
+
+    @group(0) @binding(3)
+    var lut: texture_3d<f32>;
+
+    // fullscreen triangle
+    @vertex
+    fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+        var out: VertexOutput;
+        let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+        out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+        out.tex_coords = uv;
+        return out;
+    }
+
+    fn luma(color: vec3<f32>) -> f32 {
+        return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+    }
+
+    fn sample(uv: vec2<f32>) -> vec3<f32> {
+        return textureSampleLevel(texture, texture_sampler, uv, 0.0).rgb;
+    }
+
+    @fragment
+    fn fs_fxaa(in: VertexOutput) -> @location(0) vec4<f32> {
+        let pixel = 1.0 / vec2<f32>(textureDimensions(texture));
+        let uv = in.tex_coords;
+
+        let color = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+        let luma_nw = luma(sample(uv + vec2<f32>(-1.0, -1.0) * pixel));
+        let luma_ne = luma(sample(uv + vec2<f32>(1.0, -1.0) * pixel));
+        let luma_sw = luma(sample(uv + vec2<f32>(-1.0, 1.0) * pixel));
+        let luma_se = luma(sample(uv + vec2<f32>(1.0, 1.0) * pixel));
+        let luma_m = luma(color.rgb);
+
+        let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+        let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+        var direction = vec2<f32>(
+            -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+            (luma_nw + luma_sw) - (luma_ne + luma_se),
+        );
+        let reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 / 8.0, 1.0 / 128.0);
+        let scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+        direction = clamp(direction * scale, vec2<f32>(-8.0), vec2<f32>(8.0)) * pixel;
+
+        let color_a = 0.5 * (sample(uv + direction * (1.0 / 3.0 - 0.5)) + sample(uv + direction * (2.0 / 3.0 - 0.5)));
+        let color_b = 0.5 * color_a + 0.25 * (sample(uv - direction * 0.5) + sample(uv + direction * 0.5));
+
+        let luma_b = luma(color_b);
+        let outside = luma_b < luma_min || luma_b > luma_max;
+
+        return vec4<f32>(select(color_b, color_a, outside), color.a);
+    }
+
+    @fragment
+    fn fs_bloom(in: VertexOutput) -> @location(0) vec4<f32> {
+        let pixel = 1.0 / vec2<f32>(textureDimensions(texture));
+        let threshold = parameters.values[0].x;
+        let intensity = parameters.values[0].y;
+        let radius = parameters.values[0].z;
+
+        let color = textureSampleLevel(texture, texture_sampler, in.tex_coords, 0.0);
+
+        var glow = vec3<f32>(0.0);
+        var total = 0.0;
+        for (var x = -4; x <= 4; x++) {
+            for (var y = -4; y <= 4; y++) {
+                let offset = vec2<f32>(f32(x), f32(y));
+                let weight = exp(-dot(offset, offset) / 8.0);
+                let tap = sample(in.tex_coords + offset * radius * pixel);
+
+                glow += max(tap - vec3<f32>(threshold), vec3<f32>(0.0)) * weight;
+                total += weight;
+            }
+        }
+
+        return vec4<f32>(color.rgb + glow / total * intensity, color.a);
+    }
+
+    @fragment
+    fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+        let strength = parameters.values[0].x;
+
+        let color = textureSampleLevel(texture, texture_sampler, in.tex_coords, 0.0);
+        let distance = length(in.tex_coords - vec2<f32>(0.5)) * 1.41421356;
+
+        return vec4<f32>(color.rgb * (1.0 - strength * distance * distance), color.a);
+    }
+
+    @fragment
+    fn fs_color_grading(in: VertexOutput) -> @location(0) vec4<f32> {
+        let color = textureSampleLevel(texture, texture_sampler, in.tex_coords, 0.0);
+
+        // sample at the centers of the outermost entries
+        let size = f32(textureDimensions(lut).x);
+        let coordinates = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0)) * (size - 1.0) / size + 0.5 / size;
+
+        return vec4<f32>(textureSampleLevel(lut, texture_sampler, coordinates, 0.0).rgb, color.a);
+    }
+
+    fn to_linear(c: vec3<f32>) -> vec3<f32> {
+        return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+    }
+
+    fn to_srgb(c: vec3<f32>) -> vec3<f32> {
+        return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+    }
+
+    @fragment
+    fn fs_color_vision(in: VertexOutput) -> @location(0) vec4<f32> {
+        let color = textureSampleLevel(texture, texture_sampler, in.tex_coords, 0.0);
+        let linear = to_linear(color.rgb);
+
+        let simulated = vec3<f32>(
+            dot(parameters.values[0].xyz, linear),
+            dot(parameters.values[1].xyz, linear),
+            dot(parameters.values[2].xyz, linear),
+        );
+
+        return vec4<f32>(to_srgb(clamp(simulated, vec3<f32>(0.0), vec3<f32>(1.0))), color.a);
+    }
+"#;
+
+const BIND_GROUP_LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
+    wgpu::BindGroupLayoutDescriptor {
+        label: Some("Post Process Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D3,
+                    multisampled: false,
+                },
+                count: None,
+            },
+        ],
+    };
+
+/// Pipeline and resources of a single `PostEffect`.
+struct PostProcessPass {
+    /// Effect name for the pass label, e.g. `bloom`
+    name: &'static str,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    parameter_buffer: wgpu::Buffer,
+    lut_view: wgpu::TextureView,
+    lut_bytes: u64,
+}
+
+impl PostProcessPass {
+    fn new(
+        effect: &PostEffect,
+        format: wgpu::TextureFormat,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Self {
+        let mut parameters = [[0.0f32; 4]; 3];
+        let entry_point = match effect {
+            PostEffect::Fxaa => "fs_fxaa",
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                parameters[0] = [*threshold, *intensity, *radius, 0.0];
+                "fs_bloom"
+            }
+            PostEffect::Vignette { strength } => {
+                parameters[0][0] = *strength;
+                "fs_vignette"
+            }
+            PostEffect::ColorGrading(_) => "fs_color_grading",
+            PostEffect::ColorVision(filter) => {
+                for (target, row) in parameters.iter_mut().zip(filter.matrix()) {
+                    target[..3].copy_from_slice(&row);
+                }
+                "fs_color_vision"
+            }
+        };
+
+        let parameter_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Parameter Buffer"),
+            contents: bytemuck::cast_slice(&parameters),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // effects without a lookup table still need something bound
+        let lut = match effect {
+            PostEffect::ColorGrading(lut) => lut.as_ref().clone(),
+            _ => ColorLut::identity(2),
+        };
+        let lut_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Post Process Lut Texture"),
+                size: wgpu::Extent3d {
+                    width: lut.size,
+                    height: lut.size,
+                    depth_or_array_layers: lut.size,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D3,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &lut.data,
+        );
+
+        let bind_group_layout = device.create_bind_group_layout(&BIND_GROUP_LAYOUT_DESCRIPTOR);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Process Shader Module"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post Process Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some(entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            name: entry_point.trim_start_matches("fs_"),
+            bind_group_layout,
+            pipeline,
+            parameter_buffer,
+            lut_view: lut_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            lut_bytes: texture_bytes(&lut_texture),
+        }
+    }
+
+    fn render(
+        &self,
+        source: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Process Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.parameter_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&self.lut_view),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&format!("Post Process {} Pass", self.name)),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// Chain of `PostEffect`s rendering between two intermediate textures.
+///
+/// The multiview composes its views and text into `input_view` and the last pass writes
+/// into the actual target.
+pub(crate) struct PostProcessChain {
+    effects: Vec<PostEffect>,
+    passes: Vec<PostProcessPass>,
+    textures: Vec<wgpu::Texture>,
+    sampler: Option<wgpu::Sampler>,
+    changed: bool,
+}
+
+impl PostProcessChain {
+    pub(crate) fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+            passes: Vec::new(),
+            textures: Vec::new(),
+            sampler: None,
+            changed: false,
+        }
+    }
+
+    pub(crate) fn set_effects(&mut self, effects: Vec<PostEffect>) {
+        self.effects = effects;
+        self.changed = true;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Bytes of the intermediate textures and the parameter buffers and lookup tables of the passes.
+    pub(crate) fn memory_bytes(&self) -> u64 {
+        self.textures.iter().map(texture_bytes).sum::<u64>()
+            + self
+                .passes
+                .iter()
+                .map(|pass| pass.parameter_buffer.size() + pass.lut_bytes)
+                .sum::<u64>()
+    }
+
+    /// Drops the intermediate textures while no effects are set, `prepare` recreates them.
+    pub(crate) fn evict_unused(&mut self) -> bool {
+        if !self.effects.is_empty() || self.textures.is_empty() {
+            return false;
+        }
+
+        for texture in self.textures.drain(..) {
+            texture.destroy();
+        }
+        self.passes.clear();
+
+        true
+    }
+
+    /// (Re)creates passes and intermediate textures matching `target`.
+    pub(crate) fn prepare(
+        &mut self,
+        target: &wgpu::Texture,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let outdated_textures = self.textures.first().is_none_or(|texture| {
+            texture.size() != target.size() || texture.format() != target.format()
+        });
+
+        if outdated_textures {
+            self.textures = (0..2)
+                .map(|_| {
+                    device.create_texture(&wgpu::TextureDescriptor {
+                        label: Some("Post Process Texture"),
+                        size: target.size(),
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: target.format(),
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                            | wgpu::TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    })
+                })
+                .collect();
+        }
+
+        if self.changed || outdated_textures {
+            self.passes = self
+                .effects
+                .iter()
+                .map(|effect| PostProcessPass::new(effect, target.format(), device, queue))
+                .collect();
+            self.changed = false;
+        }
+
+        if self.sampler.is_none() {
+            self.sampler = Some(device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("Post Process Sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            }));
+        }
+    }
+
+    /// Texture the multiview is composed into, only valid after `prepare`.
+    pub(crate) fn input_view(&self) -> wgpu::TextureView {
+        self.textures[0].create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    pub(crate) fn render(
+        &self,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+    ) {
+        let sampler = self.sampler.as_ref().unwrap();
+        let views = self
+            .textures
+            .iter()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            .collect::<Vec<_>>();
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            let source = &views[index % 2];
+            let pass_target = match index + 1 == self.passes.len() {
+                true => target,
+                false => &views[(index + 1) % 2],
+            };
+
+            pass.render(source, sampler, pass_target, encoder, device);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::f64::consts::TAU;
+use std::sync::Arc;
+
+use crate::axis_scale::AxisScale;
+use crate::interval::Interval;
+
+/// Mapping of data coordinates onto the plane a `GPUCanvas2D` shows, see
+/// `GPUCanvas2D::set_projection`.
+///
+/// It is applied before the axis scales and the mapping of the ranges onto the view, so
+/// ranges, grid and ticks are in projected coordinates while graphs, implicit graphs and
+/// layers are drawn from data coordinates.
+pub trait CoordinateTransform: std::fmt::Debug + Send + Sync {
+    /// Projected position of `data`, non finite where it can't be shown.
+    fn forward(&self, data: (f64, f64)) -> (f64, f64);
+
+    /// Data coordinates of `projected`, non finite where none exist.
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64);
+
+    /// Data x values function graphs are sampled over while the projected `x_range` and
+    /// `y_range` are visible.
+    fn x_domain(&self, x_range: Interval, _y_range: Interval) -> Interval {
+        x_range
+    }
+
+    /// Whether data coordinates are drawn as they are, which lets shaders work in data
+    /// coordinates directly.
+    fn is_identity(&self) -> bool {
+        false
+    }
+}
+
+impl<T> CoordinateTransform for Arc<T>
+where
+    T: CoordinateTransform + ?Sized,
+{
+    fn forward(&self, data: (f64, f64)) -> (f64, f64) {
+        self.as_ref().forward(data)
+    }
+
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64) {
+        self.as_ref().inverse(projected)
+    }
+
+    fn x_domain(&self, x_range: Interval, y_range: Interval) -> Interval {
+        self.as_ref().x_domain(x_range, y_range)
+    }
+
+    fn is_identity(&self) -> bool {
+        self.as_ref().is_identity()
+    }
+}
+
+/// Draws data coordinates as they are, the default projection.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IdentityTransform;
+
+impl CoordinateTransform for IdentityTransform {
+    fn forward(&self, data: (f64, f64)) -> (f64, f64) {
+        data
+    }
+
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64) {
+        projected
+    }
+
+    fn is_identity(&self) -> bool {
+        true
+    }
+}
+
+/// Axis scales applied to the data, e.g. logarithmic axes or Web-Mercator latitudes.
+///
+/// Unlike `GPUCanvas2D::set_x_scale` the ranges, grid and labels are in scaled units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScaledAxes {
+    pub x: AxisScale,
+    pub y: AxisScale,
+}
+
+impl ScaledAxes {
+    pub fn new(x: AxisScale, y: AxisScale) -> Self {
+        Self { x, y }
+    }
+}
+
+impl CoordinateTransform for ScaledAxes {
+    fn forward(&self, data: (f64, f64)) -> (f64, f64) {
+        (self.x.forward(data.0), self.y.forward(data.1))
+    }
+
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64) {
+        (self.x.inverse(projected.0), self.y.inverse(projected.1))
+    }
+
+    fn x_domain(&self, x_range: Interval, _y_range: Interval) -> Interval {
+        self.x.inverse_interval(&x_range)
+    }
+}
+
+/// Data x is an angle in radians (counterclockwise from the positive x axis) and y a
+/// radius, so function graphs become polar curves `r = f(θ)` over a full turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PolarTransform;
+
+impl CoordinateTransform for PolarTransform {
+    fn forward(&self, data: (f64, f64)) -> (f64, f64) {
+        let (angle, radius) = data;
+        let (sin, cos) = angle.sin_cos();
+
+        (radius * cos, radius * sin)
+    }
+
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64) {
+        let (x, y) = projected;
+
+        (y.atan2(x).rem_euclid(TAU), x.hypot(y))
+    }
+
+    fn x_domain(&self, _x_range: Interval, _y_range: Interval) -> Interval {
+        Interval::new(0.0, TAU)
+    }
+}
+
+/// Projection given by a pair of closures.
+///
+/// Function graphs are sampled over the visible x range, implement `CoordinateTransform`
+/// to sample over another domain.
+#[derive(Clone)]
+pub struct FnTransform {
+    pub forward: Arc<dyn Fn((f64, f64)) -> (f64, f64) + Send + Sync>,
+    pub inverse: Arc<dyn Fn((f64, f64)) -> (f64, f64) + Send + Sync>,
+}
+
+impl FnTransform {
+    /// `inverse` has to undo `forward`, it is used for picking and implicit graphs.
+    pub fn new(
+        forward: impl Fn((f64, f64)) -> (f64, f64) + Send + Sync + 'static,
+        inverse: impl Fn((f64, f64)) -> (f64, f64) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            forward: Arc::new(forward),
+            inverse: Arc::new(inverse),
+        }
+    }
+}
+
+impl std::fmt::Debug for FnTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnTransform").finish_non_exhaustive()
+    }
+}
+
+impl CoordinateTransform for FnTransform {
+    fn forward(&self, data: (f64, f64)) -> (f64, f64) {
+        (self.forward)(data)
+    }
+
+    fn inverse(&self, projected: (f64, f64)) -> (f64, f64) {
+        (self.inverse)(projected)
+    }
+}
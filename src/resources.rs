@@ -0,0 +1,383 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use wgpu_text::{
+    glyph_brush::{ab_glyph::FontArc, OwnedSection},
+    BrushBuilder, Matrix, TextBrush,
+};
+
+use crate::gpuview::Font;
+use crate::texture_copy::{texture_bytes, write_texture};
+
+/// Session wide storage of loaded fonts.
+///
+/// The registry is a cheap to clone handle, all clones share the same fonts. Fonts are only
+/// read from disk once and are released by `remove_unused` once no style or view uses them.
+#[derive(Debug, Clone, Default)]
+pub struct FontRegistry {
+    fonts: Arc<Mutex<HashMap<String, Font>>>,
+}
+
+impl FontRegistry {
+    pub const DEFAULT_FONT_NAME: &'static str = "Default";
+    pub const DEFAULT_FONT_PATH: &'static str = "fonts/DejaVuSans.ttf";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process wide registry used by default styles and `GPUMultiView`.
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<FontRegistry> = OnceLock::new();
+
+        SHARED.get_or_init(FontRegistry::new).clone()
+    }
+
+    /// Registers `font`. If a font with the same name is already registered, that font is
+    /// returned instead.
+    pub fn insert(&self, font: Font) -> Font {
+        self.fonts
+            .lock()
+            .unwrap()
+            .entry(font.name.clone())
+            .or_insert(font)
+            .clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Font> {
+        self.fonts.lock().unwrap().get(name).cloned()
+    }
+
+    /// Returns the registered font `name` or loads it from `path`.
+    pub fn load(&self, name: &str, path: impl AsRef<Path>) -> anyhow::Result<Font> {
+        if let Some(font) = self.get(name) {
+            return Ok(font);
+        }
+
+        Ok(self.insert(Font::from_file(name, path)?))
+    }
+
+    pub fn default_font(&self) -> Font {
+        self.load(Self::DEFAULT_FONT_NAME, Self::DEFAULT_FONT_PATH)
+            .expect("Failed to load the default font.")
+    }
+
+    /// Drops all fonts which are only referenced by the registry itself.
+    /// Returns the number of removed fonts.
+    pub fn remove_unused(&self) -> usize {
+        let mut fonts = self.fonts.lock().unwrap();
+
+        let len = fonts.len();
+        fonts.retain(|_, font| font.usage_count() > 1);
+
+        len - fonts.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fonts.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fonts.lock().unwrap().is_empty()
+    }
+}
+
+/// Session wide storage of named textures.
+///
+/// Textures are handed out as `Arc`s so the registry can tell when a texture is not
+/// used anymore and destroy it in `remove_unused`.
+#[derive(Debug, Clone, Default)]
+pub struct TextureRegistry {
+    textures: Arc<Mutex<HashMap<String, Arc<wgpu::Texture>>>>,
+}
+
+impl TextureRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `texture` under `name`, replacing a previously registered texture.
+    pub fn insert(&self, name: &str, texture: wgpu::Texture) -> Arc<wgpu::Texture> {
+        let texture = Arc::new(texture);
+
+        self.textures
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), texture.clone());
+
+        texture
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<wgpu::Texture>> {
+        self.textures.lock().unwrap().get(name).cloned()
+    }
+
+    /// Returns the registered texture `name` or creates it with `descriptor`.
+    pub fn get_or_create(
+        &self,
+        name: &str,
+        device: &wgpu::Device,
+        descriptor: &wgpu::TextureDescriptor,
+    ) -> Arc<wgpu::Texture> {
+        self.textures
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(device.create_texture(descriptor)))
+            .clone()
+    }
+
+    /// Creates a texture with `descriptor`, uploads the tightly packed rows `data` into it
+    /// and registers it under `name`.
+    ///
+    /// `descriptor.usage` has to contain `TextureUsages::COPY_DST`.
+    pub fn insert_with_data(
+        &self,
+        name: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        descriptor: &wgpu::TextureDescriptor,
+        data: &[u8],
+    ) -> anyhow::Result<Arc<wgpu::Texture>> {
+        let texture = device.create_texture(descriptor);
+        write_texture(&texture, device, queue, data)?;
+
+        Ok(self.insert(name, texture))
+    }
+
+    /// Destroys all textures which are only referenced by the registry itself.
+    /// Returns the number of removed textures.
+    pub fn remove_unused(&self) -> usize {
+        let mut textures = self.textures.lock().unwrap();
+
+        let len = textures.len();
+        textures.retain(|_, texture| {
+            let used = Arc::strong_count(texture) > 1;
+            if !used {
+                texture.destroy();
+            }
+            used
+        });
+
+        len - textures.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.lock().unwrap().is_empty()
+    }
+
+    /// Bytes of all registered textures.
+    pub fn memory_bytes(&self) -> u64 {
+        self.textures
+            .lock()
+            .unwrap()
+            .values()
+            .map(|texture| texture_bytes(texture))
+            .sum()
+    }
+}
+
+/// Cache of render and compute pipelines keyed by a caller chosen name, so views with
+/// identical shaders and layouts don't each compile their own pipeline.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineCache {
+    pipelines: Arc<Mutex<HashMap<String, Arc<wgpu::RenderPipeline>>>>,
+    compute_pipelines: Arc<Mutex<HashMap<String, Arc<wgpu::ComputePipeline>>>>,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<wgpu::RenderPipeline>> {
+        self.pipelines.lock().unwrap().get(key).cloned()
+    }
+
+    /// Returns the pipeline cached under `key` or creates and caches it with `create`.
+    pub fn get_or_create(
+        &self,
+        key: &str,
+        create: impl FnOnce() -> wgpu::RenderPipeline,
+    ) -> Arc<wgpu::RenderPipeline> {
+        self.pipelines
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(create()))
+            .clone()
+    }
+
+    /// See `get_or_create`, compute pipelines have keys of their own.
+    pub fn get_or_create_compute(
+        &self,
+        key: &str,
+        create: impl FnOnce() -> wgpu::ComputePipeline,
+    ) -> Arc<wgpu::ComputePipeline> {
+        self.compute_pipelines
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(create()))
+            .clone()
+    }
+
+    pub fn clear(&self) {
+        self.pipelines.lock().unwrap().clear();
+        self.compute_pipelines.lock().unwrap().clear();
+    }
+
+    /// Number of cached render and compute pipelines.
+    pub fn len(&self) -> usize {
+        self.pipelines.lock().unwrap().len() + self.compute_pipelines.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Text brush shared by all text primitives with the same font, rotation, target format and
+/// multisample state, see `TextBrushRegistry`.
+///
+/// The brush holds one set of sections and one projection at a time. Users write theirs
+/// right before drawing and the commands have to be submitted before the next user writes
+/// its own, `GPUView` does so after its text was drawn.
+pub struct SharedTextBrush {
+    brush: TextBrush<FontArc>,
+    /// Last written projection
+    matrix: Option<Matrix>,
+    /// Hash of the last queued sections
+    queued: Option<u64>,
+}
+
+impl SharedTextBrush {
+    pub(crate) fn set_matrix(&mut self, matrix: Matrix, queue: &wgpu::Queue) {
+        if self.matrix != Some(matrix) {
+            self.brush.update_matrix(matrix, queue);
+            self.matrix = Some(matrix);
+        }
+    }
+
+    /// Queues `sections` unless the brush already holds sections with the same `hash`.
+    pub(crate) fn queue(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sections: &[OwnedSection],
+        hash: u64,
+    ) -> anyhow::Result<()> {
+        if self.queued == Some(hash) {
+            return Ok(());
+        }
+
+        self.queued = None;
+        self.brush
+            .queue(device, queue, sections.iter().collect::<Vec<_>>())?;
+        self.queued = Some(hash);
+
+        Ok(())
+    }
+
+    pub(crate) fn draw<'pass>(&'pass self, render_pass: &mut wgpu::RenderPass<'pass>) {
+        self.brush.draw(render_pass);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextBrushKey {
+    font: String,
+    rotation_bits: u32,
+    format: wgpu::TextureFormat,
+    multisample_state: wgpu::MultisampleState,
+}
+
+/// Text brushes (and with them their glyph atlases) shared by the views of a multiview or
+/// of all multiviews of a `VisContext`, so every font is only rasterized once per rotation,
+/// target format and multisample state instead of once per view.
+///
+/// Brushes are handed out as `Arc`s and released by `remove_unused` once no view uses them.
+#[derive(Clone, Default)]
+pub struct TextBrushRegistry {
+    brushes: Arc<Mutex<HashMap<TextBrushKey, Arc<Mutex<SharedTextBrush>>>>>,
+}
+
+impl TextBrushRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the brush of `font` drawing rotated by `rotation` radians into targets of
+    /// `format` or creates it for a target of `render_size`.
+    pub fn get_or_create(
+        &self,
+        font: &Font,
+        rotation: f32,
+        format: wgpu::TextureFormat,
+        multisample_state: wgpu::MultisampleState,
+        device: &wgpu::Device,
+        render_size: (u32, u32),
+    ) -> Arc<Mutex<SharedTextBrush>> {
+        let key = TextBrushKey {
+            font: font.name.clone(),
+            rotation_bits: rotation.to_bits(),
+            format,
+            multisample_state,
+        };
+
+        self.brushes
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| {
+                let brush = BrushBuilder::using_font(font.font.clone())
+                    .with_multisample(multisample_state)
+                    .build(device, render_size.0, render_size.1, format);
+
+                Arc::new(Mutex::new(SharedTextBrush {
+                    brush,
+                    matrix: None,
+                    queued: None,
+                }))
+            })
+            .clone()
+    }
+
+    /// Drops all brushes which are only referenced by the registry itself.
+    /// Returns the number of removed brushes.
+    pub fn remove_unused(&self) -> usize {
+        let mut brushes = self.brushes.lock().unwrap();
+
+        let len = brushes.len();
+        brushes.retain(|_, brush| Arc::strong_count(brush) > 1);
+
+        len - brushes.len()
+    }
+
+    pub fn clear(&self) {
+        self.brushes.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.brushes.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.brushes.lock().unwrap().is_empty()
+    }
+}
+
+impl std::fmt::Debug for TextBrushRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextBrushRegistry")
+            .field("len", &self.len())
+            .finish()
+    }
+}
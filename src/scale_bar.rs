@@ -0,0 +1,178 @@
+use fraction::ToPrimitive;
+use wgpu_text::glyph_brush::{HorizontalAlign, VerticalAlign};
+
+use crate::color::RGBA;
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::graph::{LineCap, LineJoin, Thickness, TickLabelFormat};
+use crate::layer::{CanvasLayer, LayerPainter};
+use crate::text_layout::superscript_exponent;
+
+/// Corner of the canvas view an overlay is placed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleBarStyle {
+    /// Text color of the enviroment if `None`
+    pub color: Option<RGBA>,
+    pub thickness: f32,
+    /// Length of the end ticks in physical pixels, they point towards the label
+    pub tick_length: f32,
+}
+
+impl Default for ScaleBarStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            thickness: Thickness::MEDIUM,
+            tick_length: 6.0,
+        }
+    }
+}
+
+/// Bar labeled with its length in x, for plots without axes (e.g. on top of images).
+///
+/// The length is rounded up to 1, 2 or 5 times a power of ten like the grid spacing, so the
+/// bar gets `length` to 2.5 times `length` pixels long. Assumes a linear x scale. Add it with
+/// `GPUCanvas2D::add_layer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleBar {
+    pub corner: Corner,
+    /// Minimum length in physical pixels
+    pub length: f32,
+    /// Distance to the edges of the view in physical pixels
+    pub margin: f32,
+    /// Appended to the label, e.g. `"km"`
+    pub unit: Option<String>,
+    pub style: ScaleBarStyle,
+}
+
+impl Default for ScaleBar {
+    fn default() -> Self {
+        Self {
+            corner: Corner::default(),
+            length: 80.0,
+            margin: 24.0,
+            unit: None,
+            style: ScaleBarStyle::default(),
+        }
+    }
+}
+
+impl ScaleBar {
+    pub fn new(corner: Corner) -> Self {
+        Self {
+            corner,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    pub fn with_style(mut self, style: ScaleBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl<P> CanvasLayer<P> for ScaleBar
+where
+    P: Default,
+{
+    fn tessellate(&mut self, painter: &mut LayerPainter<P>) {
+        // gap between bar and label in physical pixels
+        const LABEL_GAP: f64 = 4.0;
+
+        let (ux, uy) = painter.pixel_size();
+        let (x_range, y_range) = (painter.x_range(), painter.y_range());
+        if !(ux.is_finite() && uy.is_finite()) || ux == 0.0 {
+            return;
+        }
+
+        let length = GPUCanvas2D::<P>::calculate_dynamic_spacing(self.length as f64 * ux.abs(), 1);
+        let Some(data_length) = length.to_f64() else {
+            return;
+        };
+
+        let margin = self.margin as f64;
+        let (x_start, x_end) = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => {
+                let start = x_range.start() + margin * ux;
+                (start, start + data_length * ux.signum())
+            }
+            Corner::TopRight | Corner::BottomRight => {
+                let end = x_range.end() - margin * ux;
+                (end - data_length * ux.signum(), end)
+            }
+        };
+        // labels are on the side facing the center
+        let (y, inward, v_align) = match self.corner {
+            Corner::BottomLeft | Corner::BottomRight => {
+                (y_range.start() + margin * uy, uy, VerticalAlign::Bottom)
+            }
+            Corner::TopLeft | Corner::TopRight => {
+                (y_range.end() - margin * uy, -uy, VerticalAlign::Top)
+            }
+        };
+
+        let text_style = painter.text_style().cloned();
+        let color = self
+            .style
+            .color
+            .or(text_style.as_ref().map(|text_style| text_style.color))
+            .unwrap_or(RGBA::BLACK);
+
+        let tick = y + self.style.tick_length as f64 * inward;
+        painter.stroke(
+            &[(x_start, tick), (x_start, y), (x_end, y), (x_end, tick)],
+            self.style.thickness,
+            color,
+            LineJoin::MITER,
+            LineCap::Butt,
+        );
+
+        let Some(text_style) = text_style else {
+            return;
+        };
+
+        let text = TickLabelFormat::Auto.format(&length, text_style.max_digits);
+        let text = match text_style.superscript_exponents {
+            true => superscript_exponent(&text).into_owned(),
+            false => text,
+        };
+        let text = match &self.unit {
+            Some(unit) => format!("{} {}", text, unit),
+            None => text,
+        };
+        painter.label_aligned(
+            &text_style.locale.localize(&text),
+            ((x_start + x_end) / 2.0, y + LABEL_GAP * inward),
+            HorizontalAlign::Center,
+            v_align,
+            &text_style,
+        );
+    }
+
+    fn uses_parameter(&self) -> bool {
+        false
+    }
+}
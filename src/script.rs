@@ -0,0 +1,351 @@
+//! Graphs, parameter updates and styles defined in rhai scripts.
+//!
+//! A script defines its graphs through a `graphs` function and may optionally define
+//! `params`, `update`, `style` and `range`:
+//!
+//! ```rhai
+//! fn graphs() {
+//!     [#{ function: "square", color: [131, 39, 196], thickness: 0.005 }]
+//! }
+//!
+//! fn square(x, p) { (x - p.a) ** 2 }
+//!
+//! fn params() { #{ a: 0.0 } }
+//!
+//! // `t`: seconds since the script was loaded
+//! fn update(p, t) { p.a = sin(t); p }
+//!
+//! fn style() { #{ text: true, text_size: 32.0, grid: true, subgrid: false, axis: true } }
+//!
+//! fn range() { [-2.0, 2.0, -1.0, 3.0] }
+//! ```
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+use crate::color::RGBA;
+use crate::graph::*;
+use crate::plot::{Plot, PlotHandle, PlotSeries};
+use crate::watch::FileWatcher;
+
+/// Parameters passed to script functions as their second argument.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptParams(pub Map);
+
+fn script_error(error: Box<rhai::EvalAltResult>) -> anyhow::Error {
+    anyhow::Error::msg(error.to_string())
+}
+
+fn dynamic_to_f64(value: &Dynamic) -> Option<f64> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|value| value as f64))
+}
+
+fn dynamic_to_color(value: &Dynamic) -> Option<RGBA> {
+    let components = value
+        .clone()
+        .try_cast::<Array>()?
+        .iter()
+        .map(|component| dynamic_to_f64(component).map(|c| c.clamp(0.0, 255.0) as u8))
+        .collect::<Option<Vec<_>>>()?;
+
+    match components.as_slice() {
+        [r, g, b] => Some(RGBA::new(*r, *g, *b, 255)),
+        [r, g, b, a] => Some(RGBA::new(*r, *g, *b, *a)),
+        _ => None,
+    }
+}
+
+/// A compiled script which can be reloaded when its file changes on disk.
+pub struct Script {
+    watcher: FileWatcher,
+
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+}
+
+impl Script {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let watcher = FileWatcher::new(path);
+
+        let engine = Arc::new(Engine::new());
+        let ast = Arc::new(Self::compile(&engine, watcher.path())?);
+
+        Ok(Self {
+            watcher,
+            engine,
+            ast,
+        })
+    }
+
+    fn compile(engine: &Engine, path: &Path) -> anyhow::Result<AST> {
+        engine
+            .compile_file(path.to_path_buf())
+            .map_err(script_error)
+            .with_context(|| format!("Failed to compile script {}.", path.display()))
+    }
+
+    /// Recompiles the script if its file was modified since it was last loaded.
+    /// Returns `true` if the script got reloaded.
+    pub fn reload_if_changed(&mut self) -> anyhow::Result<bool> {
+        if !self.watcher.changed() {
+            return Ok(false);
+        }
+
+        self.ast = Arc::new(Self::compile(&self.engine, self.watcher.path())?);
+
+        Ok(true)
+    }
+
+    pub fn path(&self) -> &Path {
+        self.watcher.path()
+    }
+
+    fn has_function(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name)
+    }
+
+    fn call(&self, name: &str, args: impl rhai::FuncArgs) -> anyhow::Result<Dynamic> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+            .map_err(script_error)
+            .with_context(|| format!("Failed to call script function `{}`.", name))
+    }
+
+    fn call_optional(
+        &self,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> anyhow::Result<Option<Dynamic>> {
+        match self.has_function(name) {
+            true => self.call(name, args).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    pub fn graphs(&self) -> anyhow::Result<Vec<FunctionGraph<f64, ScriptParams, f64>>> {
+        let graphs = self
+            .call("graphs", ())?
+            .try_cast::<Array>()
+            .context("Script function `graphs` has to return an array.")?;
+
+        graphs
+            .into_iter()
+            .map(|graph| {
+                let graph = graph
+                    .try_cast::<Map>()
+                    .context("Graph definitions have to be object maps.")?;
+
+                let function = graph
+                    .get("function")
+                    .and_then(|function| function.clone().into_string().ok())
+                    .context("Graph definition is missing the name of its `function`.")?;
+
+                if !self.has_function(&function) {
+                    return Err(anyhow::Error::msg(format!(
+                        "Script function `{}` is not defined.",
+                        function
+                    )));
+                }
+
+                let mut style = GraphStyle::default();
+                if let Some(color) = graph.get("color").and_then(dynamic_to_color) {
+                    style.color = color;
+                }
+                if let Some(thickness) = graph.get("thickness").and_then(dynamic_to_f64) {
+                    style.thickness = thickness as f32;
+                }
+
+                let engine = self.engine.clone();
+                let ast = self.ast.clone();
+
+                Ok(FunctionGraph::new(
+                    move |x: f64, p: &ScriptParams| {
+                        engine
+                            .call_fn::<Dynamic>(
+                                &mut Scope::new(),
+                                &ast,
+                                &function,
+                                (x, p.0.clone()),
+                            )
+                            .ok()
+                            .and_then(|y| dynamic_to_f64(&y))
+                            .unwrap_or(f64::NAN)
+                    },
+                    style,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn params(&self) -> anyhow::Result<ScriptParams> {
+        match self.call_optional("params", ())? {
+            Some(params) => {
+                Ok(ScriptParams(params.try_cast::<Map>().context(
+                    "Script function `params` has to return an object map.",
+                )?))
+            }
+            None => Ok(ScriptParams::default()),
+        }
+    }
+
+    /// Applies the `update` rule of the script (if any) at `time` seconds.
+    pub fn update(&self, params: &ScriptParams, time: f64) -> anyhow::Result<Option<ScriptParams>> {
+        match self.call_optional("update", (params.0.clone(), time))? {
+            Some(params) => {
+                Ok(Some(ScriptParams(params.try_cast::<Map>().context(
+                    "Script function `update` has to return an object map.",
+                )?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn style(&self) -> anyhow::Result<Option<EnviromentStyle>> {
+        let Some(style_map) = self.call_optional("style", ())? else {
+            return Ok(None);
+        };
+
+        let style_map = style_map
+            .try_cast::<Map>()
+            .context("Script function `style` has to return an object map.")?;
+        let flag = |name: &str| style_map.get(name).and_then(|value| value.as_bool().ok());
+
+        let mut style = EnviromentStyle::default();
+
+        for dimension in [&mut style.x, &mut style.y] {
+            if flag("axis") == Some(false) {
+                dimension.axis = None;
+            }
+            if flag("grid") == Some(false) {
+                dimension.grid = None;
+            }
+            if flag("subgrid") == Some(false) {
+                dimension.subgrid = None;
+            }
+        }
+
+        if flag("text") == Some(false) {
+            style.text = None;
+        }
+        if let (Some(text), Some(size)) = (
+            &mut style.text,
+            style_map.get("text_size").and_then(dynamic_to_f64),
+        ) {
+            text.size = size as f32;
+        }
+
+        Ok(Some(style))
+    }
+
+    pub fn range(&self) -> anyhow::Result<Option<(Range<f64>, Range<f64>)>> {
+        let Some(range) = self.call_optional("range", ())? else {
+            return Ok(None);
+        };
+
+        let range = range
+            .try_cast::<Array>()
+            .and_then(|range| range.iter().map(dynamic_to_f64).collect::<Option<Vec<_>>>())
+            .context("Script function `range` has to return an array of numbers.")?;
+
+        match range.as_slice() {
+            [x_start, x_end, y_start, y_end] => Ok(Some((*x_start..*x_end, *y_start..*y_end))),
+            _ => Err(anyhow::Error::msg(
+                "Script function `range` has to return [x_start, x_end, y_start, y_end].",
+            )),
+        }
+    }
+
+    /// Builds a plot showing the graphs defined by the script.
+    pub fn plot(&self) -> anyhow::Result<Plot<ScriptParams>> {
+        let mut plot = Plot::with_parameter(self.params()?).title(
+            &self
+                .path()
+                .file_name()
+                .map_or("Script".to_string(), |name| {
+                    name.to_string_lossy().to_string()
+                }),
+        );
+
+        for graph in self.graphs()? {
+            plot = plot.series(graph);
+        }
+
+        if let Some(style) = self.style()? {
+            *plot.style_get_mut() = style;
+        }
+
+        if let Some((x_range, y_range)) = self.range()? {
+            plot = plot.x_range(x_range).y_range(y_range);
+        }
+
+        Ok(plot)
+    }
+
+    /// Sends the graphs, parameters and style of the (reloaded) script to a shown plot.
+    pub fn apply(&self, handle: &PlotHandle<ScriptParams>) -> anyhow::Result<ScriptParams> {
+        let graphs = self.graphs()?;
+        let params = self.params()?;
+
+        handle.clear_series()?;
+        for graph in graphs {
+            handle.add_series(PlotSeries::Line(graph))?;
+        }
+
+        handle.set_param(params.clone())?;
+        handle.set_style(self.style()?.unwrap_or_default())?;
+
+        Ok(params)
+    }
+}
+
+/// Shows the graphs of the script at `path` and reloads them whenever the file changes,
+/// until the window gets closed.
+///
+/// Errors of the initial load are returned. Errors in a reloaded script or its `update` are
+/// passed to `on_error` and keep the previous state shown.
+pub fn run_script(
+    path: impl AsRef<Path>,
+    mut on_error: impl FnMut(anyhow::Error),
+) -> anyhow::Result<()> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+    let mut script = Script::load(path)?;
+    let mut params = script.params()?;
+
+    let handle = script.plot()?.show_detached()?;
+    let start = Instant::now();
+
+    while handle.is_open() {
+        std::thread::sleep(POLL_INTERVAL);
+
+        match script.reload_if_changed() {
+            Ok(true) => match script.apply(&handle) {
+                Ok(new_params) => params = new_params,
+                Err(error) => on_error(error),
+            },
+            Ok(false) => (),
+            Err(error) => on_error(error),
+        }
+
+        match script.update(&params, start.elapsed().as_secs_f64()) {
+            Ok(Some(new_params)) => {
+                params = new_params;
+                let _ = handle.set_param(params.clone());
+            }
+            Ok(None) => (),
+            Err(error) => on_error(error),
+        }
+    }
+
+    handle.join()
+}
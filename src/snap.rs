@@ -0,0 +1,67 @@
+use crate::graph::GraphId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapMode {
+    /// Points of point series.
+    SamplePoint,
+    /// Closest point on a function graph.
+    Curve,
+    /// Intersections of the major grid lines.
+    GridIntersection,
+}
+
+/// Snap modes which are active and the radius in physical pixels they are applied in.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapping {
+    pub modes: Vec<SnapMode>,
+    pub radius: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            modes: Vec::new(),
+            radius: Self::DEFAULT_RADIUS,
+        }
+    }
+}
+
+impl Snapping {
+    pub const DEFAULT_RADIUS: f32 = 12.0;
+
+    pub fn new(modes: &[SnapMode]) -> Self {
+        Self {
+            modes: modes.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.modes.is_empty() && self.radius > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapTarget {
+    /// Point `index` of a point series.
+    SamplePoint(GraphId, usize),
+    Curve(GraphId),
+    GridIntersection,
+}
+
+/// Result of `GPUCanvas2D::snap`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    /// Snapped position in data coordinates.
+    pub position: (f64, f64),
+    pub target: SnapTarget,
+    /// Distance to the unsnapped position in physical pixels.
+    pub distance: f32,
+}
@@ -0,0 +1,490 @@
+//! Themes loaded from (and watched in) JSON, TOML or RON files.
+//!
+//! A style file overrides parts of a base theme, keys which are left out keep their base
+//! value:
+//!
+//! ```toml
+//! base = "dark"
+//! clear_color = "#101018"
+//!
+//! [graph]
+//! color = "#f0c040"
+//! thickness = 0.005
+//!
+//! [x]
+//! grid = { color = "#303040", thickness = 0.0025 }
+//! subgrid = false
+//! spacing = { steps = 8, substeps = 1 }
+//!
+//! [y]
+//! label = { format = "scientific", decimals = 1, every = 2 }
+//! title = "Im f(x)"
+//!
+//! [text]
+//! size = 28
+//! color = [230, 230, 230]
+//! ```
+//!
+//! Optional parts (axes, ticks, grids, labels, titles and the text) are hidden with `false`.
+//! TOML and RON need the `toml` and `ron` features.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::color::RGBA;
+use crate::decimal_math::decimal_from_f64;
+use crate::gpucanvas_2d::GPUCanvas2D;
+use crate::graph::{
+    AxisLabelStyle, AxisStyle, AxisTitleStyle, DimensionStyle, GridSpacing, GridStyle,
+    TextHaloStyle, TextStyle, TickLabelFormat, TickStyle,
+};
+use crate::math::Degrees;
+use crate::plot::PlotHandle;
+use crate::resources::FontRegistry;
+use crate::theme::Theme;
+use crate::watch::FileWatcher;
+
+/// Syntax of a style file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleFormat {
+    Json,
+    Toml,
+    Ron,
+}
+
+impl StyleFormat {
+    /// Format of a file with the extension of `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "ron" => Some(Self::Ron),
+            _ => None,
+        }
+    }
+
+    fn parse(&self, source: &str) -> anyhow::Result<Value> {
+        match self {
+            Self::Json => Ok(serde_json::from_str(source)?),
+            #[cfg(feature = "toml")]
+            Self::Toml => Ok(toml::from_str(source)?),
+            #[cfg(not(feature = "toml"))]
+            Self::Toml => Err(anyhow::Error::msg(
+                "TOML style files need the `toml` feature.",
+            )),
+            #[cfg(feature = "ron")]
+            // anonymous structs only deserialize through `ron::Value`
+            Self::Ron => Ok(ron::from_str::<ron::Value>(source)?.into_rust()?),
+            #[cfg(not(feature = "ron"))]
+            Self::Ron => Err(anyhow::Error::msg(
+                "RON style files need the `ron` feature.",
+            )),
+        }
+    }
+}
+
+/// Applies the style file `source` on top of `base`.
+pub fn parse_theme(source: &str, format: StyleFormat, base: &Theme) -> anyhow::Result<Theme> {
+    let value = format.parse(source)?;
+    let Value::Object(object) = &value else {
+        return Err(type_error("a table", &value));
+    };
+
+    let mut theme = match object.get("base") {
+        None => base.clone(),
+        Some(Value::String(name)) => match name.as_str() {
+            "light" => Theme::light(),
+            "dark" => Theme::dark(),
+            name => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unknown base theme \"{}\", expected \"light\" or \"dark\".",
+                    name
+                )))
+            }
+        },
+        Some(value) => return Err(type_error("a theme name", value)),
+    };
+
+    for (key, value) in object {
+        match key.as_str() {
+            "base" => (),
+            "clear_color" => theme.clear_color = color(value)?,
+            "graph" => {
+                for (key, value) in table(value)? {
+                    match key.as_str() {
+                        "color" => theme.graph.color = color(value)?,
+                        "thickness" => theme.graph.thickness = number(value)? as f32,
+                        key => return Err(unknown_key(key)).context("Invalid graph style."),
+                    }
+                }
+            }
+            "x" => dimension(&mut theme.enviroment.x, value).context("Invalid x style.")?,
+            "y" => dimension(&mut theme.enviroment.y, value).context("Invalid y style.")?,
+            "axes" => {
+                dimension(&mut theme.enviroment.x, value).context("Invalid axes style.")?;
+                dimension(&mut theme.enviroment.y, value).context("Invalid axes style.")?;
+            }
+            "text" => {
+                theme.enviroment.text =
+                    optional(&theme.enviroment.text, value, text).context("Invalid text style.")?
+            }
+            key => return Err(unknown_key(key)),
+        }
+    }
+
+    Ok(theme)
+}
+
+/// Reads the style file at `path` (format by extension) on top of `base`.
+pub fn read_theme(path: impl AsRef<Path>, base: &Theme) -> anyhow::Result<Theme> {
+    let path = path.as_ref();
+    let format = StyleFormat::from_path(path).ok_or_else(|| {
+        anyhow::Error::msg(format!(
+            "Unknown style format of {}, expected .json, .toml or .ron.",
+            path.display()
+        ))
+    })?;
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}.", path.display()))?;
+
+    parse_theme(&source, format, base)
+        .with_context(|| format!("Failed to parse {}.", path.display()))
+}
+
+/// Style file which gets applied to running canvases whenever it changes on disk.
+pub struct WatchedTheme {
+    watcher: FileWatcher,
+    base: Theme,
+}
+
+impl WatchedTheme {
+    /// `base` is the theme the file is applied on top of.
+    pub fn new(path: impl AsRef<Path>, base: Theme) -> Self {
+        Self {
+            watcher: FileWatcher::new(path),
+            base,
+        }
+    }
+
+    /// Reads the current file, e.g. for the initial theme.
+    pub fn load(&self) -> anyhow::Result<Theme> {
+        read_theme(self.watcher.path(), &self.base)
+    }
+
+    /// The reloaded theme if the file changed since the last call.
+    pub fn reload(&mut self) -> anyhow::Result<Option<Theme>> {
+        if !self.watcher.changed() {
+            return Ok(None);
+        }
+
+        self.load().map(Some)
+    }
+
+    /// Reloads the file if it changed and sets the theme of `canvas`.
+    ///
+    /// Returns `true` if the file got reloaded.
+    pub fn update_canvas<P: Default>(
+        &mut self,
+        canvas: &mut GPUCanvas2D<P>,
+    ) -> anyhow::Result<bool> {
+        let Some(theme) = self.reload()? else {
+            return Ok(false);
+        };
+
+        canvas.set_theme(&theme);
+        Ok(true)
+    }
+
+    /// Reloads the file if it changed and sends the theme to a detached plot.
+    ///
+    /// Returns `true` if the file got reloaded.
+    pub fn update<P: 'static>(&mut self, handle: &PlotHandle<P>) -> anyhow::Result<bool> {
+        let Some(theme) = self.reload()? else {
+            return Ok(false);
+        };
+
+        handle.set_theme(theme)?;
+        Ok(true)
+    }
+}
+
+//-- parsing
+
+fn dimension(style: &mut DimensionStyle, value: &Value) -> anyhow::Result<()> {
+    for (key, value) in table(value)? {
+        match key.as_str() {
+            "spacing" => style.spacing = spacing(&style.spacing, value)?,
+            "axis" => {
+                style.axis = optional(&style.axis, value, |axis: &AxisStyle, value| {
+                    let mut axis = *axis;
+                    for (key, value) in table(value)? {
+                        match key.as_str() {
+                            "color" => axis.color = color(value)?,
+                            "thickness" => axis.thickness = number(value)? as f32,
+                            key => return Err(unknown_key(key)),
+                        }
+                    }
+                    Ok(axis)
+                })?
+            }
+            "tick" => style.tick = optional(&style.tick, value, tick)?,
+            "subtick" => style.subtick = optional(&style.subtick, value, tick)?,
+            "grid" => style.grid = optional(&style.grid, value, grid)?,
+            "subgrid" => style.subgrid = optional(&style.subgrid, value, grid)?,
+            "label" => style.label = optional(&style.label, value, label)?,
+            "title" => style.title = optional(&style.title, value, title)?,
+            key => return Err(unknown_key(key)),
+        }
+    }
+
+    Ok(())
+}
+
+fn spacing(base: &GridSpacing, value: &Value) -> anyhow::Result<GridSpacing> {
+    let spacing = table(value)?;
+    let substeps = match (spacing.get("substeps"), base) {
+        (Some(value), _) => unsigned(value)?,
+        (None, GridSpacing::Dynamic { substeps, .. } | GridSpacing::Fixed { substeps, .. }) => {
+            *substeps
+        }
+    };
+
+    match (spacing.get("steps"), spacing.get("spacing")) {
+        (Some(steps), None) => Ok(GridSpacing::Dynamic {
+            steps: unsigned(steps)?.max(1),
+            substeps,
+        }),
+        (None, Some(value)) => match number(value)? {
+            value if value > 0.0 => Ok(GridSpacing::Fixed {
+                spacing: decimal_from_f64(value),
+                substeps,
+            }),
+            _ => Err(anyhow::Error::msg("The grid spacing must be positive.")),
+        },
+        _ => Err(anyhow::Error::msg(
+            "Expected either \"steps\" or \"spacing\" in the grid spacing.",
+        )),
+    }
+}
+
+fn tick(base: &TickStyle, value: &Value) -> anyhow::Result<TickStyle> {
+    let mut tick = *base;
+    for (key, value) in table(value)? {
+        match key.as_str() {
+            "color" => tick.color = color(value)?,
+            "length" => tick.length = number(value)? as f32,
+            "thickness" => tick.thickness = number(value)? as f32,
+            key => return Err(unknown_key(key)),
+        }
+    }
+    Ok(tick)
+}
+
+fn grid(base: &GridStyle, value: &Value) -> anyhow::Result<GridStyle> {
+    let mut grid = *base;
+    for (key, value) in table(value)? {
+        match key.as_str() {
+            "color" => grid.color = color(value)?,
+            "thickness" => grid.thickness = number(value)? as f32,
+            key => return Err(unknown_key(key)),
+        }
+    }
+    Ok(grid)
+}
+
+fn label(base: &AxisLabelStyle, value: &Value) -> anyhow::Result<AxisLabelStyle> {
+    let mut label = *base;
+    let object = table(value)?;
+    let decimals = object.get("decimals").map(unsigned).transpose()?;
+
+    for (key, value) in object {
+        match key.as_str() {
+            "format" => {
+                label.format =
+                    match value.as_str() {
+                        Some("auto") => TickLabelFormat::Auto,
+                        Some("fixed") => TickLabelFormat::Fixed {
+                            decimals: decimals.unwrap_or(2),
+                        },
+                        Some("scientific") => TickLabelFormat::Scientific {
+                            decimals: decimals.unwrap_or(2),
+                        },
+                        Some("longitude") => TickLabelFormat::Longitude,
+                        Some("latitude") => TickLabelFormat::Latitude,
+                        _ => return Err(type_error(
+                            "\"auto\", \"fixed\", \"scientific\", \"longitude\" or \"latitude\"",
+                            value,
+                        )),
+                    }
+            }
+            "decimals" => (),
+            "every" => label.every = unsigned(value)?.max(1),
+            "rotation" => label.rotation = Degrees(number(value)?),
+            "offset" => label.offset = number(value)? as f32,
+            key => return Err(unknown_key(key)),
+        }
+    }
+    Ok(label)
+}
+
+/// Either the text or a table with it.
+fn title(base: &AxisTitleStyle, value: &Value) -> anyhow::Result<AxisTitleStyle> {
+    let mut title = base.clone();
+    match value {
+        Value::String(text) => title.text = text.clone(),
+        value => {
+            for (key, value) in table(value)? {
+                match key.as_str() {
+                    "text" => {
+                        title.text = value
+                            .as_str()
+                            .ok_or_else(|| type_error("a string", value))?
+                            .to_string()
+                    }
+                    "rotation" => title.rotation = Some(Degrees(number(value)?)),
+                    "offset" => title.offset = number(value)? as f32,
+                    key => return Err(unknown_key(key)),
+                }
+            }
+        }
+    }
+    Ok(title)
+}
+
+fn text(base: &TextStyle, value: &Value) -> anyhow::Result<TextStyle> {
+    let mut text = base.clone();
+    for (key, value) in table(value)? {
+        match key.as_str() {
+            "size" => text.size = number(value)? as f32,
+            "color" => text.color = color(value)?,
+            "max_digits" => text.max_digits = unsigned(value)?,
+            "superscript_exponents" => text.superscript_exponents = boolean(value)?,
+            "font" => {
+                let fonts = FontRegistry::shared();
+                text.font = match value {
+                    Value::String(name) => fonts.get(name).ok_or_else(|| {
+                        anyhow::Error::msg(format!("Font \"{}\" is not registered.", name))
+                    })?,
+                    Value::Object(font) => match (font.get("name"), font.get("path")) {
+                        (Some(Value::String(name)), Some(Value::String(path))) => {
+                            fonts.load(name, path)?
+                        }
+                        _ => return Err(type_error("a font name and path", value)),
+                    },
+                    value => return Err(type_error("a font", value)),
+                }
+            }
+            "halo" => {
+                text.halo = optional(&text.halo, value, |halo: &TextHaloStyle, value| {
+                    let mut halo = *halo;
+                    for (key, value) in table(value)? {
+                        match key.as_str() {
+                            "color" => halo.color = color(value)?,
+                            "width" => halo.width = number(value)? as f32,
+                            key => return Err(unknown_key(key)),
+                        }
+                    }
+                    Ok(halo)
+                })?
+            }
+            "background" => {
+                text.background = match value {
+                    Value::Bool(false) | Value::Null => None,
+                    value => Some(color(value)?),
+                }
+            }
+            key => return Err(unknown_key(key)),
+        }
+    }
+    Ok(text)
+}
+
+/// `false` hides an optional part, anything else overrides the current (or default) one.
+fn optional<T: Default + Clone>(
+    current: &Option<T>,
+    value: &Value,
+    parse: impl Fn(&T, &Value) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<T>> {
+    match value {
+        Value::Bool(false) | Value::Null => Ok(None),
+        Value::Bool(true) => Ok(Some(current.clone().unwrap_or_default())),
+        value => parse(&current.clone().unwrap_or_default(), value).map(Some),
+    }
+}
+
+/// `"#rrggbb"`, `"#rrggbbaa"` or `[r, g, b]` / `[r, g, b, a]` with components in `0..=255`.
+fn color(value: &Value) -> anyhow::Result<RGBA> {
+    match value {
+        Value::String(hex) => {
+            let digits = hex.strip_prefix('#').unwrap_or(hex);
+            let component = |i: usize| {
+                digits
+                    .get(i * 2..i * 2 + 2)
+                    .and_then(|component| u8::from_str_radix(component, 16).ok())
+            };
+
+            match (digits.len(), component(0), component(1), component(2)) {
+                (6 | 8, Some(r), Some(g), Some(b)) => {
+                    let a = match digits.len() {
+                        8 => component(3).ok_or_else(|| type_error("a color", value))?,
+                        _ => 255,
+                    };
+                    Ok(RGBA::new(r, g, b, a))
+                }
+                _ => Err(type_error("a color", value)),
+            }
+        }
+        Value::Array(components) if matches!(components.len(), 3 | 4) => {
+            let components = components
+                .iter()
+                .map(|component| {
+                    component
+                        .as_u64()
+                        .and_then(|component| u8::try_from(component).ok())
+                        .ok_or_else(|| type_error("a color component in 0..=255", component))
+                })
+                .collect::<anyhow::Result<Vec<u8>>>()?;
+
+            Ok(RGBA::new(
+                components[0],
+                components[1],
+                components[2],
+                components.get(3).copied().unwrap_or(255),
+            ))
+        }
+        value => Err(type_error("a color", value)),
+    }
+}
+
+fn table(value: &Value) -> anyhow::Result<&Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| type_error("a table", value))
+}
+
+fn number(value: &Value) -> anyhow::Result<f64> {
+    value.as_f64().ok_or_else(|| type_error("a number", value))
+}
+
+fn unsigned(value: &Value) -> anyhow::Result<u32> {
+    value
+        .as_u64()
+        .and_then(|value| u32::try_from(value).ok())
+        .ok_or_else(|| type_error("a positive integer", value))
+}
+
+fn boolean(value: &Value) -> anyhow::Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| type_error("a boolean", value))
+}
+
+fn type_error(expected: &str, found: &Value) -> anyhow::Error {
+    anyhow::Error::msg(format!("Expected {} but found {}.", expected, found))
+}
+
+fn unknown_key(key: &str) -> anyhow::Error {
+    anyhow::Error::msg(format!("Unknown style key \"{}\".", key))
+}
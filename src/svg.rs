@@ -0,0 +1,318 @@
+//! Vector output of multiviews, see `GPUMultiView::to_svg`.
+
+use std::fmt::Write;
+
+use wgpu_text::glyph_brush::{HorizontalAlign, Layout, OwnedSection, VerticalAlign};
+
+use crate::geometry::Rect;
+use crate::gpuview::Vertex;
+
+/// Area of an SVG document in pixels, y pointing down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SvgRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl SvgRect {
+    /// Maps screen space (`-1..1`, y pointing up) onto the area.
+    pub fn point(&self, screen: [f32; 2]) -> [f32; 2] {
+        [
+            self.x + (screen[0] + 1.0) / 2.0 * self.width,
+            self.y + (1.0 - screen[1]) / 2.0 * self.height,
+        ]
+    }
+
+    /// Area of `rect`, which is given in screen space of this area.
+    pub fn sub_rect(&self, rect: Rect) -> Self {
+        let upper_left = self.point([rect.min[0], rect.max[1]]);
+        let lower_right = self.point([rect.max[0], rect.min[1]]);
+
+        Self {
+            x: upper_left[0],
+            y: upper_left[1],
+            width: lower_right[0] - upper_left[0],
+            height: lower_right[1] - upper_left[1],
+        }
+    }
+}
+
+/// Builds an SVG document in the order things are drawn, later elements paint over earlier ones.
+pub(crate) struct SvgWriter {
+    svg: String,
+    next_id: usize,
+}
+
+impl SvgWriter {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+
+        Self { svg, next_id: 0 }
+    }
+
+    pub fn finish(mut self) -> String {
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+
+    pub fn fill_rect(&mut self, rect: SvgRect, color: wgpu::Color) {
+        if color.a <= 0.0 {
+            return;
+        }
+
+        let color = [color.r, color.g, color.b, color.a].map(|channel| channel as f32);
+        let _ = writeln!(
+            self.svg,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" {}/>"#,
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            fill(color)
+        );
+    }
+
+    /// Starts a group clipped to `rect`, closed by `end_group`.
+    pub fn begin_clip(&mut self, rect: SvgRect) {
+        let id = self.next_id();
+        let _ = writeln!(
+            self.svg,
+            r#"<clipPath id="clip{id}"><rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}"/></clipPath>"#,
+            rect.x, rect.y, rect.width, rect.height
+        );
+        let _ = writeln!(self.svg, r#"<g clip-path="url(#clip{id})">"#);
+    }
+
+    /// Starts a group blurred by `radius` pixels, closed by `end_group`.
+    pub fn begin_blur(&mut self, radius: f32) {
+        let id = self.next_id();
+        let _ = writeln!(
+            self.svg,
+            r#"<filter id="blur{id}" x="-50%" y="-50%" width="200%" height="200%"><feGaussianBlur stdDeviation="{:.2}"/></filter>"#,
+            radius / 2.0
+        );
+        let _ = writeln!(self.svg, r#"<g filter="url(#blur{id})">"#);
+    }
+
+    pub fn end_group(&mut self) {
+        self.svg.push_str("</g>\n");
+    }
+
+    /// Appends a triangle list, consecutive triangles of the same color are merged into one
+    /// path. Triangles are filled with the mean of their vertex colors, so color gradients
+    /// turn into steps of the tessellation.
+    pub fn triangles(&mut self, rect: SvgRect, vertices: &[Vertex], indices: Option<&[u32]>) {
+        let triangles: Vec<[&Vertex; 3]> = match indices {
+            Some(indices) => indices
+                .chunks_exact(3)
+                .filter_map(|triangle| {
+                    let vertex = |index: u32| vertices.get(index as usize);
+                    Some([
+                        vertex(triangle[0])?,
+                        vertex(triangle[1])?,
+                        vertex(triangle[2])?,
+                    ])
+                })
+                .collect(),
+            None => vertices
+                .chunks_exact(3)
+                .map(|triangle| [&triangle[0], &triangle[1], &triangle[2]])
+                .collect(),
+        };
+
+        let mut path = String::new();
+        let mut path_color = None;
+
+        for triangle in triangles {
+            let mut color = [0.0; 4];
+            for vertex in triangle {
+                for (channel, value) in color.iter_mut().zip(vertex.color) {
+                    *channel += value / 3.0;
+                }
+            }
+            // quantized like the 8 bit target, so rounding noise doesn't split paths
+            let color = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() / 255.0);
+            if color[3] == 0.0 {
+                continue;
+            }
+
+            if path_color != Some(color) {
+                self.path(&path, path_color);
+                path.clear();
+                path_color = Some(color);
+            }
+
+            let [a, b, c] =
+                triangle.map(|vertex| rect.point([vertex.position[0], vertex.position[1]]));
+            let _ = write!(
+                path,
+                "M{:.2} {:.2}L{:.2} {:.2}L{:.2} {:.2}Z",
+                a[0], a[1], b[0], b[1], c[0], c[1]
+            );
+        }
+
+        self.path(&path, path_color);
+    }
+
+    fn path(&mut self, path: &str, color: Option<[f32; 4]>) {
+        let Some(color) = color.filter(|_| !path.is_empty()) else {
+            return;
+        };
+
+        let _ = writeln!(self.svg, r#"<path d="{}" {}/>"#, path, fill(color));
+    }
+
+    /// Appends `section` (positioned in pixels of `rect`) rotated counterclockwise around its
+    /// position by `rotation` radians.
+    pub fn text(
+        &mut self,
+        rect: SvgRect,
+        section: &OwnedSection,
+        font_family: &str,
+        rotation: f32,
+    ) {
+        let text = section
+            .text
+            .iter()
+            .map(|part| part.text.as_str())
+            .collect::<String>();
+        let Some(first) = section.text.first().filter(|_| !text.trim().is_empty()) else {
+            return;
+        };
+
+        let (h_align, v_align) = match section.layout {
+            Layout::SingleLine {
+                h_align, v_align, ..
+            }
+            | Layout::Wrap {
+                h_align, v_align, ..
+            } => (h_align, v_align),
+        };
+        let anchor = match h_align {
+            HorizontalAlign::Left => "start",
+            HorizontalAlign::Center => "middle",
+            HorizontalAlign::Right => "end",
+        };
+        let baseline = match v_align {
+            VerticalAlign::Top => "text-before-edge",
+            VerticalAlign::Center => "central",
+            VerticalAlign::Bottom => "text-after-edge",
+        };
+
+        let x = rect.x + section.screen_position.0;
+        let y = rect.y + section.screen_position.1;
+        let transform = match rotation {
+            0.0 => String::new(),
+            rotation => format!(
+                r#" transform="rotate({:.2} {:.2} {:.2})""#,
+                -rotation.to_degrees(),
+                x,
+                y
+            ),
+        };
+
+        let _ = writeln!(
+            self.svg,
+            r#"<text x="{:.2}" y="{:.2}" font-family="{}" font-size="{:.2}" text-anchor="{}" dominant-baseline="{}" {}{}>{}</text>"#,
+            x,
+            y,
+            escape_xml(font_family),
+            first.scale.y,
+            anchor,
+            baseline,
+            fill(first.extra.color),
+            transform,
+            escape_xml(&text)
+        );
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+fn fill(color: [f32; 4]) -> String {
+    let [r, g, b, a] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+
+    match a {
+        255 => format!(r##"fill="#{r:02x}{g:02x}{b:02x}""##),
+        a => format!(
+            r##"fill="#{r:02x}{g:02x}{b:02x}" fill-opacity="{:.3}""##,
+            a as f32 / 255.0
+        ),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32, y: f32, color: [f32; 4]) -> Vertex {
+        Vertex {
+            position: [x, y, 0.0],
+            color,
+        }
+    }
+
+    #[test]
+    fn sub_rect_flips_y() {
+        let rect = SvgRect {
+            x: 10.0,
+            y: 20.0,
+            width: 200.0,
+            height: 100.0,
+        };
+        let sub = rect.sub_rect(Rect::new([0.0, 0.0], [1.0, 1.0]));
+
+        assert_eq!(
+            sub,
+            SvgRect {
+                x: 110.0,
+                y: 20.0,
+                width: 100.0,
+                height: 50.0
+            }
+        );
+    }
+
+    #[test]
+    fn triangles_of_one_color_share_a_path() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 0.5];
+        let vertices = [
+            vertex(-1.0, -1.0, red),
+            vertex(1.0, -1.0, red),
+            vertex(1.0, 1.0, red),
+            vertex(-1.0, 1.0, red),
+            vertex(0.0, 0.0, blue),
+        ];
+        let rect = SvgRect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+
+        let mut writer = SvgWriter::new(100, 100);
+        writer.triangles(rect, &vertices, Some(&[0, 1, 2, 0, 2, 3, 2, 3, 4]));
+        let svg = writer.finish();
+
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.contains(r##"fill="#ff0000""##));
+        assert!(svg.contains("M0.00 100.00L100.00 100.00L100.00 0.00Z"));
+    }
+}
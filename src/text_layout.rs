@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::gpuview::Font;
+
+/// Digits and signs substituted into formatted numbers, see `TextStyle::locale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub digits: [char; 10],
+    pub decimal_separator: char,
+    pub minus_sign: char,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self::LATIN
+    }
+}
+
+impl NumberLocale {
+    pub const LATIN: Self = Self {
+        digits: ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'],
+        decimal_separator: '.',
+        minus_sign: '-',
+    };
+
+    /// Latin digits with a decimal comma (e.g. German, French).
+    pub const LATIN_DECIMAL_COMMA: Self = Self {
+        decimal_separator: ',',
+        ..Self::LATIN
+    };
+
+    pub const ARABIC_INDIC: Self = Self {
+        digits: ['٠', '١', '٢', '٣', '٤', '٥', '٦', '٧', '٨', '٩'],
+        decimal_separator: '٫',
+        minus_sign: '−',
+    };
+
+    /// Digits used for Persian and Urdu.
+    pub const EXTENDED_ARABIC_INDIC: Self = Self {
+        digits: ['۰', '۱', '۲', '۳', '۴', '۵', '۶', '۷', '۸', '۹'],
+        ..Self::ARABIC_INDIC
+    };
+
+    pub const DEVANAGARI: Self = Self {
+        digits: ['०', '१', '२', '३', '४', '५', '६', '७', '८', '९'],
+        ..Self::LATIN
+    };
+
+    /// Substitutes the ASCII digits, `.` and `-` of a formatted number.
+    ///
+    /// The exponent marker of scientific notation is kept as is.
+    pub fn localize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if *self == Self::LATIN {
+            return Cow::Borrowed(text);
+        }
+
+        Cow::Owned(
+            text.chars()
+                .map(|c| match c {
+                    '0'..='9' => self.digits[c as usize - '0' as usize],
+                    '.' => self.decimal_separator,
+                    '-' => self.minus_sign,
+                    c => c,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Writes the exponent of scientific notation (`1.5e-3`, `1e6`, `e^2`) with Unicode
+/// superscripts (`1.5×10⁻³`, `10⁶`, `e²`), since the text brush only draws plain text.
+///
+/// Other text is returned unchanged.
+pub fn superscript_exponent(text: &str) -> Cow<'_, str> {
+    const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+    let (mantissa, base, exponent) = match (text.rfind("e^"), text.rfind('e')) {
+        (Some(index), _) => (&text[..index], "e", &text[index + 2..]),
+        (None, Some(index)) if text[..index].ends_with(|c: char| c.is_ascii_digit()) => {
+            match text[..index].trim_start() {
+                "1" => (&text[..index - 1], "10", &text[index + 1..]),
+                _ => (&text[..index], "×10", &text[index + 1..]),
+            }
+        }
+        _ => return Cow::Borrowed(text),
+    };
+
+    let (sign, digits) = match exponent.strip_prefix('-') {
+        Some(digits) => ("⁻", digits),
+        None => ("", exponent.strip_prefix('+').unwrap_or(exponent)),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Cow::Borrowed(text);
+    }
+
+    let digits: String = digits
+        .chars()
+        .map(|c| SUPERSCRIPT_DIGITS[c as usize - '0' as usize])
+        .collect();
+
+    Cow::Owned(format!("{}{}{}{}", mantissa, base, sign, digits))
+}
+
+/// Single line of text prepared for the text brush, which only lays out glyphs left to right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedLine {
+    /// Characters in visual (left to right) order.
+    pub text: String,
+    /// Whether the paragraph direction of the text is right to left.
+    pub rtl: bool,
+    /// Advance width in pixels if the text got shaped with the font.
+    pub width: Option<f32>,
+}
+
+/// `shape_line` results of recently drawn labels keyed by text, font and size, so labels which
+/// stay the same across redraws only get shaped once.
+#[derive(Debug, Default)]
+pub(crate) struct ShapeCache {
+    entries: HashMap<(String, String, u32), ShapeCacheEntry>,
+    /// Size at which the entries not used since the last eviction are dropped
+    limit: usize,
+}
+
+#[derive(Debug)]
+struct ShapeCacheEntry {
+    line: ShapedLine,
+    used: bool,
+}
+
+impl ShapeCache {
+    const MIN_LIMIT: usize = 1024;
+
+    pub fn shape(&mut self, text: &str, font: &Font, size: f32) -> ShapedLine {
+        if self.entries.len() >= self.limit.max(Self::MIN_LIMIT) {
+            self.evict_unused();
+        }
+
+        let key = (text.to_string(), font.name.clone(), size.to_bits());
+        let entry = self.entries.entry(key).or_insert_with(|| ShapeCacheEntry {
+            line: shape_line(text, font, size),
+            used: false,
+        });
+        entry.used = true;
+
+        entry.line.clone()
+    }
+
+    /// Drops the entries which were not used since the last eviction.
+    pub fn evict_unused(&mut self) {
+        self.entries
+            .retain(|_, entry| std::mem::replace(&mut entry.used, false));
+
+        // labels in use are kept, so many of them would evict on every call otherwise
+        self.limit = self.entries.len() * 2;
+    }
+}
+
+/// Reorders bidirectional text into visual order and measures it with `rustybuzz`.
+///
+/// Contextual forms (e.g. joined Arabic letters) are only used for the measurement,
+/// the text brush still renders the characters in their isolated form.
+#[cfg(feature = "shaping")]
+pub fn shape_line(text: &str, font: &Font, size: f32) -> ShapedLine {
+    use unicode_bidi::BidiInfo;
+    use wgpu_text::glyph_brush::ab_glyph::Font as _;
+
+    let bidi = BidiInfo::new(text, None);
+    let Some(paragraph) = bidi.paragraphs.first() else {
+        return ShapedLine {
+            text: text.to_string(),
+            rtl: false,
+            width: Some(0.0),
+        };
+    };
+
+    let line = paragraph.range.clone();
+    let rtl = paragraph.level.is_rtl();
+    let visual = bidi.reorder_line(paragraph, line.clone()).into_owned();
+
+    let width = rustybuzz::Face::from_slice(font.font.font_data(), 0).map(|face| {
+        let (levels, runs) = bidi.visual_runs(paragraph, line);
+
+        let units: i32 = runs
+            .into_iter()
+            .map(|run| {
+                let mut buffer = rustybuzz::UnicodeBuffer::new();
+                buffer.push_str(&text[run.clone()]);
+                buffer.set_direction(match levels[run.start].is_rtl() {
+                    true => rustybuzz::Direction::RightToLeft,
+                    false => rustybuzz::Direction::LeftToRight,
+                });
+
+                rustybuzz::shape(&face, &[], buffer)
+                    .glyph_positions()
+                    .iter()
+                    .map(|position| position.x_advance)
+                    .sum::<i32>()
+            })
+            .sum();
+
+        // the text brush scales glyphs by the unscaled line height, not units per em
+        units as f32 * size / font.font.height_unscaled()
+    });
+
+    ShapedLine {
+        text: visual,
+        rtl,
+        width,
+    }
+}
+
+/// Without the `shaping` feature text is passed through in logical order.
+#[cfg(not(feature = "shaping"))]
+pub fn shape_line(text: &str, _font: &Font, _size: f32) -> ShapedLine {
+    ShapedLine {
+        text: text.to_string(),
+        rtl: false,
+        width: None,
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::Context;
+use wgpu::util::DeviceExt;
+
+/// Row layout of a buffer used in texture copies.
+///
+/// Buffer rows of copies between buffers and textures have to be aligned to
+/// `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`, user data is kept as tightly packed rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddedRows {
+    pub width: u32,
+    pub height: u32,
+    pub bytes_per_texel: u32,
+}
+
+impl PaddedRows {
+    pub fn new(width: u32, height: u32, bytes_per_texel: u32) -> Self {
+        Self {
+            width,
+            height,
+            bytes_per_texel,
+        }
+    }
+
+    /// Layout of the whole first layer of `texture`, fails for compressed or depth formats.
+    pub fn for_texture(texture: &wgpu::Texture) -> anyhow::Result<Self> {
+        let bytes_per_texel = texture.format().block_copy_size(None).with_context(|| {
+            format!(
+                "Textures of format {:?} can not be copied.",
+                texture.format()
+            )
+        })?;
+
+        Ok(Self::new(
+            texture.width(),
+            texture.height(),
+            bytes_per_texel,
+        ))
+    }
+
+    pub fn unpadded_bytes_per_row(&self) -> u32 {
+        self.width * self.bytes_per_texel
+    }
+
+    pub fn padded_bytes_per_row(&self) -> u32 {
+        self.unpadded_bytes_per_row()
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+    }
+
+    pub fn unpadded_size(&self) -> usize {
+        self.unpadded_bytes_per_row() as usize * self.height as usize
+    }
+
+    pub fn padded_size(&self) -> u64 {
+        self.padded_bytes_per_row() as u64 * self.height as u64
+    }
+
+    pub fn image_data_layout(&self) -> wgpu::ImageDataLayout {
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(self.padded_bytes_per_row()),
+            rows_per_image: Some(self.height),
+        }
+    }
+
+    /// Pads tightly packed rows for an upload through a buffer.
+    pub fn pad(&self, data: &[u8]) -> Vec<u8> {
+        let unpadded = self.unpadded_bytes_per_row() as usize;
+        let padded = self.padded_bytes_per_row() as usize;
+
+        let mut result = vec![0; self.padded_size() as usize];
+        for (row, target) in data.chunks(unpadded).zip(result.chunks_mut(padded)) {
+            target[..row.len()].copy_from_slice(row);
+        }
+
+        result
+    }
+
+    /// Strips the padding of rows read back through a buffer.
+    pub fn strip(&self, data: &[u8]) -> Vec<u8> {
+        let unpadded = self.unpadded_bytes_per_row() as usize;
+        let padded = self.padded_bytes_per_row() as usize;
+
+        let mut result = Vec::with_capacity(self.unpadded_size());
+        for row in data.chunks(padded).take(self.height as usize) {
+            result.extend_from_slice(&row[..unpadded]);
+        }
+
+        result
+    }
+}
+
+/// Memory of `texture` including all mip levels and samples in bytes.
+///
+/// Depth/stencil formats without a defined copy size are counted with 4 bytes per texel.
+pub fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let block_bytes = format.block_copy_size(None).unwrap_or(4) as u64;
+    let (block_width, block_height) = format.block_dimensions();
+
+    let level_bytes = (0..texture.mip_level_count())
+        .map(|level| {
+            let size = texture.size().mip_level_size(level, texture.dimension());
+
+            size.width.div_ceil(block_width) as u64
+                * size.height.div_ceil(block_height) as u64
+                * size.depth_or_array_layers as u64
+                * block_bytes
+        })
+        .sum::<u64>();
+
+    level_bytes * texture.sample_count() as u64
+}
+
+/// Swaps the red and blue channel of 4 byte texels, converting BGRA to RGBA and back.
+pub fn swap_red_blue(data: &mut [u8]) {
+    for texel in data.chunks_exact_mut(4) {
+        texel.swap(0, 2);
+    }
+}
+
+/// Copies the first layer of `texture` into a buffer, maps it and returns tightly packed rows.
+///
+/// The texture needs `TextureUsages::COPY_SRC`.
+pub fn read_texture(
+    texture: &wgpu::Texture,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) -> anyhow::Result<Vec<u8>> {
+    let rows = PaddedRows::for_texture(texture)?;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: rows.padded_size(),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: rows.image_data_layout(),
+        },
+        wgpu::Extent3d {
+            width: rows.width,
+            height: rows.height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv()??;
+
+    let data = rows.strip(&buffer.slice(..).get_mapped_range());
+    buffer.unmap();
+
+    Ok(data)
+}
+
+/// Reads back a `Bgra8Unorm`/`Rgba8Unorm` texture as tightly packed RGBA rows.
+pub fn read_texture_rgba(
+    texture: &wgpu::Texture,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) -> anyhow::Result<Vec<u8>> {
+    let bgra = match texture.format() {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        format => {
+            return Err(anyhow::Error::msg(format!(
+                "Textures of format {:?} can not be read back as RGBA.",
+                format
+            )))
+        }
+    };
+
+    let mut data = read_texture(texture, device, queue)?;
+    if bgra {
+        swap_red_blue(&mut data);
+    }
+
+    Ok(data)
+}
+
+/// Uploads tightly packed rows into the first layer of `texture` through a padded buffer.
+///
+/// The texture needs `TextureUsages::COPY_DST`.
+pub fn write_texture(
+    texture: &wgpu::Texture,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[u8],
+) -> anyhow::Result<()> {
+    write_texture_region(
+        texture,
+        device,
+        queue,
+        (0, 0),
+        (texture.width(), texture.height()),
+        data,
+    )
+}
+
+/// Uploads tightly packed rows into the `size` large region at `origin` of `texture`,
+/// so frequently updated parts don't require an upload of the whole texture.
+///
+/// The texture needs `TextureUsages::COPY_DST`.
+pub fn write_texture_region(
+    texture: &wgpu::Texture,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    origin: (u32, u32),
+    size: (u32, u32),
+    data: &[u8],
+) -> anyhow::Result<()> {
+    let texture_rows = PaddedRows::for_texture(texture)?;
+    let rows = PaddedRows::new(size.0, size.1, texture_rows.bytes_per_texel);
+
+    if origin.0 + size.0 > texture_rows.width || origin.1 + size.1 > texture_rows.height {
+        return Err(anyhow::Error::msg(format!(
+            "Region {:?} + {:?} exceeds texture of size {}x{}.",
+            origin, size, texture_rows.width, texture_rows.height
+        )));
+    }
+
+    if data.len() != rows.unpadded_size() {
+        return Err(anyhow::Error::msg(format!(
+            "Texture data has {} bytes instead of {}.",
+            data.len(),
+            rows.unpadded_size()
+        )));
+    }
+
+    if rows.unpadded_size() == 0 {
+        return Ok(());
+    }
+
+    let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Upload Buffer"),
+        contents: &rows.pad(data),
+        usage: wgpu::BufferUsages::COPY_SRC,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Upload Encoder"),
+    });
+
+    encoder.copy_buffer_to_texture(
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: rows.image_data_layout(),
+        },
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: origin.0,
+                y: origin.1,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::Extent3d {
+            width: rows.width,
+            height: rows.height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    Ok(())
+}
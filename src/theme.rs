@@ -0,0 +1,141 @@
+use crate::color::RGBA;
+use crate::graph::{
+    AxisStyle, DimensionStyle, EnviromentStyle, GraphStyle, GridStyle, TextStyle, TickStyle,
+};
+
+/// Colors and defaults of a whole canvas, see `GPUCanvas2D::set_theme`.
+///
+/// `graph` is the lowest level of the graph style cascade, it only affects graphs which
+/// inherit their style (see `FunctionGraph::style_override`).
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub clear_color: RGBA,
+    pub enviroment: EnviromentStyle,
+    pub graph: GraphStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    /// The defaults of `GPUCanvas2D`: black on white.
+    pub fn light() -> Self {
+        Self {
+            clear_color: RGBA::WHITE,
+            enviroment: EnviromentStyle::default(),
+            graph: GraphStyle::default(),
+        }
+    }
+
+    /// Light grey on almost black.
+    pub fn dark() -> Self {
+        let foreground = RGBA::grey(230);
+
+        let dimension = |dimension: DimensionStyle| DimensionStyle {
+            axis: dimension.axis.map(|mut axis| {
+                axis.color = foreground;
+                axis
+            }),
+            tick: dimension.tick.map(|mut tick| {
+                tick.color = foreground;
+                tick
+            }),
+            subtick: dimension.subtick.map(|mut tick| {
+                tick.color = foreground;
+                tick
+            }),
+            grid: dimension.grid.map(|grid| GridStyle {
+                color: RGBA::grey(70),
+                ..grid
+            }),
+            subgrid: dimension.subgrid.map(|grid| GridStyle {
+                color: RGBA::grey(40),
+                ..grid
+            }),
+            ..dimension
+        };
+
+        let enviroment = EnviromentStyle::default();
+        Self {
+            clear_color: RGBA::grey(20),
+            enviroment: EnviromentStyle {
+                x: dimension(enviroment.x),
+                y: dimension(enviroment.y),
+                text: enviroment.text.map(|mut text| {
+                    text.color = foreground;
+                    text
+                }),
+            },
+            graph: GraphStyle {
+                color: foreground,
+                ..GraphStyle::default()
+            },
+        }
+    }
+
+    /// Theme between `self` (`t = 0`) and `other` (`t = 1`) for animated transitions.
+    ///
+    /// Colors and thicknesses are interpolated, everything else (and parts only one of
+    /// both themes has) is taken from `other`.
+    pub fn mix(&self, other: &Theme, t: f32) -> Theme {
+        let t = t.clamp(0.0, 1.0);
+        let thickness = |a: f32, b: f32| a + (b - a) * t;
+
+        let axis = |a: &Option<AxisStyle>, b: &Option<AxisStyle>| match (a, b) {
+            (Some(a), Some(b)) => Some(AxisStyle {
+                color: a.color.mix(b.color, t),
+                thickness: thickness(a.thickness, b.thickness),
+            }),
+            _ => *b,
+        };
+        let tick = |a: &Option<TickStyle>, b: &Option<TickStyle>| match (a, b) {
+            (Some(a), Some(b)) => Some(TickStyle {
+                color: a.color.mix(b.color, t),
+                length: thickness(a.length, b.length),
+                thickness: thickness(a.thickness, b.thickness),
+            }),
+            _ => *b,
+        };
+        let grid = |a: &Option<GridStyle>, b: &Option<GridStyle>| match (a, b) {
+            (Some(a), Some(b)) => Some(GridStyle {
+                color: a.color.mix(b.color, t),
+                thickness: thickness(a.thickness, b.thickness),
+            }),
+            _ => *b,
+        };
+        let dimension = |a: &DimensionStyle, b: &DimensionStyle| DimensionStyle {
+            spacing: b.spacing.clone(),
+            axis: axis(&a.axis, &b.axis),
+            tick: tick(&a.tick, &b.tick),
+            subtick: tick(&a.subtick, &b.subtick),
+            grid: grid(&a.grid, &b.grid),
+            subgrid: grid(&a.subgrid, &b.subgrid),
+            label: b.label,
+            title: b.title.clone(),
+        };
+        let text = match (&self.enviroment.text, &other.enviroment.text) {
+            (Some(a), Some(b)) => Some(TextStyle {
+                color: a.color.mix(b.color, t),
+                ..b.clone()
+            }),
+            (_, b) => b.clone(),
+        };
+
+        Theme {
+            clear_color: self.clear_color.mix(other.clear_color, t),
+            enviroment: EnviromentStyle {
+                x: dimension(&self.enviroment.x, &other.enviroment.x),
+                y: dimension(&self.enviroment.y, &other.enviroment.y),
+                text,
+            },
+            graph: GraphStyle {
+                color: self.graph.color.mix(other.graph.color, t),
+                thickness: thickness(self.graph.thickness, other.graph.thickness),
+                ..other.graph.clone()
+            },
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::{Duration, Instant};
+
+/// Time source of a render loop, measuring the time between rendered frames.
+#[derive(Debug, Clone)]
+pub struct FrameClock {
+    start: Instant,
+    prev: Instant,
+    delta: Duration,
+    frame: u64,
+    /// Advance by this step on every tick instead of measuring the wall clock.
+    fixed_step: Option<Duration>,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+
+        Self {
+            start: now,
+            prev: now,
+            delta: Duration::ZERO,
+            frame: 0,
+            fixed_step: None,
+        }
+    }
+
+    /// Clock advancing by exactly `step` per frame, so animations driven by it
+    /// are reproducible regardless of how long frames actually take.
+    pub fn fixed(step: Duration) -> Self {
+        Self {
+            fixed_step: Some(step),
+            ..Self::new()
+        }
+    }
+
+    pub fn fixed_step(&self) -> Option<Duration> {
+        self.fixed_step
+    }
+
+    /// Advances the clock to the current frame and returns the time since the last frame.
+    pub fn tick(&mut self) -> Duration {
+        let now = match self.fixed_step {
+            Some(step) => self.prev + step,
+            None => Instant::now(),
+        };
+
+        self.delta = now - self.prev;
+        self.prev = now;
+        self.frame += 1;
+
+        self.delta
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Time since the last frame in milliseconds.
+    pub fn delta_ms(&self) -> f32 {
+        self.delta.as_micros() as f32 / 1000.0
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.prev - self.start
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to a task registered with `TickScheduler::add_task`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TickTaskId(usize);
+
+struct TickTask<S> {
+    interval: Duration,
+    next: Instant,
+    ticks: u64,
+    paused: bool,
+    callback: Box<dyn FnMut(&mut S, Duration) + Send>,
+}
+
+/// Fixed timestep callbacks (data updates, simulations, animations) which advance
+/// independently of rendered frames.
+///
+/// Every task gets called with its fixed interval as delta, missed ticks are caught up
+/// (up to `max_catch_up` per run) so the simulated time stays in sync with the wall clock
+/// even if redraws are throttled or paused.
+pub struct TickScheduler<S> {
+    tasks: Vec<Option<TickTask<S>>>,
+    max_catch_up: u32,
+}
+
+impl<S> TickScheduler<S> {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            max_catch_up: 8,
+        }
+    }
+
+    /// Maximal number of ticks a single task runs per `run_due`, further missed ticks get dropped.
+    pub fn with_max_catch_up(mut self, max_catch_up: u32) -> Self {
+        self.max_catch_up = max_catch_up.max(1);
+        self
+    }
+
+    /// Registers `callback` to be called every `interval`, first after one interval passed.
+    pub fn add_task(
+        &mut self,
+        interval: Duration,
+        callback: impl FnMut(&mut S, Duration) + Send + 'static,
+    ) -> TickTaskId {
+        let interval = interval.max(Duration::from_micros(100));
+
+        self.tasks.push(Some(TickTask {
+            interval,
+            next: Instant::now() + interval,
+            ticks: 0,
+            paused: false,
+            callback: Box::new(callback),
+        }));
+
+        TickTaskId(self.tasks.len() - 1)
+    }
+
+    pub fn remove_task(&mut self, id: TickTaskId) -> bool {
+        self.tasks.get_mut(id.0).and_then(Option::take).is_some()
+    }
+
+    /// Paused tasks don't catch up the missed ticks once resumed.
+    pub fn set_task_paused(&mut self, id: TickTaskId, paused: bool) {
+        if let Some(Some(task)) = self.tasks.get_mut(id.0) {
+            if task.paused && !paused {
+                task.next = Instant::now() + task.interval;
+            }
+            task.paused = paused;
+        }
+    }
+
+    /// Number of ticks the task has run so far.
+    pub fn task_ticks(&self, id: TickTaskId) -> Option<u64> {
+        Some(self.tasks.get(id.0)?.as_ref()?.ticks)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.iter().all(Option::is_none)
+    }
+
+    /// Runs all ticks which are due and returns how many ran.
+    pub fn run_due(&mut self, state: &mut S) -> u32 {
+        let now = Instant::now();
+        let mut ran = 0;
+
+        for task in self.tasks.iter_mut().flatten() {
+            if task.paused {
+                continue;
+            }
+
+            let mut steps = 0;
+            while task.next <= now && steps < self.max_catch_up {
+                (task.callback)(state, task.interval);
+                task.next += task.interval;
+                task.ticks += 1;
+                steps += 1;
+            }
+
+            // too far behind (e.g. after a stall), skip the remaining ticks
+            if task.next <= now {
+                task.next = now + task.interval;
+            }
+
+            ran += steps;
+        }
+
+        ran
+    }
+
+    /// Time at which the next tick is due, to wake up the event loop (`ControlFlow::WaitUntil`).
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.tasks
+            .iter()
+            .flatten()
+            .filter(|task| !task.paused)
+            .map(|task| task.next)
+            .min()
+    }
+}
+
+impl<S> Default for TickScheduler<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::data::read_data;
+use crate::graph::{PointSeries, PointStyle};
+use crate::plot::PlotHandle;
+
+/// Detects changes of a file on disk by polling its modification time.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl FileWatcher {
+    /// Creates a watcher which reports changes made after its creation.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+
+        Self {
+            modified: Self::modified(&path),
+            path,
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).ok()?.modified().ok()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` once for every modification (or creation/deletion) of the file.
+    pub fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.path);
+
+        if modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+        true
+    }
+}
+
+/// Series of a data file shown in a detached plot which get replaced whenever the file
+/// changes on disk.
+pub struct WatchedData {
+    watcher: FileWatcher,
+    /// Positions of the file's series in the plot
+    series_indices: Vec<usize>,
+    style: PointStyle,
+}
+
+impl WatchedData {
+    pub fn new(path: impl AsRef<Path>, series_indices: Vec<usize>, style: PointStyle) -> Self {
+        Self {
+            watcher: FileWatcher::new(path),
+            series_indices,
+            style,
+        }
+    }
+
+    /// Reloads the file if it changed and sends the new points to the plot. Additional
+    /// columns are appended as new series.
+    ///
+    /// Returns `true` if the file got reloaded.
+    pub fn update<P: 'static>(&mut self, handle: &PlotHandle<P>) -> anyhow::Result<bool> {
+        if !self.watcher.changed() {
+            return Ok(false);
+        }
+
+        let mut data = read_data(self.watcher.path())?;
+
+        // columns which disappeared are kept as empty series
+        if data.len() < self.series_indices.len() {
+            data.resize(self.series_indices.len(), Vec::new());
+        }
+
+        for (column, points) in data.into_iter().enumerate() {
+            let series = PointSeries {
+                points,
+                style: self.style,
+            };
+
+            match self.series_indices.get(column) {
+                Some(index) => handle.replace_series(*index, series)?,
+                None => self.series_indices.push(handle.add_series(series)?),
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,151 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::color::RGBA;
+use crate::geometry::Rect;
+use crate::math::{lerp, saturate};
+
+#[derive(Debug, Clone)]
+pub enum WidgetKind {
+    Slider { range: Range<f64> },
+    Toggle,
+}
+
+/// Control drawn on top of a `GPUCanvas2D` which is bound to a value of the canvas parameter.
+///
+/// Widgets are positioned in view space (`-1..1`) and hit tested by the `CanvasController`.
+pub struct Widget<P> {
+    pub label: String,
+    pub kind: WidgetKind,
+    /// Left end of the widget, stacked below the previous widget if `None`.
+    pub position: Option<(f32, f32)>,
+    pub width: f32,
+    pub color: RGBA,
+
+    get: Arc<dyn Fn(&P) -> f64 + Send + Sync>,
+    set: Arc<dyn Fn(&mut P, f64) + Send + Sync>,
+}
+
+impl<P> Widget<P> {
+    pub const DEFAULT_WIDTH: f32 = 0.5;
+    pub const HEIGHT: f32 = 0.05;
+    /// Vertical distance of automatically stacked widgets.
+    pub const SPACING: f32 = 0.12;
+
+    pub fn slider(
+        label: &str,
+        range: Range<f64>,
+        get: impl Fn(&P) -> f64 + Send + Sync + 'static,
+        set: impl Fn(&mut P, f64) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label: label.to_string(),
+            kind: WidgetKind::Slider { range },
+            position: None,
+            width: Self::DEFAULT_WIDTH,
+            color: RGBA::BLACK,
+            get: Arc::new(get),
+            set: Arc::new(set),
+        }
+    }
+
+    pub fn toggle(
+        label: &str,
+        get: impl Fn(&P) -> bool + Send + Sync + 'static,
+        set: impl Fn(&mut P, bool) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label: label.to_string(),
+            kind: WidgetKind::Toggle,
+            position: None,
+            width: Self::HEIGHT,
+            color: RGBA::BLACK,
+            get: Arc::new(move |parameter| get(parameter) as u8 as f64),
+            set: Arc::new(move |parameter, value| set(parameter, value != 0.0)),
+        }
+    }
+
+    pub fn at(mut self, position: (f32, f32)) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_color(mut self, color: RGBA) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn value(&self, parameter: &P) -> f64 {
+        (self.get)(parameter)
+    }
+
+    /// Position of the widget with index `index` on its canvas.
+    pub(crate) fn position_or_stacked(&self, index: usize) -> (f32, f32) {
+        self.position
+            .unwrap_or((-0.9, 0.85 - index as f32 * Self::SPACING))
+    }
+
+    /// Hit area in view space, `position` is the left end at the vertical center.
+    pub(crate) fn bounds(&self, position: (f32, f32)) -> Rect {
+        let half_height = Self::HEIGHT / 2.0;
+
+        Rect::new(
+            [position.0 - half_height, position.1 - half_height],
+            [
+                position.0 + self.width + half_height,
+                position.1 + half_height,
+            ],
+        )
+    }
+
+    /// Applies a press (`pressed`) or drag at `point` (view space) to `parameter`.
+    pub(crate) fn input(
+        &self,
+        parameter: &mut P,
+        position: (f32, f32),
+        point: (f32, f32),
+        pressed: bool,
+    ) {
+        match &self.kind {
+            WidgetKind::Slider { range } => {
+                let t = saturate((point.0 - position.0) / self.width);
+                (self.set)(parameter, lerp(t as f64, &(0.0..1.0), range));
+            }
+            WidgetKind::Toggle if pressed => {
+                let value = (self.get)(parameter);
+                (self.set)(parameter, if value != 0.0 { 0.0 } else { 1.0 });
+            }
+            WidgetKind::Toggle => (),
+        }
+    }
+}
+
+impl<P> Clone for Widget<P> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            kind: self.kind.clone(),
+            position: self.position,
+            width: self.width,
+            color: self.color,
+            get: self.get.clone(),
+            set: self.set.clone(),
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for Widget<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Widget")
+            .field("label", &self.label)
+            .field("kind", &self.kind)
+            .field("position", &self.position)
+            .field("width", &self.width)
+            .finish_non_exhaustive()
+    }
+}